@@ -0,0 +1,45 @@
+use dynamic_graph::*;
+use dynamic_graph::CleanupStrategy::*;
+
+pub struct Add;
+pub struct Mul;
+pub struct Num(i64);
+
+payload_enum!{
+    enum Expr {
+        Add(Add),
+        Mul(Mul),
+        Num(Num),
+    }
+}
+
+fn eval<'id>(anchor : &AnchorMut<'_, 'id, VecGraph<VecNode<Expr, ()>>>, node : GraphPtr<'id, VecNode<Expr, ()>>) -> i64 {
+    if let Some(x) = anchor.get_variant::<Num>(node) {
+        return x.0;
+    }
+    let args = anchor.neighbors(node).map(|x| eval(anchor, x));
+    if anchor.get_variant::<Add>(node).is_some() {
+        args.sum()
+    } else {
+        args.product()
+    }
+}
+
+fn main() {
+    let mut graph = VecGraph::<VecNode<Expr, ()>>::new();
+    anchor_mut!(graph, Never);
+
+    // 2 * (3 + 4)
+    let root = graph.spawn_variant(Mul);
+    graph.root_mut().push(root);
+    let mut cursor = graph.cursor_mut(root);
+    cursor.spawn_attached(Num(2).into(), ());
+    let sum = cursor.spawn_attached(Add.into(), ());
+    cursor.jump(sum);
+    cursor.spawn_attached(Num(3).into(), ());
+    cursor.spawn_attached(Num(4).into(), ());
+
+    let constants : i64 = graph.edges(sum).edges_filtered::<Num>().map(|x| x.values.this.0).sum();
+    println!("constants under the sum: {}", constants);
+    println!("result: {}", eval(&graph, root));
+}
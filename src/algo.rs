@@ -0,0 +1,763 @@
+use super::*;
+use crate::graph_raw::GraphRaw;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use core::cmp::Ordering;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Visits every node reachable from `src` in breadth-first order. `visit` receives a node and its distance from `src`.
+/// Nodes of the same level are split between tasks of the rayon pool, levels are visited one after another.
+#[cfg(feature = "parallel")]
+pub fn par_bfs<'id, Root, NodeType, A : Allocator>(anchor : &Anchor<'_, 'id, GenericGraph<Root, NodeType, A>>, src : GraphPtr<'id, NodeType>,
+                                                   visit : impl Fn(GraphPtr<'id, NodeType>, usize) + Sync)
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode + Sync
+{
+    let guard = anchor._guard;
+    //(W)
+    anchor.internal().par_bfs(src.as_mut(), |x, depth| visit(unsafe { GraphPtr::from_mut(x, guard) }, depth));
+}
+
+impl <NodeType, A : Allocator> GraphRaw<NodeType, A>
+where NodeType : GraphNode
+{
+    // Returns store indices of edge targets of every node.
+    pub(crate) fn adjacency(&self) -> Vec<Vec<usize>>
+    {
+        self.data.iter().map(|i| {
+            let mut targets = Vec::new();
+            //(Q)
+            Self::for_each_target(i.as_ptr(), |x| targets.push(unsafe { (*x).meta().store_index }));
+            targets
+        }).collect()
+    }
+
+    // Replaces contents of `out` with edge targets of `item`.
+    pub(crate) fn targets_of(&self, item : *const NodeType, out : &mut Vec<*mut NodeType>)
+    {
+        out.clear();
+        Self::for_each_target(item as *mut NodeType, |x| out.push(x));
+    }
+
+    // Same as `adjacency`, but every edge is also stored in the reverse direction.
+    pub(crate) fn undirected_adjacency(&self) -> Vec<Vec<usize>>
+    {
+        let adjacency = self.adjacency();
+        let mut res = adjacency.clone();
+        for (i, targets) in adjacency.iter().enumerate() {
+            for &x in targets {
+                res[x].push(i);
+            }
+        }
+        res
+    }
+}
+
+// Converts a store index of a node to a pointer.
+fn ptr<'id, Root, NodeType, A : Allocator>(anchor : &Anchor<'_, 'id, GenericGraph<Root, NodeType, A>>, index : usize) -> GraphPtr<'id, NodeType>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    //(W)
+    unsafe { GraphPtr::from_mut(anchor.internal().data[index].as_ptr(), anchor._guard) }
+}
+
+/// One of the two classes of a bipartite graph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Color {
+    Left,
+    Right,
+}
+
+impl Color {
+    /// Returns the other color.
+    pub fn flip(self) -> Color
+    {
+        match self {
+            Color::Left => Color::Right,
+            Color::Right => Color::Left,
+        }
+    }
+}
+
+/// Splits every allocated node into two classes so that no edge connects nodes of the same class. Edges are treated
+/// as undirected. The first node of every connected component is colored `Left`. Fails if the graph has a cycle of
+/// odd length.
+pub fn two_color<'id, Root, NodeType, A : Allocator>(anchor : &Anchor<'_, 'id, GenericGraph<Root, NodeType, A>>)
+    -> Result<HashMap<GraphPtr<'id, NodeType>, Color>, OddCycle>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    let adjacency = anchor.internal().undirected_adjacency();
+    let mut colors = vec![None; adjacency.len()];
+    let mut queue = VecDeque::new();
+    for start in 0..adjacency.len() {
+        if colors[start].is_some() {
+            continue;
+        }
+        colors[start] = Some(Color::Left);
+        queue.push_back(start);
+        while let Some(i) = queue.pop_front() {
+            let color = colors[i].map(Color::flip);
+            for &x in &adjacency[i] {
+                match colors[x] {
+                    None => {
+                        colors[x] = color;
+                        queue.push_back(x);
+                    },
+                    Some(c) if Some(c) != color => return Err(OddCycle),
+                    _ => {},
+                }
+            }
+        }
+    }
+    Ok(colors.into_iter().enumerate().filter_map(|(i, c)| Some((ptr(anchor, i), c?))).collect())
+}
+
+/// Finds a maximum matching between `left` nodes and the nodes they share edges with using the Hopcroft-Karp
+/// algorithm. Edges are treated as undirected, edges between two `left` nodes are ignored. Returns matched pairs
+/// with the `left` node first.
+pub fn max_bipartite_matching<'id, Root, NodeType, A : Allocator>(anchor : &Anchor<'_, 'id, GenericGraph<Root, NodeType, A>>,
+                                                                  left : impl IntoIterator<Item = GraphPtr<'id, NodeType>>)
+    -> Vec<(GraphPtr<'id, NodeType>, GraphPtr<'id, NodeType>)>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    const UNREACHED : usize = usize::MAX;
+    let adjacency = anchor.internal().undirected_adjacency();
+    let n = adjacency.len();
+    let mut is_left = vec![false; n];
+    let mut left_nodes = Vec::new();
+    for i in left {
+        let i = anchor.ptr_to_index(i);
+        if !is_left[i] {
+            is_left[i] = true;
+            left_nodes.push(i);
+        }
+    }
+    let mut match_left = vec![None; n];
+    let mut match_right = vec![None; n];
+    let mut dist = vec![UNREACHED; n];
+    let mut next = vec![0; n];
+    loop {
+        //Layers alternating paths starting at free left nodes.
+        let mut queue = VecDeque::new();
+        for &i in &left_nodes {
+            dist[i] = if match_left[i].is_none() { queue.push_back(i); 0 } else { UNREACHED };
+        }
+        let mut found = false;
+        while let Some(u) = queue.pop_front() {
+            for &v in &adjacency[u] {
+                if is_left[v] {
+                    continue;
+                }
+                match match_right[v] {
+                    None => found = true,
+                    Some(w) if dist[w] == UNREACHED => {
+                        dist[w] = dist[u] + 1;
+                        queue.push_back(w);
+                    },
+                    _ => {},
+                }
+            }
+        }
+        if !found {
+            break;
+        }
+        //Augments along vertex-disjoint shortest paths.
+        for &i in &left_nodes {
+            next[i] = 0;
+        }
+        for &start in &left_nodes {
+            if match_left[start].is_some() {
+                continue;
+            }
+            let mut stack = vec![start];
+            while let Some(&u) = stack.last() {
+                let v = match adjacency[u].get(next[u]) {
+                    Some(&v) => v,
+                    None => {
+                        dist[u] = UNREACHED;
+                        stack.pop();
+                        continue;
+                    }
+                };
+                if is_left[v] {
+                    next[u] += 1;
+                    continue;
+                }
+                match match_right[v] {
+                    None => {
+                        for &x in &stack {
+                            let y = adjacency[x][next[x]];
+                            match_left[x] = Some(y);
+                            match_right[y] = Some(x);
+                        }
+                        break;
+                    },
+                    Some(w) if dist[w] != UNREACHED && dist[w] == dist[u] + 1 => stack.push(w),
+                    _ => next[u] += 1,
+                }
+            }
+        }
+    }
+    left_nodes.into_iter().filter_map(|i| Some((ptr(anchor, i), ptr(anchor, match_left[i]?)))).collect()
+}
+
+/// Computes PageRank of every allocated node with `iterations` rounds of power iteration. Scores sum up to 1. Rank
+/// of nodes without outgoing edges is spread evenly over the graph.
+pub fn pagerank<'id, Root, NodeType, A : Allocator>(anchor : &Anchor<'_, 'id, GenericGraph<Root, NodeType, A>>, damping : f64,
+                                                    iterations : usize) -> HashMap<GraphPtr<'id, NodeType>, f64>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    let adjacency = anchor.internal().adjacency();
+    let n = adjacency.len();
+    if n == 0 {
+        return HashMap::new();
+    }
+    let mut rank = vec![1.0 / n as f64; n];
+    let mut next = vec![0.0; n];
+    for _ in 0..iterations {
+        let dangling : f64 = (0..n).filter(|&i| adjacency[i].is_empty()).map(|i| rank[i]).sum();
+        let base = (1.0 - damping + damping * dangling) / n as f64;
+        next.iter_mut().for_each(|x| *x = base);
+        for (i, targets) in adjacency.iter().enumerate() {
+            let share = damping * rank[i] / targets.len() as f64;
+            for &x in targets {
+                next[x] += share;
+            }
+        }
+        core::mem::swap(&mut rank, &mut next);
+    }
+    rank.into_iter().enumerate().map(|(i, x)| (ptr(anchor, i), x)).collect()
+}
+
+/// Returns the number of edges incident to every allocated node, in either direction, divided by the number of
+/// other nodes.
+pub fn degree_centrality<'id, Root, NodeType, A : Allocator>(anchor : &Anchor<'_, 'id, GenericGraph<Root, NodeType, A>>)
+    -> HashMap<GraphPtr<'id, NodeType>, f64>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    let adjacency = anchor.internal().undirected_adjacency();
+    let others = adjacency.len().saturating_sub(1).max(1) as f64;
+    adjacency.iter().enumerate().map(|(i, x)| (ptr(anchor, i), x.len() as f64 / others)).collect()
+}
+
+// Edge targets of every node along with their weights.
+fn weighted_adjacency<'id, Root, NodeType, A : Allocator>(anchor : &Anchor<'_, 'id, GenericGraph<Root, NodeType, A>>,
+                                                          mut weight : impl FnMut(GraphPtr<'id, NodeType>, GraphPtr<'id, NodeType>) -> f64)
+    -> Vec<Vec<(usize, f64)>>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    let adjacency = anchor.internal().adjacency();
+    adjacency.iter().enumerate().map(|(i, targets)| {
+        targets.iter().map(|&x| (x, weight(ptr(anchor, i), ptr(anchor, x)))).collect()
+    }).collect()
+}
+
+// An entry of the Dijkstra queue. Ordered so that BinaryHeap pops the closest node first.
+#[derive(PartialEq)]
+struct Queued {
+    dist : f64,
+    node : usize,
+}
+
+impl Eq for Queued {}
+
+impl Ord for Queued {
+    fn cmp(&self, other : &Self) -> Ordering
+    {
+        other.dist.partial_cmp(&self.dist).unwrap_or(Ordering::Equal).then(other.node.cmp(&self.node))
+    }
+}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other : &Self) -> Option<Ordering>
+    {
+        Some(self.cmp(other))
+    }
+}
+
+// Sums dependencies of every node on shortest paths starting at `sources`. Edges weigh 1 unless `weighted`.
+fn brandes(adjacency : &[Vec<(usize, f64)>], weighted : bool, sources : impl Iterator<Item = usize>) -> Vec<f64>
+{
+    let n = adjacency.len();
+    let mut res = vec![0.0; n];
+    let mut sigma = vec![0.0; n];
+    let mut dist = vec![f64::INFINITY; n];
+    let mut delta = vec![0.0; n];
+    let mut settled = vec![false; n];
+    let mut preds : Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut order : Vec<usize> = Vec::with_capacity(n);
+    for s in sources {
+        for &i in &order {
+            sigma[i] = 0.0;
+            dist[i] = f64::INFINITY;
+            delta[i] = 0.0;
+            settled[i] = false;
+            preds[i].clear();
+        }
+        order.clear();
+        sigma[s] = 1.0;
+        dist[s] = 0.0;
+        let mut relax = |v : usize, w : usize, d : f64, sigma : &mut Vec<f64>, dist : &mut Vec<f64>| -> bool {
+            if d < dist[w] {
+                dist[w] = d;
+                sigma[w] = sigma[v];
+                preds[w].clear();
+                preds[w].push(v);
+                true
+            } else {
+                if d == dist[w] {
+                    sigma[w] += sigma[v];
+                    preds[w].push(v);
+                }
+                false
+            }
+        };
+        if weighted {
+            let mut heap = BinaryHeap::new();
+            heap.push(Queued { dist : 0.0, node : s });
+            while let Some(Queued { node : v, .. }) = heap.pop() {
+                if settled[v] {
+                    continue;
+                }
+                settled[v] = true;
+                order.push(v);
+                for &(w, weight) in &adjacency[v] {
+                    let d = dist[v] + weight;
+                    if !settled[w] && relax(v, w, d, &mut sigma, &mut dist) {
+                        heap.push(Queued { dist : d, node : w });
+                    }
+                }
+            }
+        } else {
+            let mut queue = VecDeque::new();
+            queue.push_back(s);
+            settled[s] = true;
+            while let Some(v) = queue.pop_front() {
+                order.push(v);
+                for &(w, _) in &adjacency[v] {
+                    if relax(v, w, dist[v] + 1.0, &mut sigma, &mut dist) && !settled[w] {
+                        settled[w] = true;
+                        queue.push_back(w);
+                    }
+                }
+            }
+        }
+        for &w in order.iter().rev() {
+            for &v in &preds[w] {
+                delta[v] += sigma[v] / sigma[w] * (1.0 + delta[w]);
+            }
+            if w != s {
+                res[w] += delta[w];
+            }
+        }
+    }
+    res
+}
+
+// Same as `brandes`, but sources are split between tasks of the rayon pool.
+#[cfg(feature = "parallel")]
+fn par_brandes(adjacency : &[Vec<(usize, f64)>], weighted : bool) -> Vec<f64>
+{
+    let n = adjacency.len();
+    let chunk = n.div_ceil(rayon::current_num_threads()).max(1);
+    (0..n.div_ceil(chunk)).into_par_iter()
+        .map(|i| brandes(adjacency, weighted, i * chunk..((i + 1) * chunk).min(n)))
+        .reduce(|| vec![0.0; n], |mut res, x| {
+            for (a, b) in res.iter_mut().zip(x) {
+                *a += b;
+            }
+            res
+        })
+}
+
+fn into_scores<'id, Root, NodeType, A : Allocator>(anchor : &Anchor<'_, 'id, GenericGraph<Root, NodeType, A>>, scores : Vec<f64>)
+    -> HashMap<GraphPtr<'id, NodeType>, f64>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    scores.into_iter().enumerate().map(|(i, x)| (ptr(anchor, i), x)).collect()
+}
+
+/// Computes betweenness centrality of every allocated node with Brandes' algorithm: the number of shortest paths
+/// between other pairs of nodes going through the node, split evenly between paths of equal length. Edges are
+/// directed and weigh 1. Scores are not normalized.
+pub fn betweenness<'id, Root, NodeType, A : Allocator>(anchor : &Anchor<'_, 'id, GenericGraph<Root, NodeType, A>>)
+    -> HashMap<GraphPtr<'id, NodeType>, f64>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    let adjacency = weighted_adjacency(anchor, |_, _| 1.0);
+    into_scores(anchor, brandes(&adjacency, false, 0..adjacency.len()))
+}
+
+/// Same as `betweenness`, but an edge from `src` to `dst` weighs `weight(src, dst)`. Weights must not be negative.
+pub fn weighted_betweenness<'id, Root, NodeType, A : Allocator>(anchor : &Anchor<'_, 'id, GenericGraph<Root, NodeType, A>>,
+                                                                weight : impl FnMut(GraphPtr<'id, NodeType>, GraphPtr<'id, NodeType>) -> f64)
+    -> HashMap<GraphPtr<'id, NodeType>, f64>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    let adjacency = weighted_adjacency(anchor, weight);
+    into_scores(anchor, brandes(&adjacency, true, 0..adjacency.len()))
+}
+
+/// Same as `betweenness`, but source nodes are split between tasks of the rayon pool.
+#[cfg(feature = "parallel")]
+pub fn par_betweenness<'id, Root, NodeType, A : Allocator>(anchor : &Anchor<'_, 'id, GenericGraph<Root, NodeType, A>>)
+    -> HashMap<GraphPtr<'id, NodeType>, f64>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    let adjacency = weighted_adjacency(anchor, |_, _| 1.0);
+    into_scores(anchor, par_brandes(&adjacency, false))
+}
+
+/// Same as `weighted_betweenness`, but source nodes are split between tasks of the rayon pool. Weights are computed
+/// beforehand on the calling thread.
+#[cfg(feature = "parallel")]
+pub fn par_weighted_betweenness<'id, Root, NodeType, A : Allocator>(anchor : &Anchor<'_, 'id, GenericGraph<Root, NodeType, A>>,
+                                                                    weight : impl FnMut(GraphPtr<'id, NodeType>, GraphPtr<'id, NodeType>) -> f64)
+    -> HashMap<GraphPtr<'id, NodeType>, f64>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    let adjacency = weighted_adjacency(anchor, weight);
+    into_scores(anchor, par_brandes(&adjacency, true))
+}
+
+/// Splits allocated nodes into communities with label propagation. Every node starts in its own community and
+/// repeatedly joins the one most common among its neighbors, edges are treated as undirected. Stops once no label
+/// changes or after `max_iters` rounds. `rng` is a source of random numbers used to shuffle nodes and break ties.
+/// Labels are numbered from 0 in the order of first appearance in the storage.
+pub fn label_propagation<'id, Root, NodeType, A : Allocator>(anchor : &Anchor<'_, 'id, GenericGraph<Root, NodeType, A>>, max_iters : usize,
+                                                             mut rng : impl FnMut() -> u64) -> HashMap<GraphPtr<'id, NodeType>, usize>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    let adjacency = anchor.internal().undirected_adjacency();
+    let n = adjacency.len();
+    let mut labels : Vec<usize> = (0..n).collect();
+    let mut order : Vec<usize> = (0..n).collect();
+    let mut counts = HashMap::new();
+    let mut best = Vec::new();
+    for _ in 0..max_iters {
+        for i in (1..n).rev() {
+            order.swap(i, (rng() % (i as u64 + 1)) as usize);
+        }
+        let mut changed = false;
+        for &i in &order {
+            counts.clear();
+            for &x in &adjacency[i] {
+                *counts.entry(labels[x]).or_insert(0usize) += 1;
+            }
+            let max = match counts.values().max() {
+                Some(&max) => max,
+                None => continue,
+            };
+            if counts.get(&labels[i]) == Some(&max) {
+                continue;
+            }
+            best.clear();
+            best.extend(counts.iter().filter(|x| *x.1 == max).map(|x| *x.0));
+            best.sort_unstable();
+            labels[i] = best[(rng() % best.len() as u64) as usize];
+            changed = true;
+        }
+        if !changed {
+            break;
+        }
+    }
+    let mut renumbered = HashMap::new();
+    labels.iter().enumerate().map(|(i, x)| {
+        let next = renumbered.len();
+        (ptr(anchor, i), *renumbered.entry(*x).or_insert(next))
+    }).collect()
+}
+
+/// Finds a path using every edge exactly once with Hierholzer's algorithm. Returns a circuit if one exists, the
+/// path starts at the node with one more outgoing than incoming edge otherwise. Returns None if the degrees of nodes
+/// don't allow such path or edges are not connected.
+pub fn eulerian_path<'id, Root, NodeType, A : Allocator>(anchor : &Anchor<'_, 'id, GenericGraph<Root, NodeType, A>>)
+    -> Option<Vec<(GraphPtr<'id, NodeType>, GraphPtr<'id, NodeType>)>>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    let adjacency = anchor.internal().adjacency();
+    let n = adjacency.len();
+    let mut balance = vec![0isize; n];
+    let mut edges = 0;
+    for (i, targets) in adjacency.iter().enumerate() {
+        balance[i] += targets.len() as isize;
+        for &x in targets {
+            balance[x] -= 1;
+        }
+        edges += targets.len();
+    }
+    let mut start = None;
+    let mut ends = 0;
+    for (i, &x) in balance.iter().enumerate() {
+        match x {
+            0 => {},
+            1 if start.is_none() => start = Some(i),
+            -1 if ends == 0 => ends += 1,
+            _ => return None,
+        }
+    }
+    if start.is_some() != (ends == 1) {
+        return None;
+    }
+    let start = match start.or_else(|| adjacency.iter().position(|x| !x.is_empty())) {
+        Some(start) => start,
+        None => return Some(Vec::new()),
+    };
+    let mut next = vec![0; n];
+    let mut stack = vec![start];
+    let mut nodes = Vec::with_capacity(edges + 1);
+    while let Some(&i) = stack.last() {
+        match adjacency[i].get(next[i]) {
+            Some(&x) => {
+                next[i] += 1;
+                stack.push(x);
+            },
+            None => {
+                nodes.push(i);
+                stack.pop();
+            }
+        }
+    }
+    if nodes.len() != edges + 1 {
+        return None;
+    }
+    nodes.reverse();
+    Some(nodes.windows(2).map(|x| (ptr(anchor, x[0]), ptr(anchor, x[1]))).collect())
+}
+
+// Runs Dijkstra's algorithm from `src` avoiding `removed` nodes and edges, stops early once `dst` is settled.
+// Returns distances and predecessors of nodes.
+fn dijkstra_search(adjacency : &[Vec<(usize, f64)>], src : usize, dst : Option<usize>, removed_nodes : &[bool],
+                   removed_edges : &HashSet<(usize, usize)>) -> (Vec<f64>, Vec<usize>)
+{
+    let n = adjacency.len();
+    let mut dist = vec![f64::INFINITY; n];
+    let mut prev = vec![usize::MAX; n];
+    let mut settled = vec![false; n];
+    let mut heap = BinaryHeap::new();
+    dist[src] = 0.0;
+    heap.push(Queued { dist : 0.0, node : src });
+    while let Some(Queued { node : v, .. }) = heap.pop() {
+        if settled[v] {
+            continue;
+        }
+        settled[v] = true;
+        if Some(v) == dst {
+            break;
+        }
+        for &(w, weight) in &adjacency[v] {
+            let d = dist[v] + weight;
+            if !removed_nodes[w] && d < dist[w] && !removed_edges.contains(&(v, w)) {
+                dist[w] = d;
+                prev[w] = v;
+                heap.push(Queued { dist : d, node : w });
+            }
+        }
+    }
+    (dist, prev)
+}
+
+// Finds the shortest path from `src` to `dst` avoiding `removed` nodes and edges. Returns nodes of the path along
+// with their distances from `src`.
+fn dijkstra(adjacency : &[Vec<(usize, f64)>], src : usize, dst : usize, removed_nodes : &[bool],
+            removed_edges : &HashSet<(usize, usize)>) -> Option<Vec<(usize, f64)>>
+{
+    let (dist, prev) = dijkstra_search(adjacency, src, Some(dst), removed_nodes, removed_edges);
+    if dist[dst].is_infinite() {
+        return None;
+    }
+    let mut path = vec![(dst, dist[dst])];
+    let mut i = dst;
+    while i != src {
+        i = prev[i];
+        path.push((i, dist[i]));
+    }
+    path.reverse();
+    Some(path)
+}
+
+// Finds the shortest path from `src` to `dst` with Dijkstra's algorithm, or with Bellman-Ford's if some weight is
+// negative. Returns None if `dst` is unreachable or reachable through a cycle of negative length.
+pub(crate) fn shortest_path(adjacency : &[Vec<(usize, f64)>], src : usize, dst : usize) -> Option<Vec<usize>>
+{
+    let n = adjacency.len();
+    if !adjacency.iter().flatten().any(|x| x.1 < 0.0) {
+        let path = dijkstra(adjacency, src, dst, &vec![false; n], &HashSet::new())?;
+        return Some(path.into_iter().map(|x| x.0).collect());
+    }
+    let mut dist = vec![f64::INFINITY; n];
+    let mut prev = vec![usize::MAX; n];
+    dist[src] = 0.0;
+    for _ in 1..n {
+        let mut changed = false;
+        for (v, targets) in adjacency.iter().enumerate() {
+            for &(w, weight) in targets {
+                if dist[v] + weight < dist[w] {
+                    dist[w] = dist[v] + weight;
+                    prev[w] = v;
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    if dist[dst].is_infinite() {
+        return None;
+    }
+    //Nodes reachable from a negative cycle have no shortest path.
+    let mut queue : VecDeque<_> = adjacency.iter().enumerate()
+        .filter(|(v, targets)| targets.iter().any(|&(w, weight)| dist[*v] + weight < dist[w]))
+        .map(|x| x.0)
+        .collect();
+    let mut affected = vec![false; n];
+    while let Some(v) = queue.pop_front() {
+        if !affected[v] {
+            affected[v] = true;
+            queue.extend(adjacency[v].iter().map(|x| x.0));
+        }
+    }
+    if affected[dst] {
+        return None;
+    }
+    let mut path = vec![dst];
+    let mut i = dst;
+    while i != src {
+        i = prev[i];
+        path.push(i);
+    }
+    path.reverse();
+    Some(path)
+}
+
+/// Finds up to `k` shortest loopless paths from `src` to `dst` with Yen's algorithm, ordered by length. An edge from
+/// `a` to `b` weighs `weight(a, b)`, weights must not be negative. Returns the length and nodes of every path.
+pub fn k_shortest_paths<'id, Root, NodeType, A : Allocator>(anchor : &Anchor<'_, 'id, GenericGraph<Root, NodeType, A>>,
+                                                            src : GraphPtr<'id, NodeType>, dst : GraphPtr<'id, NodeType>, k : usize,
+                                                            weight : impl FnMut(GraphPtr<'id, NodeType>, GraphPtr<'id, NodeType>) -> f64)
+    -> Vec<(f64, Vec<GraphPtr<'id, NodeType>>)>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    let adjacency = weighted_adjacency(anchor, weight);
+    let (src, dst) = (anchor.ptr_to_index(src), anchor.ptr_to_index(dst));
+    let mut removed_nodes = vec![false; adjacency.len()];
+    let mut removed_edges = HashSet::new();
+    let mut found : Vec<Vec<(usize, f64)>> = Vec::new();
+    let mut candidates : Vec<Vec<(usize, f64)>> = Vec::new();
+    let mut seen = HashSet::new();
+    if k > 0 {
+        if let Some(path) = dijkstra(&adjacency, src, dst, &removed_nodes, &removed_edges) {
+            seen.insert(path.iter().map(|x| x.0).collect::<Vec<_>>());
+            found.push(path);
+        }
+    }
+    while !found.is_empty() && found.len() < k {
+        let last = &found[found.len() - 1];
+        for i in 0..last.len() - 1 {
+            let (spur, root_length) = last[i];
+            //Paths sharing the root must leave it through another edge.
+            for path in &found {
+                if path.len() > i + 1 && path[..=i].iter().zip(&last[..=i]).all(|(a, b)| a.0 == b.0) {
+                    removed_edges.insert((path[i].0, path[i + 1].0));
+                }
+            }
+            for x in &last[..i] {
+                removed_nodes[x.0] = true;
+            }
+            if let Some(tail) = dijkstra(&adjacency, spur, dst, &removed_nodes, &removed_edges) {
+                let path : Vec<_> = last[..i].iter().copied()
+                    .chain(tail.into_iter().map(|(x, d)| (x, d + root_length)))
+                    .collect();
+                if seen.insert(path.iter().map(|x| x.0).collect::<Vec<_>>()) {
+                    candidates.push(path);
+                }
+            }
+            removed_edges.clear();
+            for x in &last[..i] {
+                removed_nodes[x.0] = false;
+            }
+        }
+        let best = candidates.iter().enumerate()
+            .min_by(|a, b| a.1[a.1.len() - 1].1.partial_cmp(&b.1[b.1.len() - 1].1).unwrap_or(Ordering::Equal))
+            .map(|x| x.0);
+        match best {
+            Some(best) => found.push(candidates.swap_remove(best)),
+            None => break,
+        }
+    }
+    found.into_iter().map(|path| {
+        (path[path.len() - 1].1, path.into_iter().map(|x| ptr(anchor, x.0)).collect())
+    }).collect()
+}
+
+// Computes potentials which make every edge weight non-negative with Bellman-Ford's algorithm run from a virtual
+// node connected to every other one. Fails if the graph has a cycle of negative length.
+fn potentials(adjacency : &[Vec<(usize, f64)>]) -> Result<Vec<f64>, NegativeCycle>
+{
+    let n = adjacency.len();
+    let mut h = vec![0.0; n];
+    for round in 0..=n {
+        let mut changed = false;
+        for (v, targets) in adjacency.iter().enumerate() {
+            for &(w, weight) in targets {
+                if h[v] + weight < h[w] {
+                    h[w] = h[v] + weight;
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            return Ok(h);
+        }
+        if round == n {
+            break;
+        }
+    }
+    Err(NegativeCycle)
+}
+
+/// Computes lengths of shortest paths between every pair of allocated nodes with Johnson's algorithm. An edge from
+/// `a` to `b` weighs `weight(a, b)`, negative weights are allowed. Pairs without a path are absent from the map.
+/// Fails if the graph has a cycle of negative length.
+#[allow(clippy::type_complexity)]
+pub fn johnson<'id, Root, NodeType, A : Allocator>(anchor : &Anchor<'_, 'id, GenericGraph<Root, NodeType, A>>,
+                                                   weight : impl FnMut(GraphPtr<'id, NodeType>, GraphPtr<'id, NodeType>) -> f64)
+    -> Result<HashMap<(GraphPtr<'id, NodeType>, GraphPtr<'id, NodeType>), f64>, NegativeCycle>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    let mut adjacency = weighted_adjacency(anchor, weight);
+    let h = potentials(&adjacency)?;
+    for (v, targets) in adjacency.iter_mut().enumerate() {
+        for (w, weight) in targets.iter_mut() {
+            *weight = (*weight + h[v] - h[*w]).max(0.0);
+        }
+    }
+    let removed_nodes = vec![false; adjacency.len()];
+    let removed_edges = HashSet::new();
+    let mut res = HashMap::new();
+    for src in 0..adjacency.len() {
+        let (dist, _) = dijkstra_search(&adjacency, src, None, &removed_nodes, &removed_edges);
+        for (dst, d) in dist.into_iter().enumerate() {
+            if d.is_finite() {
+                res.insert((ptr(anchor, src), ptr(anchor, dst)), d - h[src] + h[dst]);
+            }
+        }
+    }
+    Ok(res)
+}
@@ -0,0 +1,210 @@
+use super::*;
+use crate::graph_raw::{GraphRaw, TopoOrder};
+use std::collections::{HashMap, HashSet};
+
+impl <T> TopoOrder<T> {
+    // Removes `items` from the order keeping the order of other nodes.
+    pub(crate) fn remove(&mut self, items : &[*const T])
+    {
+        for i in items {
+            self.index.remove(i);
+        }
+        let index = &self.index;
+        self.order.retain(|x| index.contains_key(&(*x as *const T)));
+        for (i, &x) in self.order.iter().enumerate() {
+            self.index.insert(x, i);
+        }
+    }
+}
+
+impl <NodeType, A : Allocator> GraphRaw<NodeType, A>
+where NodeType : GraphNode
+{
+    fn targets(item : *mut NodeType) -> Vec<*mut NodeType>
+    {
+        let mut res = Vec::new();
+        Self::for_each_target(item, |x| res.push(x));
+        res
+    }
+
+    // Returns true if `dst` can be reached from `src`.
+    fn reaches(&self, src : *const NodeType, dst : *const NodeType) -> bool
+    {
+        let mut visited = HashSet::new();
+        let mut stack = vec![src as *mut NodeType];
+        visited.insert(src);
+        while let Some(i) = stack.pop() {
+            if core::ptr::eq(i, dst) {
+                return true;
+            }
+            Self::for_each_target(i, |x| {
+                if visited.insert(x as *const NodeType) {
+                    stack.push(x);
+                }
+            });
+        }
+        false
+    }
+
+    // Moves nodes so that an edge from `src` to `dst` points forward. Returns false if `dst` reaches `src`, in
+    // which case the order is left intact. Does nothing outside of acyclic mode.
+    pub(crate) fn order_edge(&mut self, src : *const NodeType, dst : *const NodeType) -> bool
+    {
+        if self.topo.as_ref().is_some_and(|x| x.stale) {
+            let order = self.sort().unwrap_or_else(|_| panic!("an edge added through a node view closed a cycle in an acyclic graph"));
+            self.topo = Some(order);
+        }
+        let topo = match &mut self.topo {
+            Some(topo) => topo,
+            None => return true,
+        };
+        let (lower, upper) = (topo.index[&dst], topo.index[&src]);
+        if lower > upper {
+            return true;
+        }
+        //Only nodes placed before `src` might reach it.
+        let mut reached = HashSet::new();
+        let mut stack = vec![dst as *mut NodeType];
+        reached.insert(dst);
+        while let Some(i) = stack.pop() {
+            if core::ptr::eq(i, src) {
+                return false;
+            }
+            Self::for_each_target(i, |x| {
+                if topo.index[&(x as *const NodeType)] <= upper && reached.insert(x as *const NodeType) {
+                    stack.push(x);
+                }
+            });
+        }
+        //Nodes reached from `dst` are moved after the rest of the range, both keep their relative order.
+        let (mut rest, moved) : (Vec<_>, Vec<_>) = topo.order[lower..=upper].iter().copied()
+            .partition(|x| !reached.contains(&(*x as *const NodeType)));
+        rest.extend(moved);
+        for (i, x) in rest.into_iter().enumerate() {
+            topo.order[lower + i] = x;
+            topo.index.insert(x, lower + i);
+        }
+        true
+    }
+
+    // Panics if an edge from `src` to `dst` would close a cycle in acyclic mode. Called before the edge is stored.
+    pub(crate) fn check_edge(&mut self, src : *const NodeType, dst : *const NodeType)
+    {
+        if !self.order_edge(src, dst) {
+            panic!("the edge creates a cycle in an acyclic graph, use `try_connect` to check edges");
+        }
+    }
+
+    // Returns true if an edge from `src` to `dst` would close a cycle. Updates the order in acyclic mode.
+    pub(crate) fn would_cycle(&mut self, src : *const NodeType, dst : *const NodeType) -> bool
+    {
+        if self.topo.is_some() {
+            !self.order_edge(src, dst)
+        } else {
+            self.reaches(dst, src)
+        }
+    }
+
+    pub(crate) fn set_acyclic(&mut self, enabled : bool) -> Result<(), WouldCycle>
+    {
+        if !enabled {
+            self.topo = None;
+            return Ok(());
+        }
+        if self.topo.as_ref().is_some_and(|x| !x.stale) {
+            return Ok(());
+        }
+        self.topo = Some(self.sort()?);
+        Ok(())
+    }
+
+    // Sorts every node topologically, fails if the graph has a cycle.
+    fn sort(&self) -> Result<TopoOrder<NodeType>, WouldCycle>
+    {
+        //Nodes map to false while on the stack and to true once finished.
+        let mut state = HashMap::new();
+        let mut finished = Vec::with_capacity(self.data.len());
+        for i in self.data.iter() {
+            let start = i.as_ptr();
+            if state.contains_key(&(start as *const NodeType)) {
+                continue;
+            }
+            state.insert(start as *const NodeType, false);
+            let mut stack = vec![(start, Self::targets(start), 0)];
+            while let Some((node, targets, next)) = stack.last_mut() {
+                match targets.get(*next) {
+                    Some(&x) => {
+                        *next += 1;
+                        match state.get(&(x as *const NodeType)) {
+                            Some(false) => return Err(WouldCycle),
+                            Some(true) => {},
+                            None => {
+                                state.insert(x as *const NodeType, false);
+                                stack.push((x, Self::targets(x), 0));
+                            }
+                        }
+                    },
+                    None => {
+                        let node = *node;
+                        state.insert(node as *const NodeType, true);
+                        finished.push(node);
+                        stack.pop();
+                    }
+                }
+            }
+        }
+        finished.reverse();
+        let index = finished.iter().enumerate().map(|(i, &x)| (x as *const NodeType, i)).collect();
+        Ok(TopoOrder { order : finished, index, stale : false })
+    }
+}
+
+impl <Root, NodeType, A : Allocator> GenericGraph<Root, NodeType, A>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    /// Enables or disables acyclic mode. While enabled, the graph keeps a topological order of its nodes up to date,
+    /// `try_connect` rejects edges closing a cycle in time proportional to the affected part of the order and
+    /// other ways of connecting nodes panic instead of closing one. Edges added through node views are only checked
+    /// by the next connection: handing out a mutable view makes it sort the whole graph again and panic if the view
+    /// closed a cycle. Fails if the graph already has a cycle. Not supported for `UndirectedNode`, whose edges
+    /// point both ways.
+    pub fn set_acyclic(&mut self, enabled : bool) -> Result<(), WouldCycle>
+    {
+        self.internal.set_acyclic(enabled)
+    }
+
+    /// Returns true if acyclic mode is enabled.
+    pub fn is_acyclic(&self) -> bool
+    {
+        self.internal.topo.is_some()
+    }
+}
+
+macro_rules! impl_try_connect {
+    ($NodeType:ident $(, $S:ident)?) => {
+        impl <'this, 'id, N : 'this, E : 'this $(, $S : BuildHasher + Default + 'this)?, Root : 'this, A : Allocator>
+        AnchorMut<'this, 'id, GenericGraph<Root, $NodeType<N, E $(, $S)?>, A>>
+        where Root : RootCollection<'static, $NodeType<N, E $(, $S)?>>
+        {
+            /// Same as `connect`, but fails if the edge would create a cycle. Outside of acyclic mode the graph
+            /// is searched on every call.
+            pub fn try_connect(&mut self, src : GraphPtr<'id, $NodeType<N, E $(, $S)?>>, dst : GraphPtr<'id, $NodeType<N, E $(, $S)?>>,
+                               edge : E) -> Result<Option<E>, WouldCycle>
+            {
+                let internal = self.internal_mut();
+                if internal.would_cycle(src.as_ptr(), dst.as_ptr()) {
+                    return Err(WouldCycle);
+                }
+                Ok(internal.connect(src, dst, edge))
+            }
+        }
+    }
+}
+
+impl_try_connect!{NamedNode, S}
+#[cfg(feature = "indexmap")]
+impl_try_connect!{OrderedNamedNode}
+impl_try_connect!{VecNode}
+impl_try_connect!{OptionNode}
+impl_try_connect!{DiNode}
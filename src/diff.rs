@@ -0,0 +1,80 @@
+use super::*;
+use std::collections::{HashMap, HashSet};
+
+/// Changes turning one graph into another, produced by `diff`. Nodes are identified by keys computed from their data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphDiff<K> {
+    /// Keys of nodes present only in the new graph.
+    pub added_nodes : Vec<K>,
+    /// Keys of nodes present only in the old graph.
+    pub removed_nodes : Vec<K>,
+    /// Keys of nodes present in both graphs whose data differs.
+    pub changed_nodes : Vec<K>,
+    /// Edges present only in the new graph.
+    pub added_edges : Vec<(K, K)>,
+    /// Edges present only in the old graph.
+    pub removed_edges : Vec<(K, K)>,
+}
+
+impl <K> GraphDiff<K> {
+    /// Returns true if the graphs are the same.
+    pub fn is_empty(&self) -> bool
+    {
+        self.added_nodes.is_empty() && self.removed_nodes.is_empty() && self.changed_nodes.is_empty() &&
+        self.added_edges.is_empty() && self.removed_edges.is_empty()
+    }
+}
+
+// Maps keys of nodes accessible from the root to their data and collects edges between these keys in the order of
+// a breadth-first traversal.
+#[allow(clippy::type_complexity)]
+fn key_graph<'a, K, Root, NodeType, A : Allocator>(graph : &'a GenericGraph<Root, NodeType, A>, key : &mut impl FnMut(&NodeType::Node) -> K)
+    -> (Vec<K>, HashMap<K, &'a NodeType::Node>, Vec<(K, K)>)
+where K : Hash + Eq + Clone,
+      Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    let nodes = graph.internal.reachable(&graph.root);
+    //(W)
+    let keys : HashMap<_, _> = nodes.iter().map(|&x| (x as *const NodeType, key(unsafe { (*x).get() }))).collect();
+    let mut order = Vec::with_capacity(nodes.len());
+    let mut data = HashMap::with_capacity(nodes.len());
+    let mut edges = Vec::new();
+    for &i in &nodes {
+        let k = keys[&(i as *const NodeType)].clone();
+        //(W)
+        if data.insert(k.clone(), unsafe { (*i).get() }).is_none() {
+            order.push(k.clone());
+        }
+        GraphRaw::<NodeType>::for_each_target(i, |x| edges.push((k.clone(), keys[&(x as *const NodeType)].clone())));
+    }
+    (order, data, edges)
+}
+
+/// Compares graphs accessible from the roots of `old` and `new`. Nodes are matched by `key`, nodes with the same key
+/// in one graph are merged. Parallel edges are reported once. Lists follow the breadth-first order of the graph they
+/// come from.
+pub fn diff<'a, 'b, K, OldRoot, NewRoot, OldAlloc, NewAlloc, N, NodeType>(old : &Anchor<'_, 'a, GenericGraph<OldRoot, NodeType, OldAlloc>>,
+                                                                          new : &Anchor<'_, 'b, GenericGraph<NewRoot, NodeType, NewAlloc>>,
+                                                                          mut key : impl FnMut(&N) -> K) -> GraphDiff<K>
+where K : Hash + Eq + Clone,
+      N : PartialEq,
+      OldRoot : RootCollection<'static, NodeType>,
+      NewRoot : RootCollection<'static, NodeType>,
+      NodeType : GraphNode<Node = N>,
+      OldAlloc : Allocator,
+      NewAlloc : Allocator
+{
+    let (old_order, old_data, old_edges) = key_graph(old.parent, &mut key);
+    let (new_order, new_data, new_edges) = key_graph(new.parent, &mut key);
+    let old_set : HashSet<_> = old_edges.iter().cloned().collect();
+    let new_set : HashSet<_> = new_edges.iter().cloned().collect();
+    let mut seen = HashSet::new();
+    GraphDiff {
+        added_nodes : new_order.iter().filter(|x| !old_data.contains_key(*x)).cloned().collect(),
+        removed_nodes : old_order.iter().filter(|x| !new_data.contains_key(*x)).cloned().collect(),
+        changed_nodes : new_order.iter().filter(|x| old_data.get(*x).is_some_and(|y| *y != new_data[*x])).cloned().collect(),
+        added_edges : new_edges.into_iter().filter(|x| !old_set.contains(x) && seen.insert(x.clone())).collect(),
+        removed_edges : old_edges.into_iter().filter(|x| !new_set.contains(x) && seen.insert(x.clone())).collect(),
+    }
+}
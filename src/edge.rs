@@ -1,4 +1,6 @@
 use core::hint::unreachable_unchecked;
+use core::iter::Map;
+use crate::GraphItem;
 
 pub struct EdgeBoth<N, E> {
     /// Value from the source node.
@@ -31,7 +33,23 @@ pub trait OptionEdge<N, E> {
     fn that(self) -> Option<EdgeLoop<N, E>>;
     fn both(self) -> Option<EdgeBoth<N, E>>;
     fn edge(self) -> Option<E>;
+    /// # Safety
+    /// Caller must guarantee value of self to be None or Some(Both).
     unsafe fn both_unchecked(self) -> Option<EdgeBoth<N, E>>;
+    /// Returns data from both nodes and the edge.
+    /// # Panics
+    /// Panics with `msg` if self is None or a Loop.
+    fn expect_both(self, msg : &str) -> EdgeBoth<N, E>;
+    /// Borrows data from the source node in place.
+    fn this_mut(&mut self) -> Option<&mut N>;
+    /// Borrows data from the destination node in place, the source node if the edge is a Loop.
+    fn that_mut(&mut self) -> Option<&mut N>;
+    /// Borrows the edge data in place.
+    fn edge_mut(&mut self) -> Option<&mut E>;
+    /// Splits the view like `Edge::split`.
+    fn split(self) -> Option<(N, Option<N>, E)>;
+    /// Returns data from both nodes and the edge as a tuple. Returns None if self is a Loop.
+    fn unzip_both(self) -> Option<(N, N, E)>;
 }
 
 impl <N, E> OptionEdge<N, E> for Option<Edge<N, E>>
@@ -66,6 +84,40 @@ impl <N, E> OptionEdge<N, E> for Option<Edge<N, E>>
             x.edge()
         })
     }
+
+    fn expect_both(self, msg : &str) -> EdgeBoth<N, E> {
+        self.both().expect(msg)
+    }
+
+    fn this_mut(&mut self) -> Option<&mut N> {
+        self.as_mut().map(|x| {
+            x.as_mut().split().0
+        })
+    }
+
+    fn that_mut(&mut self) -> Option<&mut N> {
+        self.as_mut().map(|x| {
+            x.as_mut().that().this
+        })
+    }
+
+    fn edge_mut(&mut self) -> Option<&mut E> {
+        self.as_mut().map(|x| {
+            x.as_mut().edge()
+        })
+    }
+
+    fn split(self) -> Option<(N, Option<N>, E)> {
+        self.map(|x| {
+            x.split()
+        })
+    }
+
+    fn unzip_both(self) -> Option<(N, N, E)> {
+        self.both().map(|x| {
+            (x.this, x.that, x.edge)
+        })
+    }
 }
 
 impl <N, E> Edge<N, E> {
@@ -112,4 +164,109 @@ impl <N, E> Edge<N, E> {
     {
         self.this().edge
     }
-}
\ No newline at end of file
+
+    /// Returns data from the source node, data from the destination node or None if self is a Loop, and the edge data.
+    pub fn split(self) -> (N, Option<N>, E)
+    {
+        match self {
+            Both(s) => (s.this, Some(s.that), s.edge),
+            Loop(s) => (s.this, None, s.edge),
+        }
+    }
+
+    /// Applies `f` to the data of every node in the view.
+    pub fn map_nodes<M>(self, mut f : impl FnMut(N) -> M) -> Edge<M, E>
+    {
+        match self {
+            Both(s) => Both(EdgeBoth { this : f(s.this), that : f(s.that), edge : s.edge }),
+            Loop(s) => Loop(EdgeLoop { this : f(s.this), edge : s.edge }),
+        }
+    }
+
+    /// Applies `f` to the edge data.
+    pub fn map_edge<F>(self, f : impl FnOnce(E) -> F) -> Edge<N, F>
+    {
+        match self {
+            Both(s) => Both(EdgeBoth { this : s.this, that : s.that, edge : f(s.edge) }),
+            Loop(s) => Loop(EdgeLoop { this : s.this, edge : f(s.edge) }),
+        }
+    }
+
+    /// Returns a view borrowing the values of self.
+    pub fn as_ref(&self) -> Edge<&N, &E>
+    {
+        match self {
+            Both(s) => Both(EdgeBoth { this : &s.this, that : &s.that, edge : &s.edge }),
+            Loop(s) => Loop(EdgeLoop { this : &s.this, edge : &s.edge }),
+        }
+    }
+
+    /// Returns a view mutably borrowing the values of self.
+    pub fn as_mut(&mut self) -> Edge<&mut N, &mut E>
+    {
+        match self {
+            Both(s) => Both(EdgeBoth { this : &mut s.this, that : &mut s.that, edge : &mut s.edge }),
+            Loop(s) => Loop(EdgeLoop { this : &mut s.this, edge : &mut s.edge }),
+        }
+    }
+}
+
+impl <E, T> GraphItem<E, T> {
+    /// Applies `f` to the edge data leaving the pointer intact.
+    pub fn map_values<F>(self, f : impl FnOnce(E) -> F) -> GraphItem<F, T>
+    {
+        GraphItem { values : f(self.values), ptr : self.ptr }
+    }
+
+    /// Returns the edge data and the pointer.
+    pub fn into_parts(self) -> (E, T)
+    {
+        (self.values, self.ptr)
+    }
+}
+
+/// An iterator over the items of `edges`, `edges_mut` and similar methods which only yields the items whose
+/// edge data satisfies a predicate. Created by `GraphItemIteratorExt::filter_edges`.
+pub struct FilterEdges<I, P> {
+    iter : I,
+    pred : P,
+}
+
+impl <I, P, E, T> Iterator for FilterEdges<I, P>
+where I : Iterator<Item = GraphItem<E, T>>,
+      P : FnMut(&E) -> bool
+{
+    type Item = GraphItem<E, T>;
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        let pred = &mut self.pred;
+        self.iter.find(|x| pred(&x.values))
+    }
+}
+
+/// An add-on to iterators over GraphItems which saves destructuring them in every closure.
+pub trait GraphItemIteratorExt<E, T> : Iterator<Item = GraphItem<E, T>> + Sized {
+    /// Yields pointers to nodes.
+    #[allow(clippy::type_complexity)]
+    fn ptrs(self) -> Map<Self, fn(GraphItem<E, T>) -> T>
+    {
+        self.map(|x| x.ptr)
+    }
+
+    /// Yields edge data.
+    #[allow(clippy::type_complexity)]
+    fn values(self) -> Map<Self, fn(GraphItem<E, T>) -> E>
+    {
+        self.map(|x| x.values)
+    }
+
+    /// Yields only the items whose edge data satisfies `pred`.
+    fn filter_edges<P>(self, pred : P) -> FilterEdges<Self, P>
+    where P : FnMut(&E) -> bool
+    {
+        FilterEdges { iter : self, pred }
+    }
+}
+
+impl <I, E, T> GraphItemIteratorExt<E, T> for I
+where I : Iterator<Item = GraphItem<E, T>> {}
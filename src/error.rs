@@ -0,0 +1,174 @@
+use core::fmt;
+
+/// An error returned when a cursor fails to follow an edge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepError {
+    /// The current node has no edge under the requested key.
+    NoEdge,
+    /// The requested index exceeds the number of edges of the current node.
+    OutOfBounds,
+}
+
+impl fmt::Display for StepError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StepError::NoEdge => write!(f, "no edge under the requested key"),
+            StepError::OutOfBounds => write!(f, "edge index out of bounds"),
+        }
+    }
+}
+
+impl std::error::Error for StepError {}
+
+/// An error returned when adding an edge would create a cycle in a graph required to stay acyclic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WouldCycle;
+
+impl fmt::Display for WouldCycle {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the edge would create a cycle")
+    }
+}
+
+impl std::error::Error for WouldCycle {}
+
+/// An error returned when a graph expected to be bipartite has a cycle of odd length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OddCycle;
+
+impl fmt::Display for OddCycle {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the graph has a cycle of odd length")
+    }
+}
+
+impl std::error::Error for OddCycle {}
+
+/// An error returned when shortest paths are undefined because the graph has a cycle of negative length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NegativeCycle;
+
+impl fmt::Display for NegativeCycle {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the graph has a cycle of negative length")
+    }
+}
+
+impl std::error::Error for NegativeCycle {}
+
+/// An error returned when a path is not backed by the graph. Holds the position of the first node which has no edge
+/// to the next one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BrokenPath(pub usize);
+
+impl fmt::Display for BrokenPath {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the path has no edge after node {}", self.0)
+    }
+}
+
+impl std::error::Error for BrokenPath {}
+
+/// An error returned when a graph fails to load.
+#[derive(Debug)]
+pub enum ImportError {
+    /// Reading the input failed.
+    Io(std::io::Error),
+    /// A row on the given line, counted from 1, lacks a column or a parser rejected one of its columns.
+    Malformed(usize),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Io(e) => write!(f, "failed to read the input: {}", e),
+            ImportError::Malformed(line) => write!(f, "malformed row on line {}", line),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImportError::Io(e) => Some(e),
+            ImportError::Malformed(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ImportError {
+    fn from(e : std::io::Error) -> Self {
+        ImportError::Io(e)
+    }
+}
+
+/// An error of any fallible operation on a graph. Every specific error of the crate except `ImportError` converts
+/// into it, so callers can propagate them with `?` through a single type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GraphError {
+    /// Memory for a node could not be allocated.
+    AllocationFailed,
+    /// A cursor failed to follow an edge.
+    Step(StepError),
+    /// The edge would create a cycle in a graph required to stay acyclic.
+    WouldCycle,
+    /// The graph has a cycle of odd length.
+    OddCycle,
+    /// The graph has a cycle of negative length.
+    NegativeCycle,
+    /// The path has no edge after the node at the given position.
+    BrokenPath(usize),
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::AllocationFailed => write!(f, "failed to allocate memory for a node"),
+            GraphError::Step(e) => e.fmt(f),
+            GraphError::WouldCycle => WouldCycle.fmt(f),
+            GraphError::OddCycle => OddCycle.fmt(f),
+            GraphError::NegativeCycle => NegativeCycle.fmt(f),
+            GraphError::BrokenPath(i) => BrokenPath(*i).fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for GraphError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GraphError::Step(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<StepError> for GraphError {
+    fn from(e : StepError) -> Self {
+        GraphError::Step(e)
+    }
+}
+
+impl From<WouldCycle> for GraphError {
+    fn from(_ : WouldCycle) -> Self {
+        GraphError::WouldCycle
+    }
+}
+
+impl From<OddCycle> for GraphError {
+    fn from(_ : OddCycle) -> Self {
+        GraphError::OddCycle
+    }
+}
+
+impl From<NegativeCycle> for GraphError {
+    fn from(_ : NegativeCycle) -> Self {
+        GraphError::NegativeCycle
+    }
+}
+
+impl From<BrokenPath> for GraphError {
+    fn from(e : BrokenPath) -> Self {
+        GraphError::BrokenPath(e.0)
+    }
+}
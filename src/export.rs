@@ -0,0 +1,107 @@
+use super::*;
+use std::io::{self, Write};
+use core::ops::Index;
+
+/// Writes a row of `src`, `dst` and the edge payload split by `separator` for every edge between nodes accessible from
+/// the root, the format read by `import::edge_list`. Nodes are written with `format_node` and payloads with
+/// `format_edge`; an empty payload column is omitted.
+pub fn edge_list<'id, Root, N, E, S, A : Allocator>(anchor : &Anchor<'_, 'id, GenericGraph<Root, NamedNode<N, E, S>, A>>, mut writer : impl Write,
+                                                    separator : char, mut format_node : impl FnMut(&N) -> String,
+                                                    mut format_edge : impl FnMut(&E) -> String) -> io::Result<()>
+where Root : RootCollection<'static, NamedNode<N, E, S>>,
+      S : BuildHasher + Default
+{
+    let internal = anchor.internal();
+    for x in internal.reachable(&anchor.parent.root) {
+        //(W)
+        let src = unsafe { GraphPtr::from_mut(x, anchor._guard) };
+        let name = format_node(&internal.get_view(src).data);
+        for i in internal.iter(src) {
+            let edge = format_edge(i.values.edge());
+            write!(writer, "{}{}{}", name, separator, format_node(&internal.get_view(i.ptr).data))?;
+            if !edge.is_empty() {
+                write!(writer, "{}{}", separator, edge)?;
+            }
+            writeln!(writer)?;
+        }
+    }
+    Ok(())
+}
+
+/// A copy of the graph made by `GenericGraph::export`. Nothing is shared with the graph, node and edge data are
+/// cloned, so the export can be inspected while the graph is modified. Nodes are numbered by their position in the
+/// graph storage at the moment of the export, see `ptr_to_index`.
+#[derive(Clone, Debug, Default)]
+pub struct GraphExport<N, E> {
+    pub(crate) data : Vec<N>,
+    // Targets and payloads of edges of node i are stored in targets[offsets[i]..offsets[i + 1]] and the same range
+    // of edges.
+    pub(crate) offsets : Vec<usize>,
+    pub(crate) targets : Vec<usize>,
+    pub(crate) edges : Vec<E>,
+    pub(crate) roots : Vec<usize>,
+}
+
+impl <N, E> GraphExport<N, E> {
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize
+    {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.data.is_empty()
+    }
+
+    /// Returns the data of node `index` or None if `index` is out of bounds.
+    pub fn get(&self, index : usize) -> Option<&N>
+    {
+        self.data.get(index)
+    }
+
+    /// Returns targets of edges of node `index`.
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn neighbors(&self, index : usize) -> &[usize]
+    {
+        &self.targets[self.offsets[index]..self.offsets[index + 1]]
+    }
+
+    /// Returns payloads of edges of node `index` in the order of `neighbors`.
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn edges(&self, index : usize) -> &[E]
+    {
+        &self.edges[self.offsets[index]..self.offsets[index + 1]]
+    }
+
+    /// Returns the number of edges of node `index`.
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn degree(&self, index : usize) -> usize
+    {
+        self.neighbors(index).len()
+    }
+
+    /// Returns nodes referenced by the root.
+    pub fn roots(&self) -> &[usize]
+    {
+        &self.roots
+    }
+
+    /// Returns an iterator over indices and data of every node.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &N)>
+    {
+        self.data.iter().enumerate()
+    }
+}
+
+impl <N, E> Index<usize> for GraphExport<N, E> {
+    type Output = N;
+
+    fn index(&self, index : usize) -> &N
+    {
+        &self.data[index]
+    }
+}
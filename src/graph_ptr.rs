@@ -3,6 +3,8 @@ pub use generativity::*;
 
 /// A checked pointer type used to access and traverse graph nodes in the crate. This pointer cannot be dereferenced
 /// and requires the parent anchor object to access the data stored in the collection.
+// Id is a zero-sized marker, generativity just doesn't promise it.
+#[allow(repr_transparent_non_zst_fields)]
 #[repr(transparent)]
 pub struct GraphPtr<'id, T> {
     pub(crate) node : NonNull<T>,
@@ -18,6 +20,24 @@ impl <'id, T> PartialEq for GraphPtr<'id, T> {
 
 impl <'id, T> Eq for GraphPtr<'id, T> {}
 
+/// Pointers are ordered by the addresses of their nodes. Nodes never move while they are allocated, so the order
+/// of two pointers never changes while their anchor exists and pointers can be used as keys of `BTreeMap` and
+/// `BTreeSet`. Addresses depend on the allocator, so the order is not reproducible between runs. Use `stable_id`
+/// or the order of spawning when that is required.
+impl <'id, T> PartialOrd for GraphPtr<'id, T> {
+    fn partial_cmp(&self, other : &Self) -> Option<core::cmp::Ordering>
+    {
+        Some(self.cmp(other))
+    }
+}
+
+impl <'id, T> Ord for GraphPtr<'id, T> {
+    fn cmp(&self, other : &Self) -> core::cmp::Ordering
+    {
+        self.node.cmp(&other.node)
+    }
+}
+
 impl <'id, T> GraphPtr<'id, T> {
     pub(crate) fn as_mut(self) -> *mut T
     {
@@ -58,11 +78,106 @@ impl <'id, T> Hash for GraphPtr<'id, T>  {
     }
 }
 
+/// Prints the address of the node. Use `display` of an anchor to print its position instead.
+impl <'id, T> core::fmt::Debug for GraphPtr<'id, T> {
+    fn fmt(&self, f : &mut core::fmt::Formatter<'_>) -> core::fmt::Result
+    {
+        write!(f, "GraphPtr({:p})", self.node)
+    }
+}
+
 impl <'id, T> Clone for GraphPtr<'id, T> {
     fn clone(&self) -> GraphPtr<'id, T>
     {
-        GraphPtr { node : self.node, _guard : self._guard }
+        *self
     }
 }
 
 impl <'id, T> Copy for GraphPtr<'id, T> {}
+
+// GraphPtr cannot be dereferenced without an anchor of the same graph, which enforces thread safety on its own.
+unsafe impl <'id, T> Send for GraphPtr<'id, T> {}
+unsafe impl <'id, T> Sync for GraphPtr<'id, T> {}
+
+/// A stable handle of a node which stays valid across anchors and cleanups. Use `AnchorMut::stable_id` to obtain one
+/// and `resolve` to get the node back. Ids are unique within a graph and never reused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(pub(crate) u64);
+
+impl NodeId {
+    /// Returns the raw value of the id.
+    pub fn as_u64(self) -> u64
+    {
+        self.0
+    }
+
+    /// Restores an id from a value returned by `as_u64`.
+    pub fn from_u64(id : u64) -> Self
+    {
+        NodeId(id)
+    }
+}
+
+/// A pointer which does not keep its node alive and can be stored across anchors. Use `AnchorMut::downgrade` to
+/// obtain one and `upgrade` to check whether the node is still allocated.
+pub struct WeakGraphPtr<T> {
+    pub(crate) node : *const T,
+    // Stable id of the node, ids are never reused, so a new node in the same memory does not match.
+    pub(crate) generation : u64,
+}
+
+impl <T> WeakGraphPtr<T> {
+    /// Returns a raw pointer to the node. The node might have been dropped.
+    pub fn as_ptr(self) -> *const T
+    {
+        self.node
+    }
+}
+
+impl <T> PartialEq for WeakGraphPtr<T> {
+    fn eq(&self, other : &Self) -> bool
+    {
+        self.generation == other.generation && self.node == other.node
+    }
+}
+
+impl <T> Eq for WeakGraphPtr<T> {}
+
+impl <T> Hash for WeakGraphPtr<T> {
+    fn hash<H: Hasher>(&self, state: &mut H)
+    {
+        self.generation.hash(state);
+    }
+}
+
+impl <T> Clone for WeakGraphPtr<T> {
+    fn clone(&self) -> WeakGraphPtr<T>
+    {
+        *self
+    }
+}
+
+impl <T> Copy for WeakGraphPtr<T> {}
+
+/// A token which keeps a node alive across cleanups until it is passed back to `AnchorMut::unpin`. Nodes are
+/// pinned once per token. The token is branded by the anchor which pinned the node, use `AnchorMut::pinned` to
+/// access the node through later anchors.
+#[must_use = "the node stays pinned until the token is passed to `unpin`"]
+pub struct PinToken<'id, T> {
+    pub(crate) node : GraphPtr<'id, T>,
+    pub(crate) id : u64,
+}
+
+impl <'id, T> PinToken<'id, T> {
+    /// Returns a pointer to the pinned node.
+    pub fn ptr(&self) -> GraphPtr<'id, T>
+    {
+        self.node
+    }
+
+    /// Returns a raw pointer to the pinned node. The node is not dropped by cleanup while the token exists.
+    pub fn as_ptr(&self) -> *const T
+    {
+        self.node.as_ptr()
+    }
+}
@@ -2,12 +2,23 @@
 
 use super::*;
 
-use unsafer::shared_box::*;
 use unsafer::pointers::*;
 use unsafer::assume::*;
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::hash_map::Entry;
+use std::collections::btree_map::Entry as BTreeEntry;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+#[cfg(feature = "parallel")]
+use std::sync::atomic::AtomicBool;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use core::mem::size_of;
+use crate::node_pool::NodePool;
+use core::ops::RangeBounds;
 
+/// An item of edge iterators: a view into the edge and a pointer to the node it leads to.
 pub struct GraphItem<E, T> {
     /// Edge data.
     pub values : E,
@@ -15,27 +26,227 @@ pub struct GraphItem<E, T> {
     pub ptr : T,
 }
 
-pub (crate) struct GraphRaw<T> {
-    pub(crate) data : Vec<SharedBox<T>>,
+// An owning pointer to a node. Nodes are released with `GraphRaw::free`.
+pub(crate) struct NodeBox<T>(NonNull<T>);
+
+impl <T> NodeBox<T> {
+    pub(crate) fn as_ptr(&self) -> *mut T
+    {
+        self.0.as_ptr()
+    }
+}
+
+pub (crate) struct GraphRaw<T, A : Allocator = Global> {
+    pub(crate) data : Vec<NodeBox<T>>,
+    pub(crate) pool : NodePool<T>,
+    // Allocates memory of the pool.
+    pub(crate) allocator : A,
     pub(crate) cleanup_gen : CleanupGen,
+    pub(crate) shrink : ShrinkPolicy,
+    // Number of spawns, removed edges, removed nodes and mutable views handed out since the last cleanup, each of
+    // them might have left a node inaccessible.
+    pub(crate) changes : usize,
+    // Nodes stored before this index belong to the old generation and are skipped by the minor cleanup.
+    pub(crate) old : usize,
+    // Old nodes which might point to young nodes.
+    pub(crate) remembered : Vec<*mut T>,
+    // Number of minor cleanups since the last full one.
+    pub(crate) minor_cleanups : usize,
+    // Nodes modified since the last `drain_dirty`, only recorded while dirty tracking is enabled.
+    pub(crate) dirty : Vec<*mut T>,
+    pub(crate) track_dirty : bool,
+    // Receives nodes dropped by cleanup.
+    pub(crate) on_collect : Option<Box<dyn FnMut(T) + Send>>,
+    // Receive every change made through anchors and cursors.
+    pub(crate) observers : Vec<Box<dyn FnMut(GraphEvent<T>) + Send>>,
+    // Nodes treated as a part of the root and the number of tokens pinning each of them.
+    pub(crate) pinned : HashMap<*const T, usize>,
+    // Nodes pinned by every live token.
+    pub(crate) pins : HashMap<u64, *const T>,
+    // Nodes which were given a stable id.
+    pub(crate) ids : HashMap<u64, *const T>,
+    pub(crate) next_id : u64,
+    // Number of nodes ever spawned.
+    pub(crate) spawned : u64,
+    // Generation of mark bits, marks of other generations read as zero. Never 0, the generation of new nodes.
+    pub(crate) mark_gen : u32,
+    // Number of node modifications recorded while modification tracking was enabled.
+    pub(crate) modified : u64,
+    pub(crate) track_modified : bool,
+    // Rebuilt lazily through shared references, hence the Mutex.
+    pub(crate) reverse : Mutex<ReverseIndex<T>>,
+    // Index of user keys, updated lazily through shared references, hence the Mutex.
+    pub(crate) key_index : Mutex<Option<Box<dyn KeyIndexHook<T> + Send>>>,
+    // Topological order of every node, maintained in acyclic mode only.
+    pub(crate) topo : Option<TopoOrder<T>>,
+}
+
+// GraphRaw owns its nodes, every raw pointer it stores points either to one of them or into memory of the pool,
+// so moving the graph to another thread moves the nodes along. `on_collect`, `observers` and `key_index` are Send.
+unsafe impl <T : Send, A : Allocator + Send> Send for GraphRaw<T, A> {}
+// Shared references only read nodes and the storage, `reverse` and `key_index` are the only state updated through
+// them. `allocator`, `on_collect` and `observers` are only used through mutable references.
+unsafe impl <T : Sync, A : Allocator> Sync for GraphRaw<T, A> {}
+
+impl <T, A : Allocator> Drop for GraphRaw<T, A> {
+    fn drop(&mut self)
+    {
+        for i in core::mem::take(&mut self.data) {
+            //the graph is being dropped along with every node
+            drop(unsafe { self.free(i) });
+        }
+        self.pool.release(&self.allocator);
+    }
+}
+
+// Maps nodes to the sources of edges pointing to them, a source is listed once per edge. Follows edges added and
+// removed by the graph and is rebuilt lazily once node views were handed out.
+pub(crate) struct ReverseIndex<T> {
+    enabled : bool,
+    // Set once node views were handed out, edges might have changed without the index knowing.
+    stale : bool,
+    sources : HashMap<*const T, Vec<*const T>>,
+}
+
+impl <T> ReverseIndex<T> {
+    fn add(&mut self, src : *const T, dst : *const T)
+    {
+        if self.enabled && !self.stale {
+            self.sources.entry(dst).or_default().push(src);
+        }
+    }
+
+    fn remove(&mut self, src : *const T, dst : *const T)
+    {
+        if !self.enabled || self.stale {
+            return;
+        }
+        if let Some(sources) = self.sources.get_mut(&dst) {
+            if let Some(i) = sources.iter().position(|x| core::ptr::eq(*x, src)) {
+                sources.swap_remove(i);
+            }
+            if sources.is_empty() {
+                self.sources.remove(&dst);
+            }
+        }
+    }
+}
+
+// Nodes sorted so that every edge points forward, along with the position of every node.
+pub(crate) struct TopoOrder<T> {
+    pub(crate) order : Vec<*mut T>,
+    pub(crate) index : HashMap<*const T, usize>,
+    // Set once node views were handed out, edges might have changed without the order knowing.
+    pub(crate) stale : bool,
 }
 
+// Id of the next pin token. Shared by every graph so that tokens of different graphs never collide.
+static NEXT_PIN : AtomicU64 = AtomicU64::new(0);
+
+// Heuristic cleanup is skipped unless at least 1/GARBAGE_RATIO of the young generation might be garbage.
+const GARBAGE_RATIO : usize = 4;
+// Number of cleanups a node has to survive to be promoted to the old generation.
+const PROMOTION_AGE : u8 = 3;
+// Every MAJOR_INTERVAL-th heuristic cleanup which traverses the graph is a full one, so old garbage is dropped
+// eventually.
+const MAJOR_INTERVAL : usize = 8;
+
 pub struct CleanupState<'this, T> 
 {
-    parent : &'this mut GraphRaw<T>,
+    marker : Marker<'this, T>,
     queue : VecDeque<*mut T>,
     index : usize
 }
 
+enum Marker<'this, T> {
+    /// Moves touched nodes to the start of the storage.
+    Storage(&'this mut dyn Touch<T>),
+    /// Records touched nodes without modifying the graph.
+    Visited(HashSet<*mut T>),
+    /// Passes every touched pointer to a callback without traversing any further.
+    Callback(&'this mut dyn FnMut(*mut T)),
+    /// Sets flags shared between threads, indexed by the store index of a node.
+    #[cfg(feature = "parallel")]
+    Shared(&'this [AtomicBool]),
+}
+
+// Hides the allocator of the graph from the cleanup state.
+trait Touch<T> {
+    fn touch(&mut self, frontier : usize, item : *mut T) -> bool;
+}
+
+impl <NodeType : GraphNode, A : Allocator> Touch<NodeType> for GraphRaw<NodeType, A> {
+    fn touch(&mut self, frontier : usize, item : *mut NodeType) -> bool
+    {
+        GraphRaw::touch(self, frontier, item)
+    }
+}
+
+// Allows sharing node pointers with worker threads. Sound as long as the nodes are Sync.
+#[cfg(feature = "parallel")]
+struct SendPtr<T>(T);
+
+#[cfg(feature = "parallel")]
+unsafe impl <T> Send for SendPtr<T> {}
+
+#[cfg(feature = "parallel")]
+unsafe impl <T> Sync for SendPtr<T> {}
+
+// Smallest amount of nodes worth a separate task.
+#[cfg(feature = "parallel")]
+const PARALLEL_CHUNK : usize = 1024;
+
 impl <'this, NodeType : 'this> CleanupState<'this, NodeType>
 where NodeType : GraphNode
 {
-    pub fn touch(&mut self, node : *mut NodeType) {
-        if self.parent.touch(self.index, node) {
+    // Creates a state which passes every touched pointer to `f` without traversing any further.
+    pub(crate) fn from_callback(f : &'this mut dyn FnMut(*mut NodeType)) -> Self
+    {
+        CleanupState { marker : Marker::Callback(f), index : 0, queue : VecDeque::new() }
+    }
+
+    pub(crate) fn touch(&mut self, node : *mut NodeType) {
+        let fresh = match &mut self.marker {
+            Marker::Storage(parent) => parent.touch(self.index, node),
+            Marker::Visited(visited) => visited.insert(node),
+            Marker::Callback(f) => {
+                f(node);
+                false
+            }
+            #[cfg(feature = "parallel")]
+            Marker::Shared(flags) => {
+                let index = unsafe { (*node).meta().store_index };
+                !flags[index].swap(true, AtomicOrdering::Relaxed)
+            }
+        };
+        if fresh {
             self.index += 1;
             self.queue.push_back(node);
         }
     }
+
+    // Touches every node accessible from `root` or `pinned` and passes it to `visit` in breadth-first order.
+    // Nodes are only read by traverse, so (E) applies.
+    fn mark<'id>(&mut self, root : &impl RootCollection<'id, NodeType>, pinned : &[*mut NodeType],
+                 mut visit : impl FnMut(*mut NodeType))
+    {
+        RootCollection::traverse(root, self);
+        for i in pinned {
+            self.touch(*i);
+        }
+
+        while let Some(q) = self.queue.pop_front() {
+            visit(q);
+            unsafe {
+                (*q).traverse(self);
+            }
+        }
+    }
+}
+
+pub(crate) fn peek<T>(node : &NodeBox<T>) -> *const T
+{
+    node.as_ptr()
 }
 
 //Invariant Q: A graph node only contains references to existing nodes.
@@ -51,13 +262,12 @@ where NodeType : GraphNode
 // 2. A reference bound to &self/&mut self  is dropped when another function bound to &mut self is called.
 // 3. A GraphPtr can only be dereferenced by calling a function bound to &self.
 
-impl <'a, N : 'a, NodeType> GraphRaw<NodeType>
+impl <'a, N : 'a, NodeType, A : Allocator> GraphRaw<NodeType, A>
 where NodeType : GraphNode<Node = N>
 {
     pub(crate) fn spawn_detached(&mut self, data : N) -> *const NodeType
     {
-        let node = Box::new(NodeType::from_data(data));
-        let mut node : SharedBox<_> = node.into();
+        let node = self.alloc(NodeType::from_data(data));
         let ptr = node.as_ptr();
 
         unsafe {
@@ -65,12 +275,37 @@ where NodeType : GraphNode<Node = N>
             let r = bind.get_mut(ptr).meta_mut();
             r.store_index = self.data.len();
             r.cleanup_gen = self.cleanup_gen;
+            r.serial = self.spawned;
         }
+        self.spawned += 1;
 
         self.data.push(node);
+        self.mark_dirty(ptr);
+        if let Some(topo) = &mut self.topo {
+            topo.index.insert(ptr, topo.order.len());
+            topo.order.push(ptr);
+        }
+        self.notify(GraphEvent::NodeSpawned(ptr));
+        trace_event!(nodes = self.data.len(), "spawn");
         ptr
     }
 
+    // Same as `spawn_detached`, but reserves every piece of memory the spawn needs beforehand and fails if it can't.
+    pub(crate) fn try_spawn_detached(&mut self, data : N) -> Result<*const NodeType, GraphError>
+    {
+        let failed = |_| GraphError::AllocationFailed;
+        self.data.try_reserve(1).map_err(failed)?;
+        if self.track_dirty {
+            self.dirty.try_reserve(1).map_err(failed)?;
+        }
+        if let Some(topo) = &mut self.topo {
+            topo.order.try_reserve(1).map_err(failed)?;
+            topo.index.try_reserve(1).map_err(failed)?;
+        }
+        self.pool.try_reserve_one(&self.allocator).map_err(|_| GraphError::AllocationFailed)?;
+        Ok(self.spawn_detached(data))
+    }
+
     // Moves internal pointer to the start of the storage, requires item to be a valid pointer
     // This function is used in the preparatory stage of cleanup before any node is dropped therefore (Q W E)
     // apply.
@@ -85,6 +320,10 @@ where NodeType : GraphNode<Node = N>
         if s.cleanup_gen != self.cleanup_gen {
             s.cleanup_gen = self.cleanup_gen;
             let item_index = s.store_index;
+            if item_index < self.old {
+                //Old nodes stay in place, the remembered ones are traversed separately.
+                return false;
+            }
             s.store_index = frontier;
 
             let old_frontier = unsafe {
@@ -112,22 +351,156 @@ where NodeType : GraphNode<Node = N>
 
     pub(crate) fn get_mut<'id>(&mut self, item : GraphPtr<'id, NodeType>) -> &mut N
     {
+        self.mark_dirty(item.as_mut());
         // (E)
         unsafe {
             (*item.as_mut()).get_mut()
         }
     }
 
+    pub(crate) fn nodes<'id : 'a>(&'a self, guard : Id<'id>) -> impl Iterator<Item = GraphItem<&'a N, GraphPtr<'id, NodeType>>>
+    {
+        self.data.iter().map(move |x| {
+            let p = peek(x);
+            //(W)
+            let ptr = unsafe { GraphPtr::from_ptr(p, guard) };
+            // (E)
+            let values = unsafe { (*p).get() };
+            GraphItem { values, ptr }
+        })
+    }
+
+    pub(crate) fn degree<'id>(&self, item : GraphPtr<'id, NodeType>) -> usize
+    {
+        // (E)
+        unsafe {
+            (*item.as_ptr()).degree()
+        }
+    }
+
+    pub(crate) fn store_index<'id>(&self, item : GraphPtr<'id, NodeType>) -> usize
+    {
+        // (E)
+        unsafe {
+            (*item.as_ptr()).meta().store_index
+        }
+    }
+
+    // Removes every edge pointing to `item` from every allocated node.
+    pub(crate) fn unlink(&mut self, item : *const NodeType)
+    {
+        let mut sources = Vec::new();
+        for i in &self.data {
+            let mut found = false;
+            Self::for_each_target(i.as_ptr(), |x| found |= core::ptr::eq(x, item));
+            if found {
+                sources.push(i.as_ptr());
+            }
+        }
+        for i in sources {
+            // (E)
+            unsafe {
+                (*i).unlink(item);
+            }
+            let mut found = false;
+            Self::for_each_target(i, |x| found |= core::ptr::eq(x, item));
+            assert!(!found, "the edge collection does not implement unlink");
+            self.mark_dirty(i);
+            self.notify(GraphEvent::EdgeRemoved { src : i, dst : item });
+        }
+    }
+
+    // Checks whether `root` holds a pointer to `item`.
+    pub(crate) fn root_contains<'id>(root : &impl RootCollection<'id, NodeType>, item : *const NodeType) -> bool
+    {
+        let mut found = false;
+        let mut f = |x : *mut NodeType| found |= core::ptr::eq(x, item);
+        RootCollection::traverse(root, &mut CleanupState::from_callback(&mut f));
+        found
+    }
+
+    // Returns pointers to `items` or None if some of them are equal.
+    pub(crate) fn disjoint<'id, const K : usize>(&mut self, items : [GraphPtr<'id, NodeType>; K]) -> Option<[*mut NodeType; K]>
+    {
+        for i in 0..K {
+            if items[..i].contains(&items[i]) {
+                return None;
+            }
+        }
+        Some(items.map(|x| {
+            self.remember(x.as_mut());
+            x.as_mut()
+        }))
+    }
+
+    pub(crate) fn swap_data<'id>(&mut self, a : GraphPtr<'id, NodeType>, b : GraphPtr<'id, NodeType>)
+    {
+        if let Some([a, b]) = self.disjoint([a, b]) {
+            // (E)
+            unsafe {
+                core::mem::swap((*a).get_mut(), (*b).get_mut());
+            }
+        }
+    }
+
+    pub(crate) fn clear_edges<'id>(&mut self, item : GraphPtr<'id, NodeType>)
+    {
+        if !self.observers.is_empty() {
+            let mut targets = Vec::new();
+            Self::for_each_target(item.as_mut(), |x| targets.push(x as *const NodeType));
+            for dst in targets {
+                self.notify(GraphEvent::EdgeRemoved { src : item.as_ptr(), dst });
+            }
+        }
+        self.mark_dirty(item.as_mut());
+        // (E)
+        unsafe {
+            (*item.as_mut()).clear_edges();
+        }
+    }
+
     pub(crate) unsafe fn kill(&mut self, item : *const NodeType)
     {
+        drop(self.take(item));
+        trace_event!(nodes = self.data.len(), "kill");
+    }
+
+    // Removes `item` from the storage and returns the node.
+    pub(crate) unsafe fn take(&mut self, item : *const NodeType) -> NodeType
+    {
+        // targets of the node might become inaccessible
+        self.changes += 1;
         // (E)
+        unsafe {
+            (*(item as *mut NodeType)).forget_targets();
+        }
         let mut bind = Bind::new();
     
         let victim = unsafe {
             bind.get(item)
         };
 
-        let item_index = victim.meta().store_index;
+        let mut item_index = victim.meta().store_index;
+        if self.pinned.remove(&item).is_some() {
+            self.pins.retain(|_, x| !core::ptr::eq(*x, item));
+        }
+        self.ids.remove(&victim.meta().stable_id);
+        self.notify(GraphEvent::NodeCollected(item));
+        if victim.meta().remembered {
+            self.remembered.retain(|x| !core::ptr::eq(*x, item));
+        }
+        if victim.meta().dirty {
+            self.dirty.retain(|x| !core::ptr::eq(*x, item));
+        }
+        if let Some(topo) = &mut self.topo {
+            topo.remove(&[item]);
+        }
+        if item_index < self.old {
+            //Keep the old generation contiguous.
+            self.old -= 1;
+            self.swap_nodes(item_index, self.old);
+            item_index = self.old;
+        }
 
         let last = unsafe {
             let ptr = self.data.last_mut().assume_some().as_ptr();
@@ -140,7 +513,9 @@ where NodeType : GraphNode<Node = N>
             //item_index always points to the current position in the Vec
             assume(|| item_index < self.data.len());
         }
-        self.data.swap_remove(item_index);
+        let node = self.data.swap_remove(item_index);
+        //the node was removed from the storage
+        unsafe { self.free(node) }
     }
 
     pub(crate) fn get_edge_raw<E : 'a>(&'a self, src : GraphPtr<'static, NodeType>, dst : GraphPtr<'static, NodeType>, edge : &'a E)
@@ -168,9 +543,9 @@ where NodeType : GraphNode<Node = N>
         }
     }
 
-    pub(crate) fn iter_from_raw<'id : 'a, Iter : 'a, E : 'a>(&'a self, src : GraphPtr<'id, NodeType>, iter : Iter)
+    pub(crate) fn iter_from_raw<'id : 'a, Iter, E : 'a>(&'a self, src : GraphPtr<'id, NodeType>, iter : Iter)
                -> impl Iterator<Item = GraphItem<Edge<&'a N, &'a E>, GraphPtr<'id, NodeType>>>
-    where Iter : Iterator<Item = (*const NodeType, &'a E)>
+    where Iter : Iterator<Item = (*const NodeType, &'a E)> + 'a
     {
         let g = src._guard;
         let current = src.as_ptr();
@@ -190,12 +565,18 @@ where NodeType : GraphNode<Node = N>
         })
     }
 
-    pub(crate) fn iter_mut_from_raw<'id : 'a, Iter : 'a, E: 'a>(&'a mut self, src : GraphPtr<'id, NodeType>, iter : Iter)
+    pub(crate) fn iter_mut_from_raw<'id : 'a, Iter, E: 'a>(&'a mut self, src : GraphPtr<'id, NodeType>, iter : Iter)
         -> impl Iterator<Item = GraphItem<Edge<&'a mut N, &'a mut E>, GraphPtr<'id, NodeType>>>
-    where Iter : Iterator<Item = (*mut NodeType, &'a mut E)>
+    where Iter : Iterator<Item = (*mut NodeType, &'a mut E)> + 'a
     {
         let g = src._guard;
         let current = src.as_mut();
+        // Every yielded node can be mutated.
+        let mut targets = vec![current];
+        Self::for_each_target(current, |x| targets.push(x));
+        for i in targets {
+            self.mark_dirty(i);
+        }
         // (E)
         iter.map(move |x| {
             let p = x.0;
@@ -214,201 +595,1150 @@ where NodeType : GraphNode<Node = N>
         })
     }
 
-    pub(crate) fn cleanup_precise<'id>(&mut self, root : &impl RootCollection<'id, NodeType>)
+    // Swaps two entries of the storage keeping store indices intact.
+    fn swap_nodes(&mut self, a : usize, b : usize)
     {
-        let mut bind = Bind::new();
-        self.cleanup_gen.flip();
-        let mut state = CleanupState { parent : self, index : 0, queue : VecDeque::new() };
-        RootCollection::traverse(root, &mut state);
+        unsafe {
+            assume(|| a < self.data.len() && b < self.data.len());
+            let pa = self.data[a].as_ptr();
+            let pb = self.data[b].as_ptr();
+            (*pa).meta_mut().store_index = b;
+            (*pb).meta_mut().store_index = a;
+        }
+        self.data.swap(a, b);
+    }
 
-        while let Some(q) = state.queue.pop_front() {
-            unsafe {
-                bind.get_mut(q).traverse(&mut state);
+    // Pins `item` and returns the id of the pin.
+    pub(crate) fn pin(&mut self, item : *const NodeType) -> u64
+    {
+        let id = NEXT_PIN.fetch_add(1, AtomicOrdering::Relaxed);
+        *self.pinned.entry(item).or_insert(0) += 1;
+        self.pins.insert(id, item);
+        id
+    }
+
+    // Releases a pin. Ids of other graphs and pins of dropped nodes are ignored.
+    pub(crate) fn unpin(&mut self, id : u64)
+    {
+        if let Some(item) = self.pins.remove(&id) {
+            let count = self.pinned.get_mut(&item).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                self.pinned.remove(&item);
             }
         }
-        //Every accessible node is stored before index.
-        let index = state.index;
-        self.data.truncate(index);
-        self.data.shrink_to_fit();
     }
-}
 
-impl <N, E> GraphRaw<NamedNode<N, E>>
-{
-    pub(crate) fn get_edge<'id>(&self, src : GraphPtr<'id, NamedNode<N, E>>, dst : GraphPtr<'id, NamedNode<N, E>>)
-               -> Option<Edge<&'_ N, &'_ E>>
+    // Returns the node pinned by `id` or None if the pin is not from this graph or was released.
+    pub(crate) fn pinned(&self, id : u64) -> Option<*const NodeType>
     {
-        //(E)
-        let src = src.into_static();
-        let dst = dst.into_static();
+        self.pins.get(&id).copied()
+    }
 
-        let src_refs = unsafe { &(*src.as_ptr()).internal.refs };
+    // Returns the stable id of `item`, assigning a new one if needed.
+    pub(crate) fn stable_id(&mut self, item : *mut NodeType) -> u64
+    {
+        // (E)
+        let meta = unsafe { (*item).meta_mut() };
+        if meta.stable_id == 0 {
+            meta.stable_id = self.next_id;
+            self.next_id += 1;
+            self.ids.insert(meta.stable_id, item);
+        }
+        meta.stable_id
+    }
 
-        src_refs.get(&dst)
-                .map(move |e| self.get_edge_raw(src, dst, e))
+    pub(crate) fn mark(&self, item : *const NodeType) -> u8
+    {
+        let meta = unsafe { (*item).meta() };
+        if meta.mark_gen == self.mark_gen { meta.mark } else { 0 }
     }
 
-    pub(crate) fn get_edge_mut<'id>(&mut self, src : GraphPtr<'id, NamedNode<N, E>>, dst : GraphPtr<'id, NamedNode<N, E>>)
-               -> Option<Edge<&'_ mut N, &'_ mut E>>
+    pub(crate) fn set_mark(&mut self, item : *mut NodeType, bits : u8)
     {
-        //(E)
-        let src = src.into_static();
-        let dst = dst.into_static();
+        // (E)
+        let meta = unsafe { (*item).meta_mut() };
+        meta.mark = bits;
+        meta.mark_gen = self.mark_gen;
+    }
 
-        let src_refs = unsafe { &mut (*src.as_mut()).internal.refs };
+    // Clears every mark in O(1) by starting a new generation. Sweeps the nodes once the counter wraps around.
+    pub(crate) fn clear_marks(&mut self)
+    {
+        self.mark_gen = self.mark_gen.wrapping_add(1);
+        if self.mark_gen == 0 {
+            for i in &self.data {
+                unsafe { (*i.as_ptr()).meta_mut().mark_gen = 0; }
+            }
+            self.mark_gen = 1;
+        }
+    }
 
-        src_refs.get_mut(&dst)
-                .map(move |e| self.get_edge_mut_raw(src, dst, e))
+    fn pinned_nodes(&self) -> Vec<*mut NodeType>
+    {
+        self.pinned.keys().map(|x| *x as *mut NodeType).collect()
     }
-}
 
-impl <N, E> GraphRaw<VecNode<N, E>>
-{
-    pub(crate) fn get_edge<'id>(&self, src : GraphPtr<'id, VecNode<N, E>>, dst : usize)
-               -> Option<Edge<&'_ N, &'_ E>>
+    // Counts a mutable view handed out to the user, it might drop edges without notifying the graph.
+    pub(crate) fn expose_views(&mut self) -> &mut Self
     {
-        //(E)
-        let src = src.into_static();
-        let src_refs = unsafe { &(*src.as_ptr()).internal.refs };
-        src_refs.get(dst)
-                .map(move |x| self.get_edge_raw(src, x.0, &x.1))
+        self.changes += 1;
+        if let Some(topo) = &mut self.topo {
+            topo.stale = true;
+        }
+        self.reverse.get_mut().unwrap_or_else(|x| x.into_inner()).stale = true;
+        self
     }
 
-    pub(crate) fn get_edge_mut<'id>(&mut self, src : GraphPtr<'id, VecNode<N, E>>, dst : usize)
-               -> Option<Edge<&'_ mut N, &'_ mut E>>
+    // Adds an old node to the remembered set before its edges are modified.
+    pub(crate) fn remember(&mut self, item : *mut NodeType)
     {
-        //(E)
-        let src = src.into_static();
-        let src_refs = unsafe { &mut (*src.as_mut()).internal.refs };
-        src_refs.get_mut(dst)
-                .map(move |x| self.get_edge_mut_raw(src, x.0, &mut x.1))
+        let meta = unsafe { (*item).meta_mut() };
+        if meta.store_index < self.old && !meta.remembered {
+            meta.remembered = true;
+            self.remembered.push(item);
+        }
+        self.mark_dirty(item);
     }
-}
 
-impl <N, E> GraphRaw<OptionNode<N, E>>
-{
-    pub(crate) fn get_edge<'id>(&self, src : GraphPtr<'id, OptionNode<N, E>>)
-               -> Option<Edge<&'_ N, &'_ E>>
+    pub(crate) fn mark_dirty(&mut self, item : *mut NodeType)
     {
-        //(E)
-        let src = src.into_static();
-        let src_refs = unsafe { (*src.as_ptr()).internal.refs.as_ref() };
-        src_refs.map(move |x| self
-                .get_edge_raw(src, x.0, &x.1))
+        // (E)
+        let meta = unsafe { (*item).meta_mut() };
+        if self.track_modified {
+            self.modified += 1;
+            meta.modified = self.modified;
+        }
+        if self.track_dirty && !meta.dirty {
+            meta.dirty = true;
+            self.dirty.push(item);
+        }
+        if let Some(index) = self.key_index.get_mut().unwrap_or_else(|x| x.into_inner()) {
+            index.touch(item);
+        }
     }
 
-    pub(crate) fn get_edge_mut<'id>(&mut self, src : GraphPtr<'id, OptionNode<N, E>>)
-               -> Option<Edge<&'_ mut N, &'_ mut E>>
+    pub(crate) fn set_dirty_tracking(&mut self, enabled : bool)
     {
-        //(E)
-        let src = src.into_static();
-        let src_refs = unsafe { (*src.as_mut()).internal.refs.as_mut() };
-        src_refs.map(move |x| self
-                .get_edge_mut_raw(src, x.0, &mut x.1))
+        self.drain_dirty();
+        self.track_dirty = enabled;
     }
-}
 
-impl <K, N, E> GraphRaw<TreeNode<K, N, E>> where K : Ord
-{
-    pub(crate) fn get_edge<'id>(&self, src : GraphPtr<'id, TreeNode<K, N, E>>, dst : &K)
-               -> Option<Edge<&'_ N, &'_ E>>
+    // Returns nodes modified since the last call and resets their flags.
+    pub(crate) fn drain_dirty(&mut self) -> Vec<*mut NodeType>
     {
-        //(E)
-        let src = src.into_static();
-        let src_refs = unsafe { &(*src.as_ptr()).internal.refs };
-        src_refs.get(dst).map(move |x| self
-                .get_edge_raw(src, x.0, &x.1))
+        let dirty = core::mem::take(&mut self.dirty);
+        for &i in &dirty {
+            // (E)
+            unsafe {
+                (*i).meta_mut().dirty = false;
+            }
+        }
+        dirty
     }
 
-    pub(crate) fn get_edge_mut<'id>(&mut self, src : GraphPtr<'id, TreeNode<K, N, E>>, dst : &K)
-               -> Option<Edge<&'_ mut N, &'_ mut E>>
+    // Returns true if `item` has an edge to a node of the young generation.
+    fn points_to_young(&self, item : *mut NodeType) -> bool
     {
-        //(E)
-        let src = src.into_static();
-        let src_refs = unsafe { &mut (*src.as_mut()).internal.refs };
-        src_refs.get_mut(dst).map(move |x| self
-                .get_edge_mut_raw(src, x.0, &mut x.1))
+        let mut res = false;
+        Self::for_each_target(item, |x| res |= unsafe { (*x).meta().store_index } >= self.old);
+        res
     }
-}
 
-macro_rules! impl_graph_raw {
-    ($NodeType:ident, $IterMap:tt, $IterMutMap:tt) => {
-        impl <N, E> GraphRaw<$NodeType<N, E>>
-        {
-            pub(crate) fn bridge<'id>(&mut self, src : GraphPtr<'id, $NodeType<N, E>>,
-                                                 dst : GraphPtr<'id, $NodeType<N, E>>)
-                -> Option<(&'_ mut node_views::$NodeType<'id, N, E>, &'_ mut node_views::$NodeType<'id, N, E>)>
-            {
-                if src != dst { 
-                    //this transmute only affects lifetime parameter
-                    let src = unsafe { (*src.as_mut()).get_view_mut() };
-                    let dst = self.get_view_mut(dst);
-                    Some((src, dst))
-                } else {
-                    None
-                }
-            }
+    // Passes the destination of every edge of `item` to `f`.
+    pub(crate) fn for_each_target(item : *mut NodeType, mut f : impl FnMut(*mut NodeType))
+    {
+        let mut state = CleanupState::from_callback(&mut f);
+        // (E)
+        unsafe {
+            (*item).traverse(&mut state);
+        }
+    }
 
-            pub(crate) fn get_view<'id>(&self, dst : GraphPtr<'id, $NodeType<N, E>>) -> &node_views::$NodeType<'id, N, E>
-            {
-                //(E)
-                unsafe {
-                    (*dst.as_ptr()).get_view()
-                }
-            }
+    pub(crate) fn set_reverse_index(&mut self, enabled : bool)
+    {
+        let index = self.reverse.get_mut().unwrap_or_else(|x| x.into_inner());
+        index.enabled = enabled;
+        index.sources = HashMap::new();
+        //Forces the index to be built on next use.
+        index.stale = true;
+    }
 
-            pub(crate) fn get_view_mut<'id>(&mut self, dst : GraphPtr<'id, $NodeType<N, E>>) -> &mut node_views::$NodeType<'id, N, E>
-            {
-                //(E)
-                unsafe {
-                    (*dst.as_mut()).get_view_mut()
-                }
+    // Returns sources of every edge pointing to `item`.
+    pub(crate) fn in_neighbors(&self, item : *const NodeType) -> Vec<*const NodeType>
+    {
+        let mut index = self.reverse.lock().unwrap_or_else(|x| x.into_inner());
+        if !index.enabled {
+            let mut res = Vec::new();
+            for i in &self.data {
+                let src = peek(i) as *mut NodeType;
+                Self::for_each_target(src, |x| if core::ptr::eq(x, item) {
+                    res.push(src as *const NodeType);
+                });
             }
+            return res;
+        }
 
-            pub(crate) fn iter<'a, 'id : 'a>(&'a self, dst : GraphPtr<'id, $NodeType<N, E>>)
-                       -> impl Iterator<Item = GraphItem<Edge<&'a N, &'a E>, GraphPtr<'id, $NodeType<N, E>>>>
-            {
+        if index.stale {
+            index.sources.clear();
+            for i in &self.data {
+                let src = peek(i) as *mut NodeType;
+                Self::for_each_target(src, |x| {
+                    index.sources.entry(x as *const NodeType).or_default().push(src);
+                });
+            }
+            index.stale = false;
+        }
+        index.sources.get(&item).cloned().unwrap_or_default()
+    }
+
+    // Drops every young node stored after `index`, ages the survivors and promotes the ones old enough.
+    // `candidates` are old nodes which might still belong to the remembered set.
+    fn sweep(&mut self, index : usize, mut candidates : Vec<*mut NodeType>) -> CleanupReport
+    {
+        let nodes_freed = self.data.len() - index;
+        //Dirty nodes are alive here, so their indices can be read.
+        if self.data[index..].iter().any(|x| unsafe { (*x.as_ptr()).meta().dirty }) {
+            self.dirty.retain(|x| unsafe { (**x).meta().store_index } < index);
+        }
+        if let Some(topo) = &mut self.topo {
+            let garbage : Vec<_> = self.data[index..].iter().map(|x| x.as_ptr() as *const NodeType).collect();
+            topo.remove(&garbage);
+        }
+        for i in &self.data[index..] {
+            // (E)
+            unsafe {
+                (*i.as_ptr()).forget_targets();
+            }
+        }
+        let garbage : Vec<_> = self.data.drain(index..).collect();
+        for i in garbage {
+            // (E)
+            let id = unsafe { (*i.as_ptr()).meta().stable_id };
+            if id != 0 {
+                self.ids.remove(&id);
+            }
+            self.notify(GraphEvent::NodeCollected(i.as_ptr()));
+            //Nodes after index are inaccessible.
+            let node = unsafe { self.free(i) };
+            if let Some(f) = &mut self.on_collect {
+                f(node);
+            }
+        }
+        let (shrink_index, shrink_pool) = match self.shrink {
+            ShrinkPolicy::Never => (false, false),
+            ShrinkPolicy::Always => (true, true),
+            ShrinkPolicy::WhenUnusedExceeds(limit) => (self.data.capacity() - self.data.len() > limit,
+                                                       self.pool.capacity() - self.data.len() > limit),
+        };
+        if shrink_index {
+            self.data.shrink_to_fit();
+        }
+        if shrink_pool {
+            self.pool.shrink(&self.allocator);
+        }
+
+        for i in self.old..index {
+            let item = self.data[i].as_ptr();
+            let meta = unsafe { (*item).meta_mut() };
+            meta.age = meta.age.saturating_add(1);
+            if meta.age >= PROMOTION_AGE {
+                let old = self.old;
+                self.swap_nodes(i, old);
+                self.old += 1;
+                candidates.push(item);
+            }
+        }
+
+        for i in candidates {
+            let remembered = self.points_to_young(i);
+            unsafe {
+                (*i).meta_mut().remembered = remembered;
+            }
+            if remembered {
+                self.remembered.push(i);
+            }
+        }
+
+        let report = CleanupReport {
+            nodes_freed,
+            nodes_retained : index,
+            bytes_freed_estimate : nodes_freed * size_of::<NodeType>(),
+        };
+        trace_event!(nodes_freed, nodes_retained = index, bytes_freed_estimate = report.bytes_freed_estimate, "cleanup finished");
+        report
+    }
+
+    pub(crate) fn cleanup_precise<'id>(&mut self, root : &impl RootCollection<'id, NodeType>) -> CleanupReport
+    {
+        trace_span!("cleanup", kind = "precise", nodes = self.data.len());
+        self.cleanup_gen.advance();
+        self.changes = 0;
+        self.minor_cleanups = 0;
+        //Full cleanup dissolves the old generation, survivors are promoted again afterwards.
+        for i in self.remembered.drain(..) {
+            unsafe {
+                (*i).meta_mut().remembered = false;
+            }
+        }
+        self.old = 0;
+
+        let pinned = self.pinned_nodes();
+        let mut state = CleanupState { marker : Marker::Storage(self), index : 0, queue : VecDeque::new() };
+        state.mark(root, &pinned, |_| ());
+        //Every accessible node is stored before index.
+        let index = state.index;
+        self.sweep(index, Vec::new())
+    }
+
+    // Collects the young generation only. Old nodes are assumed to be accessible and only the remembered ones
+    // are traversed.
+    pub(crate) fn cleanup_minor<'id>(&mut self, root : &impl RootCollection<'id, NodeType>) -> CleanupReport
+    {
+        trace_span!("cleanup", kind = "minor", nodes = self.data.len());
+        //Old nodes pointing to young ones are only traversed if remembered. This is an invariant of the graph:
+        //edges only change through `get_view_mut`, `disjoint` and the connect methods, which all call `remember`
+        //on the source before handing out access. The check below costs a full pass over the old generation and
+        //is only run in debug builds.
+        debug_assert!(self.data[..self.old].iter().all(|x| unsafe { (*x.as_ptr()).meta().remembered } || !self.points_to_young(x.as_ptr())),
+                      "an old node points to a young one without being remembered");
+        self.cleanup_gen.advance();
+        self.changes = 0;
+        self.minor_cleanups += 1;
+        let remembered = core::mem::take(&mut self.remembered);
+        let old = self.old;
+
+        let pinned = self.pinned_nodes();
+        let mut state = CleanupState { marker : Marker::Storage(self), index : old, queue : VecDeque::new() };
+        for i in &remembered {
+            unsafe {
+                (**i).traverse(&mut state);
+            }
+        }
+        state.mark(root, &pinned, |_| ());
+        //Every accessible young node is stored between old and index.
+        let index = state.index;
+        self.sweep(index, remembered)
+    }
+
+    // Same as cleanup_precise, but the graph is traversed one breadth-first level at a time with every level
+    // split between rayon tasks. Traversal only reads edge collections and metadata of nodes and never
+    // touches node or edge data, so (E) applies.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn cleanup_parallel<'id>(&mut self, root : &impl RootCollection<'id, NodeType>) -> CleanupReport
+    where NodeType : Sync
+    {
+        trace_span!("cleanup", kind = "parallel", nodes = self.data.len());
+        self.changes = 0;
+        self.minor_cleanups = 0;
+        for i in self.remembered.drain(..) {
+            unsafe {
+                (*i).meta_mut().remembered = false;
+            }
+        }
+        self.old = 0;
+
+        let flags : Vec<_> = self.data.iter().map(|_| AtomicBool::new(false)).collect();
+
+        let mut state = CleanupState { marker : Marker::Shared(&flags), index : 0, queue : VecDeque::new() };
+        RootCollection::traverse(root, &mut state);
+        for i in self.pinned_nodes() {
+            state.touch(i);
+        }
+        let mut frontier : Vec<_> = state.queue.into_iter().map(SendPtr).collect();
+
+        while !frontier.is_empty() {
+            frontier = frontier.par_chunks(PARALLEL_CHUNK).flat_map_iter(|nodes| {
+                let mut state = CleanupState { marker : Marker::Shared(&flags), index : 0, queue : VecDeque::new() };
+                for i in nodes {
+                    unsafe {
+                        (*i.0).traverse(&mut state);
+                    }
+                }
+                state.queue.into_iter().map(SendPtr)
+            }).collect();
+        }
+
+        //Move every touched node to the start of the storage. Positions after i were never swapped, so the node
+        //at i is still indexed by i in flags.
+        let mut index = 0;
+        for (i, flag) in flags.iter().enumerate() {
+            if flag.load(AtomicOrdering::Relaxed) {
+                self.swap_nodes(i, index);
+                let item = self.data[index].as_ptr();
+                unsafe {
+                    (*item).meta_mut().cleanup_gen = self.cleanup_gen;
+                }
+                index += 1;
+            }
+        }
+        self.sweep(index, Vec::new())
+    }
+
+    // Calls `f` on the data of every allocated node, the storage is split between rayon tasks.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn par_for_each_mut(&mut self, f : impl Fn(&mut N) + Sync)
+    where N : Send
+    {
+        for i in 0..self.data.len() {
+            self.mark_dirty(self.data[i].as_ptr());
+        }
+        let data = SendPtr(self.data.as_slice());
+        (0..self.data.len()).into_par_iter().with_min_len(PARALLEL_CHUNK).for_each(|i| {
+            //every node is visited once and the graph is borrowed mutably
+            unsafe {
+                f((*data.0[i].as_ptr()).get_mut());
+            }
+        });
+    }
+
+    // Visits nodes reachable from `src` one breadth-first level at a time, every level is split between rayon
+    // tasks. Tasks only read edge collections and metadata, new levels are merged on the calling thread.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn par_bfs(&self, src : *mut NodeType, visit : impl Fn(*mut NodeType, usize) + Sync)
+    {
+        fn index<NodeType : GraphNode>(item : *mut NodeType) -> usize {
+            // (E)
+            unsafe { (*item).meta().store_index }
+        }
+
+        let mut visited = vec![0u64; self.data.len().div_ceil(64)];
+        visited[index(src) / 64] |= 1 << (index(src) % 64);
+        let mut frontier = vec![SendPtr(src)];
+        let mut depth = 0;
+
+        while !frontier.is_empty() {
+            let seen = &visited;
+            let found : Vec<_> = frontier.par_chunks(PARALLEL_CHUNK).flat_map_iter(|nodes| {
+                let mut found = Vec::new();
+                for i in nodes {
+                    visit(i.0, depth);
+                    Self::for_each_target(i.0, |x| {
+                        let k = index(x);
+                        if seen[k / 64] & (1 << (k % 64)) == 0 {
+                            found.push(SendPtr(x));
+                        }
+                    });
+                }
+                found
+            }).collect();
+
+            frontier = Vec::new();
+            for i in found {
+                let k = index(i.0);
+                if visited[k / 64] & (1 << (k % 64)) == 0 {
+                    visited[k / 64] |= 1 << (k % 64);
+                    frontier.push(i);
+                }
+            }
+            depth += 1;
+        }
+    }
+
+    // Every counted change might have dropped a young node at most.
+    pub(crate) fn garbage_estimate(&self) -> usize
+    {
+        self.changes.min(self.data.len() - self.old)
+    }
+
+    pub(crate) fn cleanup<'id>(&mut self, root : &impl RootCollection<'id, NodeType>) -> CleanupReport
+    {
+        if self.changes * GARBAGE_RATIO < self.data.len() - self.old {
+            CleanupReport { nodes_retained : self.data.len(), ..CleanupReport::default() }
+        } else if self.minor_cleanups + 1 >= MAJOR_INTERVAL {
+            self.cleanup_precise(root)
+        } else {
+            self.cleanup_minor(root)
+        }
+    }
+
+    // Returns every node accessible from `root` in breadth-first order without touching the storage.
+    pub(crate) fn reachable<'id>(&self, root : &impl RootCollection<'id, NodeType>) -> Vec<*mut NodeType>
+    {
+        let mut state = CleanupState { marker : Marker::Visited(HashSet::new()), index : 0, queue : VecDeque::new() };
+        let mut res = Vec::new();
+        state.mark(root, &self.pinned_nodes(), |x| res.push(x));
+        res
+    }
+
+    // Returns every allocated node which is not accessible from `root`.
+    pub(crate) fn unreachable<'id>(&self, root : &impl RootCollection<'id, NodeType>) -> Vec<*const NodeType>
+    {
+        let reachable : HashSet<_> = self.reachable(root).into_iter().map(|x| x as *const NodeType).collect();
+        self.data.iter().map(peek)
+                 .filter(|x| !reachable.contains(x))
+                 .collect()
+    }
+
+    // Copies data of every node along with targets and payloads of its edges. `edges` passes every edge going out
+    // of a node to its last argument.
+    pub(crate) fn export<'id, E : Clone>(&self, root : &impl RootCollection<'id, NodeType>,
+                                         mut edges : impl FnMut(&Self, GraphPtr<'static, NodeType>, &mut dyn FnMut(*mut NodeType, &E)))
+        -> GraphExport<N, E>
+    where N : Clone
+    {
+        fn index<NodeType : GraphNode>(item : *mut NodeType) -> usize {
+            // (E)
+            unsafe { (*item).meta().store_index }
+        }
+
+        let mut data = Vec::with_capacity(self.data.len());
+        let mut offsets = Vec::with_capacity(self.data.len() + 1);
+        let mut targets = Vec::new();
+        let mut payloads = Vec::new();
+        offsets.push(0);
+        for i in &self.data {
+            let item = i.as_ptr();
+            // (E)
+            data.push(unsafe { (*item).get() }.clone());
+            // the pointer does not leave the crate
+            let src = unsafe { GraphPtr::from_mut(item, Id::new()) };
+            edges(self, src, &mut |x, e| {
+                targets.push(index(x));
+                payloads.push(e.clone());
+            });
+            offsets.push(targets.len());
+        }
+
+        let mut roots = Vec::new();
+        let mut f = |x| roots.push(index(x));
+        let mut state = CleanupState::from_callback(&mut f);
+        RootCollection::traverse(root, &mut state);
+        GraphExport { data, offsets, targets, edges : payloads, roots }
+    }
+
+    // Releases unused capacity of the node index and chunks of node memory without nodes.
+    pub(crate) fn shrink_to_fit(&mut self)
+    {
+        self.data.shrink_to_fit();
+        self.pool.shrink(&self.allocator);
+    }
+
+    pub(crate) fn memory_usage(&self) -> MemoryReport
+    {
+        let index_bytes = self.data.capacity() * size_of::<NodeBox<NodeType>>();
+        let node_bytes = self.pool.capacity() * size_of::<NodeType>();
+        // (E)
+        let edge_bytes = self.data.iter().map(|x| unsafe { (*peek(x)).edge_bytes() }).sum();
+        MemoryReport { index_bytes, node_bytes, edge_bytes, total_bytes : index_bytes + node_bytes + edge_bytes }
+    }
+
+    pub(crate) fn stats(&self) -> GraphStats
+    {
+        let node_count = self.data.len();
+        let mut edge_count = 0;
+        let mut max_degree = 0;
+        let mut min_degree = usize::MAX;
+        let mut loops = 0;
+
+        for i in &self.data {
+            // (E)
+            let degree = unsafe { (*peek(i)).degree() };
+            edge_count += degree;
+            max_degree = max_degree.max(degree);
+            min_degree = min_degree.min(degree);
+            Self::for_each_target(i.as_ptr(), |x| if x == i.as_ptr() { loops += 1 });
+        }
+
+        if node_count == 0 {
+            return GraphStats::default();
+        }
+
+        let avg_degree = edge_count as f64 / node_count as f64;
+        let density = if node_count > 1 {
+            (edge_count - loops) as f64 / (node_count * (node_count - 1)) as f64
+        } else {
+            0.0
+        };
+        GraphStats { node_count, edge_count, density, max_degree, min_degree, avg_degree }
+    }
+}
+
+impl <N, E, S : BuildHasher + Default, A : Allocator> GraphRaw<NamedNode<N, E, S>, A>
+{
+    pub(crate) fn get_edge<'id>(&self, src : GraphPtr<'id, NamedNode<N, E, S>>, dst : GraphPtr<'id, NamedNode<N, E, S>>)
+               -> Option<Edge<&'_ N, &'_ E>>
+    {
+        //(E)
+        let src = src.into_static();
+        let dst = dst.into_static();
+
+        let src_refs = unsafe { &(*src.as_ptr()).internal.refs };
+
+        src_refs.get(&dst)
+                .map(move |e| self.get_edge_raw(src, dst, e))
+    }
+
+    pub(crate) fn get_edge_mut<'id>(&mut self, src : GraphPtr<'id, NamedNode<N, E, S>>, dst : GraphPtr<'id, NamedNode<N, E, S>>)
+               -> Option<Edge<&'_ mut N, &'_ mut E>>
+    {
+        //(E)
+        let src = src.into_static();
+        let dst = dst.into_static();
+
+        let src_refs = unsafe { &mut (*src.as_mut()).internal.refs };
+
+        src_refs.get_mut(&dst)
+                .map(move |e| self.get_edge_mut_raw(src, dst, e))
+    }
+}
+
+impl <N, E, A : Allocator> GraphRaw<VecNode<N, E>, A>
+{
+    pub(crate) fn get_edge<'id>(&self, src : GraphPtr<'id, VecNode<N, E>>, dst : usize)
+               -> Option<Edge<&'_ N, &'_ E>>
+    {
+        //(E)
+        let src = src.into_static();
+        let src_refs = unsafe { &(*src.as_ptr()).internal.refs };
+        src_refs.get(dst)
+                .map(move |x| self.get_edge_raw(src, x.0, &x.1))
+    }
+
+    pub(crate) fn get_edge_mut<'id>(&mut self, src : GraphPtr<'id, VecNode<N, E>>, dst : usize)
+               -> Option<Edge<&'_ mut N, &'_ mut E>>
+    {
+        //(E)
+        let src = src.into_static();
+        let src_refs = unsafe { &mut (*src.as_mut()).internal.refs };
+        src_refs.get_mut(dst)
+                .map(move |x| self.get_edge_mut_raw(src, x.0, &mut x.1))
+    }
+}
+
+impl <N, E, A : Allocator> GraphRaw<OptionNode<N, E>, A>
+{
+    pub(crate) fn get_edge<'id>(&self, src : GraphPtr<'id, OptionNode<N, E>>)
+               -> Option<Edge<&'_ N, &'_ E>>
+    {
+        //(E)
+        let src = src.into_static();
+        let src_refs = unsafe { (*src.as_ptr()).internal.refs.as_ref() };
+        src_refs.map(move |x| self
+                .get_edge_raw(src, x.0, &x.1))
+    }
+
+    pub(crate) fn get_edge_mut<'id>(&mut self, src : GraphPtr<'id, OptionNode<N, E>>)
+               -> Option<Edge<&'_ mut N, &'_ mut E>>
+    {
+        //(E)
+        let src = src.into_static();
+        let src_refs = unsafe { (*src.as_mut()).internal.refs.as_mut() };
+        src_refs.map(move |x| self
+                .get_edge_mut_raw(src, x.0, &mut x.1))
+    }
+}
+
+impl <K, N, E, A : Allocator> GraphRaw<TreeNode<K, N, E>, A> where K : Ord
+{
+    pub(crate) fn get_edge<'id>(&self, src : GraphPtr<'id, TreeNode<K, N, E>>, dst : &K)
+               -> Option<Edge<&'_ N, &'_ E>>
+    {
+        //(E)
+        let src = src.into_static();
+        let src_refs = unsafe { &(*src.as_ptr()).internal.refs };
+        src_refs.get(dst).map(move |x| self
+                .get_edge_raw(src, x.0, &x.1))
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn get_edge_mut<'id>(&mut self, src : GraphPtr<'id, TreeNode<K, N, E>>, dst : &K)
+               -> Option<Edge<&'_ mut N, &'_ mut E>>
+    {
+        //(E)
+        let src = src.into_static();
+        let src_refs = unsafe { &mut (*src.as_mut()).internal.refs };
+        src_refs.get_mut(dst).map(move |x| self
+                .get_edge_mut_raw(src, x.0, &mut x.1))
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn keyed_item<'a, 'id : 'a>(&'a self, src : GraphPtr<'id, TreeNode<K, N, E>>,
+                               item : (&'a K, &'a (GraphPtr<'static, TreeNode<K, N, E>>, E)))
+               -> (&'a K, GraphItem<Edge<&'a N, &'a E>, GraphPtr<'id, TreeNode<K, N, E>>>)
+    {
+        let (key, (dst, edge)) = item;
+        //(W)
+        let ptr = unsafe { GraphPtr::from_ptr(dst.as_ptr(), src._guard) };
+        (key, GraphItem { values : self.get_edge_raw(src.into_static(), *dst, edge), ptr })
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn range<'a, 'id : 'a, R>(&'a self, src : GraphPtr<'id, TreeNode<K, N, E>>, range : R)
+               -> impl DoubleEndedIterator<Item = (&'a K, GraphItem<Edge<&'a N, &'a E>, GraphPtr<'id, TreeNode<K, N, E>>>)>
+    where R : RangeBounds<K>
+    {
+        //(E)
+        let src_refs = unsafe { &(*src.as_ptr()).internal.refs };
+        src_refs.range(range).map(move |x| self.keyed_item(src, x))
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn first_edge<'a, 'id : 'a>(&'a self, src : GraphPtr<'id, TreeNode<K, N, E>>)
+               -> Option<(&'a K, GraphItem<Edge<&'a N, &'a E>, GraphPtr<'id, TreeNode<K, N, E>>>)>
+    {
+        //(E)
+        let src_refs = unsafe { &(*src.as_ptr()).internal.refs };
+        src_refs.iter().next().map(|x| self.keyed_item(src, x))
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn last_edge<'a, 'id : 'a>(&'a self, src : GraphPtr<'id, TreeNode<K, N, E>>)
+               -> Option<(&'a K, GraphItem<Edge<&'a N, &'a E>, GraphPtr<'id, TreeNode<K, N, E>>>)>
+    {
+        //(E)
+        let src_refs = unsafe { &(*src.as_ptr()).internal.refs };
+        src_refs.iter().next_back().map(|x| self.keyed_item(src, x))
+    }
+}
+
+macro_rules! impl_graph_raw {
+    ($NodeType:ident, $IterMap:tt, $IterMutMap:tt $(, $S:ident)?) => {
+        impl <N, E $(, $S : BuildHasher + Default)?, A : Allocator> GraphRaw<$NodeType<N, E $(, $S)?>, A>
+        {
+            pub(crate) fn bridge<'id>(&mut self, src : GraphPtr<'id, $NodeType<N, E $(, $S)?>>,
+                                                 dst : GraphPtr<'id, $NodeType<N, E $(, $S)?>>)
+                -> Option<(&'_ mut node_views::$NodeType<'id, N, E $(, $S)?>, &'_ mut node_views::$NodeType<'id, N, E $(, $S)?>)>
+            {
+                if src != dst { 
+                    self.remember(src.as_mut());
+                    //this transmute only affects lifetime parameter
+                    let src = unsafe { (*src.as_mut()).get_view_mut() };
+                    let dst = self.get_view_mut(dst);
+                    Some((src, dst))
+                } else {
+                    None
+                }
+            }
+
+            pub(crate) fn get_view<'id>(&self, dst : GraphPtr<'id, $NodeType<N, E $(, $S)?>>) -> &node_views::$NodeType<'id, N, E $(, $S)?>
+            {
+                //(E)
+                unsafe {
+                    (*dst.as_ptr()).get_view()
+                }
+            }
+
+            pub(crate) fn get_view_mut<'id>(&mut self, dst : GraphPtr<'id, $NodeType<N, E $(, $S)?>>) -> &mut node_views::$NodeType<'id, N, E $(, $S)?>
+            {
+                self.remember(dst.as_mut());
+                //(E)
+                unsafe {
+                    (*dst.as_mut()).get_view_mut()
+                }
+            }
+
+            pub(crate) fn iter<'a, 'id : 'a>(&'a self, dst : GraphPtr<'id, $NodeType<N, E $(, $S)?>>)
+                       -> impl Iterator<Item = GraphItem<Edge<&'a N, &'a E>, GraphPtr<'id, $NodeType<N, E $(, $S)?>>>>
+            {
                 //(E)
                 let current = dst.as_ptr();
                 let node_refs = unsafe { &(*current).internal.refs };
                 self.iter_from_raw(dst, node_refs.iter().map($IterMap))
             }
 
-            pub(crate) fn iter_mut<'a, 'id : 'a>(&'a mut self, src : GraphPtr<'id, $NodeType<N, E>>)
-                        -> impl Iterator<Item = GraphItem<Edge<&'a mut N, &'a mut E>, GraphPtr<'id, $NodeType<N, E>>>>
-            {
-                //(E)
-                let current = src.as_mut();
-                //*current is dropped before closure is ever invoked and does not alias
-                let node_refs = unsafe { &mut (*current).internal.refs };
-                self.iter_mut_from_raw(src, node_refs.iter_mut().map($IterMutMap))
-            }
-        }
+            pub(crate) fn neighbors<'a, 'id : 'a>(&'a self, src : GraphPtr<'id, $NodeType<N, E $(, $S)?>>)
+                       -> impl Iterator<Item = GraphPtr<'id, $NodeType<N, E $(, $S)?>>> + 'a
+            {
+                //(E)
+                let g = src._guard;
+                let node_refs = unsafe { &(*src.as_ptr()).internal.refs };
+                //(W)
+                node_refs.iter().map($IterMap).map(move |x| unsafe { GraphPtr::from_ptr(x.0, g) })
+            }
+
+            pub(crate) fn iter_mut<'a, 'id : 'a>(&'a mut self, src : GraphPtr<'id, $NodeType<N, E $(, $S)?>>)
+                        -> impl Iterator<Item = GraphItem<Edge<&'a mut N, &'a mut E>, GraphPtr<'id, $NodeType<N, E $(, $S)?>>>>
+            {
+                //(E)
+                let current = src.as_mut();
+                //*current is dropped before closure is ever invoked and does not alias
+                let node_refs = unsafe { &mut (*current).internal.refs };
+                self.iter_mut_from_raw(src, node_refs.iter_mut().map($IterMutMap))
+            }
+        }
+    }
+}
+
+impl_graph_raw!{NamedNode,  {|x| (x.0.as_ptr(),  x.1)}, {|x| (x.0.as_mut(),      x.1)}, S}
+impl_graph_raw!{VecNode,    {|x| (x.0.as_ptr(), &x.1)}, {|x| (x.0.as_mut(), &mut x.1)}}
+impl_graph_raw!{OptionNode, {|x| (x.0.as_ptr(), &x.1)}, {|x| (x.0.as_mut(), &mut x.1)}}
+#[cfg(feature = "indexmap")]
+impl_graph_raw!{OrderedNamedNode, {|x| (x.0.as_ptr(), x.1)}, {|x| (x.0.as_mut(), x.1)}}
+
+
+impl <K, N, E, A : Allocator> GraphRaw<TreeNode<K, N, E>, A> where K : Ord
+{
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn bridge<'id>(&mut self, src : GraphPtr<'id, TreeNode<K, N, E>>,
+                                         dst : GraphPtr<'id, TreeNode<K, N, E>>)
+        -> Option<(&'_ mut node_views::TreeNode<'id, K, N, E>, &'_ mut node_views::TreeNode<'id, K, N, E>)>
+    {
+        if src != dst { 
+            self.remember(src.as_mut());
+            //this transmute only affects lifetime parameter
+            let src = unsafe { (*src.as_mut()).get_view_mut() };
+            let dst = self.get_view_mut(dst);
+            Some((src, dst))
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn get_view<'id>(&self, dst : GraphPtr<'id, TreeNode<K, N, E>>) -> &node_views::TreeNode<'id, K, N, E>
+    {
+        //(E)
+        unsafe {
+            (*dst.as_ptr()).get_view()
+        }
+    }
+
+    pub(crate) fn get_view_mut<'id>(&mut self, dst : GraphPtr<'id, TreeNode<K, N, E>>) -> &mut node_views::TreeNode<'id, K, N, E>
+    {
+        self.remember(dst.as_mut());
+        //(E)
+        unsafe {
+            (*dst.as_mut()).get_view_mut()
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn iter<'a, 'id : 'a>(&'a self, dst : GraphPtr<'id, TreeNode<K, N, E>>)
+               -> impl Iterator<Item = GraphItem<Edge<&'a N, &'a E>, GraphPtr<'id, TreeNode<K, N, E>>>>
+    {
+        //(E)
+        let current = dst.as_ptr();
+        let node_refs = unsafe { &(*current).internal.refs };
+        let iter = node_refs.values().map(|x| (x.0.as_ptr(), &x.1));
+        self.iter_from_raw(dst, iter)
+    }
+
+    pub(crate) fn neighbors<'a, 'id : 'a>(&'a self, src : GraphPtr<'id, TreeNode<K, N, E>>)
+               -> impl Iterator<Item = GraphPtr<'id, TreeNode<K, N, E>>> + 'a
+    {
+        //(E)
+        let g = src._guard;
+        let node_refs = unsafe { &(*src.as_ptr()).internal.refs };
+        //(W)
+        node_refs.values().map(move |x| unsafe { GraphPtr::from_ptr(x.0.as_ptr(), g) })
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn iter_mut<'a, 'id : 'a>(&'a mut self, src : GraphPtr<'id, TreeNode<K, N, E>>)
+                -> impl Iterator<Item = GraphItem<Edge<&'a mut N, &'a mut E>, GraphPtr<'id, TreeNode<K, N, E>>>>
+    {
+        //(E)
+        let current = src.as_mut();
+        //*current is dropped before closure is ever invoked and does not alias
+        let node_refs = unsafe { &mut (*current).internal.refs };
+        let iter = node_refs.values_mut().map(|x| (x.0.as_mut(), &mut x.1));
+        self.iter_mut_from_raw(src, iter)
+    }
+
+    
+}
+
+#[cfg(feature = "smallvec")]
+impl <N, E, const INLINE : usize, A : Allocator> GraphRaw<SmallVecNode<N, E, INLINE>, A>
+{
+    pub(crate) fn get_view<'id>(&self, dst : GraphPtr<'id, SmallVecNode<N, E, INLINE>>)
+               -> &node_views::SmallVecNode<'id, N, E, INLINE>
+    {
+        //(E)
+        unsafe {
+            (*dst.as_ptr()).get_view()
+        }
+    }
+
+    pub(crate) fn get_view_mut<'id>(&mut self, dst : GraphPtr<'id, SmallVecNode<N, E, INLINE>>)
+               -> &mut node_views::SmallVecNode<'id, N, E, INLINE>
+    {
+        self.remember(dst.as_mut());
+        //(E)
+        unsafe {
+            (*dst.as_mut()).get_view_mut()
+        }
+    }
+
+    pub(crate) fn iter<'a, 'id : 'a>(&'a self, src : GraphPtr<'id, SmallVecNode<N, E, INLINE>>)
+               -> impl Iterator<Item = GraphItem<Edge<&'a N, &'a E>, GraphPtr<'id, SmallVecNode<N, E, INLINE>>>>
+    {
+        //(E)
+        let node_refs = unsafe { &(*src.as_ptr()).internal.refs };
+        self.iter_from_raw(src, node_refs.iter().map(|x| (x.0.as_ptr(), &x.1)))
+    }
+
+    pub(crate) fn neighbors<'a, 'id : 'a>(&'a self, src : GraphPtr<'id, SmallVecNode<N, E, INLINE>>)
+               -> impl Iterator<Item = GraphPtr<'id, SmallVecNode<N, E, INLINE>>> + 'a
+    {
+        //(E)
+        let g = src._guard;
+        let node_refs = unsafe { &(*src.as_ptr()).internal.refs };
+        //(W)
+        node_refs.iter().map(move |x| unsafe { GraphPtr::from_ptr(x.0.as_ptr(), g) })
+    }
+
+    pub(crate) fn iter_mut<'a, 'id : 'a>(&'a mut self, src : GraphPtr<'id, SmallVecNode<N, E, INLINE>>)
+               -> impl Iterator<Item = GraphItem<Edge<&'a mut N, &'a mut E>, GraphPtr<'id, SmallVecNode<N, E, INLINE>>>>
+    {
+        //(E)
+        //*current is dropped before closure is ever invoked and does not alias
+        let node_refs = unsafe { &mut (*src.as_mut()).internal.refs };
+        self.iter_mut_from_raw(src, node_refs.iter_mut().map(|x| (x.0.as_mut(), &mut x.1)))
+    }
+}
+
+impl <N, E, A : Allocator> GraphRaw<MultiNamedNode<N, E>, A>
+{
+    pub(crate) fn get_view<'id>(&self, dst : GraphPtr<'id, MultiNamedNode<N, E>>) -> &node_views::MultiNamedNode<'id, N, E>
+    {
+        //(E)
+        unsafe {
+            (*dst.as_ptr()).get_view()
+        }
+    }
+
+    pub(crate) fn get_view_mut<'id>(&mut self, dst : GraphPtr<'id, MultiNamedNode<N, E>>) -> &mut node_views::MultiNamedNode<'id, N, E>
+    {
+        self.remember(dst.as_mut());
+        //(E)
+        unsafe {
+            (*dst.as_mut()).get_view_mut()
+        }
+    }
+
+    pub(crate) fn connect<'id>(&mut self, src : GraphPtr<'id, MultiNamedNode<N, E>>, dst : GraphPtr<'id, MultiNamedNode<N, E>>, edge : E)
+    {
+        self.check_edge(src.as_ptr(), dst.as_ptr());
+        self.get_view_mut(src).refs.entry(dst).or_default().push(edge);
+        self.notify(GraphEvent::EdgeAdded { src : src.as_ptr(), dst : dst.as_ptr() });
+    }
+
+    pub(crate) fn disconnect<'id>(&mut self, src : GraphPtr<'id, MultiNamedNode<N, E>>, dst : GraphPtr<'id, MultiNamedNode<N, E>>)
+        -> Vec<E>
+    {
+        let edges = self.get_view_mut(src).refs.remove(&dst).unwrap_or_default();
+        for _ in edges.iter() {
+            self.notify(GraphEvent::EdgeRemoved { src : src.as_ptr(), dst : dst.as_ptr() });
+        }
+        edges
+    }
+
+    pub(crate) fn edges_between<'id>(&self, src : GraphPtr<'id, MultiNamedNode<N, E>>, dst : GraphPtr<'id, MultiNamedNode<N, E>>)
+               -> impl Iterator<Item = Edge<&'_ N, &'_ E>>
+    {
+        //(E)
+        let src = src.into_static();
+        let dst = dst.into_static();
+        let src_refs = unsafe { &(*src.as_ptr()).internal.refs };
+        src_refs.get(&dst).into_iter().flatten().map(move |e| self.get_edge_raw(src, dst, e))
+    }
+
+    pub(crate) fn iter<'a, 'id : 'a>(&'a self, src : GraphPtr<'id, MultiNamedNode<N, E>>)
+               -> impl Iterator<Item = GraphItem<Edge<&'a N, &'a E>, GraphPtr<'id, MultiNamedNode<N, E>>>>
+    {
+        //(E)
+        let node_refs = unsafe { &(*src.as_ptr()).internal.refs };
+        self.iter_from_raw(src, node_refs.iter().flat_map(|(k, v)| v.iter().map(move |e| (k.as_ptr(), e))))
+    }
+
+    pub(crate) fn neighbors<'a, 'id : 'a>(&'a self, src : GraphPtr<'id, MultiNamedNode<N, E>>)
+               -> impl Iterator<Item = GraphPtr<'id, MultiNamedNode<N, E>>> + 'a
+    {
+        //(E)
+        let g = src._guard;
+        let node_refs = unsafe { &(*src.as_ptr()).internal.refs };
+        //(W)
+        node_refs.keys().map(move |x| unsafe { GraphPtr::from_ptr(x.as_ptr(), g) })
+    }
+
+    pub(crate) fn iter_mut<'a, 'id : 'a>(&'a mut self, src : GraphPtr<'id, MultiNamedNode<N, E>>)
+               -> impl Iterator<Item = GraphItem<Edge<&'a mut N, &'a mut E>, GraphPtr<'id, MultiNamedNode<N, E>>>>
+    {
+        //(E)
+        //*current is dropped before closure is ever invoked and does not alias
+        let node_refs = unsafe { &mut (*src.as_mut()).internal.refs };
+        self.iter_mut_from_raw(src, node_refs.iter_mut().flat_map(|(k, v)| {
+            let k = k.as_mut();
+            v.iter_mut().map(move |e| (k, e))
+        }))
+    }
+}
+
+impl <N, E, const D : usize, A : Allocator> GraphRaw<ArrayNode<N, E, D>, A>
+{
+    pub(crate) fn get_view<'id>(&self, dst : GraphPtr<'id, ArrayNode<N, E, D>>) -> &node_views::ArrayNode<'id, N, E, D>
+    {
+        //(E)
+        unsafe {
+            (*dst.as_ptr()).get_view()
+        }
+    }
+
+    pub(crate) fn get_view_mut<'id>(&mut self, dst : GraphPtr<'id, ArrayNode<N, E, D>>) -> &mut node_views::ArrayNode<'id, N, E, D>
+    {
+        self.remember(dst.as_mut());
+        //(E)
+        unsafe {
+            (*dst.as_mut()).get_view_mut()
+        }
+    }
+
+    pub(crate) fn get_edge<'id>(&self, src : GraphPtr<'id, ArrayNode<N, E, D>>, slot : usize)
+               -> Option<Edge<&'_ N, &'_ E>>
+    {
+        //(E)
+        let src = src.into_static();
+        let src_refs = unsafe { &(*src.as_ptr()).internal.refs };
+        src_refs.get(slot)?.as_ref()
+                .map(move |x| self.get_edge_raw(src, x.0, &x.1))
+    }
+
+    pub(crate) fn get_edge_mut<'id>(&mut self, src : GraphPtr<'id, ArrayNode<N, E, D>>, slot : usize)
+               -> Option<Edge<&'_ mut N, &'_ mut E>>
+    {
+        //(E)
+        let src = src.into_static();
+        let src_refs = unsafe { &mut (*src.as_mut()).internal.refs };
+        src_refs.get_mut(slot)?.as_mut()
+                .map(move |x| self.get_edge_mut_raw(src, x.0, &mut x.1))
+    }
+
+    pub(crate) fn connect<'id>(&mut self, src : GraphPtr<'id, ArrayNode<N, E, D>>, slot : usize,
+                               dst : GraphPtr<'id, ArrayNode<N, E, D>>, edge : E) -> Option<(GraphPtr<'id, ArrayNode<N, E, D>>, E)>
+    {
+        self.check_edge(src.as_ptr(), dst.as_ptr());
+        let old = self.get_view_mut(src).refs[slot].replace((dst, edge));
+        self.notify_replaced(src, old.as_ref().map(|x| x.0), dst);
+        old
+    }
+
+    pub(crate) fn disconnect<'id>(&mut self, src : GraphPtr<'id, ArrayNode<N, E, D>>, slot : usize)
+        -> Option<(GraphPtr<'id, ArrayNode<N, E, D>>, E)>
+    {
+        let old = self.get_view_mut(src).refs.get_mut(slot)?.take();
+        self.notify_removed(src, old.as_ref().map(|x| x.0));
+        old
+    }
+
+    pub(crate) fn iter<'a, 'id : 'a>(&'a self, src : GraphPtr<'id, ArrayNode<N, E, D>>)
+               -> impl Iterator<Item = GraphItem<Edge<&'a N, &'a E>, GraphPtr<'id, ArrayNode<N, E, D>>>>
+    {
+        //(E)
+        let node_refs = unsafe { &(*src.as_ptr()).internal.refs };
+        self.iter_from_raw(src, node_refs.iter().flatten().map(|x| (x.0.as_ptr(), &x.1)))
+    }
+
+    pub(crate) fn neighbors<'a, 'id : 'a>(&'a self, src : GraphPtr<'id, ArrayNode<N, E, D>>)
+               -> impl Iterator<Item = GraphPtr<'id, ArrayNode<N, E, D>>> + 'a
+    {
+        //(E)
+        let g = src._guard;
+        let node_refs = unsafe { &(*src.as_ptr()).internal.refs };
+        //(W)
+        node_refs.iter().flatten().map(move |x| unsafe { GraphPtr::from_ptr(x.0.as_ptr(), g) })
+    }
+
+    pub(crate) fn iter_mut<'a, 'id : 'a>(&'a mut self, src : GraphPtr<'id, ArrayNode<N, E, D>>)
+               -> impl Iterator<Item = GraphItem<Edge<&'a mut N, &'a mut E>, GraphPtr<'id, ArrayNode<N, E, D>>>>
+    {
+        //(E)
+        //*current is dropped before closure is ever invoked and does not alias
+        let node_refs = unsafe { &mut (*src.as_mut()).internal.refs };
+        self.iter_mut_from_raw(src, node_refs.iter_mut().flatten().map(|x| (x.0.as_mut(), &mut x.1)))
     }
 }
 
-impl_graph_raw!{NamedNode,  {|x| (x.0.as_ptr(),  x.1)}, {|x| (x.0.as_mut(),      x.1)}}
-impl_graph_raw!{VecNode,    {|x| (x.0.as_ptr(), &x.1)}, {|x| (x.0.as_mut(), &mut x.1)}}
-impl_graph_raw!{OptionNode, {|x| (x.0.as_ptr(), &x.1)}, {|x| (x.0.as_mut(), &mut x.1)}}
+impl <N, F : EdgeFamily, A : Allocator> GraphRaw<CustomNode<N, F>, A>
+{
+    pub(crate) fn get_view<'id>(&self, dst : GraphPtr<'id, CustomNode<N, F>>) -> &node_views::CustomNode<'id, N, F>
+    {
+        //(E)
+        unsafe {
+            (*dst.as_ptr()).get_view()
+        }
+    }
 
+    pub(crate) fn get_view_mut<'id>(&mut self, dst : GraphPtr<'id, CustomNode<N, F>>) -> &mut node_views::CustomNode<'id, N, F>
+    {
+        self.remember(dst.as_mut());
+        //(E)
+        unsafe {
+            (*dst.as_mut()).get_view_mut()
+        }
+    }
+}
 
-impl <K, N, E> GraphRaw<TreeNode<K, N, E>> where K : Ord
+impl <N, A : Allocator> GraphRaw<SetNode<N>, A>
 {
-    pub(crate) fn bridge<'id>(&mut self, src : GraphPtr<'id, TreeNode<K, N, E>>,
-                                         dst : GraphPtr<'id, TreeNode<K, N, E>>)
-        -> Option<(&'_ mut node_views::TreeNode<'id, K, N, E>, &'_ mut node_views::TreeNode<'id, K, N, E>)>
+    pub(crate) fn get_view<'id>(&self, dst : GraphPtr<'id, SetNode<N>>) -> &node_views::SetNode<'id, N>
     {
-        if src != dst { 
-            //this transmute only affects lifetime parameter
-            let src = unsafe { (*src.as_mut()).get_view_mut() };
-            let dst = self.get_view_mut(dst);
-            Some((src, dst))
-        } else {
-            None
+        //(E)
+        unsafe {
+            (*dst.as_ptr()).get_view()
         }
     }
 
-    pub(crate) fn get_view<'id>(&self, dst : GraphPtr<'id, TreeNode<K, N, E>>) -> &node_views::TreeNode<'id, K, N, E>
+    pub(crate) fn get_view_mut<'id>(&mut self, dst : GraphPtr<'id, SetNode<N>>) -> &mut node_views::SetNode<'id, N>
+    {
+        self.remember(dst.as_mut());
+        //(E)
+        unsafe {
+            (*dst.as_mut()).get_view_mut()
+        }
+    }
+
+    pub(crate) fn connect<'id>(&mut self, src : GraphPtr<'id, SetNode<N>>, dst : GraphPtr<'id, SetNode<N>>) -> bool
+    {
+        self.check_edge(src.as_ptr(), dst.as_ptr());
+        let added = self.get_view_mut(src).refs.insert(dst);
+        if added {
+            self.notify(GraphEvent::EdgeAdded { src : src.as_ptr(), dst : dst.as_ptr() });
+        }
+        added
+    }
+
+    pub(crate) fn disconnect<'id>(&mut self, src : GraphPtr<'id, SetNode<N>>, dst : GraphPtr<'id, SetNode<N>>) -> bool
+    {
+        let removed = self.get_view_mut(src).refs.remove(&dst);
+        if removed {
+            self.notify(GraphEvent::EdgeRemoved { src : src.as_ptr(), dst : dst.as_ptr() });
+        }
+        removed
+    }
+
+    pub(crate) fn iter<'a, 'id : 'a>(&'a self, src : GraphPtr<'id, SetNode<N>>)
+               -> impl Iterator<Item = GraphItem<Edge<&'a N, &'a ()>, GraphPtr<'id, SetNode<N>>>>
+    {
+        //(E)
+        let node_refs = unsafe { &(*src.as_ptr()).internal.refs };
+        self.iter_from_raw(src, node_refs.iter().map(|x| (x.as_ptr(), &())))
+    }
+
+    pub(crate) fn neighbors<'a, 'id : 'a>(&'a self, src : GraphPtr<'id, SetNode<N>>)
+               -> impl Iterator<Item = GraphPtr<'id, SetNode<N>>> + 'a
+    {
+        //(E)
+        let g = src._guard;
+        let node_refs = unsafe { &(*src.as_ptr()).internal.refs };
+        //(W)
+        node_refs.iter().map(move |x| unsafe { GraphPtr::from_ptr(x.as_ptr(), g) })
+    }
+
+    pub(crate) fn iter_mut<'a, 'id : 'a>(&'a mut self, src : GraphPtr<'id, SetNode<N>>)
+               -> impl Iterator<Item = GraphItem<Edge<&'a mut N, &'a mut ()>, GraphPtr<'id, SetNode<N>>>>
+    {
+        //(E)
+        //*current is dropped before closure is ever invoked and does not alias
+        let node_refs = unsafe { &(*src.as_ptr()).internal.refs };
+        //boxing a unit does not allocate
+        self.iter_mut_from_raw(src, node_refs.iter().map(|x| (x.as_mut(), Box::leak(Box::new(())))))
+    }
+}
+
+impl <N, E, A : Allocator> GraphRaw<DiNode<N, E>, A>
+{
+    pub(crate) fn get_view<'id>(&self, dst : GraphPtr<'id, DiNode<N, E>>) -> &node_views::DiNode<'id, N, E>
     {
         //(E)
         unsafe {
@@ -416,41 +1746,457 @@ impl <K, N, E> GraphRaw<TreeNode<K, N, E>> where K : Ord
         }
     }
 
-    pub(crate) fn get_view_mut<'id>(&mut self, dst : GraphPtr<'id, TreeNode<K, N, E>>) -> &mut node_views::TreeNode<'id, K, N, E>
+    pub(crate) fn get_view_mut<'id>(&mut self, dst : GraphPtr<'id, DiNode<N, E>>) -> &mut node_views::DiNode<'id, N, E>
     {
+        self.remember(dst.as_mut());
         //(E)
         unsafe {
             (*dst.as_mut()).get_view_mut()
         }
     }
 
-    pub(crate) fn iter<'a, 'id : 'a>(&'a self, dst : GraphPtr<'id, TreeNode<K, N, E>>)
-               -> impl Iterator<Item = GraphItem<Edge<&'a N, &'a E>, GraphPtr<'id, TreeNode<K, N, E>>>>
+    pub(crate) fn connect<'id>(&mut self, src : GraphPtr<'id, DiNode<N, E>>, dst : GraphPtr<'id, DiNode<N, E>>, edge : E)
+               -> Option<E>
+    {
+        self.check_edge(src.as_ptr(), dst.as_ptr());
+        self.get_view_mut(dst).in_refs.insert(src);
+        let old = self.get_view_mut(src).out_refs.insert(dst, edge);
+        self.notify_added(src, dst, &old);
+        old
+    }
+
+    pub(crate) fn disconnect<'id>(&mut self, src : GraphPtr<'id, DiNode<N, E>>, dst : GraphPtr<'id, DiNode<N, E>>)
+               -> Option<E>
+    {
+        let edge = self.get_view_mut(src).out_refs.remove(&dst);
+        if edge.is_some() {
+            self.get_view_mut(dst).in_refs.remove(&src);
+            self.notify(GraphEvent::EdgeRemoved { src : src.as_ptr(), dst : dst.as_ptr() });
+        }
+        edge
+    }
+
+    pub(crate) fn iter_out<'a, 'id : 'a>(&'a self, src : GraphPtr<'id, DiNode<N, E>>)
+               -> impl Iterator<Item = GraphItem<Edge<&'a N, &'a E>, GraphPtr<'id, DiNode<N, E>>>>
     {
         //(E)
-        let current = dst.as_ptr();
-        let node_refs = unsafe { &(*current).internal.refs };
-        let iter = node_refs.values().map(|x| (x.0.as_ptr(), &x.1));
-        self.iter_from_raw(dst, iter)
+        let node_refs = unsafe { &(*src.as_ptr()).internal.out_refs };
+        self.iter_from_raw(src, node_refs.iter().map(|x| (x.0.as_ptr(), x.1)))
     }
 
-    pub(crate) fn iter_mut<'a, 'id : 'a>(&'a mut self, src : GraphPtr<'id, TreeNode<K, N, E>>)
-                -> impl Iterator<Item = GraphItem<Edge<&'a mut N, &'a mut E>, GraphPtr<'id, TreeNode<K, N, E>>>>
+    pub(crate) fn iter_out_mut<'a, 'id : 'a>(&'a mut self, src : GraphPtr<'id, DiNode<N, E>>)
+               -> impl Iterator<Item = GraphItem<Edge<&'a mut N, &'a mut E>, GraphPtr<'id, DiNode<N, E>>>>
     {
         //(E)
-        let current = src.as_mut();
         //*current is dropped before closure is ever invoked and does not alias
-        let node_refs = unsafe { &mut (*current).internal.refs };
-        let iter = node_refs.values_mut().map(|x| (x.0.as_mut(), &mut x.1));
+        let node_refs = unsafe { &mut (*src.as_mut()).internal.out_refs };
+        self.iter_mut_from_raw(src, node_refs.iter_mut().map(|x| (x.0.as_mut(), x.1)))
+    }
+
+    // Edge data is stored by the source node.
+    pub(crate) fn iter_in<'a, 'id : 'a>(&'a self, dst : GraphPtr<'id, DiNode<N, E>>)
+               -> impl Iterator<Item = GraphItem<Edge<&'a N, &'a E>, GraphPtr<'id, DiNode<N, E>>>>
+    {
+        //(E)
+        let key = dst.into_static();
+        let node_refs = unsafe { &(*dst.as_ptr()).internal.in_refs };
+        let iter = node_refs.iter().filter_map(move |x| {
+            let edge = unsafe { (*x.as_ptr()).internal.out_refs.get(&key) };
+            edge.map(|e| (x.as_ptr(), e))
+        });
+        self.iter_from_raw(dst, iter)
+    }
+}
+
+impl <N, E, A : Allocator> GraphRaw<UndirectedNode<N, E>, A>
+{
+    pub(crate) fn get_view<'id>(&self, dst : GraphPtr<'id, UndirectedNode<N, E>>) -> &node_views::UndirectedNode<'id, N, E>
+    {
+        //(E)
+        unsafe {
+            (*dst.as_ptr()).get_view()
+        }
+    }
+
+    pub(crate) fn get_view_mut<'id>(&mut self, dst : GraphPtr<'id, UndirectedNode<N, E>>)
+               -> &mut node_views::UndirectedNode<'id, N, E>
+    {
+        self.remember(dst.as_mut());
+        //(E)
+        unsafe {
+            (*dst.as_mut()).get_view_mut()
+        }
+    }
+
+    // Edge data is stored by the endpoint with the lower address.
+    #[allow(clippy::type_complexity)]
+    fn edge_owner<'id>(a : GraphPtr<'id, UndirectedNode<N, E>>, b : GraphPtr<'id, UndirectedNode<N, E>>)
+       -> (GraphPtr<'id, UndirectedNode<N, E>>, GraphPtr<'id, UndirectedNode<N, E>>)
+    {
+        if a.as_ptr() <= b.as_ptr() { (a, b) } else { (b, a) }
+    }
+
+    pub(crate) fn connect<'id>(&mut self, a : GraphPtr<'id, UndirectedNode<N, E>>, b : GraphPtr<'id, UndirectedNode<N, E>>,
+                               edge : E) -> Option<E>
+    {
+        self.check_edge(a.as_ptr(), b.as_ptr());
+        self.get_view_mut(a).refs.insert(b);
+        self.get_view_mut(b).refs.insert(a);
+        let (owner, other) = Self::edge_owner(a, b);
+        let old = self.get_view_mut(owner).edges.insert(other, edge);
+        self.notify_added(a, b, &old);
+        old
+    }
+
+    pub(crate) fn disconnect<'id>(&mut self, a : GraphPtr<'id, UndirectedNode<N, E>>, b : GraphPtr<'id, UndirectedNode<N, E>>)
+               -> Option<E>
+    {
+        let (owner, other) = Self::edge_owner(a, b);
+        let edge = self.get_view_mut(owner).edges.remove(&other);
+        if edge.is_some() {
+            self.get_view_mut(a).refs.remove(&b);
+            self.get_view_mut(b).refs.remove(&a);
+            self.notify(GraphEvent::EdgeRemoved { src : a.as_ptr(), dst : b.as_ptr() });
+        }
+        edge
+    }
+
+    pub(crate) fn edge<'id>(&self, a : GraphPtr<'id, UndirectedNode<N, E>>, b : GraphPtr<'id, UndirectedNode<N, E>>)
+               -> Option<&E>
+    {
+        let (owner, other) = Self::edge_owner(a, b);
+        //(E)
+        unsafe { (*owner.as_ptr()).internal.edges.get(&other.into_static()) }
+    }
+
+    pub(crate) fn edge_mut<'id>(&mut self, a : GraphPtr<'id, UndirectedNode<N, E>>, b : GraphPtr<'id, UndirectedNode<N, E>>)
+               -> Option<&mut E>
+    {
+        let (owner, other) = Self::edge_owner(a, b);
+        //(E)
+        unsafe { (*owner.as_mut()).internal.edges.get_mut(&other.into_static()) }
+    }
+
+    pub(crate) fn iter<'a, 'id : 'a>(&'a self, src : GraphPtr<'id, UndirectedNode<N, E>>)
+               -> impl Iterator<Item = GraphItem<Edge<&'a N, &'a E>, GraphPtr<'id, UndirectedNode<N, E>>>>
+    {
+        //(E)
+        let node_refs = unsafe { &(*src.as_ptr()).internal.refs };
+        let iter = node_refs.iter().filter_map(move |x| {
+            let (owner, other) = Self::edge_owner(src.into_static(), *x);
+            let edge = unsafe { (*owner.as_ptr()).internal.edges.get(&other) };
+            edge.map(|e| (x.as_ptr(), e))
+        });
+        self.iter_from_raw(src, iter)
+    }
+
+    pub(crate) fn iter_mut<'a, 'id : 'a>(&'a mut self, src : GraphPtr<'id, UndirectedNode<N, E>>)
+               -> impl Iterator<Item = GraphItem<Edge<&'a mut N, &'a mut E>, GraphPtr<'id, UndirectedNode<N, E>>>>
+    {
+        //(E)
+        //Every edge is yielded once and edges map is never aliased by node data
+        let node_refs = unsafe { &(*src.as_ptr()).internal.refs };
+        let iter = node_refs.iter().filter_map(move |x| {
+            let (owner, other) = Self::edge_owner(src.into_static(), *x);
+            let edge = unsafe { (*owner.as_mut()).internal.edges.get_mut(&other) };
+            edge.map(|e| (x.as_mut(), e))
+        });
         self.iter_mut_from_raw(src, iter)
     }
+}
+
+impl <N, E, S : BuildHasher + Default, A : Allocator> GraphRaw<NamedNode<N, E, S>, A>
+{
+    pub(crate) fn connect<'id>(&mut self, src : GraphPtr<'id, NamedNode<N, E, S>>, dst : GraphPtr<'id, NamedNode<N, E, S>>, edge : E)
+        -> Option<E>
+    {
+        self.check_edge(src.as_ptr(), dst.as_ptr());
+        let old = self.get_view_mut(src).refs.insert(dst, edge);
+        self.notify_added(src, dst, &old);
+        old
+    }
 
-    
+    pub(crate) fn disconnect<'id>(&mut self, src : GraphPtr<'id, NamedNode<N, E, S>>, dst : GraphPtr<'id, NamedNode<N, E, S>>) -> Option<E>
+    {
+        let old = self.get_view_mut(src).refs.remove(&dst);
+        self.notify_removed(src, old.as_ref().map(|_| dst));
+        old
+    }
+
+    pub(crate) fn connect_or_update<'a, 'id : 'a>(&'a mut self, src : GraphPtr<'id, NamedNode<N, E, S>>, dst : GraphPtr<'id, NamedNode<N, E, S>>,
+                                                        edge : E, update : impl FnOnce(&mut E)) -> &'a mut E
+    {
+        self.check_edge(src.as_ptr(), dst.as_ptr());
+        if !self.get_view_mut(src).refs.contains_key(&dst) {
+            self.notify(GraphEvent::EdgeAdded { src : src.as_ptr(), dst : dst.as_ptr() });
+        }
+        match self.get_view_mut(src).refs.entry(dst) {
+            Entry::Occupied(e) => {
+                let e = e.into_mut();
+                update(e);
+                e
+            },
+            Entry::Vacant(e) => e.insert(edge),
+        }
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl <N, E, A : Allocator> GraphRaw<OrderedNamedNode<N, E>, A>
+{
+    pub(crate) fn connect<'id>(&mut self, src : GraphPtr<'id, OrderedNamedNode<N, E>>, dst : GraphPtr<'id, OrderedNamedNode<N, E>>,
+                               edge : E) -> Option<E>
+    {
+        self.check_edge(src.as_ptr(), dst.as_ptr());
+        let old = self.get_view_mut(src).refs.insert(dst, edge);
+        self.notify_added(src, dst, &old);
+        old
+    }
+
+    pub(crate) fn disconnect<'id>(&mut self, src : GraphPtr<'id, OrderedNamedNode<N, E>>, dst : GraphPtr<'id, OrderedNamedNode<N, E>>)
+               -> Option<E>
+    {
+        let old = self.get_view_mut(src).refs.shift_remove(&dst);
+        self.notify_removed(src, old.as_ref().map(|_| dst));
+        old
+    }
+
+    pub(crate) fn connect_or_update<'a, 'id : 'a>(&'a mut self, src : GraphPtr<'id, OrderedNamedNode<N, E>>,
+                                                  dst : GraphPtr<'id, OrderedNamedNode<N, E>>,
+                                                  edge : E, update : impl FnOnce(&mut E)) -> &'a mut E
+    {
+        self.check_edge(src.as_ptr(), dst.as_ptr());
+        if self.get_view_mut(src).refs.get_index_of(&dst).is_none() {
+            self.notify(GraphEvent::EdgeAdded { src : src.as_ptr(), dst : dst.as_ptr() });
+        }
+        let refs = &mut self.get_view_mut(src).refs;
+        match refs.get_index_of(&dst) {
+            Some(i) => {
+                let e = refs.get_index_mut(i).unwrap().1;
+                update(e);
+                e
+            },
+            None => {
+                refs.insert(dst, edge);
+                refs.get_index_mut(refs.len() - 1).unwrap().1
+            }
+        }
+    }
+}
+
+impl <N, E, A : Allocator> GraphRaw<VecNode<N, E>, A>
+{
+    pub(crate) fn connect<'id>(&mut self, src : GraphPtr<'id, VecNode<N, E>>, dst : GraphPtr<'id, VecNode<N, E>>, edge : E)
+        -> Option<E>
+    {
+        self.check_edge(src.as_ptr(), dst.as_ptr());
+        let refs = &mut self.get_view_mut(src).refs;
+        let old = match refs.iter_mut().find(|x| x.0 == dst) {
+            Some(x) => Some(core::mem::replace(&mut x.1, edge)),
+            None => {
+                refs.push((dst, edge));
+                None
+            }
+        };
+        self.notify_added(src, dst, &old);
+        old
+    }
+
+    pub(crate) fn disconnect<'id>(&mut self, src : GraphPtr<'id, VecNode<N, E>>, dst : GraphPtr<'id, VecNode<N, E>>) -> Option<E>
+    {
+        let refs = &mut self.get_view_mut(src).refs;
+        let i = refs.iter().position(|x| x.0 == dst)?;
+        let old = refs.remove(i).1;
+        self.notify_removed(src, Some(dst));
+        Some(old)
+    }
+
+    pub(crate) fn connect_or_update<'a, 'id : 'a>(&'a mut self, src : GraphPtr<'id, VecNode<N, E>>, dst : GraphPtr<'id, VecNode<N, E>>,
+                                                        edge : E, update : impl FnOnce(&mut E)) -> &'a mut E
+    {
+        self.check_edge(src.as_ptr(), dst.as_ptr());
+        if !self.get_view_mut(src).refs.iter().any(|x| x.0 == dst) {
+            self.notify(GraphEvent::EdgeAdded { src : src.as_ptr(), dst : dst.as_ptr() });
+        }
+        let refs = &mut self.get_view_mut(src).refs;
+        match refs.iter().position(|x| x.0 == dst) {
+            Some(i) => {
+                let e = &mut refs[i].1;
+                update(e);
+                e
+            },
+            None => {
+                refs.push((dst, edge));
+                &mut refs.last_mut().unwrap().1
+            }
+        }
+    }
+}
+
+impl <N, E, A : Allocator> GraphRaw<OptionNode<N, E>, A>
+{
+    pub(crate) fn connect<'id>(&mut self, src : GraphPtr<'id, OptionNode<N, E>>, dst : GraphPtr<'id, OptionNode<N, E>>, edge : E)
+        -> Option<E>
+    {
+        self.check_edge(src.as_ptr(), dst.as_ptr());
+        let old = self.get_view_mut(src).refs.replace((dst, edge));
+        self.notify_replaced(src, old.as_ref().map(|x| x.0), dst);
+        old.map(|x| x.1)
+    }
+
+    pub(crate) fn disconnect<'id>(&mut self, src : GraphPtr<'id, OptionNode<N, E>>, dst : GraphPtr<'id, OptionNode<N, E>>) -> Option<E>
+    {
+        let refs = &mut self.get_view_mut(src).refs;
+        if matches!(refs, Some((p, _)) if *p == dst) {
+            let old = refs.take().map(|x| x.1);
+            self.notify_removed(src, Some(dst));
+            old
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn connect_or_update<'a, 'id : 'a>(&'a mut self, src : GraphPtr<'id, OptionNode<N, E>>, dst : GraphPtr<'id, OptionNode<N, E>>,
+                                                        edge : E, update : impl FnOnce(&mut E)) -> &'a mut E
+    {
+        self.check_edge(src.as_ptr(), dst.as_ptr());
+        let old = self.get_view_mut(src).refs.as_ref().map(|x| x.0);
+        if old != Some(dst) {
+            self.notify_replaced(src, old, dst);
+            return &mut self.get_view_mut(src).refs.insert((dst, edge)).1;
+        }
+        let refs = &mut self.get_view_mut(src).refs;
+        let e = &mut refs.as_mut().unwrap().1;
+        update(e);
+        e
+    }
+}
+
+impl <K, N, E, A : Allocator> GraphRaw<TreeNode<K, N, E>, A> where K : Ord
+{
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn connect<'id>(&mut self, src : GraphPtr<'id, TreeNode<K, N, E>>, key : K, dst : GraphPtr<'id, TreeNode<K, N, E>>,
+                                edge : E) -> Option<(GraphPtr<'id, TreeNode<K, N, E>>, E)>
+    {
+        self.check_edge(src.as_ptr(), dst.as_ptr());
+        let old = self.get_view_mut(src).refs.insert(key, (dst, edge));
+        self.notify_replaced(src, old.as_ref().map(|x| x.0), dst);
+        old
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn disconnect<'id>(&mut self, src : GraphPtr<'id, TreeNode<K, N, E>>, key : &K)
+        -> Option<(GraphPtr<'id, TreeNode<K, N, E>>, E)>
+    {
+        let old = self.get_view_mut(src).refs.remove(key);
+        self.notify_removed(src, old.as_ref().map(|x| x.0));
+        old
+    }
+
+    pub(crate) fn connect_or_update<'a, 'id : 'a>(&'a mut self, src : GraphPtr<'id, TreeNode<K, N, E>>, key : K, dst : GraphPtr<'id, TreeNode<K, N, E>>,
+                                                        edge : E, update : impl FnOnce(&mut E)) -> &'a mut E
+    {
+        self.check_edge(src.as_ptr(), dst.as_ptr());
+        let old = self.get_view_mut(src).refs.get(&key).map(|x| x.0);
+        if old != Some(dst) {
+            self.notify_replaced(src, old, dst);
+        }
+        match self.get_view_mut(src).refs.entry(key) {
+            BTreeEntry::Occupied(e) => {
+                let e = e.into_mut();
+                e.0 = dst;
+                update(&mut e.1);
+                &mut e.1
+            },
+            BTreeEntry::Vacant(e) => &mut e.insert((dst, edge)).1,
+        }
+    }
+}
+
+impl <T, A : Allocator> GraphRaw<T, A> {
+    // Reserves memory for `additional` nodes.
+    pub(crate) fn reserve(&mut self, additional : usize)
+    {
+        self.data.reserve(additional);
+        self.pool.reserve(additional, &self.allocator);
+    }
+
+    pub(crate) fn alloc(&mut self, node : T) -> NodeBox<T>
+    {
+        let ptr = self.pool.take(&self.allocator);
+        //the slot is vacant
+        unsafe {
+            ptr.as_ptr().write(node);
+        }
+        NodeBox(ptr)
+    }
+
+    // Moves the node out of its memory and returns the memory to the pool.
+    // Safety: `node` must not be accessed afterwards.
+    pub(crate) unsafe fn free(&mut self, node : NodeBox<T>) -> T
+    {
+        let ptr = node.as_ptr();
+        let value = ptr.read();
+        self.pool.put(ptr);
+        value
+    }
+
+    pub(crate) fn new_in(allocator : A) -> GraphRaw<T, A>
+    {
+        GraphRaw { data : Vec::new(), pool : NodePool::new(), allocator, cleanup_gen : CleanupGen::new(), shrink : ShrinkPolicy::default(), changes : 0, old : 0, remembered : Vec::new(), minor_cleanups : 0, dirty : Vec::new(), track_dirty : false,
+                   on_collect : None, observers : Vec::new(), pinned : HashMap::new(), pins : HashMap::new(), ids : HashMap::new(), next_id : 1, spawned : 0, mark_gen : 1, modified : 0, track_modified : false,
+                   reverse : Mutex::new(ReverseIndex { enabled : false, stale : false, sources : HashMap::new() }),
+                   key_index : Mutex::new(None),
+                   topo : None }
+    }
 }
 
-impl <T> GraphRaw<T> {
-    pub(crate) fn new() -> GraphRaw<T>
+impl <NodeType, A : Allocator> GraphRaw<NodeType, A>
+where NodeType : GraphNode
+{
+    pub(crate) fn notify(&mut self, event : GraphEvent<NodeType>)
+    {
+        if let GraphEvent::NodeSpawned(_) | GraphEvent::EdgeRemoved { .. } = event {
+            self.changes += 1;
+        }
+        let reverse = self.reverse.get_mut().unwrap_or_else(|x| x.into_inner());
+        match event {
+            GraphEvent::EdgeAdded { src, dst } => reverse.add(src, dst),
+            GraphEvent::EdgeRemoved { src, dst } => reverse.remove(src, dst),
+            GraphEvent::NodeCollected(item) => {
+                //Nodes are collected before they are freed, so their edges can still be read.
+                Self::for_each_target(item as *mut NodeType, |x| reverse.remove(item, x));
+                reverse.sources.remove(&item);
+                if let Some(index) = self.key_index.get_mut().unwrap_or_else(|x| x.into_inner()) {
+                    index.remove(item);
+                }
+            }
+            GraphEvent::NodeSpawned(_) => (),
+        }
+        for f in self.observers.iter_mut() {
+            f(event);
+        }
+    }
+
+    // Reports a new edge unless `old` holds the data of an edge it replaced.
+    fn notify_added<'id, E>(&mut self, src : GraphPtr<'id, NodeType>, dst : GraphPtr<'id, NodeType>, old : &Option<E>)
+    {
+        if old.is_none() {
+            self.notify(GraphEvent::EdgeAdded { src : src.as_ptr(), dst : dst.as_ptr() });
+        }
+    }
+
+    fn notify_removed<'id>(&mut self, src : GraphPtr<'id, NodeType>, old : Option<GraphPtr<'id, NodeType>>)
+    {
+        if let Some(dst) = old {
+            self.notify(GraphEvent::EdgeRemoved { src : src.as_ptr(), dst : dst.as_ptr() });
+        }
+    }
+
+    // Reports an edge stored in a slot which previously pointed to `old`.
+    fn notify_replaced<'id>(&mut self, src : GraphPtr<'id, NodeType>, old : Option<GraphPtr<'id, NodeType>>, dst : GraphPtr<'id, NodeType>)
     {
-        GraphRaw { data : Vec::new(), cleanup_gen : CleanupGen::Even }
+        self.notify_removed(src, old);
+        self.notify(GraphEvent::EdgeAdded { src : src.as_ptr(), dst : dst.as_ptr() });
     }
 }
@@ -0,0 +1,40 @@
+use super::*;
+use std::collections::HashMap;
+use std::io::BufRead;
+
+/// Reads rows of `src`, `dst` and an optional edge payload split by `separator`, e.g. CSV or TSV files. Columns are
+/// trimmed, the payload column is passed to `parse_edge` as an empty string if missing. Empty lines and lines
+/// starting with `#` are skipped. Spawns a node for every distinct value returned by `parse_node`, adds it to the
+/// root and connects it as listed; a repeated edge replaces the previous one. Returns the spawned nodes.
+#[allow(clippy::type_complexity)]
+pub fn edge_list<'id, N, E, S, T, R>(anchor : &mut AnchorMut<'_, 'id, NamedGraph<NamedNode<N, E, T>, S>>, reader : R,
+                                     separator : char, mut parse_node : impl FnMut(&str) -> Option<N>,
+                                     mut parse_edge : impl FnMut(&str) -> Option<E>)
+    -> Result<HashMap<N, GraphPtr<'id, NamedNode<N, E, T>>>, ImportError>
+where N : Hash + Eq + Clone,
+      S : BuildHasher + Default,
+      T : BuildHasher + Default,
+      R : BufRead
+{
+    let mut nodes = HashMap::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut columns = line.splitn(3, separator).map(str::trim);
+        let mut parse_column = |x : Option<&str>| x.and_then(&mut parse_node).ok_or(ImportError::Malformed(i + 1));
+        let src = parse_column(columns.next())?;
+        let dst = parse_column(columns.next())?;
+        let edge = parse_edge(columns.next().unwrap_or("")).ok_or(ImportError::Malformed(i + 1))?;
+        let mut node = |data : N| *nodes.entry(data.clone()).or_insert_with(|| {
+            let ptr = anchor.spawn(data);
+            anchor.root_mut().insert(ptr);
+            ptr
+        });
+        let (src, dst) = (node(src), node(dst));
+        anchor.connect(src, dst, edge);
+    }
+    Ok(nodes)
+}
@@ -0,0 +1,136 @@
+use super::*;
+use core::any::Any;
+use std::collections::{HashMap, HashSet};
+
+// Type-erased access to a KeyIndex from GraphRaw, which does not know the type of keys.
+pub(crate) trait KeyIndexHook<T> {
+    // Schedules the key of `item` to be extracted anew, its data might have changed.
+    fn touch(&mut self, item : *const T);
+    // Forgets `item`, it is being dropped.
+    fn remove(&mut self, item : *const T);
+    fn as_any(&mut self) -> &mut dyn Any;
+}
+
+// Maps keys extracted from node data to nodes. Keys are extracted lazily from the nodes touched since the last
+// lookup, so mutating data through anchors and cursors keeps the index up to date.
+#[allow(clippy::type_complexity)]
+pub(crate) struct KeyIndex<K, T : GraphNode> {
+    extract : Box<dyn Fn(&T::Node) -> K + Send>,
+    nodes : HashMap<K, *const T>,
+    keys : HashMap<*const T, K>,
+    pending : HashSet<*const T>,
+}
+
+// Pointers are only dereferenced by the graph which owns the index.
+unsafe impl <K : Send, T : GraphNode> Send for KeyIndex<K, T> {}
+
+impl <K, T> KeyIndex<K, T>
+where K : Hash + Eq + Clone,
+      T : GraphNode
+{
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn new(extract : Box<dyn Fn(&T::Node) -> K + Send>, items : impl Iterator<Item = *const T>) -> Self
+    {
+        KeyIndex { extract, nodes : HashMap::new(), keys : HashMap::new(), pending : items.collect() }
+    }
+
+    fn unmap(&mut self, item : *const T)
+    {
+        if let Some(key) = self.keys.remove(&item) {
+            if self.nodes.get(&key) == Some(&item) {
+                self.nodes.remove(&key);
+            }
+        }
+    }
+
+    // Extracts keys of pending nodes.
+    fn flush(&mut self)
+    {
+        for item in core::mem::take(&mut self.pending) {
+            //pending nodes are allocated
+            let key = (self.extract)(unsafe { (*item).get() });
+            if self.keys.get(&item) == Some(&key) {
+                continue;
+            }
+            self.unmap(item);
+            self.nodes.insert(key.clone(), item);
+            self.keys.insert(item, key);
+        }
+    }
+
+    pub(crate) fn lookup(&mut self, key : &K) -> Option<*const T>
+    {
+        self.flush();
+        self.nodes.get(key).copied()
+    }
+}
+
+impl <K, T> KeyIndexHook<T> for KeyIndex<K, T>
+where K : Hash + Eq + Clone + 'static,
+      T : GraphNode + 'static
+{
+    fn touch(&mut self, item : *const T)
+    {
+        self.pending.insert(item);
+    }
+
+    fn remove(&mut self, item : *const T)
+    {
+        self.pending.remove(&item);
+        self.unmap(item);
+    }
+
+    fn as_any(&mut self) -> &mut dyn Any
+    {
+        self
+    }
+}
+
+impl <NodeType, Root, A : Allocator> GenericGraph<Root, NodeType, A>
+where NodeType : GraphNode + 'static,
+      Root : RootCollection<'static, NodeType>
+{
+    /// Starts maintaining an index from keys returned by `extract` to nodes, see `lookup`. The index covers every
+    /// allocated node and follows spawning, changes of node data made through anchors and cursors, and dropping
+    /// of nodes. Replaces the previous index.
+    pub fn set_key_index<K>(&mut self, extract : impl Fn(&NodeType::Node) -> K + Send + 'static)
+    where K : Hash + Eq + Clone + Send + 'static
+    {
+        let items = self.internal.data.iter().map(|x| x.as_ptr() as *const NodeType);
+        let index = KeyIndex::new(Box::new(extract), items);
+        *self.internal.key_index.get_mut().unwrap_or_else(|x| x.into_inner()) = Some(Box::new(index));
+    }
+
+    /// Drops the index created by `set_key_index`.
+    pub fn remove_key_index(&mut self)
+    {
+        *self.internal.key_index.get_mut().unwrap_or_else(|x| x.into_inner()) = None;
+    }
+}
+
+macro_rules! impl_anchor_lookup {
+    ($anchor_type:ident) => {
+        impl <'this, 'id, NodeType : 'this, Root : 'this, A : Allocator>
+        $anchor_type<'this, 'id, GenericGraph<Root, NodeType, A>>
+        where NodeType : GraphNode + 'static,
+              Root : RootCollection<'static, NodeType>
+        {
+            /// Returns a node whose key is `key` in the index created by `set_key_index`. If several nodes have
+            /// the same key, the one which got it last is returned. Returns None if there is no index.
+            /// # Panics
+            /// Panics if the index holds keys of another type.
+            pub fn lookup<K>(&self, key : &K) -> Option<GraphPtr<'id, NodeType>>
+            where K : Hash + Eq + Clone + 'static
+            {
+                let mut index = self.internal().key_index.lock().unwrap_or_else(|x| x.into_inner());
+                let index = index.as_mut()?.as_any().downcast_mut::<KeyIndex<K, NodeType>>()
+                    .expect("the key index holds keys of another type");
+                //nodes in the index are allocated
+                index.lookup(key).map(|x| unsafe { GraphPtr::from_ptr(x, self._guard) })
+            }
+        }
+    }
+}
+
+impl_anchor_lookup!{Anchor}
+impl_anchor_lookup!{AnchorMut}
@@ -0,0 +1,117 @@
+use super::*;
+use std::collections::HashMap;
+
+/// A place node data is paged in from and out to, e.g. a database. Used by anchors of graphs holding `Lazy` data.
+pub trait NodeSource<K, N> {
+    /// Returns the data of the node stored under `key`.
+    fn load(&mut self, key : &K) -> N;
+    /// Saves `data` of the node under `key`.
+    fn store(&mut self, key : &K, data : &N);
+}
+
+impl <K, N, S> NodeSource<K, N> for HashMap<K, N, S>
+where K : Hash + Eq + Clone,
+      N : Clone,
+      S : BuildHasher
+{
+    /// Panics if `key` is missing.
+    fn load(&mut self, key : &K) -> N
+    {
+        self[key].clone()
+    }
+
+    fn store(&mut self, key : &K, data : &N)
+    {
+        self.insert(key.clone(), data.clone());
+    }
+}
+
+/// Node data which may stay in a `NodeSource` until it is needed. A stub only holds the key of the data.
+pub struct Lazy<K, N> {
+    key : K,
+    data : Option<N>,
+}
+
+impl <K, N> Lazy<K, N> {
+    /// Creates a stub of the data stored under `key`.
+    pub fn stub(key : K) -> Self
+    {
+        Lazy { key, data : None }
+    }
+
+    /// Creates loaded data stored under `key`.
+    pub fn loaded(key : K, data : N) -> Self
+    {
+        Lazy { key, data : Some(data) }
+    }
+
+    /// Returns the key of the data in the source.
+    pub fn key(&self) -> &K
+    {
+        &self.key
+    }
+
+    /// Returns the data or None if this is a stub.
+    pub fn get(&self) -> Option<&N>
+    {
+        self.data.as_ref()
+    }
+
+    /// Returns the data or None if this is a stub.
+    pub fn get_mut(&mut self) -> Option<&mut N>
+    {
+        self.data.as_mut()
+    }
+
+    /// Returns true unless this is a stub.
+    pub fn is_loaded(&self) -> bool
+    {
+        self.data.is_some()
+    }
+}
+
+impl <'this, 'id, K : 'this, N : 'this, NodeType : 'this, Root : 'this, A : Allocator>
+AnchorMut<'this, 'id, GenericGraph<Root, NodeType, A>>
+where NodeType : GraphNode<Node = Lazy<K, N>>,
+      Root : RootCollection<'static, NodeType>
+{
+    /// Allocates a stub node for the data stored under `key`.
+    pub fn spawn_stub(&mut self, key : K) -> GraphPtr<'id, NodeType>
+    {
+        self.spawn(Lazy::stub(key))
+    }
+
+    /// Returns the data of `dst`. Loads it from `source` first if `dst` is a stub.
+    pub fn hydrate(&mut self, dst : GraphPtr<'id, NodeType>, source : &mut impl NodeSource<K, N>) -> &mut N
+    {
+        let lazy = self.internal_mut().get_mut(dst);
+        if lazy.data.is_none() {
+            lazy.data = Some(source.load(&lazy.key));
+        }
+        lazy.data.as_mut().unwrap()
+    }
+
+    /// Saves the data of `dst` to `source` and turns `dst` into a stub. Returns false if `dst` was a stub already.
+    pub fn evict(&mut self, dst : GraphPtr<'id, NodeType>, source : &mut impl NodeSource<K, N>) -> bool
+    {
+        let lazy = self.internal_mut().get_mut(dst);
+        match lazy.data.take() {
+            Some(data) => {
+                source.store(&lazy.key, &data);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Saves the data of every loaded node to `source`, including the nodes which are no longer accessible from
+    /// the root. Nodes stay loaded.
+    pub fn store_loaded(&self, source : &mut impl NodeSource<K, N>)
+    {
+        for i in self.all_nodes() {
+            if let Some(data) = &i.values.data {
+                source.store(&i.values.key, data);
+            }
+        }
+    }
+}
@@ -1,10 +1,28 @@
 #![allow(unused_unsafe)]
 
+// Emits a debug level `tracing` event if the `tracing` feature is enabled.
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(target : "dynamic_graph", $($arg)*);
+    }
+}
+
+// Enters a debug level `tracing` span until the end of the enclosing block if the `tracing` feature is enabled.
+macro_rules! trace_span {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(target : "dynamic_graph", $($arg)*).entered();
+    }
+}
+
 pub mod graph_ptr;
 pub use crate::graph_ptr::*;
 
 mod graph_raw;
+mod node_pool;
 use crate::graph_raw::*;
+pub use crate::graph_raw::{CleanupState, GraphItem};
 
 pub mod edge;
 pub use crate::edge::*;
@@ -12,28 +30,102 @@ pub use crate::edge::*;
 pub mod nodes;
 pub use crate::nodes::*;
 
-use core::hash::{Hash, Hasher};
+pub mod stats;
+pub use crate::stats::*;
+
+pub mod error;
+pub use crate::error::*;
+
+pub mod slab;
+pub use crate::slab::*;
+
+pub mod variant;
+pub use crate::variant::*;
+
+pub mod ptr_hash;
+pub use crate::ptr_hash::*;
+
+pub mod algo;
+
+pub mod sharded;
+pub use crate::sharded::*;
+
+pub mod persistent;
+pub use crate::persistent::*;
+
+pub mod transaction;
+pub use crate::transaction::*;
+
+pub mod diff;
+pub use crate::diff::*;
+
+pub mod pattern;
+pub use crate::pattern::*;
+
+pub mod path;
+pub use crate::path::*;
+
+pub mod render;
+pub use crate::render::*;
+
+pub mod import;
+pub mod export;
+pub use crate::export::GraphExport;
+
+pub mod lazy;
+pub use crate::lazy::*;
+
+pub mod property_map;
+pub use crate::property_map::*;
+
+pub mod visit_set;
+pub use crate::visit_set::*;
+
+mod key_index;
+use crate::key_index::*;
+
+#[cfg(feature = "storage")]
+pub mod storage;
+#[cfg(feature = "storage")]
+pub use crate::storage::*;
+
+mod dag;
+
+use core::hash::{Hash, Hasher, BuildHasher};
 use core::mem::transmute;
-use core::ops::{Index, IndexMut, Deref, DerefMut};
+use core::ops::{Index, IndexMut, Deref, DerefMut, Bound, RangeBounds};
 use core::ptr::NonNull;
+pub use allocator_api2::alloc::{AllocError, Allocator, Global};
 
-pub struct GenericGraph<Root, NodeType>
+/// A graph which owns its nodes. The graph is Send if its nodes, root and allocator are Send and Sync if its nodes
+/// and root are Sync, which holds whenever node data and edges are. Node memory comes from `A`, the global heap by
+/// default.
+pub struct GenericGraph<Root, NodeType, A = Global>
 where Root : RootCollection<'static, NodeType>,
       NodeType : GraphNode,
+      A : Allocator
 {
-    internal : GraphRaw<NodeType>,
+    internal : GraphRaw<NodeType, A>,
     root : Root
 }
 
 pub trait GraphImpl {
     /// Traverses the graph and drops any inaccessible node. Disregards any heuristic designed to improve
-    /// cleanup performance.
-    fn cleanup_precise(&mut self);
+    /// cleanup performance. Returns a summary of the performed work.
+    fn cleanup_precise(&mut self) -> CleanupReport;
     /// Traverses the graph and drops inaccessible nodes. This method will miss some of the leaked items which
-    /// might result in spikes in memory usage. !! Currently, none of the possible heuristics are implemented.
-    fn cleanup(&mut self) {
-        self.cleanup_precise();
+    /// might result in spikes in memory usage. The traversal is skipped entirely if few nodes were spawned, few
+    /// edges or nodes were removed and few mutable views were handed out since the last cleanup. Nodes which
+    /// survived several cleanups are considered old and are only dropped by a full traversal, which replaces
+    /// every 8th traversal of this method and every call to `cleanup_precise`.
+    fn cleanup(&mut self) -> CleanupReport {
+        self.cleanup_precise()
     }
+    /// Returns the number of allocated nodes, including the ones which are no longer accessible from the root.
+    fn node_count(&self) -> usize;
+    /// Returns an estimate of the number of inaccessible nodes based on the number of operations which might
+    /// have detached a node since the last cleanup. Does not traverse the graph.
+    fn garbage_estimate(&self) -> usize;
 }
 
 impl <Root, NodeType> Default for GenericGraph<Root, NodeType>
@@ -52,11 +144,86 @@ where Root : RootCollection<'static, NodeType>,
 {
     pub fn new() -> Self
     {
-        GenericGraph { root : Root::default(), internal : GraphRaw::new() }
+        Self::with_allocator(Global)
+    }
+
+    /// Creates a graph with memory reserved for `nodes` nodes.
+    pub fn with_capacity(nodes : usize) -> Self
+    {
+        let mut res = Self::new();
+        res.internal.reserve(nodes);
+        res
     }
 }
 
-impl <Root, NodeType> GenericGraph<Root, NodeType>
+impl <Root, NodeType, A : Allocator> GenericGraph<Root, NodeType, A>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    /// Creates a graph which allocates memory for its nodes from `allocator`.
+    pub fn with_allocator(allocator : A) -> Self
+    {
+        GenericGraph { root : Root::default(), internal : GraphRaw::new_in(allocator) }
+    }
+
+    /// Sets the policy cleanup uses to release unused capacity. The default is `ShrinkPolicy::Always`.
+    pub fn set_shrink_policy(&mut self, policy : ShrinkPolicy)
+    {
+        self.internal.shrink = policy;
+    }
+
+    /// Releases unused capacity of the node index and memory of nodes regardless of the shrink policy. Nodes
+    /// are allocated in chunks of up to 4096, a chunk is only released once none of its nodes is alive.
+    pub fn shrink_to_fit(&mut self)
+    {
+        self.internal.shrink_to_fit();
+    }
+
+    /// Enables or disables the index of incoming edges. When enabled, `in_neighbors` and `in_degree` look sources
+    /// up instead of scanning every node on each call. The index follows edges added and removed by the graph and
+    /// nodes dropped by cleanup, but is rebuilt once after node views were handed out, as they might change edges
+    /// unnoticed.
+    pub fn set_reverse_index(&mut self, enabled : bool)
+    {
+        self.internal.set_reverse_index(enabled);
+    }
+
+    /// Enables or disables recording of nodes returned by `drain_dirty`. Disabling drops the nodes recorded so far.
+    /// Disabled by default.
+    pub fn set_dirty_tracking(&mut self, enabled : bool)
+    {
+        self.internal.set_dirty_tracking(enabled);
+    }
+
+    /// Enables or disables recording of modification epochs, see `last_modified`. Disabled by default.
+    pub fn set_modification_tracking(&mut self, enabled : bool)
+    {
+        self.internal.track_modified = enabled;
+    }
+
+    /// Registers a callback which receives data of every node dropped by cleanup. Replaces the previously
+    /// registered callback.
+    pub fn on_collect(&mut self, mut f : impl FnMut(NodeType::Node) + Send + 'static)
+    where NodeType : 'static
+    {
+        self.internal.on_collect = Some(Box::new(move |x : NodeType| f(x.into_data())));
+    }
+
+    /// Registers a callback which receives every change of the graph made through anchors and cursors. Edges
+    /// modified directly through node views are not reported.
+    pub fn add_observer(&mut self, f : impl FnMut(GraphEvent<NodeType>) + Send + 'static)
+    {
+        self.internal.observers.push(Box::new(f));
+    }
+
+    /// Removes every registered observer.
+    pub fn clear_observers(&mut self)
+    {
+        self.internal.observers.clear();
+    }
+}
+
+impl <Root, NodeType, A : Allocator> GenericGraph<Root, NodeType, A>
 where Root : RootCollection<'static, NodeType>,
       NodeType : GraphNode
 {
@@ -65,8 +232,9 @@ where Root : RootCollection<'static, NodeType>,
     /// # Safety
     /// Caller must use a unique `guard` from generativity::Guard.
     pub unsafe fn anchor_mut<'id>(&mut self, guard : Id<'id>, strategy : CleanupStrategy)
-                                  -> AnchorMut<'_, 'id, GenericGraph<Root, NodeType>>
+                                  -> AnchorMut<'_, 'id, GenericGraph<Root, NodeType, A>>
     {
+        trace_event!(mutable = true, "anchor created");
         AnchorMut { parent : self, _guard : guard, strategy }
     }
 
@@ -74,24 +242,182 @@ where Root : RootCollection<'static, NodeType>,
     /// Prefer `anchor!` macro in application code.
     /// # Safety
     /// Caller must use a unique `guard` from generativity::Guard.
-    pub unsafe fn anchor<'id>(&self, guard : Id<'id>) -> Anchor<'_, 'id, GenericGraph<Root, NodeType>>
+    pub unsafe fn anchor<'id>(&self, guard : Id<'id>) -> Anchor<'_, 'id, GenericGraph<Root, NodeType, A>>
     {
+        trace_event!(mutable = false, "anchor created");
         Anchor { parent : self, _guard : guard }
     }
 }
 
-pub type VecGraph<T> = GenericGraph<RootVec<'static, T>, T>;
-pub type NamedGraph<T> = GenericGraph<RootNamedSet<'static, T>, T>;
-pub type OptionGraph<T> = GenericGraph<RootOption<'static, T>, T>;
+/// Node data which owns nested graphs.
+pub trait NestedGraphs {
+    /// Cleans up every graph owned by the data, normally with `cleanup_recursive` or `cleanup_precise`.
+    fn cleanup_nested(&mut self);
+}
+
+impl <Root, NodeType, A : Allocator> GenericGraph<Root, NodeType, A>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode,
+      NodeType::Node : NestedGraphs
+{
+    /// Performs `cleanup_precise` and then cleans up graphs nested in the data of every remaining node.
+    pub fn cleanup_recursive(&mut self) -> CleanupReport
+    {
+        let report = self.cleanup_precise();
+        for i in self.internal.data.iter() {
+            //(E)
+            //the graph is borrowed mutably
+            unsafe {
+                (*(peek(i) as *mut NodeType)).get_mut().cleanup_nested();
+            }
+        }
+        report
+    }
+}
+
+impl <Root, NodeType, A : Allocator> NestedGraphs for GenericGraph<Root, NodeType, A>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode,
+      NodeType::Node : NestedGraphs
+{
+    fn cleanup_nested(&mut self)
+    {
+        self.cleanup_recursive();
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl <Root, NodeType, A : Allocator> GenericGraph<Root, NodeType, A>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    /// Same as `cleanup_precise`, but the graph is traversed by several threads of the rayon pool at once. Pays
+    /// off for graphs with wide breadth-first levels.
+    pub fn cleanup_parallel(&mut self) -> CleanupReport
+    where NodeType : Sync,
+          Root : Sync
+    {
+        self.internal.cleanup_parallel(&self.root)
+    }
+}
+
+macro_rules! impl_graph_export {
+    ([$($Param:tt)*] $NodeType:ty, $iter:ident, $E:ty) => {
+        impl <Root, N : Clone, A : Allocator, $($Param)*> GenericGraph<Root, $NodeType, A>
+        where Root : RootCollection<'static, $NodeType>
+        {
+            /// Returns a copy of every node and edge of the graph which can be inspected while the graph is
+            /// modified. Takes O(nodes + edges) time and memory, see `PersistentGraph` for versions sharing
+            /// unchanged nodes.
+            pub fn export(&self) -> GraphExport<N, $E>
+            {
+                self.internal.export(&self.root, |graph, src, f| graph.$iter(src).for_each(|x| f(x.ptr.as_mut(), x.values.edge())))
+            }
+        }
+    }
+}
+
+impl_graph_export!{[E : Clone, S : BuildHasher + Default] NamedNode<N, E, S>, iter, E}
+impl_graph_export!{[E : Clone] VecNode<N, E>, iter, E}
+impl_graph_export!{[E : Clone] OptionNode<N, E>, iter, E}
+#[cfg(feature = "indexmap")]
+impl_graph_export!{[E : Clone] OrderedNamedNode<N, E>, iter, E}
+impl_graph_export!{[K : Ord, E : Clone] TreeNode<K, N, E>, iter, E}
+#[cfg(feature = "smallvec")]
+impl_graph_export!{[E : Clone, const INLINE : usize] SmallVecNode<N, E, INLINE>, iter, E}
+impl_graph_export!{[E : Clone] MultiNamedNode<N, E>, iter, E}
+impl_graph_export!{[E : Clone, const D : usize] ArrayNode<N, E, D>, iter, E}
+impl_graph_export!{[E : Clone] DiNode<N, E>, iter_out, E}
+impl_graph_export!{[E : Clone] UndirectedNode<N, E>, iter, E}
+impl_graph_export!{[] SetNode<N>, iter, ()}
+
+pub type VecGraph<T, A = Global> = GenericGraph<RootVec<'static, T>, T, A>;
+pub type NamedGraph<T, S = DefaultPtrState, A = Global> = GenericGraph<RootNamedSet<'static, T, S>, T, A>;
+pub type OptionGraph<T, A = Global> = GenericGraph<RootOption<'static, T>, T, A>;
+pub type MapGraph<K, T, A = Global> = GenericGraph<RootHashMap<'static, K, T>, T, A>;
+pub type DequeGraph<T, A = Global> = GenericGraph<RootVecDeque<'static, T>, T, A>;
+pub type SlabGraph<T, A = Global> = GenericGraph<RootSlab<'static, T>, T, A>;
 
 /// A strategy AnchorMut employs to perform cleanup after drop.
 pub enum CleanupStrategy {
     /// AnchorMut never cleans up.
     Never,
-    /// AnchorMut always performs cleanup when dropped
+    /// AnchorMut always performs precise cleanup when dropped.
+    Always,
+    /// AnchorMut performs precise cleanup when dropped if the estimated number of inaccessible nodes exceeds the limit.
+    WhenGarbageExceeds(usize),
+    /// AnchorMut performs precise cleanup when dropped if the number of allocated nodes exceeds the limit.
+    WhenCapacityExceeds(usize),
+}
+
+/// A policy deciding when cleanup returns unused capacity of the node index and chunks of node memory to the
+/// allocator. Nodes are allocated in chunks of up to 4096 nodes and a chunk is only released once none of its
+/// nodes is alive, so a few surviving nodes may keep most of the memory of a graph which used to be large.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShrinkPolicy {
+    /// Cleanup never shrinks. Call `shrink_to_fit` to release memory on demand.
+    Never,
+    /// Cleanup always shrinks.
+    #[default]
     Always,
+    /// Cleanup shrinks the node index or the node memory if either has space for more than the given number of
+    /// unused nodes.
+    WhenUnusedExceeds(usize),
+}
+
+/// A change of the graph reported to observers. Nodes are identified with the same raw pointers `as_ptr` returns.
+pub enum GraphEvent<T> {
+    /// A node was allocated.
+    NodeSpawned(*const T),
+    /// An edge from `src` to `dst` was added. Replacing data of an existing edge is not reported, while
+    /// storing an edge into an occupied slot or key reports removal of the previous edge first.
+    EdgeAdded { src : *const T, dst : *const T },
+    /// An edge from `src` to `dst` was removed.
+    EdgeRemoved { src : *const T, dst : *const T },
+    /// A node was dropped by cleanup or removed with `kill` or `take`. The pointer must not be dereferenced.
+    NodeCollected(*const T),
+}
+
+impl <T> Clone for GraphEvent<T> {
+    fn clone(&self) -> Self
+    {
+        *self
+    }
+}
+
+impl <T> Copy for GraphEvent<T> {}
+
+impl <T> PartialEq for GraphEvent<T> {
+    fn eq(&self, other : &Self) -> bool
+    {
+        use GraphEvent::*;
+        match (*self, *other) {
+            (NodeSpawned(a), NodeSpawned(b)) | (NodeCollected(a), NodeCollected(b)) => a == b,
+            (EdgeAdded { src : a, dst : b }, EdgeAdded { src : c, dst : d }) |
+            (EdgeRemoved { src : a, dst : b }, EdgeRemoved { src : c, dst : d }) => a == c && b == d,
+            _ => false,
+        }
+    }
+}
+
+impl <T> Eq for GraphEvent<T> {}
+
+impl <T> core::fmt::Debug for GraphEvent<T> {
+    fn fmt(&self, f : &mut core::fmt::Formatter<'_>) -> core::fmt::Result
+    {
+        match *self {
+            GraphEvent::NodeSpawned(x) => f.debug_tuple("NodeSpawned").field(&x).finish(),
+            GraphEvent::EdgeAdded { src, dst } => f.debug_struct("EdgeAdded").field("src", &src).field("dst", &dst).finish(),
+            GraphEvent::EdgeRemoved { src, dst } => f.debug_struct("EdgeRemoved").field("src", &src).field("dst", &dst).finish(),
+            GraphEvent::NodeCollected(x) => f.debug_tuple("NodeCollected").field(&x).finish(),
+        }
+    }
 }
 
+// Events only carry addresses which are never dereferenced.
+unsafe impl <T> Send for GraphEvent<T> {}
+unsafe impl <T> Sync for GraphEvent<T> {}
+
 pub struct AnchorMut<'this, 'id, T : 'this>
 where T : GraphImpl
 {
@@ -100,6 +426,8 @@ where T : GraphImpl
     _guard : Id<'id>,
 }
 
+/// Provides shared access to a graph. Anchors are Copy and are Send if the graph is Sync, so read-only analyses
+/// may run on several scoped threads at once.
 pub struct Anchor<'this, 'id, T : 'this>
 where T : GraphImpl
 {
@@ -107,13 +435,38 @@ where T : GraphImpl
     _guard : Id<'id>,
 }
 
-impl <Root, NodeType> GraphImpl
-for GenericGraph<Root, NodeType>
+// Anchors only provide shared access, so copies keep the brand of the original.
+impl <'this, 'id, T : 'this> Clone for Anchor<'this, 'id, T>
+where T : GraphImpl
+{
+    fn clone(&self) -> Self
+    {
+        *self
+    }
+}
+
+impl <'this, 'id, T : 'this> Copy for Anchor<'this, 'id, T>
+where T : GraphImpl {}
+
+impl <Root, NodeType, A : Allocator> GraphImpl
+for GenericGraph<Root, NodeType, A>
 where Root : RootCollection<'static, NodeType>,
       NodeType : GraphNode
 {
-    fn cleanup_precise(&mut self) {
-        self.internal.cleanup_precise(&self.root);
+    fn cleanup_precise(&mut self) -> CleanupReport {
+        self.internal.cleanup_precise(&self.root)
+    }
+
+    fn cleanup(&mut self) -> CleanupReport {
+        self.internal.cleanup(&self.root)
+    }
+
+    fn node_count(&self) -> usize {
+        self.internal.data.len()
+    }
+
+    fn garbage_estimate(&self) -> usize {
+        self.internal.garbage_estimate()
     }
 }
 
@@ -121,49 +474,65 @@ impl <'this, 'id, T : 'this> Drop for AnchorMut<'this, 'id, T>
 where T : GraphImpl
 {
     fn drop(&mut self) {
+        trace_event!("anchor dropped");
         match &self.strategy {
-            CleanupStrategy::Always => self.parent.cleanup(),
+            CleanupStrategy::Always => { self.parent.cleanup_precise(); }
+            CleanupStrategy::WhenGarbageExceeds(limit) if self.parent.garbage_estimate() > *limit => {
+                self.parent.cleanup_precise();
+            }
+            CleanupStrategy::WhenCapacityExceeds(limit) if self.parent.node_count() > *limit => {
+                self.parent.cleanup_precise();
+            }
             _ => ()
         }
     }
 }
 
 macro_rules! impl_anchor_index {
-    ($NodeType:ident) => {
-        impl <'this, 'id, N : 'this, E : 'this, Root : 'this>
-        Index<GraphPtr<'id, $NodeType<N, E>>>
-        for Anchor<'this, 'id, GenericGraph<Root, $NodeType<N, E>>>
-        where Root : RootCollection<'static, $NodeType<N, E>>
+    ($NodeType:ident $(, $S:ident)?) => {
+        impl <'this, 'id, N : 'this, E : 'this $(, $S : BuildHasher + Default + 'this)?, Root : 'this, A : Allocator>
+        Index<GraphPtr<'id, $NodeType<N, E $(, $S)?>>>
+        for Anchor<'this, 'id, GenericGraph<Root, $NodeType<N, E $(, $S)?>, A>>
+        where Root : RootCollection<'static, $NodeType<N, E $(, $S)?>>
         {
-            type Output = node_views::$NodeType<'id, N, E>;
-            fn index(&self, dst : GraphPtr<'id, $NodeType<N, E>>) -> &Self::Output
+            type Output = node_views::$NodeType<'id, N, E $(, $S)?>;
+            fn index(&self, dst : GraphPtr<'id, $NodeType<N, E $(, $S)?>>) -> &Self::Output
             {
                 self.internal().get_view(dst)
             }
         }
 
-        impl <'this, 'id, N : 'this, E : 'this, Root : 'this>
-        Anchor<'this, 'id, GenericGraph<Root, $NodeType<N, E>>>
-        where Root : RootCollection<'static, $NodeType<N, E>>
+        impl <'this, 'id, N : 'this, E : 'this $(, $S : BuildHasher + Default + 'this)?, Root : 'this, A : Allocator>
+        Anchor<'this, 'id, GenericGraph<Root, $NodeType<N, E $(, $S)?>, A>>
+        where Root : RootCollection<'static, $NodeType<N, E $(, $S)?>>
         {
             /// Returns an iterator over edges attached to `src` node.
-            pub fn edges(&self, src : GraphPtr<'id, $NodeType<N, E>>) ->
-                impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, $NodeType<N, E>>>>
+            pub fn edges(&self, src : GraphPtr<'id, $NodeType<N, E $(, $S)?>>) ->
+                impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, $NodeType<N, E $(, $S)?>>>>
             {
                 self.internal().iter(src)
             }
+
+            /// Returns an iterator over pointers to nodes attached to `src` node.
+            pub fn neighbors(&self, src : GraphPtr<'id, $NodeType<N, E $(, $S)?>>) ->
+                impl Iterator<Item = GraphPtr<'id, $NodeType<N, E $(, $S)?>>> + '_
+            {
+                self.internal().neighbors(src)
+            }
         }
     }
 }
 
 
-impl_anchor_index!{NamedNode}
+impl_anchor_index!{NamedNode, S}
 impl_anchor_index!{OptionNode}
 impl_anchor_index!{VecNode}
+#[cfg(feature = "indexmap")]
+impl_anchor_index!{OrderedNamedNode}
 
-impl <'this, 'id, K : 'this, N : 'this, E : 'this, Root : 'this>
+impl <'this, 'id, K : 'this, N : 'this, E : 'this, Root : 'this, A : Allocator>
 Index<GraphPtr<'id, TreeNode<K, N, E>>>
-for Anchor<'this, 'id, GenericGraph<Root, TreeNode<K, N, E>>>
+for Anchor<'this, 'id, GenericGraph<Root, TreeNode<K, N, E>, A>>
 where Root : RootCollection<'static, TreeNode<K, N, E>>, K : Ord
 {
     type Output = node_views::TreeNode<'id, K, N, E>;
@@ -173,84 +542,101 @@ where Root : RootCollection<'static, TreeNode<K, N, E>>, K : Ord
     }
 }
 
-impl <'this, 'id, K : 'this, N : 'this, E : 'this, Root : 'this>
-Anchor<'this, 'id, GenericGraph<Root, TreeNode<K, N, E>>>
+impl <'this, 'id, K : 'this, N : 'this, E : 'this, Root : 'this, A : Allocator>
+Anchor<'this, 'id, GenericGraph<Root, TreeNode<K, N, E>, A>>
 where Root : RootCollection<'static, TreeNode<K, N, E>>, K : Ord
 {
     /// Returns an iterator over edges attached to `src` node.
+    #[allow(clippy::type_complexity)]
     pub fn edges(&self, src : GraphPtr<'id, TreeNode<K, N, E>>) ->
         impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, TreeNode<K, N, E>>>>
     {
         self.internal().iter(src)
     }
+
+    /// Returns an iterator over pointers to nodes attached to `src` node.
+    pub fn neighbors(&self, src : GraphPtr<'id, TreeNode<K, N, E>>) ->
+        impl Iterator<Item = GraphPtr<'id, TreeNode<K, N, E>>> + '_
+    {
+        self.internal().neighbors(src)
+    }
 }
 
 
 macro_rules! impl_anchor_mut_index {
-    ($NodeType:ident) => {
-        impl <'this, 'id, N : 'this, E : 'this, Root : 'this>
-        Index<GraphPtr<'id, $NodeType<N, E>>>
-        for AnchorMut<'this, 'id, GenericGraph<Root, $NodeType<N, E>>>
-        where Root : RootCollection<'static, $NodeType<N, E>>
+    ($NodeType:ident $(, $S:ident)?) => {
+        impl <'this, 'id, N : 'this, E : 'this $(, $S : BuildHasher + Default + 'this)?, Root : 'this, A : Allocator>
+        Index<GraphPtr<'id, $NodeType<N, E $(, $S)?>>>
+        for AnchorMut<'this, 'id, GenericGraph<Root, $NodeType<N, E $(, $S)?>, A>>
+        where Root : RootCollection<'static, $NodeType<N, E $(, $S)?>>
         {
-            type Output = node_views::$NodeType<'id, N, E>;
-            fn index(&self, dst : GraphPtr<'id, $NodeType<N, E>>) -> &Self::Output
+            type Output = node_views::$NodeType<'id, N, E $(, $S)?>;
+            fn index(&self, dst : GraphPtr<'id, $NodeType<N, E $(, $S)?>>) -> &Self::Output
             {
                 self.internal().get_view(dst)
             }
         }
 
-        impl <'this, 'id, N : 'this, E : 'this, Root : 'this>
-        AnchorMut<'this, 'id, GenericGraph<Root, $NodeType<N, E>>>
-        where Root : RootCollection<'static, $NodeType<N, E>>
+        impl <'this, 'id, N : 'this, E : 'this $(, $S : BuildHasher + Default + 'this)?, Root : 'this, A : Allocator>
+        AnchorMut<'this, 'id, GenericGraph<Root, $NodeType<N, E $(, $S)?>, A>>
+        where Root : RootCollection<'static, $NodeType<N, E $(, $S)?>>
         {
             /// Returns an iterator over edges attached to `src` node.
-            pub fn edges(&self, src : GraphPtr<'id, $NodeType<N, E>>) ->
-                impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, $NodeType<N, E>>>>
+            pub fn edges(&self, src : GraphPtr<'id, $NodeType<N, E $(, $S)?>>) ->
+                impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, $NodeType<N, E $(, $S)?>>>>
             {
                 self.internal().iter(src)
             }
+
+            /// Returns an iterator over pointers to nodes attached to `src` node.
+            pub fn neighbors(&self, src : GraphPtr<'id, $NodeType<N, E $(, $S)?>>) ->
+                impl Iterator<Item = GraphPtr<'id, $NodeType<N, E $(, $S)?>>> + '_
+            {
+                self.internal().neighbors(src)
+            }
         }
 
-        impl <'this, 'id, N : 'this, E : 'this, Root : 'this>
-        IndexMut<GraphPtr<'id, $NodeType<N, E>>>
-        for AnchorMut<'this, 'id, GenericGraph<Root, $NodeType<N, E>>>
-        where Root : RootCollection<'static, $NodeType<N, E>>
+        impl <'this, 'id, N : 'this, E : 'this $(, $S : BuildHasher + Default + 'this)?, Root : 'this, A : Allocator>
+        IndexMut<GraphPtr<'id, $NodeType<N, E $(, $S)?>>>
+        for AnchorMut<'this, 'id, GenericGraph<Root, $NodeType<N, E $(, $S)?>, A>>
+        where Root : RootCollection<'static, $NodeType<N, E $(, $S)?>>
         {
-            fn index_mut(&mut self, dst : GraphPtr<'id, $NodeType<N, E>>) -> &mut Self::Output {
-                self.internal_mut().get_view_mut(dst)
+            fn index_mut(&mut self, dst : GraphPtr<'id, $NodeType<N, E $(, $S)?>>) -> &mut Self::Output {
+                self.internal_mut().expose_views().get_view_mut(dst)
             }
         }
         
-        impl <'this, 'id, N : 'this, E : 'this, Root : 'this>
-        AnchorMut<'this, 'id, GenericGraph<Root, $NodeType<N, E>>>
-        where Root : RootCollection<'static, $NodeType<N,E>>
+        impl <'this, 'id, N : 'this, E : 'this $(, $S : BuildHasher + Default + 'this)?, Root : 'this, A : Allocator>
+        AnchorMut<'this, 'id, GenericGraph<Root, $NodeType<N, E $(, $S)?>, A>>
+        where Root : RootCollection<'static, $NodeType<N, E $(, $S)?>>
         {
             /// Returns a mutable iterator over edges attached to `src` node.
-            pub fn edges_mut(&mut self, src : GraphPtr<'id, $NodeType<N, E>>) ->
-                impl Iterator<Item = GraphItem<Edge<&'_ mut N, &'_ mut E>, GraphPtr<'id, $NodeType<N, E>>>>
+            pub fn edges_mut(&mut self, src : GraphPtr<'id, $NodeType<N, E $(, $S)?>>) ->
+                impl Iterator<Item = GraphItem<Edge<&'_ mut N, &'_ mut E>, GraphPtr<'id, $NodeType<N, E $(, $S)?>>>>
             {
                 self.internal_mut().iter_mut(src)
             }
         
             /// Provides direct mutable direct access to two different nodes `src` and `dst`. Returns or None if `src` is the same as `dst`.
-            pub fn bridge(&mut self, src : GraphPtr<'id, $NodeType<N, E>>,
-                                     dst : GraphPtr<'id, $NodeType<N, E>>) ->
-                Option<(&'_ mut node_views::$NodeType<'id, N, E>, &'_ mut node_views::$NodeType<'id, N, E>)>
+            pub fn bridge(&mut self, src : GraphPtr<'id, $NodeType<N, E $(, $S)?>>,
+                                     dst : GraphPtr<'id, $NodeType<N, E $(, $S)?>>) ->
+                Option<(&'_ mut node_views::$NodeType<'id, N, E $(, $S)?>, &'_ mut node_views::$NodeType<'id, N, E $(, $S)?>)>
             {
-                self.internal_mut().bridge(src, dst)
+                self.internal_mut().expose_views().bridge(src, dst)
             }
         }
     }
 }
 
-impl_anchor_mut_index!{NamedNode}
+impl_anchor_mut_index!{NamedNode, S}
 impl_anchor_mut_index!{OptionNode}
 impl_anchor_mut_index!{VecNode}
+#[cfg(feature = "indexmap")]
+impl_anchor_mut_index!{OrderedNamedNode}
 
-impl <'this, 'id, K : 'this, N : 'this, E : 'this, Root : 'this>
+impl <'this, 'id, K : 'this, N : 'this, E : 'this, Root : 'this, A : Allocator>
 Index<GraphPtr<'id, TreeNode<K, N, E>>>
-for AnchorMut<'this, 'id, GenericGraph<Root, TreeNode<K, N, E>>>
+for AnchorMut<'this, 'id, GenericGraph<Root, TreeNode<K, N, E>, A>>
 where Root : RootCollection<'static, TreeNode<K, N, E>>, K : Ord
 {
     type Output = node_views::TreeNode<'id, K, N, E>;
@@ -260,33 +646,42 @@ where Root : RootCollection<'static, TreeNode<K, N, E>>, K : Ord
     }
 }
 
-impl <'this, 'id, K : 'this, N : 'this, E : 'this, Root : 'this>
-AnchorMut<'this, 'id, GenericGraph<Root, TreeNode<K, N, E>>>
+impl <'this, 'id, K : 'this, N : 'this, E : 'this, Root : 'this, A : Allocator>
+AnchorMut<'this, 'id, GenericGraph<Root, TreeNode<K, N, E>, A>>
 where Root : RootCollection<'static, TreeNode<K, N, E>>, K : Ord
 {
     /// Returns an iterator over edges attached to `src` node.
+    #[allow(clippy::type_complexity)]
     pub fn edges(&self, src : GraphPtr<'id, TreeNode<K, N, E>>) ->
         impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, TreeNode<K, N, E>>>>
     {
         self.internal().iter(src)
     }
+
+    /// Returns an iterator over pointers to nodes attached to `src` node.
+    pub fn neighbors(&self, src : GraphPtr<'id, TreeNode<K, N, E>>) ->
+        impl Iterator<Item = GraphPtr<'id, TreeNode<K, N, E>>> + '_
+    {
+        self.internal().neighbors(src)
+    }
 }
 
-impl <'this, 'id, K : 'this, N : 'this, E : 'this, Root : 'this>
+impl <'this, 'id, K : 'this, N : 'this, E : 'this, Root : 'this, A : Allocator>
 IndexMut<GraphPtr<'id, TreeNode<K, N, E>>>
-for AnchorMut<'this, 'id, GenericGraph<Root, TreeNode<K, N, E>>>
+for AnchorMut<'this, 'id, GenericGraph<Root, TreeNode<K, N, E>, A>>
 where Root : RootCollection<'static, TreeNode<K, N, E>>, K : Ord
 {
     fn index_mut(&mut self, dst : GraphPtr<'id,  TreeNode<K, N, E>>) -> &mut Self::Output {
-        self.internal_mut().get_view_mut(dst)
+        self.internal_mut().expose_views().get_view_mut(dst)
     }
 }
 
-impl <'this, 'id, K : 'this, N : 'this, E : 'this, Root : 'this>
-AnchorMut<'this, 'id, GenericGraph<Root, TreeNode<K, N, E>>>
+impl <'this, 'id, K : 'this, N : 'this, E : 'this, Root : 'this, A : Allocator>
+AnchorMut<'this, 'id, GenericGraph<Root, TreeNode<K, N, E>, A>>
 where Root : RootCollection<'static, TreeNode<K, N, E>>, K : Ord
 {
     /// Returns a mutable iterator over edges attached to `src` node.
+    #[allow(clippy::type_complexity)]
     pub fn edges_mut(&mut self, src : GraphPtr<'id, TreeNode<K, N, E>>) ->
         impl Iterator<Item = GraphItem<Edge<&'_ mut N, &'_ mut E>, GraphPtr<'id, TreeNode<K, N, E>>>>
     {
@@ -294,20 +689,163 @@ where Root : RootCollection<'static, TreeNode<K, N, E>>, K : Ord
     }
 
     /// Provides direct mutable direct access to two different nodes `src` and `dst`. Returns or None if `src` is the same as `dst`.
+    #[allow(clippy::type_complexity)]
     pub fn bridge(&mut self, src : GraphPtr<'id, TreeNode<K, N, E>>,
                              dst : GraphPtr<'id, TreeNode<K, N, E>>) ->
         Option<(&'_ mut node_views::TreeNode<'id, K, N, E>, &'_ mut node_views::TreeNode<'id, K, N, E>)>
     {
-        self.internal_mut().bridge(src, dst)
+        self.internal_mut().expose_views().bridge(src, dst)
+    }
+}
+
+impl <'this, 'id, N : 'this, E : 'this, S : BuildHasher + Default + 'this, Root : 'this, A : Allocator>
+AnchorMut<'this, 'id, GenericGraph<Root, NamedNode<N, E, S>, A>>
+where Root : RootCollection<'static, NamedNode<N, E, S>>
+{
+    /// Adds an edge from `src` to `dst`. Returns the previous edge data if `src` was already connected to `dst`.
+    pub fn connect(&mut self, src : GraphPtr<'id, NamedNode<N, E, S>>, dst : GraphPtr<'id, NamedNode<N, E, S>>, edge : E)
+        -> Option<E>
+    {
+        self.internal_mut().connect(src, dst, edge)
+    }
+
+    /// Removes an edge from `src` to `dst`. Returns the edge data or None if there was no such edge.
+    pub fn disconnect(&mut self, src : GraphPtr<'id, NamedNode<N, E, S>>, dst : GraphPtr<'id, NamedNode<N, E, S>>) -> Option<E>
+    {
+        self.internal_mut().disconnect(src, dst)
+    }
+
+    /// Adds an edge from `src` to `dst` or calls `update` on the existing one. Returns the edge data.
+    pub fn connect_or_update(&mut self, src : GraphPtr<'id, NamedNode<N, E, S>>, dst : GraphPtr<'id, NamedNode<N, E, S>>,
+                             edge : E, update : impl FnOnce(&mut E)) -> &mut E
+    {
+        self.internal_mut().connect_or_update(src, dst, edge, update)
+    }
+
+    /// Returns an entry of the edge from `src` to `dst` for in-place manipulation.
+    pub fn edge_entry(&mut self, src : GraphPtr<'id, NamedNode<N, E, S>>, dst : GraphPtr<'id, NamedNode<N, E, S>>)
+        -> NodeNamedEntry<'_, 'id, NamedNode<N, E, S>, E>
+    {
+        self.internal_mut().expose_views().get_view_mut(src).edge_entry(dst)
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl <'this, 'id, N : 'this, E : 'this, Root : 'this, A : Allocator>
+AnchorMut<'this, 'id, GenericGraph<Root, OrderedNamedNode<N, E>, A>>
+where Root : RootCollection<'static, OrderedNamedNode<N, E>>
+{
+    /// Adds an edge from `src` to `dst`. Returns the previous edge data if `src` was already connected to `dst`.
+    /// A new edge is placed after existing ones.
+    pub fn connect(&mut self, src : GraphPtr<'id, OrderedNamedNode<N, E>>, dst : GraphPtr<'id, OrderedNamedNode<N, E>>, edge : E)
+        -> Option<E>
+    {
+        self.internal_mut().connect(src, dst, edge)
+    }
+
+    /// Removes an edge from `src` to `dst` preserving the order of other edges. Returns the edge data or None if
+    /// there was no such edge.
+    pub fn disconnect(&mut self, src : GraphPtr<'id, OrderedNamedNode<N, E>>, dst : GraphPtr<'id, OrderedNamedNode<N, E>>)
+        -> Option<E>
+    {
+        self.internal_mut().disconnect(src, dst)
+    }
+
+    /// Adds an edge from `src` to `dst` or calls `update` on the existing one. Returns the edge data.
+    pub fn connect_or_update(&mut self, src : GraphPtr<'id, OrderedNamedNode<N, E>>, dst : GraphPtr<'id, OrderedNamedNode<N, E>>,
+                             edge : E, update : impl FnOnce(&mut E)) -> &mut E
+    {
+        self.internal_mut().connect_or_update(src, dst, edge, update)
+    }
+}
+
+impl <'this, 'id, N : 'this, E : 'this, Root : 'this, A : Allocator>
+AnchorMut<'this, 'id, GenericGraph<Root, VecNode<N, E>, A>>
+where Root : RootCollection<'static, VecNode<N, E>>
+{
+    /// Adds an edge from `src` to `dst` or replaces data of the first such edge. Returns the previous edge data
+    /// if `src` was already connected to `dst`.
+    pub fn connect(&mut self, src : GraphPtr<'id, VecNode<N, E>>, dst : GraphPtr<'id, VecNode<N, E>>, edge : E)
+        -> Option<E>
+    {
+        self.internal_mut().connect(src, dst, edge)
+    }
+
+    /// Removes the first edge from `src` to `dst`. Returns the edge data or None if there was no such edge.
+    pub fn disconnect(&mut self, src : GraphPtr<'id, VecNode<N, E>>, dst : GraphPtr<'id, VecNode<N, E>>) -> Option<E>
+    {
+        self.internal_mut().disconnect(src, dst)
+    }
+
+    /// Adds an edge from `src` to `dst` or calls `update` on the first such edge. Returns the edge data.
+    pub fn connect_or_update(&mut self, src : GraphPtr<'id, VecNode<N, E>>, dst : GraphPtr<'id, VecNode<N, E>>,
+                             edge : E, update : impl FnOnce(&mut E)) -> &mut E
+    {
+        self.internal_mut().connect_or_update(src, dst, edge, update)
+    }
+}
+
+impl <'this, 'id, N : 'this, E : 'this, Root : 'this, A : Allocator>
+AnchorMut<'this, 'id, GenericGraph<Root, OptionNode<N, E>, A>>
+where Root : RootCollection<'static, OptionNode<N, E>>
+{
+    /// Sets the edge of `src` to point to `dst`. Returns the previous edge data if `src` had an edge, even if it
+    /// pointed to another node.
+    pub fn connect(&mut self, src : GraphPtr<'id, OptionNode<N, E>>, dst : GraphPtr<'id, OptionNode<N, E>>, edge : E)
+        -> Option<E>
+    {
+        self.internal_mut().connect(src, dst, edge)
+    }
+
+    /// Removes the edge of `src` if it points to `dst`. Returns the edge data or None if there was no such edge.
+    pub fn disconnect(&mut self, src : GraphPtr<'id, OptionNode<N, E>>, dst : GraphPtr<'id, OptionNode<N, E>>) -> Option<E>
+    {
+        self.internal_mut().disconnect(src, dst)
+    }
+
+    /// Calls `update` on the edge from `src` to `dst` or replaces the edge of `src` otherwise. Returns the edge data.
+    pub fn connect_or_update(&mut self, src : GraphPtr<'id, OptionNode<N, E>>, dst : GraphPtr<'id, OptionNode<N, E>>,
+                             edge : E, update : impl FnOnce(&mut E)) -> &mut E
+    {
+        self.internal_mut().connect_or_update(src, dst, edge, update)
+    }
+}
+
+impl <'this, 'id, K : 'this, N : 'this, E : 'this, Root : 'this, A : Allocator>
+AnchorMut<'this, 'id, GenericGraph<Root, TreeNode<K, N, E>, A>>
+where Root : RootCollection<'static, TreeNode<K, N, E>>, K : Ord
+{
+    /// Adds an edge from `src` to `dst` under `key`. Returns the previous edge stored under `key`.
+    #[allow(clippy::type_complexity)]
+    pub fn connect(&mut self, src : GraphPtr<'id, TreeNode<K, N, E>>, key : K, dst : GraphPtr<'id, TreeNode<K, N, E>>,
+                   edge : E) -> Option<(GraphPtr<'id, TreeNode<K, N, E>>, E)>
+    {
+        self.internal_mut().connect(src, key, dst, edge)
+    }
+
+    /// Removes an edge stored under `key`. Returns the edge or None if there was no such edge.
+    #[allow(clippy::type_complexity)]
+    pub fn disconnect(&mut self, src : GraphPtr<'id, TreeNode<K, N, E>>, key : &K)
+        -> Option<(GraphPtr<'id, TreeNode<K, N, E>>, E)>
+    {
+        self.internal_mut().disconnect(src, key)
+    }
+
+    /// Adds an edge from `src` to `dst` under `key`. If `key` is already present, the edge is redirected to `dst`
+    /// and `update` is called on its data. Returns the edge data.
+    pub fn connect_or_update(&mut self, src : GraphPtr<'id, TreeNode<K, N, E>>, key : K, dst : GraphPtr<'id, TreeNode<K, N, E>>,
+                             edge : E, update : impl FnOnce(&mut E)) -> &mut E
+    {
+        self.internal_mut().connect_or_update(src, key, dst, edge, update)
     }
 }
 
-impl <'this, 'id, N : 'this, NodeType : 'this, Root : 'this>
-AnchorMut<'this, 'id, GenericGraph<Root, NodeType>>
+impl <'this, 'id, N : 'this, NodeType : 'this, Root : 'this, A : Allocator>
+AnchorMut<'this, 'id, GenericGraph<Root, NodeType, A>>
 where NodeType : GraphNode<Node = N>,
       Root : RootCollection<'static, NodeType>
 {
-    fn internal(&self) -> &GraphRaw<NodeType> {
+    fn internal(&self) -> &GraphRaw<NodeType, A> {
         &self.parent.internal
     }
 
@@ -320,18 +858,18 @@ where NodeType : GraphNode<Node = N>,
     }
 
     /// Creates an immutable cursor pointing to `dst`
-    pub fn cursor(&self, dst : GraphPtr<'id, NodeType>) -> Cursor<'_, 'id, NodeType>
+    pub fn cursor(&self, dst : GraphPtr<'id, NodeType>) -> Cursor<'_, 'id, NodeType, A>
     {
-        Cursor { parent : self.internal(), current : dst }
+        Cursor { parent : self.internal(), current : dst, back : Vec::new(), forward : Vec::new() }
     }
 }
 
-impl <'this, 'id, N : 'this, NodeType : 'this, Root : 'this>
-Anchor<'this, 'id, GenericGraph<Root, NodeType>>
+impl <'this, 'id, N : 'this, NodeType : 'this, Root : 'this, A : Allocator>
+Anchor<'this, 'id, GenericGraph<Root, NodeType, A>>
 where NodeType : GraphNode<Node = N>,
       Root : RootCollection<'static, NodeType>
 {
-    fn internal(&self) -> &GraphRaw<NodeType> {
+    fn internal(&self) -> &GraphRaw<NodeType, A> {
         &self.parent.internal
     }
 
@@ -344,22 +882,67 @@ where NodeType : GraphNode<Node = N>,
     }
 
     /// Creates an immutable cursor pointing to `dst`
-    pub fn cursor(&self, dst : GraphPtr<'id, NodeType>) -> Cursor<'_, 'id, NodeType>
+    pub fn cursor(&self, dst : GraphPtr<'id, NodeType>) -> Cursor<'_, 'id, NodeType, A>
+    {
+        Cursor { parent : self.internal(), current : dst, back : Vec::new(), forward : Vec::new() }
+    }
+
+    /// Passes an anchor of the graph nested in the data of `dst` to `f`. The nested graph gets its own brand.
+    pub fn descend<R, SubRoot, SubNode, SubAlloc>(&self, dst : GraphPtr<'id, NodeType>,
+                                                  project : impl FnOnce(&N) -> &GenericGraph<SubRoot, SubNode, SubAlloc>,
+                                                  f : impl for<'a, 'sub> FnOnce(Anchor<'a, 'sub, GenericGraph<SubRoot, SubNode, SubAlloc>>) -> R) -> R
+    where SubRoot : RootCollection<'static, SubNode>,
+          SubNode : GraphNode,
+          SubAlloc : Allocator
     {
-        Cursor { parent : self.internal(), current : dst }
+        let sub = project(self.internal().get(dst));
+        make_guard!(g);
+        //the guard is unique
+        f(unsafe { sub.anchor(Id::from(g)) })
     }
 }
 
-impl <'this, 'id, N : 'this, NodeType : 'this, Root : 'this>
-AnchorMut<'this, 'id, GenericGraph<Root, NodeType>>
+impl <'this, 'id, N : 'this, NodeType : 'this, Root : 'this, A : Allocator>
+AnchorMut<'this, 'id, GenericGraph<Root, NodeType, A>>
 where NodeType : GraphNode<Node = N>,
       Root : RootCollection<'static, NodeType>
 {
-    fn internal_mut(&mut self) -> &mut GraphRaw<NodeType>
+    fn internal_mut(&mut self) -> &mut GraphRaw<NodeType, A>
     {
         &mut self.parent.internal
     }
 
+    /// Reserves memory for at least `additional` more nodes, so spawning them won't allocate.
+    pub fn reserve(&mut self, additional : usize)
+    {
+        self.parent.internal.reserve(additional);
+    }
+
+    /// Returns nodes spawned or mutably accessed since the previous call, each once, and starts tracking anew.
+    /// Nodes dropped in the meantime are not returned. Returns nothing unless enabled with `set_dirty_tracking`.
+    pub fn drain_dirty(&mut self) -> impl Iterator<Item = GraphPtr<'id, NodeType>>
+    {
+        let guard = self._guard;
+        //dirty nodes are allocated and belong to the graph
+        self.parent.internal.drain_dirty().into_iter().map(move |x| unsafe { GraphPtr::from_mut(x, guard) })
+    }
+
+    /// Releases unused capacity of the node index and memory of nodes regardless of the shrink policy, see
+    /// `GenericGraph::shrink_to_fit`.
+    pub fn shrink_to_fit(&mut self)
+    {
+        self.parent.internal.shrink_to_fit();
+    }
+
+    /// Calls `f` on the data of every allocated node, including the ones which are no longer accessible from
+    /// the root. Nodes are split between tasks of the rayon pool.
+    #[cfg(feature = "parallel")]
+    pub fn par_for_each_mut(&mut self, f : impl Fn(&mut N) + Sync)
+    where N : Send
+    {
+        self.parent.internal.par_for_each_mut(f);
+    }
+
     /// Allocates a new node and returns the pointer. This node will become inaccessible when parent anchor
     /// is dropped and will be disposed of upon next cleanup unless you attach it to the root or another node accessible
     /// from the root.
@@ -372,6 +955,61 @@ where NodeType : GraphNode<Node = N>,
         }
     }
 
+    /// Allocates a node for every item of `payloads` and returns the pointers in the same order. Memory is reserved
+    /// once for the number of items reported by the iterator.
+    pub fn spawn_iter(&mut self, payloads : impl IntoIterator<Item = N>) -> Vec<GraphPtr<'id, NodeType>>
+    {
+        let payloads = payloads.into_iter();
+        let additional = payloads.size_hint().0;
+        self.reserve(additional);
+        let mut res = Vec::with_capacity(additional);
+        for data in payloads {
+            let ptr = self.internal_mut().spawn_detached(data);
+            //allocation never fails
+            res.push(unsafe { GraphPtr::from_ptr(ptr, self._guard) });
+        }
+        res
+    }
+
+    /// Same as `spawn`, but returns `GraphError::AllocationFailed` instead of aborting if memory for the node can't
+    /// be allocated. `data` is dropped in that case.
+    pub fn try_spawn(&mut self, data : N) -> Result<GraphPtr<'id, NodeType>, GraphError>
+    {
+        let ptr = self.internal_mut().try_spawn_detached(data)?;
+        unsafe {
+            //allocation succeeded
+            Ok(GraphPtr::from_ptr(ptr, self._guard))
+        }
+    }
+
+    /// Passes a mutable anchor of the graph nested in the data of `dst` to `f`. The nested graph gets its own brand
+    /// and is cleaned up according to `strategy` once `f` returns.
+    pub fn descend<R, SubRoot, SubNode, SubAlloc>(&mut self, dst : GraphPtr<'id, NodeType>,
+                                                  project : impl FnOnce(&mut N) -> &mut GenericGraph<SubRoot, SubNode, SubAlloc>,
+                                                  strategy : CleanupStrategy,
+                                                  f : impl for<'a, 'sub> FnOnce(AnchorMut<'a, 'sub, GenericGraph<SubRoot, SubNode, SubAlloc>>) -> R) -> R
+    where SubRoot : RootCollection<'static, SubNode>,
+          SubNode : GraphNode,
+          SubAlloc : Allocator
+    {
+        let sub = project(self.internal_mut().get_mut(dst));
+        make_guard!(g);
+        //the guard is unique
+        f(unsafe { sub.anchor_mut(Id::from(g), strategy) })
+    }
+
+    /// Allocates a new node holding `variant` of an enum and returns the pointer.
+    pub fn spawn_variant<V : Variant<N>>(&mut self, variant : V) -> GraphPtr<'id, NodeType>
+    {
+        self.spawn(variant.wrap())
+    }
+
+    /// Returns the data of `dst` if it holds variant `V`.
+    pub fn get_variant_mut<V : Variant<N>>(&mut self, dst : GraphPtr<'id, NodeType>) -> Option<&mut V>
+    {
+        V::peek_mut(self.internal_mut().get_mut(dst))
+    }
+
     /// Immediately drops `dst` node and frees allocated memory.
     /// # Safety
     /// Caller must ensure killed node will never be accessed. `dst` must become inaccesible from root before
@@ -380,63 +1018,441 @@ where NodeType : GraphNode<Node = N>,
         self.internal_mut().kill(dst.as_mut());
     }
 
-    /// Creates a mutable cursor pointing to `dst`.
-    pub fn cursor_mut(&mut self, dst : GraphPtr<'id, NodeType>)
-           -> CursorMut<'_, 'id, NodeType>
+    /// Removes every edge pointing to `dst`, detaches it from the root, immediately frees allocated memory and
+    /// returns the data of `dst`. The anchor is consumed along with the brand of `dst`, so no copy of `dst` can be
+    /// used afterwards.
+    /// # Panics
+    /// Panics if the root or an edge collection still holds `dst` after `unlink`.
+    pub fn remove(mut self, dst : GraphPtr<'id, NodeType>) -> N
     {
-        CursorMut { parent : self.internal_mut(), current : dst }
+        // No pointer to `dst` is left in the graph and the brand ends along with the anchor.
+        unsafe { self.take(dst) }
     }
-}
-
-macro_rules! impl_root_mut_iter {
-    ($root_type:ident) => {
-        impl <'this, 'id, N : 'this, NodeType : 'this>
-        AnchorMut<'this, 'id, $root_type<NodeType>>
-        where NodeType : GraphNode<Node = N>
-        {
-            /// Returns an iterator over data and pointers to nodes attached to the root.
-            pub fn iter(&self) -> impl Iterator<Item = GraphItem<&'_ N, GraphPtr<'id, NodeType>>>
-            {
-                self.root().iter().map(move |x| {
-                    let p = x.as_ptr();
-                    let values = unsafe { (*p).get() };
-                    GraphItem { values, ptr : *x }
-                })
-            }
 
-            /// Returns a mutable iterator over data and pointers to nodes attached to the root.
-            pub fn iter_mut(&mut self) -> impl Iterator<Item = GraphItem<&'_ mut N, GraphPtr<'id, NodeType>>>
-            {
-                self.root_mut().iter().map(move |x| {
-                    let p = x.as_mut();
-                    let values = unsafe { (*p).get_mut() };
-                    GraphItem { values, ptr : *x }
-                })
-            }
-        }
+    /// Removes every edge pointing to `dst`, detaches it from the root, frees it and returns its data. Unlike
+    /// `remove`, keeps the anchor, so several nodes can be moved out of the graph at once.
+    /// # Safety
+    /// Caller must ensure `dst` will never be accessed. Any copies of `dst` in external collections should be
+    /// disposed of as well.
+    /// # Panics
+    /// Panics if the root or an edge collection still holds `dst` after `unlink`.
+    pub unsafe fn take(&mut self, dst : GraphPtr<'id, NodeType>) -> N
+    {
+        RootCollection::unlink(&mut self.parent.root, dst.as_ptr());
+        assert!(!GraphRaw::<NodeType>::root_contains(&self.parent.root, dst.as_ptr()), "the root collection does not implement unlink");
+        let internal = self.internal_mut();
+        internal.unlink(dst.as_ptr());
+        //(E)
+        unsafe { internal.take(dst.as_ptr()) }.into_data()
     }
-}
 
-impl_root_mut_iter!{VecGraph}
-impl_root_mut_iter!{NamedGraph}
-impl_root_mut_iter!{OptionGraph}
+    /// Swaps data of `a` and `b` leaving their edges intact. Does nothing if `a` is the same as `b`.
+    pub fn swap_data(&mut self, a : GraphPtr<'id, NodeType>, b : GraphPtr<'id, NodeType>)
+    {
+        self.internal_mut().swap_data(a, b);
+    }
+
+    /// Removes every edge going out of `dst`. Edges of `UndirectedNode` are removed from both endpoints.
+    pub fn clear_edges(&mut self, dst : GraphPtr<'id, NodeType>)
+    {
+        self.internal_mut().clear_edges(dst);
+    }
+
+    /// Returns the stable id of `dst`. The id resolves to the same node until the node is dropped.
+    pub fn stable_id(&mut self, dst : GraphPtr<'id, NodeType>) -> NodeId
+    {
+        NodeId(self.parent.internal.stable_id(dst.as_mut()))
+    }
+
+    /// Creates a weak pointer to `dst`. Nodes referenced by weak pointers are tracked in the same table as stable ids.
+    pub fn downgrade(&mut self, dst : GraphPtr<'id, NodeType>) -> WeakGraphPtr<NodeType>
+    {
+        let generation = self.parent.internal.stable_id(dst.as_mut());
+        WeakGraphPtr { node : dst.as_ptr(), generation }
+    }
+
+    /// Makes cleanup treat `dst` as a part of the root until the returned token is passed to `unpin`.
+    pub fn pin(&mut self, dst : GraphPtr<'id, NodeType>) -> PinToken<'id, NodeType>
+    {
+        let id = self.internal_mut().pin(dst.as_ptr());
+        PinToken { node : dst, id }
+    }
+
+    /// Releases a node pinned by `token`, which may come from an earlier anchor. Tokens obtained from a different
+    /// graph and tokens of removed nodes are ignored.
+    pub fn unpin(&mut self, token : PinToken<'_, NodeType>)
+    {
+        self.internal_mut().unpin(token.id);
+    }
+
+    /// Returns the node pinned by `token`, which may come from an earlier anchor, or None if the token was obtained
+    /// from a different graph or the node was removed.
+    pub fn pinned(&self, token : &PinToken<'_, NodeType>) -> Option<GraphPtr<'id, NodeType>>
+    {
+        //(W)
+        self.internal().pinned(token.id).map(|x| unsafe { GraphPtr::from_ptr(x, self._guard) })
+    }
+
+    /// Sets mark bits of `dst`. Marks are scratch space for traversals and are not used by the graph itself.
+    pub fn set_mark(&mut self, dst : GraphPtr<'id, NodeType>, bits : u8)
+    {
+        self.parent.internal.set_mark(dst.as_mut(), bits);
+    }
+
+    /// Resets mark bits of every node to zero. Takes constant time.
+    pub fn clear_marks(&mut self)
+    {
+        self.parent.internal.clear_marks();
+    }
+
+    /// Creates a mutable cursor pointing to `dst`.
+    pub fn cursor_mut(&mut self, dst : GraphPtr<'id, NodeType>)
+           -> CursorMut<'_, 'id, NodeType, A>
+    where Root : Send
+    {
+        let parent = &mut *self.parent;
+        CursorMut { parent : &mut parent.internal, root : &mut parent.root, current : dst, back : Vec::new(), forward : Vec::new() }
+    }
+}
+
+macro_rules! impl_anchor_common {
+    ($anchor_type:ident) => {
+        impl <'this, 'id, N : 'this, NodeType : 'this, Root : 'this, A : Allocator>
+        $anchor_type<'this, 'id, GenericGraph<Root, NodeType, A>>
+        where NodeType : GraphNode<Node = N>,
+              Root : RootCollection<'static, NodeType>
+        {
+            /// Returns the data of `dst` if it holds variant `V`.
+            pub fn get_variant<V : Variant<N>>(&self, dst : GraphPtr<'id, NodeType>) -> Option<&V>
+            {
+                V::peek(self.internal().get(dst))
+            }
+
+            /// Returns the number of allocated nodes, including the ones which are no longer accessible from the root.
+            pub fn node_count(&self) -> usize
+            {
+                self.internal().data.len()
+            }
+
+            /// Returns a pointer to the node with stable id `id` or None if the node was dropped. Ids obtained from
+            /// a different graph resolve to arbitrary nodes of this graph.
+            pub fn resolve(&self, id : NodeId) -> Option<GraphPtr<'id, NodeType>>
+            {
+                self.internal().ids.get(&id.0)
+                    //nodes are removed from ids when dropped
+                    .map(|x| unsafe { GraphPtr::from_ptr(*x, self._guard) })
+            }
+
+            /// Returns a pointer to the node referenced by `weak` or None if the node was dropped.
+            pub fn upgrade(&self, weak : WeakGraphPtr<NodeType>) -> Option<GraphPtr<'id, NodeType>>
+            {
+                match self.internal().ids.get(&weak.generation) {
+                    //nodes are removed from ids when dropped
+                    Some(x) if core::ptr::eq(*x, weak.node) => Some(unsafe { GraphPtr::from_ptr(*x, self._guard) }),
+                    _ => None,
+                }
+            }
+
+            /// Returns the number of nodes the graph can hold without reallocating its node index.
+            pub fn capacity(&self) -> usize
+            {
+                self.internal().data.capacity()
+            }
+
+            /// Traverses the graph and returns the number of nodes accessible from the root.
+            pub fn reachable_count(&self) -> usize
+            {
+                self.internal().reachable(&self.parent.root).len()
+            }
+
+            /// Returns a pointer to the first node accessible from the root which satisfies `pred`. Nodes are visited
+            /// in breadth-first order.
+            pub fn find(&self, mut pred : impl FnMut(&N) -> bool) -> Option<GraphPtr<'id, NodeType>>
+            {
+                let internal = self.internal();
+                internal.reachable(&self.parent.root).into_iter()
+                        .find(|x| pred(unsafe { (**x).get() }))
+                        //(W)
+                        .map(|x| unsafe { GraphPtr::from_mut(x, self._guard) })
+            }
+
+            /// Returns pointers to every node accessible from the root which satisfies `pred`. Nodes are visited
+            /// in breadth-first order.
+            pub fn find_all(&self, mut pred : impl FnMut(&N) -> bool) -> Vec<GraphPtr<'id, NodeType>>
+            {
+                let internal = self.internal();
+                internal.reachable(&self.parent.root).into_iter()
+                        .filter(|x| pred(unsafe { (**x).get() }))
+                        //(W)
+                        .map(|x| unsafe { GraphPtr::from_mut(x, self._guard) })
+                        .collect()
+            }
+
+            /// Returns pointers to every allocated node which is no longer accessible from the root and will be
+            /// disposed of upon next cleanup. The graph is left intact.
+            pub fn find_unreachable(&self) -> Vec<GraphPtr<'id, NodeType>>
+            {
+                self.internal().unreachable(&self.parent.root).into_iter()
+                    //(W)
+                    .map(|x| unsafe { GraphPtr::from_ptr(x, self._guard) })
+                    .collect()
+            }
+
+            /// Returns the number of edges stored in allocated nodes.
+            pub fn edge_count(&self) -> usize
+            {
+                self.internal().stats().edge_count
+            }
+
+            /// Returns an estimate of memory used by the graph.
+            pub fn memory_usage(&self) -> MemoryReport
+            {
+                self.internal().memory_usage()
+            }
+
+            /// Returns a summary of the graph storage.
+            pub fn stats(&self) -> GraphStats
+            {
+                self.internal().stats()
+            }
+
+            /// Returns an iterator over data and pointers to every allocated node, including the ones which are no
+            /// longer accessible from the root.
+            pub fn all_nodes(&self) -> impl Iterator<Item = GraphItem<&'_ N, GraphPtr<'id, NodeType>>>
+            {
+                self.internal().nodes(self._guard)
+            }
+
+            /// Returns the position of `dst` among allocated nodes, a number in `0..node_count()`. Positions match the
+            /// order of `all_nodes` and only change when a node is killed or removed, or a cleanup is performed.
+            /// Spawning new nodes never changes positions of existing ones.
+            pub fn ptr_to_index(&self, dst : GraphPtr<'id, NodeType>) -> usize
+            {
+                self.internal().store_index(dst)
+            }
+
+            /// Returns mark bits of `dst` set by `set_mark` since the last `clear_marks`, zero if there are none.
+            pub fn get_mark(&self, dst : GraphPtr<'id, NodeType>) -> u8
+            {
+                self.internal().mark(dst.as_ptr())
+            }
+
+            /// Returns the modification epoch of the last change of `dst`: spawning it, mutable access to its data or
+            /// edges through anchors and cursors, or removal of one of its edges. Epochs grow with every change while
+            /// modification tracking is enabled. Returns 0 if `dst` was not changed since tracking was enabled.
+            pub fn last_modified(&self, dst : GraphPtr<'id, NodeType>) -> u64
+            {
+                //(W)
+                unsafe { (*dst.as_ptr()).meta().modified }
+            }
+
+            /// Returns the modification epoch of the latest change of any node, see `last_modified`.
+            pub fn modification_epoch(&self) -> u64
+            {
+                self.internal().modified
+            }
+
+            /// Returns a pointer to the node at position `index` or None if `index` is out of bounds.
+            /// See `ptr_to_index`.
+            pub fn index_to_ptr(&self, index : usize) -> Option<GraphPtr<'id, NodeType>>
+            {
+                self.internal().data.get(index)
+                    //(W)
+                    .map(|x| unsafe { GraphPtr::from_mut(x.as_ptr(), self._guard) })
+            }
+
+            /// Returns pointers to the sources of edges pointing to `dst`, including the nodes which are no longer
+            /// accessible from the root. Scans the whole graph unless the reverse index is enabled.
+            pub fn in_neighbors(&self, dst : GraphPtr<'id, NodeType>) -> impl Iterator<Item = GraphPtr<'id, NodeType>>
+            {
+                let g = self._guard;
+                //(W)
+                self.internal().in_neighbors(dst.as_ptr()).into_iter().map(move |x| unsafe { GraphPtr::from_ptr(x, g) })
+            }
+
+            /// Returns the number of edges pointing to `dst`.
+            pub fn in_degree(&self, dst : GraphPtr<'id, NodeType>) -> usize
+            {
+                self.internal().in_neighbors(dst.as_ptr()).len()
+            }
+
+            /// Returns the number of edges going out of `src` node.
+            pub fn degree(&self, src : GraphPtr<'id, NodeType>) -> usize
+            {
+                self.internal().degree(src)
+            }
+
+            /// Finds the shortest path from `src` to `dst`, an edge from `a` to `b` weighs `weight(a, b)`. Uses
+            /// Dijkstra's algorithm if no weight is negative and Bellman-Ford's otherwise. Returns None if `dst` is
+            /// unreachable or reachable through a cycle of negative length.
+            pub fn shortest_path(&self, src : GraphPtr<'id, NodeType>, dst : GraphPtr<'id, NodeType>,
+                                 mut weight : impl FnMut(GraphPtr<'id, NodeType>, GraphPtr<'id, NodeType>) -> f64)
+                -> Option<Path<'id, NodeType>>
+            {
+                let internal = self.internal();
+                let g = self._guard;
+                //(W)
+                let ptr = |i : usize| unsafe { GraphPtr::from_mut(internal.data[i].as_ptr(), g) };
+                let adjacency : Vec<Vec<_>> = internal.adjacency().iter().enumerate().map(|(i, targets)| {
+                    targets.iter().map(|&x| (x, weight(ptr(i), ptr(x)))).collect()
+                }).collect();
+                let nodes = algo::shortest_path(&adjacency, internal.store_index(src), internal.store_index(dst))?;
+                Path::from_nodes(nodes.into_iter().map(ptr).collect())
+            }
+
+            /// Returns an endless walk starting at `src` which follows a random outgoing edge on every step and
+            /// stops at a node without outgoing edges. `rng` is a source of random numbers. Use `take` to limit
+            /// the length of the walk.
+            pub fn random_walk<'a>(&'a self, src : GraphPtr<'id, NodeType>, rng : impl FnMut() -> u64 + 'a)
+                -> impl Iterator<Item = GraphPtr<'id, NodeType>> + 'a
+            {
+                self.weighted_random_walk(src, rng, |_, _| 1.0)
+            }
+
+            /// Same as `random_walk`, but an edge from `a` to `b` is chosen with probability proportional to
+            /// `weight(a, b)`. Edges of zero weight are never chosen.
+            pub fn weighted_random_walk<'a>(&'a self, src : GraphPtr<'id, NodeType>, mut rng : impl FnMut() -> u64 + 'a,
+                                            mut weight : impl FnMut(GraphPtr<'id, NodeType>, GraphPtr<'id, NodeType>) -> f64 + 'a)
+                -> impl Iterator<Item = GraphPtr<'id, NodeType>> + 'a
+            {
+                let internal = self.internal();
+                let g = self._guard;
+                let mut targets = Vec::new();
+                let mut weights = Vec::new();
+                core::iter::successors(Some(src), move |&x| {
+                    internal.targets_of(x.as_ptr(), &mut targets);
+                    weights.clear();
+                    //(W)
+                    weights.extend(targets.iter().map(|&y| weight(x, unsafe { GraphPtr::from_mut(y, g) })));
+                    let total : f64 = weights.iter().sum();
+                    if total <= 0.0 {
+                        return None;
+                    }
+                    //Uniform value in [0, total).
+                    let mut r = (rng() >> 11) as f64 / (1u64 << 53) as f64 * total;
+                    let i = weights.iter().position(|&w| {
+                        r -= w;
+                        r < 0.0
+                    }).unwrap_or_else(|| weights.iter().rposition(|&w| w > 0.0).unwrap_or(0));
+                    //(W)
+                    Some(unsafe { GraphPtr::from_mut(targets[i], g) })
+                })
+            }
+        }
+    }
+}
+
+impl_anchor_common!{AnchorMut}
+impl_anchor_common!{Anchor}
+
+impl <'this, 'id, N : 'this, NodeType : 'this, Root : 'this, A : Allocator>
+AnchorMut<'this, 'id, GenericGraph<Root, NodeType, A>>
+where NodeType : GraphNode<Node = N>,
+      Root : RootCollection<'static, NodeType> + RootIterable<NodeType>
+{
+    /// Returns an iterator over data and pointers to nodes attached to the root, paired with their keys if the root
+    /// is a keyed collection.
+    pub fn iter(&self) -> impl Iterator<Item = Root::Item<'_, GraphItem<&'_ N, GraphPtr<'id, NodeType>>>>
+    {
+        let guard = self._guard;
+        RootIterable::iter_with(&self.parent.root, move |p| {
+            //nodes attached to the root are allocated
+            let values = unsafe { (*p).get() };
+            GraphItem { values, ptr : unsafe { GraphPtr::from_mut(p, guard) } }
+        })
+    }
+
+    /// Returns a mutable iterator over data and pointers to nodes attached to the root, paired with their keys if
+    /// the root is a keyed collection.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = Root::Item<'_, GraphItem<&'_ mut N, GraphPtr<'id, NodeType>>>>
+    {
+        let guard = self._guard;
+        // Every yielded node can be mutated.
+        let mut yielded = Vec::new();
+        RootIterable::iter_with(&self.parent.root, |p| yielded.push(p)).for_each(drop);
+        for p in yielded {
+            self.parent.internal.mark_dirty(p);
+        }
+        RootIterable::iter_with(&self.parent.root, move |p| {
+            //nodes attached to the root are allocated and the anchor is borrowed mutably
+            let values = unsafe { (*p).get_mut() };
+            GraphItem { values, ptr : unsafe { GraphPtr::from_mut(p, guard) } }
+        })
+    }
+}
+
+impl <'this, 'id, N : 'this, NodeType : 'this, Root : 'this, A : Allocator>
+Anchor<'this, 'id, GenericGraph<Root, NodeType, A>>
+where NodeType : GraphNode<Node = N>,
+      Root : RootCollection<'static, NodeType> + RootIterable<NodeType>
+{
+    /// Returns an iterator over data and pointers to nodes attached to the root, paired with their keys if the root
+    /// is a keyed collection.
+    pub fn iter(&self) -> impl Iterator<Item = Root::Item<'_, GraphItem<&'_ N, GraphPtr<'id, NodeType>>>>
+    {
+        let guard = self._guard;
+        RootIterable::iter_with(&self.parent.root, move |p| {
+            //nodes attached to the root are allocated
+            let values = unsafe { (*p).get() };
+            GraphItem { values, ptr : unsafe { GraphPtr::from_mut(p, guard) } }
+        })
+    }
+}
+
+impl <'this, 'id, K : 'this, N : 'this, NodeType : 'this>
+AnchorMut<'this, 'id, MapGraph<K, NodeType>>
+where NodeType : GraphNode<Node = N>,
+      K : Hash + Eq
+{
+    /// Returns the node attached to the root under `key`. If there is none, spawns a node holding the data returned
+    /// by `payload` and attaches it under `key`.
+    pub fn get_or_spawn(&mut self, key : K, payload : impl FnOnce() -> N) -> GraphPtr<'id, NodeType>
+    {
+        if let Some(&ptr) = self.root().get(&key) {
+            return ptr;
+        }
+        let ptr = self.spawn(payload());
+        self.root_mut().insert(key, ptr);
+        ptr
+    }
+}
+
+// Object safe access to the root of the graph a CursorMut was created from.
+trait RootUnlink<T> {
+    // Unlinks `dst` and returns true if the root still holds it.
+    fn unlink(&mut self, dst : *const T) -> bool;
+}
+
+impl <T, Root> RootUnlink<T> for Root
+where T : GraphNode, Root : RootCollection<'static, T>
+{
+    fn unlink(&mut self, dst : *const T) -> bool {
+        RootCollection::unlink(self, dst);
+        GraphRaw::<T>::root_contains(self, dst)
+    }
+}
 
 /// A wrapper over a GraphPtr which provides simplified access to AnchorMut API.
-pub struct CursorMut<'this, 'id, T : 'this> {
-    parent : &'this mut GraphRaw<T>,
-    current : GraphPtr<'id, T>
+pub struct CursorMut<'this, 'id, T : 'this, A : Allocator = Global> {
+    parent : &'this mut GraphRaw<T, A>,
+    root : &'this mut (dyn RootUnlink<T> + Send + 'this),
+    current : GraphPtr<'id, T>,
+    back : Vec<GraphPtr<'id, T>>,
+    forward : Vec<GraphPtr<'id, T>>,
 }
 
 /// A wrapper over a GraphPtr which provides simplified access to Anchor API.
-pub struct Cursor<'this, 'id, T : 'this> {
-    parent : &'this GraphRaw<T>,
-    current : GraphPtr<'id, T>
+pub struct Cursor<'this, 'id, T : 'this, A : Allocator = Global> {
+    parent : &'this GraphRaw<T, A>,
+    current : GraphPtr<'id, T>,
+    back : Vec<GraphPtr<'id, T>>,
+    forward : Vec<GraphPtr<'id, T>>,
 }
 
 macro_rules! impl_cursor_immutable {
-    ($cursor_type:ident) => {
-        impl <'this, 'id, N : 'this, NodeType : 'this>
-        $cursor_type<'this, 'id, NodeType>
+    ($cursor_type:ident ) => {
+        impl <'this, 'id, N : 'this, NodeType : 'this , A : Allocator>
+        $cursor_type<'this, 'id, NodeType , A>
         where NodeType : GraphNode<Node = N>
         {
             /// Returns a pointer to the current node the cursor points to.
@@ -456,51 +1472,132 @@ macro_rules! impl_cursor_immutable {
             {
                 self.current = dst;
             }
+
+            /// Moves the cursor to `dst` and records the current node in the navigation history. Discards nodes
+            /// which could be reached with `forward`.
+            pub fn push_jump(&mut self, dst : GraphPtr<'id, NodeType>)
+            {
+                self.forward.clear();
+                self.back.push(self.current);
+                self.current = dst;
+            }
+
+            /// Moves the cursor to the previous node in the navigation history. Returns false if there is none.
+            pub fn back(&mut self) -> bool
+            {
+                match self.back.pop() {
+                    Some(dst) => {
+                        self.forward.push(self.current);
+                        self.current = dst;
+                        true
+                    },
+                    None => false,
+                }
+            }
+
+            /// Moves the cursor to the node it left with `back`. Returns false if there is none.
+            pub fn forward(&mut self) -> bool
+            {
+                match self.forward.pop() {
+                    Some(dst) => {
+                        self.back.push(self.current);
+                        self.current = dst;
+                        true
+                    },
+                    None => false,
+                }
+            }
+
+            /// Returns nodes recorded in the navigation history before the current one, oldest first.
+            pub fn trail(&self) -> &[GraphPtr<'id, NodeType>]
+            {
+                &self.back
+            }
+
+            /// Returns the number of edges going out of the current node.
+            pub fn degree(&self) -> usize
+            {
+                self.parent.degree(self.at())
+            }
         }
         
-        impl <'this, 'id, N : 'this, E : 'this>
-        $cursor_type<'this, 'id, NamedNode<N, E>>
+        impl <'this, 'id, N : 'this, E : 'this, S : BuildHasher + Default + 'this , A : Allocator>
+        $cursor_type<'this, 'id, NamedNode<N, E, S> , A>
         {    
             /// Returns Some if `dst` is attached to the current node and None otherwise.
-            pub fn get_edge(&self, dst : GraphPtr<'id, NamedNode<N, E>>) -> Option<Edge<&'_ N, &'_ E>>
+            pub fn get_edge(&self, dst : GraphPtr<'id, NamedNode<N, E, S>>) -> Option<Edge<&'_ N, &'_ E>>
             {
                 self.parent.get_edge(self.at(), dst)
             }
+
+            /// Moves the cursor to `dst` if it is attached to the current node.
+            pub fn step(&mut self, dst : GraphPtr<'id, NamedNode<N, E, S>>) -> Result<(), StepError>
+            {
+                if !self.parent.get_view(self.at()).refs.contains_key(&dst) {
+                    return Err(StepError::NoEdge);
+                }
+                self.jump(dst);
+                Ok(())
+            }
         }
 
-        impl <'this, 'id, N : 'this, E : 'this>
-        $cursor_type<'this, 'id, VecNode<N, E>>
+        impl <'this, 'id, N : 'this, E : 'this , A : Allocator>
+        $cursor_type<'this, 'id, VecNode<N, E> , A>
         {    
             /// Returns Some if `dst` is attached to the current node and None otherwise.
             pub fn get_edge(&self, dst : usize) -> Option<Edge<&'_ N, &'_ E>>
             {
                 self.parent.get_edge(self.at(), dst)
             }
+
+            /// Moves the cursor to the node referenced by the edge with index `dst`.
+            pub fn step(&mut self, dst : usize) -> Result<(), StepError>
+            {
+                let next = self.parent.get_view(self.at()).refs.get(dst).ok_or(StepError::OutOfBounds)?.0;
+                self.jump(next);
+                Ok(())
+            }
         }
 
-        impl <'this, 'id, N : 'this, E : 'this>
-        $cursor_type<'this, 'id, OptionNode<N, E>>
+        impl <'this, 'id, N : 'this, E : 'this , A : Allocator>
+        $cursor_type<'this, 'id, OptionNode<N, E> , A>
         {    
             /// Returns Some if a node is attached to the current node and None otherwise.
             pub fn get_edge(&self, _dst : ()) -> Option<Edge<&'_ N, &'_ E>>
             {
                 self.parent.get_edge(self.at())
             }
+
+            /// Moves the cursor to the node attached to the current node.
+            pub fn step(&mut self, _dst : ()) -> Result<(), StepError>
+            {
+                let next = self.parent.get_view(self.at()).refs.as_ref().ok_or(StepError::NoEdge)?.0;
+                self.jump(next);
+                Ok(())
+            }
         }
 
-        impl <'this, 'id, K : 'this, N : 'this, E : 'this>
-        $cursor_type<'this, 'id, TreeNode<K, N, E>> where K : Ord
+        impl <'this, 'id, K : 'this, N : 'this, E : 'this , A : Allocator>
+        $cursor_type<'this, 'id, TreeNode<K, N, E> , A> where K : Ord
         {    
             /// Returns Some if a node is attached to the current node and None otherwise.
             pub fn get_edge(&self, dst : &K) -> Option<Edge<&'_ N, &'_ E>>
             {
                 self.parent.get_edge(self.at(), dst)
             }
+
+            /// Moves the cursor to the node stored under `dst`.
+            pub fn step(&mut self, dst : &K) -> Result<(), StepError>
+            {
+                let next = self.parent.get_view(self.at()).refs.get(dst).ok_or(StepError::NoEdge)?.0;
+                self.jump(next);
+                Ok(())
+            }
         }
 
 
-        impl <'this, 'id, K : 'this, N : 'this, E : 'this>
-        $cursor_type<'this, 'id, TreeNode<K, N, E>> where K : Ord
+        impl <'this, 'id, K : 'this, N : 'this, E : 'this , A : Allocator>
+        $cursor_type<'this, 'id, TreeNode<K, N, E> , A> where K : Ord
         {
             /// Returns an iterator over edges and node pointers attached to the current node.
             pub fn edges(&self) ->
@@ -508,9 +1605,41 @@ macro_rules! impl_cursor_immutable {
             {
                 self.parent.iter(self.at())
             }
+
+            /// Returns an iterator over pointers to nodes attached to the current node.
+            pub fn neighbors(&self) -> impl Iterator<Item = GraphPtr<'id, TreeNode<K, N, E>>> + '_
+            {
+                self.parent.neighbors(self.at())
+            }
+
+            /// Returns an iterator over edges with keys within `range` in key order.
+            pub fn range<R>(&self, range : R) ->
+                impl DoubleEndedIterator<Item = (&'_ K, GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, TreeNode<K, N, E>>>)>
+            where R : RangeBounds<K>
+            {
+                self.parent.range(self.at(), range)
+            }
+
+            /// Returns the edge with the smallest key or None if the current node has no edges.
+            pub fn first_edge(&self) -> Option<(&'_ K, GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, TreeNode<K, N, E>>>)>
+            {
+                self.parent.first_edge(self.at())
+            }
+
+            /// Returns the edge with the largest key or None if the current node has no edges.
+            pub fn last_edge(&self) -> Option<(&'_ K, GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, TreeNode<K, N, E>>>)>
+            {
+                self.parent.last_edge(self.at())
+            }
+
+            /// Returns the edge with the largest key not exceeding `key` or None if there is no such edge.
+            pub fn nearest(&self, key : &K) -> Option<(&'_ K, GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, TreeNode<K, N, E>>>)>
+            {
+                self.parent.range(self.at(), (Bound::Unbounded, Bound::Included(key))).next_back()
+            }
         }
         
-        impl <'this, 'id, K : 'this, N : 'this, E : 'this> Deref for $cursor_type<'this, 'id, TreeNode<K, N, E>> where K : Ord
+        impl <'this, 'id, K : 'this, N : 'this, E : 'this , A : Allocator> Deref for $cursor_type<'this, 'id, TreeNode<K, N, E> , A> where K : Ord
         {
             type Target = node_views::TreeNode<'id, K, N, E>;
             fn deref(&self) -> &Self::Target
@@ -521,21 +1650,27 @@ macro_rules! impl_cursor_immutable {
 
 
     };
-    ($cursor_type:ident, $node_type:ident) => {
-        impl <'this, 'id, N : 'this, E : 'this>
-        $cursor_type<'this, 'id, $node_type<N, E>>
+    ($cursor_type:ident , $node_type:ident $(, $S:ident)?) => {
+        impl <'this, 'id, N : 'this, E : 'this $(, $S : BuildHasher + Default + 'this)? , A : Allocator>
+        $cursor_type<'this, 'id, $node_type<N, E $(, $S)?> , A>
         {
             /// Returns an iterator over edges and node pointers attached to the current node.
             pub fn edges(&self) ->
-                impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, $node_type<N, E>>>>
+                impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, $node_type<N, E $(, $S)?>>>>
             {
                 self.parent.iter(self.at())
             }
+
+            /// Returns an iterator over pointers to nodes attached to the current node.
+            pub fn neighbors(&self) -> impl Iterator<Item = GraphPtr<'id, $node_type<N, E $(, $S)?>>> + '_
+            {
+                self.parent.neighbors(self.at())
+            }
         }
         
-        impl <'this, 'id, N : 'this, E : 'this> Deref for $cursor_type<'this, 'id, $node_type<N, E>>
+        impl <'this, 'id, N : 'this, E : 'this $(, $S : BuildHasher + Default + 'this)? , A : Allocator> Deref for $cursor_type<'this, 'id, $node_type<N, E $(, $S)?> , A>
         {
-            type Target = node_views::$node_type<'id, N, E>;
+            type Target = node_views::$node_type<'id, N, E $(, $S)?>;
             fn deref(&self) -> &Self::Target
             {
                 self.parent.get_view(self.at())
@@ -547,25 +1682,29 @@ macro_rules! impl_cursor_immutable {
 impl_cursor_immutable!{CursorMut}
 impl_cursor_immutable!{Cursor}
 
-impl_cursor_immutable!{CursorMut, NamedNode}
-impl_cursor_immutable!{Cursor, NamedNode}
+impl_cursor_immutable!{CursorMut, NamedNode, S}
+impl_cursor_immutable!{Cursor, NamedNode, S}
 impl_cursor_immutable!{CursorMut, VecNode}
 impl_cursor_immutable!{Cursor, VecNode}
 impl_cursor_immutable!{CursorMut, OptionNode}
 impl_cursor_immutable!{Cursor, OptionNode}
+#[cfg(feature = "indexmap")]
+impl_cursor_immutable!{CursorMut, OrderedNamedNode}
+#[cfg(feature = "indexmap")]
+impl_cursor_immutable!{Cursor, OrderedNamedNode}
 
-impl <'this, 'id, N : 'this, E : 'this>
-CursorMut<'this, 'id, NamedNode<N, E>>
+impl <'this, 'id, N : 'this, E : 'this, S : BuildHasher + Default + 'this, A : Allocator>
+CursorMut<'this, 'id, NamedNode<N, E, S>, A>
 {    
     /// Returns Some if `dst` is attached to the current node and None otherwise.
-    pub fn get_edge_mut(&mut self, dst : GraphPtr<'id, NamedNode<N, E>>) -> Option<Edge<&'_ mut N, &'_ mut E>>
+    pub fn get_edge_mut(&mut self, dst : GraphPtr<'id, NamedNode<N, E, S>>) -> Option<Edge<&'_ mut N, &'_ mut E>>
     {
         self.parent.get_edge_mut(self.at(), dst)
     }
 }
 
-impl <'this, 'id, N : 'this, E : 'this>
-CursorMut<'this, 'id, VecNode<N, E>>
+impl <'this, 'id, N : 'this, E : 'this, A : Allocator>
+CursorMut<'this, 'id, VecNode<N, E>, A>
 {    
     /// Returns Some if `dst` is attached to the current node and None otherwise.
     pub fn get_edge_mut(&mut self, dst : usize) -> Option<Edge<&'_ mut N, &'_ mut E>>
@@ -574,8 +1713,8 @@ CursorMut<'this, 'id, VecNode<N, E>>
     }
 }
 
-impl <'this, 'id, N : 'this, E : 'this>
-CursorMut<'this, 'id, OptionNode<N, E>>
+impl <'this, 'id, N : 'this, E : 'this, A : Allocator>
+CursorMut<'this, 'id, OptionNode<N, E>, A>
 {    
     /// Returns Some if a node is attached to the current node and None otherwise.
     pub fn get_edge_mut(&mut self, _key : ()) -> Option<Edge<&'_ mut N, &'_ mut E>>
@@ -584,44 +1723,132 @@ CursorMut<'this, 'id, OptionNode<N, E>>
     }
 }
 
+impl <'this, 'id, N : 'this, NodeType : 'this, A : Allocator>
+CursorMut<'this, 'id, NodeType, A>
+where NodeType : GraphNode<Node = N>
+{
+    fn spawn_detached(&mut self, data : N) -> GraphPtr<'id, NodeType>
+    {
+        let ptr = self.parent.spawn_detached(data);
+        unsafe {
+            //allocation never fails
+            GraphPtr::from_ptr(ptr, self.current._guard)
+        }
+    }
+
+    /// Removes every edge going out of the current node. Edges of `UndirectedNode` are removed from both endpoints.
+    pub fn clear_edges(&mut self)
+    {
+        self.parent.clear_edges(self.at());
+    }
+
+    /// Removes every edge pointing to the current node, detaches it from the root, frees it, moves the cursor to
+    /// `next` and returns the data of the removed node. The node is dropped from the navigation history as well.
+    /// # Safety
+    /// Caller must ensure the removed node will never be accessed. Any copies of its pointer in external collections
+    /// should be disposed of.
+    /// # Panics
+    /// Panics if the root or an edge collection still holds the current node after `unlink`.
+    pub unsafe fn remove_current(&mut self, next : GraphPtr<'id, NodeType>) -> N
+    {
+        let at = self.at();
+        assert!(!self.root.unlink(at.as_ptr()), "the root collection does not implement unlink");
+        self.parent.unlink(at.as_ptr());
+        self.back.retain(|x| *x != at);
+        self.forward.retain(|x| *x != at);
+        self.jump(next);
+        //(E)
+        unsafe { self.parent.take(at.as_ptr()) }.into_data()
+    }
+}
+
 macro_rules! impl_cursor_mut {
-    ($node_type:ident) => {
-        impl <'this, 'id, N : 'this, E : 'this>
-        CursorMut<'this, 'id, $node_type<N, E>>
+    ($node_type:ident $(, $S:ident)?) => {
+        impl <'this, 'id, N : 'this, E : 'this $(, $S : BuildHasher + Default + 'this)?, A : Allocator>
+        CursorMut<'this, 'id, $node_type<N, E $(, $S)?>, A>
         {
+            /// Allocates a new node, connects the current node to it and returns the pointer.
+            pub fn spawn_attached(&mut self, data : N, edge : E) -> GraphPtr<'id, $node_type<N, E $(, $S)?>>
+            {
+                let dst = self.spawn_detached(data);
+                self.parent.connect(self.at(), dst, edge);
+                dst
+            }
+
+            /// Adds an edge from the current node to `dst`. Returns the previous edge data if `dst` was already attached.
+            pub fn attach(&mut self, dst : GraphPtr<'id, $node_type<N, E $(, $S)?>>, edge : E) -> Option<E>
+            {
+                self.parent.connect(self.at(), dst, edge)
+            }
+
+            /// Removes an edge from the current node to `dst`. Returns the edge data or None if there was no such edge.
+            pub fn detach(&mut self, dst : GraphPtr<'id, $node_type<N, E $(, $S)?>>) -> Option<E>
+            {
+                self.parent.disconnect(self.at(), dst)
+            }
+
             /// Returns a mutable iterator over edges and node pointers attached to the current node.
             pub fn edges_mut(&mut self) ->
-                impl Iterator<Item = GraphItem<Edge<&'_ mut  N, &'_ mut E>, GraphPtr<'id, $node_type<N, E>>>>
+                impl Iterator<Item = GraphItem<Edge<&'_ mut  N, &'_ mut E>, GraphPtr<'id, $node_type<N, E $(, $S)?>>>>
             {
                 self.parent.iter_mut(self.at())
             }
 
             /// Provides direct mutable access to current and `dst` nodes or or None if current is the same as `dst`.
             /// Returns mutable views into the current and `dst` nodes or None if current is the same as `dst`.
-            pub fn bridge(&mut self, dst : GraphPtr<'id, $node_type<N, E>>) ->
-                Option<(&'_ mut node_views::$node_type<'id, N, E>, &'_ mut node_views::$node_type<'id, N, E>)>
+            pub fn bridge(&mut self, dst : GraphPtr<'id, $node_type<N, E $(, $S)?>>) ->
+                Option<(&'_ mut node_views::$node_type<'id, N, E $(, $S)?>, &'_ mut node_views::$node_type<'id, N, E $(, $S)?>)>
             {
-                self.parent.bridge(self.at(), dst)
+                let at = self.at();
+                self.parent.expose_views().bridge(at, dst)
             }
         }
 
-        impl <'this, 'id, N : 'this, E : 'this> DerefMut for CursorMut<'this, 'id, $node_type<N, E>>
+        impl <'this, 'id, N : 'this, E : 'this $(, $S : BuildHasher + Default + 'this)?, A : Allocator> DerefMut for CursorMut<'this, 'id, $node_type<N, E $(, $S)?>, A>
         {
             fn deref_mut(&mut self) -> &mut Self::Target {
-                self.parent.get_view_mut(self.at())
+                let at = self.at();
+                self.parent.expose_views().get_view_mut(at)
             }
         }
     }
 }
 
-impl_cursor_mut!{NamedNode}
+impl_cursor_mut!{NamedNode, S}
 impl_cursor_mut!{VecNode}
 impl_cursor_mut!{OptionNode}
+#[cfg(feature = "indexmap")]
+impl_cursor_mut!{OrderedNamedNode}
 
-impl <'this, 'id, K : 'this, N : 'this, E : 'this>
-CursorMut<'this, 'id, TreeNode<K, N, E>> where K : Ord
+impl <'this, 'id, K : 'this, N : 'this, E : 'this, A : Allocator>
+CursorMut<'this, 'id, TreeNode<K, N, E>, A> where K : Ord
 {
+    /// Allocates a new node, connects the current node to it under `key` and returns the pointer. An edge
+    /// previously stored under `key` is dropped.
+    pub fn spawn_attached(&mut self, key : K, data : N, edge : E) -> GraphPtr<'id, TreeNode<K, N, E>>
+    {
+        let dst = self.spawn_detached(data);
+        self.parent.connect(self.at(), key, dst, edge);
+        dst
+    }
+
+    /// Adds an edge from the current node to `dst` under `key`. Returns the previous edge stored under `key`.
+    #[allow(clippy::type_complexity)]
+    pub fn attach(&mut self, key : K, dst : GraphPtr<'id, TreeNode<K, N, E>>, edge : E)
+        -> Option<(GraphPtr<'id, TreeNode<K, N, E>>, E)>
+    {
+        self.parent.connect(self.at(), key, dst, edge)
+    }
+
+    /// Removes an edge stored under `key`. Returns the edge or None if there was no such edge.
+    #[allow(clippy::type_complexity)]
+    pub fn detach(&mut self, key : &K) -> Option<(GraphPtr<'id, TreeNode<K, N, E>>, E)>
+    {
+        self.parent.disconnect(self.at(), key)
+    }
+
     /// Returns a mutable iterator over edges and node pointers attached to the current node.
+    #[allow(clippy::type_complexity)]
     pub fn edges_mut(&mut self) ->
         impl Iterator<Item = GraphItem<Edge<&'_ mut  N, &'_ mut E>, GraphPtr<'id, TreeNode<K, N, E>>>>
     {
@@ -630,75 +1857,1279 @@ CursorMut<'this, 'id, TreeNode<K, N, E>> where K : Ord
 
     /// Provides direct mutable access to current and `dst` nodes or or None if current is the same as `dst`.
     /// Returns mutable views into the current and `dst` nodes or None if current is the same as `dst`.
+    #[allow(clippy::type_complexity)]
     pub fn bridge(&mut self, dst : GraphPtr<'id, TreeNode<K, N, E>>) ->
         Option<(&'_ mut node_views::TreeNode<'id, K, N, E>, &'_ mut node_views::TreeNode<'id, K, N, E>)>
     {
-        self.parent.bridge(self.at(), dst)
+        let at = self.at();
+        self.parent.expose_views().bridge(at, dst)
     }
 }
 
-impl <'this, 'id, K : 'this, N : 'this, E : 'this>
-DerefMut for CursorMut<'this, 'id, TreeNode<K, N, E>> where K : Ord
+impl <'this, 'id, K : 'this, N : 'this, E : 'this, A : Allocator>
+DerefMut for CursorMut<'this, 'id, TreeNode<K, N, E>, A> where K : Ord
 {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        self.parent.get_view_mut(self.at())
+        let at = self.at();
+        self.parent.expose_views().get_view_mut(at)
     }
 }
 
-macro_rules! impl_generic_graph_root {
-    ($collection:ident, $graph:ident) => {
-        impl <'this, 'id, N : 'this, NodeType : 'this>
-        AnchorMut<'this, 'id, $graph<NodeType>>
-        where NodeType : GraphNode<Node = N>
+macro_rules! impl_get_disjoint_mut {
+    ($NodeType:ident $(, $S:ident)?) => {
+        impl <'this, 'id, N : 'this, E : 'this $(, $S : BuildHasher + Default + 'this)?, Root : 'this, A : Allocator>
+        AnchorMut<'this, 'id, GenericGraph<Root, $NodeType<N, E $(, $S)?>, A>>
+        where Root : RootCollection<'static, $NodeType<N, E $(, $S)?>>
         {
-            /// Provides direct access to the collection of the root.
-            pub fn root(&self) -> &$collection<'id, NodeType>
+            /// Provides direct mutable access to several different nodes at once. Returns None if some of `dst`
+            /// are the same.
+            pub fn get_disjoint_mut<const K : usize>(&mut self, dst : [GraphPtr<'id, $NodeType<N, E $(, $S)?>>; K])
+                -> Option<[&'_ mut node_views::$NodeType<'id, N, E $(, $S)?>; K]>
             {
-                //this transmute only affects lifetime parameter
-                unsafe {
-                    transmute(&self.parent.root)
-                }
+                //(E)
+                //pointers were checked to be distinct
+                self.internal_mut().expose_views().disjoint(dst).map(|x| x.map(|p| unsafe { (*p).get_view_mut() }))
+            }
+        }
+    }
+}
+
+impl_get_disjoint_mut!{NamedNode, S}
+impl_get_disjoint_mut!{VecNode}
+impl_get_disjoint_mut!{OptionNode}
+#[cfg(feature = "indexmap")]
+impl_get_disjoint_mut!{OrderedNamedNode}
+impl_get_disjoint_mut!{DiNode}
+impl_get_disjoint_mut!{UndirectedNode}
+
+impl <'this, 'id, K : 'this, N : 'this, E : 'this, Root : 'this, A : Allocator>
+AnchorMut<'this, 'id, GenericGraph<Root, TreeNode<K, N, E>, A>>
+where Root : RootCollection<'static, TreeNode<K, N, E>>, K : Ord
+{
+    /// Provides direct mutable access to several different nodes at once. Returns None if some of `dst`
+    /// are the same.
+    pub fn get_disjoint_mut<const C : usize>(&mut self, dst : [GraphPtr<'id, TreeNode<K, N, E>>; C])
+        -> Option<[&'_ mut node_views::TreeNode<'id, K, N, E>; C]>
+    {
+        //(E)
+        //pointers were checked to be distinct
+        self.internal_mut().expose_views().disjoint(dst).map(|x| x.map(|p| unsafe { (*p).get_view_mut() }))
+    }
+}
+
+macro_rules! impl_connect_sym {
+    ($NodeType:ident $(, $S:ident)?) => {
+        impl <'this, 'id, N : 'this, E : 'this $(, $S : BuildHasher + Default + 'this)?, Root : 'this, A : Allocator>
+        AnchorMut<'this, 'id, GenericGraph<Root, $NodeType<N, E $(, $S)?>, A>>
+        where Root : RootCollection<'static, $NodeType<N, E $(, $S)?>>
+        {
+            /// Connects `a` to `b` and `b` to `a` with clones of `edge`. Returns the previous data of both edges.
+            pub fn connect_sym(&mut self, a : GraphPtr<'id, $NodeType<N, E $(, $S)?>>, b : GraphPtr<'id, $NodeType<N, E $(, $S)?>>, edge : E)
+                -> (Option<E>, Option<E>) where E : Clone
+            {
+                self.connect_sym_pair(a, b, edge.clone(), edge)
             }
 
-            /// Provides direct mutable access to the collection of the root.
-            pub fn root_mut(&mut self) -> &mut $collection<'id, NodeType>
+            /// Connects `a` to `b` with `ab` and `b` to `a` with `ba`. Returns the previous data of both edges.
+            pub fn connect_sym_pair(&mut self, a : GraphPtr<'id, $NodeType<N, E $(, $S)?>>, b : GraphPtr<'id, $NodeType<N, E $(, $S)?>>,
+                                    ab : E, ba : E) -> (Option<E>, Option<E>)
             {
-                //this transmute only affects lifetime parameter
-                unsafe {
-                    transmute(&mut self.parent.root)
-                }
+                let internal = self.internal_mut();
+                (internal.connect(a, b, ab), internal.connect(b, a, ba))
+            }
+
+            /// Removes edges from `a` to `b` and from `b` to `a`. Returns data of both edges.
+            pub fn disconnect_sym(&mut self, a : GraphPtr<'id, $NodeType<N, E $(, $S)?>>, b : GraphPtr<'id, $NodeType<N, E $(, $S)?>>)
+                -> (Option<E>, Option<E>)
+            {
+                let internal = self.internal_mut();
+                (internal.disconnect(a, b), internal.disconnect(b, a))
             }
         }
 
-        impl <'this, 'id, N : 'this, NodeType : 'this>
-        Anchor<'this, 'id, $graph<NodeType>>
-        where NodeType : GraphNode<Node = N>
+        impl <'this, 'id, N : 'this, E : 'this $(, $S : BuildHasher + Default + 'this)?, A : Allocator>
+        CursorMut<'this, 'id, $NodeType<N, E $(, $S)?>, A>
         {
-            /// Provides direct access to the collection of the root.
-            pub fn root(&self) -> &$collection<'id, NodeType>
+            /// Connects the current node to `dst` and `dst` to the current node with clones of `edge`. Returns the
+            /// previous data of both edges.
+            pub fn connect_sym(&mut self, dst : GraphPtr<'id, $NodeType<N, E $(, $S)?>>, edge : E) -> (Option<E>, Option<E>)
+            where E : Clone
             {
-                //this transmute only affects lifetime parameter
-                unsafe {
-                    transmute(&self.parent.root)
-                }
+                self.connect_sym_pair(dst, edge.clone(), edge)
+            }
+
+            /// Connects the current node to `dst` with `to` and `dst` to the current node with `from`. Returns the
+            /// previous data of both edges.
+            pub fn connect_sym_pair(&mut self, dst : GraphPtr<'id, $NodeType<N, E $(, $S)?>>, to : E, from : E)
+                -> (Option<E>, Option<E>)
+            {
+                let at = self.at();
+                (self.parent.connect(at, dst, to), self.parent.connect(dst, at, from))
+            }
+
+            /// Removes edges between the current node and `dst` in both directions. Returns data of both edges.
+            pub fn disconnect_sym(&mut self, dst : GraphPtr<'id, $NodeType<N, E $(, $S)?>>) -> (Option<E>, Option<E>)
+            {
+                let at = self.at();
+                (self.parent.disconnect(at, dst), self.parent.disconnect(dst, at))
+            }
+        }
+    }
+}
+
+impl_connect_sym!{NamedNode, S}
+impl_connect_sym!{VecNode}
+impl_connect_sym!{OptionNode}
+#[cfg(feature = "indexmap")]
+impl_connect_sym!{OrderedNamedNode}
+
+macro_rules! impl_connect_all {
+    ($NodeType:ident $(, $S:ident)?) => {
+        impl <'this, 'id, N : 'this, E : 'this $(, $S : BuildHasher + Default + 'this)?, Root : 'this, A : Allocator>
+        AnchorMut<'this, 'id, GenericGraph<Root, $NodeType<N, E $(, $S)?>, A>>
+        where Root : RootCollection<'static, $NodeType<N, E $(, $S)?>>
+        {
+            /// Calls `connect` for every `(src, dst, edge)` in `edges`. Returns the number of edges which did not
+            /// replace existing ones.
+            pub fn connect_all(&mut self, edges : impl IntoIterator<Item = (GraphPtr<'id, $NodeType<N, E $(, $S)?>>,
+                                                                           GraphPtr<'id, $NodeType<N, E $(, $S)?>>, E)>)
+                -> usize
+            {
+                edges.into_iter().fold(0, |added, (src, dst, edge)| {
+                    added + self.internal_mut().connect(src, dst, edge).is_none() as usize
+                })
             }
         }
     }
 }
 
-impl_generic_graph_root!{RootVec, VecGraph}
-impl_generic_graph_root!{RootNamedSet, NamedGraph}
-impl_generic_graph_root!{RootOption, OptionGraph}
+impl_connect_all!{NamedNode, S}
+impl_connect_all!{VecNode}
+impl_connect_all!{OptionNode}
+#[cfg(feature = "indexmap")]
+impl_connect_all!{OrderedNamedNode}
+
+impl <'this, 'id, K : 'this, N : 'this, E : 'this, Root : 'this, A : Allocator>
+AnchorMut<'this, 'id, GenericGraph<Root, TreeNode<K, N, E>, A>>
+where Root : RootCollection<'static, TreeNode<K, N, E>>, K : Ord
+{
+    /// Calls `connect` for every `(src, key, dst, edge)` in `edges`. Returns the number of edges which did not
+    /// replace existing ones.
+    pub fn connect_all(&mut self, edges : impl IntoIterator<Item = (GraphPtr<'id, TreeNode<K, N, E>>, K,
+                                                                   GraphPtr<'id, TreeNode<K, N, E>>, E)>) -> usize
+    {
+        edges.into_iter().fold(0, |added, (src, key, dst, edge)| {
+            added + self.internal_mut().connect(src, key, dst, edge).is_none() as usize
+        })
+    }
+}
+
+macro_rules! impl_split_node {
+    ($NodeType:ident $(, $S:ident)?) => {
+        impl <'this, 'id, N : 'this, E : 'this $(, $S : BuildHasher + Default + 'this)?, Root : 'this, A : Allocator>
+        AnchorMut<'this, 'id, GenericGraph<Root, $NodeType<N, E $(, $S)?>, A>>
+        where Root : RootCollection<'static, $NodeType<N, E $(, $S)?>>
+        {
+            /// Spawns a copy of `src` holding `clone_payload(data)` and connects it to the targets of the edges of
+            /// `src` accepted by `keep_edge` with clones of these edges. A loop of `src` becomes a loop of the copy.
+            /// Then edges pointing to `src` from nodes accepted by `rewire` are disconnected and connected to the copy
+            /// instead, parallel edges of `VecNode` are merged as by `connect`. Returns the copy.
+            pub fn split_node(&mut self, src : GraphPtr<'id, $NodeType<N, E $(, $S)?>>, clone_payload : impl FnOnce(&N) -> N,
+                              mut keep_edge : impl FnMut(GraphPtr<'id, $NodeType<N, E $(, $S)?>>, &E) -> bool,
+                              mut rewire : impl FnMut(GraphPtr<'id, $NodeType<N, E $(, $S)?>>) -> bool)
+                -> GraphPtr<'id, $NodeType<N, E $(, $S)?>>
+            where E : Clone
+            {
+                let data = clone_payload(&self.internal().get_view(src).data);
+                let edges : Vec<_> = self.internal().iter(src)
+                    .filter_map(|x| {
+                        let edge = x.values.edge();
+                        if keep_edge(x.ptr, edge) { Some((x.ptr, edge.clone())) } else { None }
+                    })
+                    .collect();
+                let mut seen = std::collections::HashSet::new();
+                let sources : Vec<_> = self.internal().in_neighbors(src.as_ptr()).into_iter()
+                    //(W)
+                    .map(|x| unsafe { GraphPtr::from_ptr(x, self._guard) })
+                    .filter(|x| *x != src && seen.insert(*x) && rewire(*x))
+                    .collect();
+
+                let copy = self.spawn(data);
+                let internal = self.internal_mut();
+                for (dst, edge) in edges {
+                    internal.connect(copy, if dst == src { copy } else { dst }, edge);
+                }
+                for i in sources {
+                    while let Some(edge) = internal.disconnect(i, src) {
+                        internal.connect(i, copy, edge);
+                    }
+                }
+                copy
+            }
+        }
+    }
+}
+
+impl_split_node!{NamedNode, S}
+impl_split_node!{VecNode}
+impl_split_node!{OptionNode}
+#[cfg(feature = "indexmap")]
+impl_split_node!{OrderedNamedNode}
+
+#[cfg(feature = "smallvec")]
+macro_rules! impl_anchor_small_vec_node {
+    ($anchor_type:ident) => {
+        impl <'this, 'id, N : 'this, E : 'this, Root : 'this, const INLINE : usize, A : Allocator>
+        Index<GraphPtr<'id, SmallVecNode<N, E, INLINE>>>
+        for $anchor_type<'this, 'id, GenericGraph<Root, SmallVecNode<N, E, INLINE>, A>>
+        where Root : RootCollection<'static, SmallVecNode<N, E, INLINE>>
+        {
+            type Output = node_views::SmallVecNode<'id, N, E, INLINE>;
+            fn index(&self, dst : GraphPtr<'id, SmallVecNode<N, E, INLINE>>) -> &Self::Output
+            {
+                self.internal().get_view(dst)
+            }
+        }
+
+        impl <'this, 'id, N : 'this, E : 'this, Root : 'this, const INLINE : usize, A : Allocator>
+        $anchor_type<'this, 'id, GenericGraph<Root, SmallVecNode<N, E, INLINE>, A>>
+        where Root : RootCollection<'static, SmallVecNode<N, E, INLINE>>
+        {
+            /// Returns an iterator over edges attached to `src` node.
+            pub fn edges(&self, src : GraphPtr<'id, SmallVecNode<N, E, INLINE>>) ->
+                impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, SmallVecNode<N, E, INLINE>>>>
+            {
+                self.internal().iter(src)
+            }
+
+            /// Returns an iterator over pointers to nodes attached to `src` node.
+            pub fn neighbors(&self, src : GraphPtr<'id, SmallVecNode<N, E, INLINE>>) ->
+                impl Iterator<Item = GraphPtr<'id, SmallVecNode<N, E, INLINE>>> + '_
+            {
+                self.internal().neighbors(src)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl_anchor_small_vec_node!{Anchor}
+#[cfg(feature = "smallvec")]
+impl_anchor_small_vec_node!{AnchorMut}
+
+#[cfg(feature = "smallvec")]
+impl <'this, 'id, N : 'this, E : 'this, Root : 'this, const INLINE : usize, A : Allocator>
+IndexMut<GraphPtr<'id, SmallVecNode<N, E, INLINE>>>
+for AnchorMut<'this, 'id, GenericGraph<Root, SmallVecNode<N, E, INLINE>, A>>
+where Root : RootCollection<'static, SmallVecNode<N, E, INLINE>>
+{
+    fn index_mut(&mut self, dst : GraphPtr<'id, SmallVecNode<N, E, INLINE>>) -> &mut Self::Output {
+        self.internal_mut().expose_views().get_view_mut(dst)
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl <'this, 'id, N : 'this, E : 'this, Root : 'this, const INLINE : usize, A : Allocator>
+AnchorMut<'this, 'id, GenericGraph<Root, SmallVecNode<N, E, INLINE>, A>>
+where Root : RootCollection<'static, SmallVecNode<N, E, INLINE>>
+{
+    /// Returns a mutable iterator over edges attached to `src` node.
+    pub fn edges_mut(&mut self, src : GraphPtr<'id, SmallVecNode<N, E, INLINE>>) ->
+        impl Iterator<Item = GraphItem<Edge<&'_ mut N, &'_ mut E>, GraphPtr<'id, SmallVecNode<N, E, INLINE>>>>
+    {
+        self.internal_mut().iter_mut(src)
+    }
+}
+
+#[cfg(feature = "smallvec")]
+macro_rules! impl_cursor_small_vec_node {
+    ($cursor_type:ident ) => {
+        impl <'this, 'id, N : 'this, E : 'this, const INLINE : usize , A : Allocator>
+        $cursor_type<'this, 'id, SmallVecNode<N, E, INLINE> , A>
+        {
+            /// Returns an iterator over edges and node pointers attached to the current node.
+            pub fn edges(&self) ->
+                impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, SmallVecNode<N, E, INLINE>>>>
+            {
+                self.parent.iter(self.at())
+            }
+
+            /// Returns an iterator over pointers to nodes attached to the current node.
+            pub fn neighbors(&self) -> impl Iterator<Item = GraphPtr<'id, SmallVecNode<N, E, INLINE>>> + '_
+            {
+                self.parent.neighbors(self.at())
+            }
+        }
+
+        impl <'this, 'id, N : 'this, E : 'this, const INLINE : usize , A : Allocator> Deref for $cursor_type<'this, 'id, SmallVecNode<N, E, INLINE> , A>
+        {
+            type Target = node_views::SmallVecNode<'id, N, E, INLINE>;
+            fn deref(&self) -> &Self::Target
+            {
+                self.parent.get_view(self.at())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl_cursor_small_vec_node!{Cursor}
+#[cfg(feature = "smallvec")]
+impl_cursor_small_vec_node!{CursorMut}
+
+#[cfg(feature = "smallvec")]
+impl <'this, 'id, N : 'this, E : 'this, const INLINE : usize, A : Allocator>
+CursorMut<'this, 'id, SmallVecNode<N, E, INLINE>, A>
+{
+    /// Returns a mutable iterator over edges and node pointers attached to the current node.
+    pub fn edges_mut(&mut self) ->
+        impl Iterator<Item = GraphItem<Edge<&'_ mut N, &'_ mut E>, GraphPtr<'id, SmallVecNode<N, E, INLINE>>>>
+    {
+        self.parent.iter_mut(self.at())
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl <'this, 'id, N : 'this, E : 'this, const INLINE : usize, A : Allocator>
+DerefMut for CursorMut<'this, 'id, SmallVecNode<N, E, INLINE>, A>
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        let at = self.at();
+        self.parent.expose_views().get_view_mut(at)
+    }
+}
+
+macro_rules! impl_anchor_multi_named_node {
+    ($anchor_type:ident) => {
+        impl <'this, 'id, N : 'this, E : 'this, Root : 'this, A : Allocator>
+        Index<GraphPtr<'id, MultiNamedNode<N, E>>>
+        for $anchor_type<'this, 'id, GenericGraph<Root, MultiNamedNode<N, E>, A>>
+        where Root : RootCollection<'static, MultiNamedNode<N, E>>
+        {
+            type Output = node_views::MultiNamedNode<'id, N, E>;
+            fn index(&self, dst : GraphPtr<'id, MultiNamedNode<N, E>>) -> &Self::Output
+            {
+                self.internal().get_view(dst)
+            }
+        }
+
+        impl <'this, 'id, N : 'this, E : 'this, Root : 'this, A : Allocator>
+        $anchor_type<'this, 'id, GenericGraph<Root, MultiNamedNode<N, E>, A>>
+        where Root : RootCollection<'static, MultiNamedNode<N, E>>
+        {
+            /// Returns an iterator over all edges attached to `src` node, including parallel ones.
+            pub fn edges(&self, src : GraphPtr<'id, MultiNamedNode<N, E>>) ->
+                impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, MultiNamedNode<N, E>>>>
+            {
+                self.internal().iter(src)
+            }
+
+            /// Returns an iterator over every parallel edge from `src` to `dst`.
+            pub fn edges_between(&self, src : GraphPtr<'id, MultiNamedNode<N, E>>, dst : GraphPtr<'id, MultiNamedNode<N, E>>) ->
+                impl Iterator<Item = Edge<&'_ N, &'_ E>>
+            {
+                self.internal().edges_between(src, dst)
+            }
+
+            /// Returns an iterator over pointers to nodes attached to `src` node. Each node is returned once.
+            pub fn neighbors(&self, src : GraphPtr<'id, MultiNamedNode<N, E>>) ->
+                impl Iterator<Item = GraphPtr<'id, MultiNamedNode<N, E>>> + '_
+            {
+                self.internal().neighbors(src)
+            }
+        }
+    }
+}
+
+impl_anchor_multi_named_node!{Anchor}
+impl_anchor_multi_named_node!{AnchorMut}
+
+impl <'this, 'id, N : 'this, E : 'this, Root : 'this, A : Allocator>
+IndexMut<GraphPtr<'id, MultiNamedNode<N, E>>>
+for AnchorMut<'this, 'id, GenericGraph<Root, MultiNamedNode<N, E>, A>>
+where Root : RootCollection<'static, MultiNamedNode<N, E>>
+{
+    fn index_mut(&mut self, dst : GraphPtr<'id, MultiNamedNode<N, E>>) -> &mut Self::Output {
+        self.internal_mut().expose_views().get_view_mut(dst)
+    }
+}
+
+impl <'this, 'id, N : 'this, E : 'this, Root : 'this, A : Allocator>
+AnchorMut<'this, 'id, GenericGraph<Root, MultiNamedNode<N, E>, A>>
+where Root : RootCollection<'static, MultiNamedNode<N, E>>
+{
+    /// Returns a mutable iterator over all edges attached to `src` node.
+    pub fn edges_mut(&mut self, src : GraphPtr<'id, MultiNamedNode<N, E>>) ->
+        impl Iterator<Item = GraphItem<Edge<&'_ mut N, &'_ mut E>, GraphPtr<'id, MultiNamedNode<N, E>>>>
+    {
+        self.internal_mut().iter_mut(src)
+    }
+
+    /// Adds an edge from `src` to `dst` next to the existing ones.
+    pub fn connect(&mut self, src : GraphPtr<'id, MultiNamedNode<N, E>>, dst : GraphPtr<'id, MultiNamedNode<N, E>>, edge : E)
+    {
+        self.internal_mut().connect(src, dst, edge)
+    }
+
+    /// Removes every edge from `src` to `dst` and returns their data.
+    pub fn disconnect(&mut self, src : GraphPtr<'id, MultiNamedNode<N, E>>, dst : GraphPtr<'id, MultiNamedNode<N, E>>) -> Vec<E>
+    {
+        self.internal_mut().disconnect(src, dst)
+    }
+}
+
+impl_get_disjoint_mut!{MultiNamedNode}
+
+macro_rules! impl_cursor_multi_named_node {
+    ($cursor_type:ident ) => {
+        impl <'this, 'id, N : 'this, E : 'this , A : Allocator>
+        $cursor_type<'this, 'id, MultiNamedNode<N, E> , A>
+        {
+            /// Returns an iterator over all edges and node pointers attached to the current node.
+            pub fn edges(&self) ->
+                impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, MultiNamedNode<N, E>>>>
+            {
+                self.parent.iter(self.at())
+            }
+
+            /// Returns an iterator over every parallel edge from the current node to `dst`.
+            pub fn edges_between(&self, dst : GraphPtr<'id, MultiNamedNode<N, E>>) -> impl Iterator<Item = Edge<&'_ N, &'_ E>>
+            {
+                self.parent.edges_between(self.at(), dst)
+            }
+
+            /// Returns an iterator over pointers to nodes attached to the current node. Each node is returned once.
+            pub fn neighbors(&self) -> impl Iterator<Item = GraphPtr<'id, MultiNamedNode<N, E>>> + '_
+            {
+                self.parent.neighbors(self.at())
+            }
+
+            /// Moves the cursor to `dst` if it is attached to the current node.
+            pub fn step(&mut self, dst : GraphPtr<'id, MultiNamedNode<N, E>>) -> Result<(), StepError>
+            {
+                if !self.parent.get_view(self.at()).refs.contains_key(&dst) {
+                    return Err(StepError::NoEdge);
+                }
+                self.jump(dst);
+                Ok(())
+            }
+        }
+
+        impl <'this, 'id, N : 'this, E : 'this , A : Allocator> Deref for $cursor_type<'this, 'id, MultiNamedNode<N, E> , A>
+        {
+            type Target = node_views::MultiNamedNode<'id, N, E>;
+            fn deref(&self) -> &Self::Target
+            {
+                self.parent.get_view(self.at())
+            }
+        }
+    }
+}
+
+impl_cursor_multi_named_node!{Cursor}
+impl_cursor_multi_named_node!{CursorMut}
+
+impl <'this, 'id, N : 'this, E : 'this, A : Allocator>
+CursorMut<'this, 'id, MultiNamedNode<N, E>, A>
+{
+    /// Allocates a new node, connects the current node to it and returns the pointer.
+    pub fn spawn_attached(&mut self, data : N, edge : E) -> GraphPtr<'id, MultiNamedNode<N, E>>
+    {
+        let dst = self.spawn_detached(data);
+        self.parent.connect(self.at(), dst, edge);
+        dst
+    }
+
+    /// Adds an edge from the current node to `dst` next to the existing ones.
+    pub fn attach(&mut self, dst : GraphPtr<'id, MultiNamedNode<N, E>>, edge : E)
+    {
+        self.parent.connect(self.at(), dst, edge)
+    }
+
+    /// Removes every edge from the current node to `dst` and returns their data.
+    pub fn detach(&mut self, dst : GraphPtr<'id, MultiNamedNode<N, E>>) -> Vec<E>
+    {
+        self.parent.disconnect(self.at(), dst)
+    }
+
+    /// Returns a mutable iterator over all edges and node pointers attached to the current node.
+    pub fn edges_mut(&mut self) ->
+        impl Iterator<Item = GraphItem<Edge<&'_ mut N, &'_ mut E>, GraphPtr<'id, MultiNamedNode<N, E>>>>
+    {
+        self.parent.iter_mut(self.at())
+    }
+}
+
+impl <'this, 'id, N : 'this, E : 'this, A : Allocator>
+DerefMut for CursorMut<'this, 'id, MultiNamedNode<N, E>, A>
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        let at = self.at();
+        self.parent.expose_views().get_view_mut(at)
+    }
+}
+
+macro_rules! impl_anchor_array_node {
+    ($anchor_type:ident) => {
+        impl <'this, 'id, N : 'this, E : 'this, Root : 'this, const D : usize, A : Allocator>
+        Index<GraphPtr<'id, ArrayNode<N, E, D>>>
+        for $anchor_type<'this, 'id, GenericGraph<Root, ArrayNode<N, E, D>, A>>
+        where Root : RootCollection<'static, ArrayNode<N, E, D>>
+        {
+            type Output = node_views::ArrayNode<'id, N, E, D>;
+            fn index(&self, dst : GraphPtr<'id, ArrayNode<N, E, D>>) -> &Self::Output
+            {
+                self.internal().get_view(dst)
+            }
+        }
+
+        impl <'this, 'id, N : 'this, E : 'this, Root : 'this, const D : usize, A : Allocator>
+        $anchor_type<'this, 'id, GenericGraph<Root, ArrayNode<N, E, D>, A>>
+        where Root : RootCollection<'static, ArrayNode<N, E, D>>
+        {
+            /// Returns an iterator over edges attached to `src` node.
+            pub fn edges(&self, src : GraphPtr<'id, ArrayNode<N, E, D>>) ->
+                impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, ArrayNode<N, E, D>>>>
+            {
+                self.internal().iter(src)
+            }
+
+            /// Returns an iterator over pointers to nodes attached to `src` node.
+            pub fn neighbors(&self, src : GraphPtr<'id, ArrayNode<N, E, D>>) ->
+                impl Iterator<Item = GraphPtr<'id, ArrayNode<N, E, D>>> + '_
+            {
+                self.internal().neighbors(src)
+            }
+        }
+    }
+}
+
+impl_anchor_array_node!{Anchor}
+impl_anchor_array_node!{AnchorMut}
+
+impl <'this, 'id, N : 'this, E : 'this, Root : 'this, const D : usize, A : Allocator>
+IndexMut<GraphPtr<'id, ArrayNode<N, E, D>>>
+for AnchorMut<'this, 'id, GenericGraph<Root, ArrayNode<N, E, D>, A>>
+where Root : RootCollection<'static, ArrayNode<N, E, D>>
+{
+    fn index_mut(&mut self, dst : GraphPtr<'id, ArrayNode<N, E, D>>) -> &mut Self::Output {
+        self.internal_mut().expose_views().get_view_mut(dst)
+    }
+}
+
+impl <'this, 'id, N : 'this, E : 'this, Root : 'this, const D : usize, A : Allocator>
+AnchorMut<'this, 'id, GenericGraph<Root, ArrayNode<N, E, D>, A>>
+where Root : RootCollection<'static, ArrayNode<N, E, D>>
+{
+    /// Returns a mutable iterator over edges attached to `src` node.
+    pub fn edges_mut(&mut self, src : GraphPtr<'id, ArrayNode<N, E, D>>) ->
+        impl Iterator<Item = GraphItem<Edge<&'_ mut N, &'_ mut E>, GraphPtr<'id, ArrayNode<N, E, D>>>>
+    {
+        self.internal_mut().iter_mut(src)
+    }
+
+    /// Puts an edge from `src` to `dst` into `slot`. Returns the edge which previously occupied the slot.
+    /// # Panics
+    /// Panics if `slot` is not less than `D`.
+    pub fn connect(&mut self, src : GraphPtr<'id, ArrayNode<N, E, D>>, slot : usize, dst : GraphPtr<'id, ArrayNode<N, E, D>>,
+                   edge : E) -> Option<(GraphPtr<'id, ArrayNode<N, E, D>>, E)>
+    {
+        self.internal_mut().connect(src, slot, dst, edge)
+    }
+
+    /// Empties `slot` of `src`. Returns the removed edge or None if the slot was empty.
+    pub fn disconnect(&mut self, src : GraphPtr<'id, ArrayNode<N, E, D>>, slot : usize)
+        -> Option<(GraphPtr<'id, ArrayNode<N, E, D>>, E)>
+    {
+        self.internal_mut().disconnect(src, slot)
+    }
+
+    /// Provides direct mutable access to several different nodes at once. Returns None if some of `dst`
+    /// are the same.
+    pub fn get_disjoint_mut<const K : usize>(&mut self, dst : [GraphPtr<'id, ArrayNode<N, E, D>>; K])
+        -> Option<[&'_ mut node_views::ArrayNode<'id, N, E, D>; K]>
+    {
+        //(E)
+        //pointers were checked to be distinct
+        self.internal_mut().expose_views().disjoint(dst).map(|x| x.map(|p| unsafe { (*p).get_view_mut() }))
+    }
+}
+
+macro_rules! impl_cursor_array_node {
+    ($cursor_type:ident ) => {
+        impl <'this, 'id, N : 'this, E : 'this, const D : usize , A : Allocator>
+        $cursor_type<'this, 'id, ArrayNode<N, E, D> , A>
+        {
+            /// Returns an iterator over edges and node pointers attached to the current node.
+            pub fn edges(&self) ->
+                impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, ArrayNode<N, E, D>>>>
+            {
+                self.parent.iter(self.at())
+            }
+
+            /// Returns an iterator over pointers to nodes attached to the current node.
+            pub fn neighbors(&self) -> impl Iterator<Item = GraphPtr<'id, ArrayNode<N, E, D>>> + '_
+            {
+                self.parent.neighbors(self.at())
+            }
+
+            /// Returns Some if `slot` of the current node is occupied and None otherwise.
+            pub fn get_edge(&self, slot : usize) -> Option<Edge<&'_ N, &'_ E>>
+            {
+                self.parent.get_edge(self.at(), slot)
+            }
+
+            /// Moves the cursor to the node in `slot` of the current node.
+            pub fn step(&mut self, slot : usize) -> Result<(), StepError>
+            {
+                match self.parent.get_view(self.at()).refs.get(slot) {
+                    Some(Some(x)) => {
+                        let dst = x.0;
+                        self.jump(dst);
+                        Ok(())
+                    },
+                    Some(None) => Err(StepError::NoEdge),
+                    None => Err(StepError::OutOfBounds),
+                }
+            }
+        }
+
+        impl <'this, 'id, N : 'this, E : 'this, const D : usize , A : Allocator> Deref for $cursor_type<'this, 'id, ArrayNode<N, E, D> , A>
+        {
+            type Target = node_views::ArrayNode<'id, N, E, D>;
+            fn deref(&self) -> &Self::Target
+            {
+                self.parent.get_view(self.at())
+            }
+        }
+    }
+}
+
+impl_cursor_array_node!{Cursor}
+impl_cursor_array_node!{CursorMut}
+
+impl <'this, 'id, N : 'this, E : 'this, const D : usize, A : Allocator>
+CursorMut<'this, 'id, ArrayNode<N, E, D>, A>
+{
+    /// Allocates a new node, puts an edge to it into `slot` of the current node and returns the pointer.
+    /// # Panics
+    /// Panics if `slot` is not less than `D`.
+    pub fn spawn_attached(&mut self, slot : usize, data : N, edge : E) -> GraphPtr<'id, ArrayNode<N, E, D>>
+    {
+        let dst = self.spawn_detached(data);
+        self.parent.connect(self.at(), slot, dst, edge);
+        dst
+    }
+
+    /// Puts an edge to `dst` into `slot` of the current node. Returns the edge which previously occupied the slot.
+    pub fn attach(&mut self, slot : usize, dst : GraphPtr<'id, ArrayNode<N, E, D>>, edge : E)
+        -> Option<(GraphPtr<'id, ArrayNode<N, E, D>>, E)>
+    {
+        self.parent.connect(self.at(), slot, dst, edge)
+    }
+
+    /// Empties `slot` of the current node. Returns the removed edge or None if the slot was empty.
+    pub fn detach(&mut self, slot : usize) -> Option<(GraphPtr<'id, ArrayNode<N, E, D>>, E)>
+    {
+        self.parent.disconnect(self.at(), slot)
+    }
+
+    /// Returns Some if `slot` of the current node is occupied and None otherwise.
+    pub fn get_edge_mut(&mut self, slot : usize) -> Option<Edge<&'_ mut N, &'_ mut E>>
+    {
+        self.parent.get_edge_mut(self.at(), slot)
+    }
+
+    /// Returns a mutable iterator over edges and node pointers attached to the current node.
+    pub fn edges_mut(&mut self) ->
+        impl Iterator<Item = GraphItem<Edge<&'_ mut N, &'_ mut E>, GraphPtr<'id, ArrayNode<N, E, D>>>>
+    {
+        self.parent.iter_mut(self.at())
+    }
+}
+
+impl <'this, 'id, N : 'this, E : 'this, const D : usize, A : Allocator>
+DerefMut for CursorMut<'this, 'id, ArrayNode<N, E, D>, A>
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        let at = self.at();
+        self.parent.expose_views().get_view_mut(at)
+    }
+}
+
+macro_rules! impl_anchor_custom_node {
+    ($anchor_type:ident) => {
+        impl <'this, 'id, N : 'this, F : EdgeFamily + 'this, Root : 'this, A : Allocator>
+        Index<GraphPtr<'id, CustomNode<N, F>>>
+        for $anchor_type<'this, 'id, GenericGraph<Root, CustomNode<N, F>, A>>
+        where Root : RootCollection<'static, CustomNode<N, F>>
+        {
+            type Output = node_views::CustomNode<'id, N, F>;
+            fn index(&self, dst : GraphPtr<'id, CustomNode<N, F>>) -> &Self::Output
+            {
+                self.internal().get_view(dst)
+            }
+        }
+    }
+}
+
+impl_anchor_custom_node!{Anchor}
+impl_anchor_custom_node!{AnchorMut}
+
+impl <'this, 'id, N : 'this, F : EdgeFamily + 'this, Root : 'this, A : Allocator>
+IndexMut<GraphPtr<'id, CustomNode<N, F>>>
+for AnchorMut<'this, 'id, GenericGraph<Root, CustomNode<N, F>, A>>
+where Root : RootCollection<'static, CustomNode<N, F>>
+{
+    fn index_mut(&mut self, dst : GraphPtr<'id, CustomNode<N, F>>) -> &mut Self::Output {
+        self.internal_mut().expose_views().get_view_mut(dst)
+    }
+}
+
+macro_rules! impl_anchor_set_node {
+    ($anchor_type:ident) => {
+        impl <'this, 'id, N : 'this, Root : 'this, A : Allocator>
+        Index<GraphPtr<'id, SetNode<N>>>
+        for $anchor_type<'this, 'id, GenericGraph<Root, SetNode<N>, A>>
+        where Root : RootCollection<'static, SetNode<N>>
+        {
+            type Output = node_views::SetNode<'id, N>;
+            fn index(&self, dst : GraphPtr<'id, SetNode<N>>) -> &Self::Output
+            {
+                self.internal().get_view(dst)
+            }
+        }
+
+        impl <'this, 'id, N : 'this, Root : 'this, A : Allocator>
+        $anchor_type<'this, 'id, GenericGraph<Root, SetNode<N>, A>>
+        where Root : RootCollection<'static, SetNode<N>>
+        {
+            /// Returns an iterator over edges attached to `src` node.
+            pub fn edges(&self, src : GraphPtr<'id, SetNode<N>>) ->
+                impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ ()>, GraphPtr<'id, SetNode<N>>>>
+            {
+                self.internal().iter(src)
+            }
+
+            /// Returns an iterator over pointers to nodes attached to `src` node.
+            pub fn neighbors(&self, src : GraphPtr<'id, SetNode<N>>) -> impl Iterator<Item = GraphPtr<'id, SetNode<N>>> + '_
+            {
+                self.internal().neighbors(src)
+            }
+        }
+    }
+}
+
+impl_anchor_set_node!{Anchor}
+impl_anchor_set_node!{AnchorMut}
+
+impl <'this, 'id, N : 'this, Root : 'this, A : Allocator>
+IndexMut<GraphPtr<'id, SetNode<N>>>
+for AnchorMut<'this, 'id, GenericGraph<Root, SetNode<N>, A>>
+where Root : RootCollection<'static, SetNode<N>>
+{
+    fn index_mut(&mut self, dst : GraphPtr<'id, SetNode<N>>) -> &mut Self::Output {
+        self.internal_mut().expose_views().get_view_mut(dst)
+    }
+}
+
+impl <'this, 'id, N : 'this, Root : 'this, A : Allocator>
+AnchorMut<'this, 'id, GenericGraph<Root, SetNode<N>, A>>
+where Root : RootCollection<'static, SetNode<N>>
+{
+    /// Returns a mutable iterator over edges attached to `src` node.
+    pub fn edges_mut(&mut self, src : GraphPtr<'id, SetNode<N>>) ->
+        impl Iterator<Item = GraphItem<Edge<&'_ mut N, &'_ mut ()>, GraphPtr<'id, SetNode<N>>>>
+    {
+        self.internal_mut().iter_mut(src)
+    }
+
+    /// Adds an edge from `src` to `dst`. Returns false if `src` was already connected to `dst`.
+    pub fn connect(&mut self, src : GraphPtr<'id, SetNode<N>>, dst : GraphPtr<'id, SetNode<N>>) -> bool
+    {
+        self.internal_mut().connect(src, dst)
+    }
+
+    /// Removes an edge from `src` to `dst`. Returns false if there was no such edge.
+    pub fn disconnect(&mut self, src : GraphPtr<'id, SetNode<N>>, dst : GraphPtr<'id, SetNode<N>>) -> bool
+    {
+        self.internal_mut().disconnect(src, dst)
+    }
+
+    /// Provides direct mutable access to several different nodes at once. Returns None if some of `dst`
+    /// are the same.
+    pub fn get_disjoint_mut<const K : usize>(&mut self, dst : [GraphPtr<'id, SetNode<N>>; K])
+        -> Option<[&'_ mut node_views::SetNode<'id, N>; K]>
+    {
+        //(E)
+        //pointers were checked to be distinct
+        self.internal_mut().expose_views().disjoint(dst).map(|x| x.map(|p| unsafe { (*p).get_view_mut() }))
+    }
+}
+
+macro_rules! impl_cursor_set_node {
+    ($cursor_type:ident ) => {
+        impl <'this, 'id, N : 'this , A : Allocator>
+        $cursor_type<'this, 'id, SetNode<N> , A>
+        {
+            /// Returns an iterator over edges and node pointers attached to the current node.
+            pub fn edges(&self) -> impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ ()>, GraphPtr<'id, SetNode<N>>>>
+            {
+                self.parent.iter(self.at())
+            }
+
+            /// Returns an iterator over pointers to nodes attached to the current node.
+            pub fn neighbors(&self) -> impl Iterator<Item = GraphPtr<'id, SetNode<N>>> + '_
+            {
+                self.parent.neighbors(self.at())
+            }
+
+            /// Moves the cursor to `dst` if it is attached to the current node.
+            pub fn step(&mut self, dst : GraphPtr<'id, SetNode<N>>) -> Result<(), StepError>
+            {
+                if !self.parent.get_view(self.at()).refs.contains(&dst) {
+                    return Err(StepError::NoEdge);
+                }
+                self.jump(dst);
+                Ok(())
+            }
+        }
+
+        impl <'this, 'id, N : 'this , A : Allocator> Deref for $cursor_type<'this, 'id, SetNode<N> , A>
+        {
+            type Target = node_views::SetNode<'id, N>;
+            fn deref(&self) -> &Self::Target
+            {
+                self.parent.get_view(self.at())
+            }
+        }
+    }
+}
+
+impl_cursor_set_node!{Cursor}
+impl_cursor_set_node!{CursorMut}
+
+impl <'this, 'id, N : 'this, A : Allocator>
+CursorMut<'this, 'id, SetNode<N>, A>
+{
+    /// Allocates a new node, connects the current node to it and returns the pointer.
+    pub fn spawn_attached(&mut self, data : N) -> GraphPtr<'id, SetNode<N>>
+    {
+        let dst = self.spawn_detached(data);
+        self.parent.connect(self.at(), dst);
+        dst
+    }
+
+    /// Adds an edge from the current node to `dst`. Returns false if `dst` was already attached.
+    pub fn attach(&mut self, dst : GraphPtr<'id, SetNode<N>>) -> bool
+    {
+        self.parent.connect(self.at(), dst)
+    }
+
+    /// Removes an edge from the current node to `dst`. Returns false if there was no such edge.
+    pub fn detach(&mut self, dst : GraphPtr<'id, SetNode<N>>) -> bool
+    {
+        self.parent.disconnect(self.at(), dst)
+    }
+
+    /// Returns a mutable iterator over edges and node pointers attached to the current node.
+    pub fn edges_mut(&mut self) -> impl Iterator<Item = GraphItem<Edge<&'_ mut N, &'_ mut ()>, GraphPtr<'id, SetNode<N>>>>
+    {
+        self.parent.iter_mut(self.at())
+    }
+}
+
+impl <'this, 'id, N : 'this, A : Allocator>
+DerefMut for CursorMut<'this, 'id, SetNode<N>, A>
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        let at = self.at();
+        self.parent.expose_views().get_view_mut(at)
+    }
+}
+
+macro_rules! impl_anchor_di_node {
+    ($anchor_type:ident) => {
+        impl <'this, 'id, N : 'this, E : 'this, Root : 'this, A : Allocator>
+        Index<GraphPtr<'id, DiNode<N, E>>>
+        for $anchor_type<'this, 'id, GenericGraph<Root, DiNode<N, E>, A>>
+        where Root : RootCollection<'static, DiNode<N, E>>
+        {
+            type Output = node_views::DiNode<'id, N, E>;
+            fn index(&self, dst : GraphPtr<'id, DiNode<N, E>>) -> &Self::Output
+            {
+                self.internal().get_view(dst)
+            }
+        }
+
+        impl <'this, 'id, N : 'this, E : 'this, Root : 'this, A : Allocator>
+        $anchor_type<'this, 'id, GenericGraph<Root, DiNode<N, E>, A>>
+        where Root : RootCollection<'static, DiNode<N, E>>
+        {
+            /// Returns an iterator over edges going out of `src` node.
+            pub fn edges_out(&self, src : GraphPtr<'id, DiNode<N, E>>) ->
+                impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, DiNode<N, E>>>>
+            {
+                self.internal().iter_out(src)
+            }
+
+            /// Returns an iterator over edges pointing to `dst` node and their sources.
+            pub fn edges_in(&self, dst : GraphPtr<'id, DiNode<N, E>>) ->
+                impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, DiNode<N, E>>>>
+            {
+                self.internal().iter_in(dst)
+            }
+        }
+    }
+}
+
+impl_anchor_di_node!{Anchor}
+impl_anchor_di_node!{AnchorMut}
+
+impl <'this, 'id, N : 'this, E : 'this, Root : 'this, A : Allocator>
+IndexMut<GraphPtr<'id, DiNode<N, E>>>
+for AnchorMut<'this, 'id, GenericGraph<Root, DiNode<N, E>, A>>
+where Root : RootCollection<'static, DiNode<N, E>>
+{
+    fn index_mut(&mut self, dst : GraphPtr<'id, DiNode<N, E>>) -> &mut Self::Output {
+        self.internal_mut().expose_views().get_view_mut(dst)
+    }
+}
+
+impl <'this, 'id, N : 'this, E : 'this, Root : 'this, A : Allocator>
+AnchorMut<'this, 'id, GenericGraph<Root, DiNode<N, E>, A>>
+where Root : RootCollection<'static, DiNode<N, E>>
+{
+    /// Returns a mutable iterator over edges going out of `src` node.
+    pub fn edges_out_mut(&mut self, src : GraphPtr<'id, DiNode<N, E>>) ->
+        impl Iterator<Item = GraphItem<Edge<&'_ mut N, &'_ mut E>, GraphPtr<'id, DiNode<N, E>>>>
+    {
+        self.internal_mut().iter_out_mut(src)
+    }
+
+    /// Adds an edge from `src` to `dst` updating both nodes. Returns the previous edge data if `src` was already
+    /// connected to `dst`.
+    pub fn connect(&mut self, src : GraphPtr<'id, DiNode<N, E>>, dst : GraphPtr<'id, DiNode<N, E>>, edge : E) -> Option<E>
+    {
+        self.internal_mut().connect(src, dst, edge)
+    }
+
+    /// Removes an edge from `src` to `dst` updating both nodes. Returns the edge data or None if there was no
+    /// such edge.
+    pub fn disconnect(&mut self, src : GraphPtr<'id, DiNode<N, E>>, dst : GraphPtr<'id, DiNode<N, E>>) -> Option<E>
+    {
+        self.internal_mut().disconnect(src, dst)
+    }
+}
+
+macro_rules! impl_cursor_di_node {
+    ($cursor_type:ident ) => {
+        impl <'this, 'id, N : 'this, E : 'this , A : Allocator>
+        $cursor_type<'this, 'id, DiNode<N, E> , A>
+        {
+            /// Returns an iterator over edges going out of the current node.
+            pub fn edges_out(&self) ->
+                impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, DiNode<N, E>>>>
+            {
+                self.parent.iter_out(self.at())
+            }
+
+            /// Returns an iterator over edges pointing to the current node and their sources.
+            pub fn edges_in(&self) ->
+                impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, DiNode<N, E>>>>
+            {
+                self.parent.iter_in(self.at())
+            }
+        }
+
+        impl <'this, 'id, N : 'this, E : 'this , A : Allocator> Deref for $cursor_type<'this, 'id, DiNode<N, E> , A>
+        {
+            type Target = node_views::DiNode<'id, N, E>;
+            fn deref(&self) -> &Self::Target
+            {
+                self.parent.get_view(self.at())
+            }
+        }
+    }
+}
+
+impl_cursor_di_node!{Cursor}
+impl_cursor_di_node!{CursorMut}
+
+impl <'this, 'id, N : 'this, E : 'this, A : Allocator>
+CursorMut<'this, 'id, DiNode<N, E>, A>
+{
+    /// Allocates a new node, adds an edge from the current node to it and returns the pointer.
+    pub fn spawn_attached(&mut self, data : N, edge : E) -> GraphPtr<'id, DiNode<N, E>>
+    {
+        let dst = self.spawn_detached(data);
+        self.parent.connect(self.at(), dst, edge);
+        dst
+    }
+
+    /// Returns a mutable iterator over edges going out of the current node.
+    pub fn edges_out_mut(&mut self) ->
+        impl Iterator<Item = GraphItem<Edge<&'_ mut N, &'_ mut E>, GraphPtr<'id, DiNode<N, E>>>>
+    {
+        self.parent.iter_out_mut(self.at())
+    }
+}
+
+impl <'this, 'id, N : 'this, E : 'this, A : Allocator>
+DerefMut for CursorMut<'this, 'id, DiNode<N, E>, A>
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        let at = self.at();
+        self.parent.expose_views().get_view_mut(at)
+    }
+}
+
+macro_rules! impl_anchor_undirected_node {
+    ($anchor_type:ident) => {
+        impl <'this, 'id, N : 'this, E : 'this, Root : 'this, A : Allocator>
+        Index<GraphPtr<'id, UndirectedNode<N, E>>>
+        for $anchor_type<'this, 'id, GenericGraph<Root, UndirectedNode<N, E>, A>>
+        where Root : RootCollection<'static, UndirectedNode<N, E>>
+        {
+            type Output = node_views::UndirectedNode<'id, N, E>;
+            fn index(&self, dst : GraphPtr<'id, UndirectedNode<N, E>>) -> &Self::Output
+            {
+                self.internal().get_view(dst)
+            }
+        }
+
+        impl <'this, 'id, N : 'this, E : 'this, Root : 'this, A : Allocator>
+        $anchor_type<'this, 'id, GenericGraph<Root, UndirectedNode<N, E>, A>>
+        where Root : RootCollection<'static, UndirectedNode<N, E>>
+        {
+            /// Returns an iterator over edges incident to `src` node.
+            pub fn edges(&self, src : GraphPtr<'id, UndirectedNode<N, E>>) ->
+                impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, UndirectedNode<N, E>>>>
+            {
+                self.internal().iter(src)
+            }
+
+            /// Returns data of the edge between `a` and `b` or None if they are not connected.
+            pub fn edge(&self, a : GraphPtr<'id, UndirectedNode<N, E>>, b : GraphPtr<'id, UndirectedNode<N, E>>)
+                -> Option<&E>
+            {
+                self.internal().edge(a, b)
+            }
+        }
+    }
+}
+
+impl_anchor_undirected_node!{Anchor}
+impl_anchor_undirected_node!{AnchorMut}
+
+impl <'this, 'id, N : 'this, E : 'this, Root : 'this, A : Allocator>
+IndexMut<GraphPtr<'id, UndirectedNode<N, E>>>
+for AnchorMut<'this, 'id, GenericGraph<Root, UndirectedNode<N, E>, A>>
+where Root : RootCollection<'static, UndirectedNode<N, E>>
+{
+    fn index_mut(&mut self, dst : GraphPtr<'id, UndirectedNode<N, E>>) -> &mut Self::Output {
+        self.internal_mut().expose_views().get_view_mut(dst)
+    }
+}
+
+impl <'this, 'id, N : 'this, E : 'this, Root : 'this, A : Allocator>
+AnchorMut<'this, 'id, GenericGraph<Root, UndirectedNode<N, E>, A>>
+where Root : RootCollection<'static, UndirectedNode<N, E>>
+{
+    /// Returns a mutable iterator over edges incident to `src` node.
+    pub fn edges_mut(&mut self, src : GraphPtr<'id, UndirectedNode<N, E>>) ->
+        impl Iterator<Item = GraphItem<Edge<&'_ mut N, &'_ mut E>, GraphPtr<'id, UndirectedNode<N, E>>>>
+    {
+        self.internal_mut().iter_mut(src)
+    }
+
+    /// Returns mutable data of the edge between `a` and `b` or None if they are not connected.
+    pub fn edge_mut(&mut self, a : GraphPtr<'id, UndirectedNode<N, E>>, b : GraphPtr<'id, UndirectedNode<N, E>>)
+        -> Option<&mut E>
+    {
+        self.internal_mut().edge_mut(a, b)
+    }
+
+    /// Connects `a` and `b`. The edge is visible from both nodes. Returns the previous edge data if nodes were
+    /// already connected.
+    pub fn connect(&mut self, a : GraphPtr<'id, UndirectedNode<N, E>>, b : GraphPtr<'id, UndirectedNode<N, E>>, edge : E)
+        -> Option<E>
+    {
+        self.internal_mut().connect(a, b, edge)
+    }
+
+    /// Removes the edge between `a` and `b` from both nodes. Returns the edge data or None if there was no
+    /// such edge.
+    pub fn disconnect(&mut self, a : GraphPtr<'id, UndirectedNode<N, E>>, b : GraphPtr<'id, UndirectedNode<N, E>>)
+        -> Option<E>
+    {
+        self.internal_mut().disconnect(a, b)
+    }
+}
+
+macro_rules! impl_cursor_undirected_node {
+    ($cursor_type:ident ) => {
+        impl <'this, 'id, N : 'this, E : 'this , A : Allocator>
+        $cursor_type<'this, 'id, UndirectedNode<N, E> , A>
+        {
+            /// Returns an iterator over edges incident to the current node.
+            pub fn edges(&self) ->
+                impl Iterator<Item = GraphItem<Edge<&'_ N, &'_ E>, GraphPtr<'id, UndirectedNode<N, E>>>>
+            {
+                self.parent.iter(self.at())
+            }
+        }
+
+        impl <'this, 'id, N : 'this, E : 'this , A : Allocator> Deref for $cursor_type<'this, 'id, UndirectedNode<N, E> , A>
+        {
+            type Target = node_views::UndirectedNode<'id, N, E>;
+            fn deref(&self) -> &Self::Target
+            {
+                self.parent.get_view(self.at())
+            }
+        }
+    }
+}
+
+impl_cursor_undirected_node!{Cursor}
+impl_cursor_undirected_node!{CursorMut}
+
+impl <'this, 'id, N : 'this, E : 'this, A : Allocator>
+CursorMut<'this, 'id, UndirectedNode<N, E>, A>
+{
+    /// Allocates a new node, connects it to the current node and returns the pointer.
+    pub fn spawn_attached(&mut self, data : N, edge : E) -> GraphPtr<'id, UndirectedNode<N, E>>
+    {
+        let dst = self.spawn_detached(data);
+        self.parent.connect(self.at(), dst, edge);
+        dst
+    }
+
+    /// Returns a mutable iterator over edges incident to the current node.
+    pub fn edges_mut(&mut self) ->
+        impl Iterator<Item = GraphItem<Edge<&'_ mut N, &'_ mut E>, GraphPtr<'id, UndirectedNode<N, E>>>>
+    {
+        self.parent.iter_mut(self.at())
+    }
+}
+
+impl <'this, 'id, N : 'this, E : 'this, A : Allocator>
+DerefMut for CursorMut<'this, 'id, UndirectedNode<N, E>, A>
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        let at = self.at();
+        self.parent.expose_views().get_view_mut(at)
+    }
+}
+
+macro_rules! impl_reroot {
+    ($graph:ident $(, $S:ident)?) => {
+        impl <'this, 'id, N : 'this, NodeType : 'this $(, $S : 'this)?>
+        AnchorMut<'this, 'id, $graph<NodeType $(, $S)?>>
+        where NodeType : GraphNode<Node = N>,
+              $($S : BuildHasher + Default)?
+        {
+            /// Replaces the contents of the root with `new_roots`. Nodes which are no longer accessible are disposed
+            /// of upon next cleanup.
+            pub fn reroot(&mut self, new_roots : impl IntoIterator<Item = GraphPtr<'id, NodeType>>)
+            {
+                let root = self.root_mut();
+                root.clear();
+                root.extend(new_roots);
+            }
+
+            /// Same as `reroot`, but consumes the anchor and immediately drops every node which is not accessible
+            /// from `new_roots`. Returns a summary of the cleanup.
+            pub fn reroot_and_cleanup(mut self, new_roots : impl IntoIterator<Item = GraphPtr<'id, NodeType>>)
+                -> CleanupReport
+            {
+                self.reroot(new_roots);
+                self.strategy = CleanupStrategy::Never;
+                //the anchor is consumed, so no pointer to a dropped node can be used
+                self.parent.cleanup_precise()
+            }
+        }
+    }
+}
+
+impl_reroot!{VecGraph}
+impl_reroot!{NamedGraph, S}
+impl_reroot!{DequeGraph}
+
+macro_rules! impl_extend_root {
+    ($graph:ident $(, $S:ident)?) => {
+        impl <'this, 'id, N : 'this, NodeType : 'this $(, $S : 'this)?>
+        AnchorMut<'this, 'id, $graph<NodeType $(, $S)?>>
+        where NodeType : GraphNode<Node = N>,
+              $($S : BuildHasher + Default)?
+        {
+            /// Same as `spawn_iter`, but also adds the new nodes to the root.
+            pub fn extend_root(&mut self, payloads : impl IntoIterator<Item = N>) -> Vec<GraphPtr<'id, NodeType>>
+            {
+                let res = self.spawn_iter(payloads);
+                self.root_mut().extend(res.iter().copied());
+                res
+            }
+        }
+    }
+}
+
+impl_extend_root!{VecGraph}
+impl_extend_root!{NamedGraph, S}
+impl_extend_root!{DequeGraph}
+
+impl <'this, 'id, NodeType : 'this, Root : 'this, A : Allocator>
+AnchorMut<'this, 'id, GenericGraph<Root, NodeType, A>>
+where Root : RootCollection<'static, NodeType> + RootCollectionView,
+      NodeType : GraphNode
+{
+    /// Provides direct access to the collection of the root.
+    pub fn root(&self) -> &Root::View<'id>
+    {
+        //(E)
+        //View only differs from Root by the brand
+        unsafe {
+            &*(&self.parent.root as *const Root as *const Root::View<'id>)
+        }
+    }
+
+    /// Provides direct mutable access to the collection of the root.
+    pub fn root_mut(&mut self) -> &mut Root::View<'id>
+    {
+        self.parent.internal.changes += 1;
+        //(E)
+        //View only differs from Root by the brand
+        unsafe {
+            &mut *(&mut self.parent.root as *mut Root as *mut Root::View<'id>)
+        }
+    }
+}
+
+impl <'this, 'id, NodeType : 'this, Root : 'this, A : Allocator>
+Anchor<'this, 'id, GenericGraph<Root, NodeType, A>>
+where Root : RootCollection<'static, NodeType> + RootCollectionView,
+      NodeType : GraphNode
+{
+    /// Provides direct access to the collection of the root.
+    pub fn root(&self) -> &Root::View<'id>
+    {
+        //(E)
+        //View only differs from Root by the brand
+        unsafe {
+            &*(&self.parent.root as *const Root as *const Root::View<'id>)
+        }
+    }
+}
 
 #[macro_export]
 /// Creates an AnchorMut using selected cleanup strategy.
 macro_rules! anchor_mut
 {
-    ($name:ident, $strategy:tt) => {
+    ($name:ident, $strategy:expr) => {
         make_guard!(g);
         let mut $name = unsafe { $name.anchor_mut(Id::from(g), $strategy)   };
     };
-    ($name:ident, $parent:tt, $strategy:tt) => {
+    ($name:ident, $parent:tt, $strategy:expr) => {
         make_guard!(g);
         let mut $name = unsafe { $parent.anchor_mut(Id::from(g), $strategy) };
     };
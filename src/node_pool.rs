@@ -0,0 +1,167 @@
+use core::ptr::NonNull;
+use std::alloc::{Layout, handle_alloc_error};
+use allocator_api2::alloc::Allocator;
+
+// Size of the first chunk, every next one is twice as large up to MAX_CHUNK.
+const MIN_CHUNK : usize = 8;
+const MAX_CHUNK : usize = 4096;
+
+// Hands out memory for nodes from large chunks. Chunks are never moved, so node addresses stay stable. A chunk is
+// released by `shrink` once every node allocated in it was put back, or along with the pool.
+pub(crate) struct NodePool<T> {
+    chunks : Vec<(NonNull<T>, usize)>,
+    // Number of slots of the last chunk which were ever handed out.
+    used : usize,
+    // Slots released by `put`.
+    vacant : Vec<*mut T>,
+}
+
+impl <T> NodePool<T> {
+    pub(crate) fn new() -> Self
+    {
+        NodePool { chunks : Vec::new(), used : 0, vacant : Vec::new() }
+    }
+
+    // Returns uninitialized memory for a single node.
+    pub(crate) fn take<A : Allocator>(&mut self, allocator : &A) -> NonNull<T>
+    {
+        if let Some(slot) = self.vacant.pop() {
+            //only valid slots are put into vacant
+            return unsafe { NonNull::new_unchecked(slot) };
+        }
+        if self.remaining() == 0 {
+            self.grow(allocator, 0);
+        }
+        let (chunk, _) = self.chunks[self.chunks.len() - 1];
+        self.used += 1;
+        //used never exceeds the capacity of the chunk
+        unsafe { NonNull::new_unchecked(chunk.as_ptr().add(self.used - 1)) }
+    }
+
+    // Makes the memory of a node available again. The node must already be moved out or dropped.
+    pub(crate) fn put(&mut self, slot : *mut T)
+    {
+        self.vacant.push(slot);
+    }
+
+    // Returns the number of slots in every chunk.
+    pub(crate) fn capacity(&self) -> usize
+    {
+        self.chunks.iter().map(|x| x.1).sum()
+    }
+
+    // Makes sure next `additional` calls to `take` won't allocate.
+    pub(crate) fn reserve<A : Allocator>(&mut self, additional : usize, allocator : &A)
+    {
+        let available = self.vacant.len() + self.remaining();
+        if available < additional {
+            self.grow(allocator, additional - available);
+        }
+    }
+
+    // Number of slots of the last chunk which were never handed out.
+    fn remaining(&self) -> usize
+    {
+        self.chunks.last().map_or(0, |x| x.1 - self.used)
+    }
+
+    // Allocates a chunk with at least `min` slots. Slots left in the previous chunk are kept in vacant.
+    fn grow<A : Allocator>(&mut self, allocator : &A, min : usize)
+    {
+        if let Err(layout) = self.try_grow(allocator, min) {
+            handle_alloc_error(layout)
+        }
+    }
+
+    // Same as `grow`, but returns the layout of the chunk instead of aborting if memory can't be allocated.
+    // The pool is left intact on failure.
+    fn try_grow<A : Allocator>(&mut self, allocator : &A, min : usize) -> Result<(), Layout>
+    {
+        let capacity = match self.chunks.last() {
+            Some(&(_, capacity)) => (capacity * 2).min(MAX_CHUNK),
+            None => MIN_CHUNK,
+        }.max(min);
+        let layout = Self::layout(capacity);
+        let left = self.remaining();
+        if self.chunks.try_reserve(1).is_err() || self.vacant.try_reserve(left).is_err() {
+            return Err(layout);
+        }
+        let ptr = allocator.allocate(layout).map_err(|_| layout)?.cast::<T>();
+        if let Some(&(chunk, last)) = self.chunks.last() {
+            //slots are within the chunk
+            self.vacant.extend((self.used..last).map(|i| unsafe { chunk.as_ptr().add(i) }));
+        }
+        self.chunks.push((ptr, capacity));
+        self.used = 0;
+        Ok(())
+    }
+
+    // Same as `reserve` for a single node, but fails instead of aborting if memory can't be allocated.
+    pub(crate) fn try_reserve_one<A : Allocator>(&mut self, allocator : &A) -> Result<(), Layout>
+    {
+        if self.vacant.is_empty() && self.remaining() == 0 {
+            self.try_grow(allocator, 0)
+        } else {
+            Ok(())
+        }
+    }
+
+    // Releases chunks without nodes. A single node left in a chunk keeps the whole chunk allocated.
+    pub(crate) fn shrink<A : Allocator>(&mut self, allocator : &A)
+    {
+        // Chunks sorted by address to find the chunk of a slot with a binary search.
+        let mut starts : Vec<(usize, usize)> = self.chunks.iter().enumerate().map(|(i, x)| (x.0.as_ptr() as usize, i)).collect();
+        starts.sort_unstable();
+        let chunk_of = |slot : *mut T| starts[starts.partition_point(|x| x.0 <= slot as usize) - 1].1;
+        let mut free = vec![0; self.chunks.len()];
+        if let Some(last) = free.last_mut() {
+            *last = self.remaining();
+        }
+        for slot in &self.vacant {
+            free[chunk_of(*slot)] += 1;
+        }
+        let empty : Vec<bool> = self.chunks.iter().zip(&free).map(|(x, free)| x.1 == *free).collect();
+        if !empty.contains(&true) {
+            return;
+        }
+        self.vacant.retain(|x| !empty[chunk_of(*x)]);
+        let last_empty = empty[empty.len() - 1];
+        let mut i = 0;
+        self.chunks.retain(|&(ptr, capacity)| {
+            i += 1;
+            if empty[i - 1] {
+                //the chunk was allocated by allocator with the same layout
+                unsafe { Self::dealloc(allocator, ptr, capacity) };
+            }
+            !empty[i - 1]
+        });
+        if last_empty {
+            // Slots left in the new last chunk were moved to vacant when the next chunk was allocated.
+            self.used = self.chunks.last().map_or(0, |x| x.1);
+        }
+    }
+
+    // Releases every chunk. Nodes must be dropped beforehand and `allocator` must be the one chunks came from.
+    pub(crate) fn release<A : Allocator>(&mut self, allocator : &A)
+    {
+        for (ptr, capacity) in self.chunks.drain(..) {
+            //chunks were allocated by allocator with the same layout
+            unsafe { Self::dealloc(allocator, ptr, capacity) };
+        }
+        self.vacant.clear();
+        self.used = 0;
+    }
+
+    unsafe fn dealloc<A : Allocator>(allocator : &A, ptr : NonNull<T>, capacity : usize)
+    {
+        allocator.deallocate(ptr.cast(), Self::layout(capacity));
+    }
+
+    fn layout(capacity : usize) -> Layout
+    {
+        match Layout::array::<T>(capacity) {
+            Ok(layout) => layout,
+            Err(_) => panic!("node chunk is too large"),
+        }
+    }
+}
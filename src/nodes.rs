@@ -1,25 +1,56 @@
+#![allow(unused_braces)]
+
 use super::*;
 
-use std::collections::{HashMap, HashSet, BTreeMap};
+use std::collections::{HashMap, HashSet, BTreeMap, VecDeque};
+use std::collections::hash_map::Entry;
+use core::hash::BuildHasher;
+use core::cmp::Ordering;
+use core::mem::size_of;
+#[cfg(feature = "indexmap")]
+use indexmap::IndexMap;
+#[cfg(feature = "smallvec")]
+use smallvec::SmallVec;
 
+// Epoch of the cleanup which visited a node last. Epochs only grow, so a node skipped by any number of cleanups
+// never looks visited to a later one.
 #[derive(PartialEq, Eq, Clone, Copy)]
-pub enum CleanupGen {
-    Even, Odd
-}
+pub struct CleanupGen(u64);
 
 #[derive(PartialEq, Eq, Clone, Copy)]
 pub struct MetaData {
     pub(crate) cleanup_gen : CleanupGen,
     pub(crate) store_index: usize,
+    // Number of cleanups the node survived.
+    pub(crate) age : u8,
+    // Whether an old node is in the remembered set of its graph.
+    pub(crate) remembered : bool,
+    // Stable id of the node or 0 if none was requested.
+    pub(crate) stable_id : u64,
+    // Whether the node is in the dirty list of its graph.
+    pub(crate) dirty : bool,
+    // Number of nodes spawned in the graph before this one.
+    pub(crate) serial : u64,
+    // Bits set by `set_mark`, they only count while `mark_gen` matches the generation of the graph.
+    pub(crate) mark : u8,
+    pub(crate) mark_gen : u32,
+    // Modification epoch of the last change of the node, 0 if it was not changed while tracking was enabled.
+    pub(crate) modified : u64,
+}
+
+impl MetaData {
+    pub(crate) fn new() -> Self {
+        MetaData { cleanup_gen : CleanupGen(0), store_index : 0, age : 0, remembered : false, stable_id : 0, dirty : false, serial : 0, mark : 0, mark_gen : 0, modified : 0 }
+    }
 }
 
 impl CleanupGen {
-    pub(crate) fn flip(&mut self) {
-        if *self == CleanupGen::Even {
-            *self = CleanupGen::Odd
-        } else {
-            *self = CleanupGen::Even
-        }
+    pub(crate) fn new() -> Self {
+        CleanupGen(0)
+    }
+
+    pub(crate) fn advance(&mut self) {
+        self.0 += 1;
     }
 }
 
@@ -32,8 +63,17 @@ pub trait GraphNode : Sized {
     fn meta_mut(&mut self) -> &mut MetaData;
 
     fn traverse(&self, cleanup : &mut CleanupState<Self>);
+    fn degree(&self) -> usize;
+    // Returns an estimate of heap memory owned by edge collections of the node.
+    fn edge_bytes(&self) -> usize;
+    fn unlink(&mut self, dst : *const Self);
+    // Removes every edge going out of the node.
+    fn clear_edges(&mut self);
+    // Called before the node is freed, removes any trace of the node kept by the nodes it points to.
+    fn forget_targets(&mut self) {}
 
     fn from_data(data : Self::Node) -> Self;
+    fn into_data(self) -> Self::Node;
 }
 
 
@@ -42,13 +82,13 @@ pub mod node_views {
     use super::*;
 
     macro_rules! define_node_view {
-        ($NodeType:ident, $Collection:ident) => {
-            pub struct $NodeType<'id, N, E> {
-                pub refs : $Collection<'id, super::$NodeType<N, E>, E>,
+        ($NodeType:ident, $Collection:ident $(, $S:ident)?) => {
+            pub struct $NodeType<'id, N, E $(, $S = DefaultPtrState)?> {
+                pub refs : $Collection<'id, super::$NodeType<N, E $(, $S)?>, E $(, $S)?>,
                 pub data : N,
             }
 
-            impl <'id, N, E> $NodeType<'id, N, E> {
+            impl <'id, N, E $(, $S : Default)?> $NodeType<'id, N, E $(, $S)?> {
                 pub(crate) fn new(data : N) -> Self {
                     $NodeType { data, refs: $Collection::default() }
                 }
@@ -57,8 +97,61 @@ pub mod node_views {
     }
 
     define_node_view!{VecNode, NodeVec}
-    define_node_view!{NamedNode, NodeNamedMap}
+    define_node_view!{NamedNode, NodeNamedMap, S}
     define_node_view!{OptionNode, NodeOption}
+    #[cfg(feature = "indexmap")]
+    define_node_view!{OrderedNamedNode, NodeOrderedMap}
+
+    impl <'id, N, E> VecNode<'id, N, E> {
+        /// Inserts an edge to `dst` at position `index`, shifting all edges after it.
+        /// # Panics
+        /// Panics if `index` is greater than the number of edges.
+        pub fn insert_edge_at(&mut self, index : usize, dst : GraphPtr<'id, super::VecNode<N, E>>, edge : E)
+        {
+            self.refs.insert(index, (dst, edge));
+        }
+
+        /// Removes an edge at position `index` and returns it or None if `index` is out of bounds.
+        pub fn remove_edge_at(&mut self, index : usize) -> Option<(GraphPtr<'id, super::VecNode<N, E>>, E)>
+        {
+            if index < self.refs.len() {
+                Some(self.refs.remove(index))
+            } else {
+                None
+            }
+        }
+
+        /// Swaps edges at positions `a` and `b`.
+        /// # Panics
+        /// Panics if `a` or `b` are out of bounds.
+        pub fn swap_edges(&mut self, a : usize, b : usize)
+        {
+            self.refs.swap(a, b);
+        }
+
+        /// Sorts edges with a comparator function. The sort is stable.
+        pub fn sort_edges_by<F>(&mut self, mut compare : F)
+        where F : FnMut((GraphPtr<'id, super::VecNode<N, E>>, &E), (GraphPtr<'id, super::VecNode<N, E>>, &E)) -> Ordering
+        {
+            self.refs.sort_by(|a, b| compare((a.0, &a.1), (b.0, &b.1)));
+        }
+
+        /// Removes consecutive edges for which `same` returns true, keeping the first one.
+        pub fn dedup_edges_by<F>(&mut self, mut same : F)
+        where F : FnMut((GraphPtr<'id, super::VecNode<N, E>>, &E), (GraphPtr<'id, super::VecNode<N, E>>, &E)) -> bool
+        {
+            self.refs.dedup_by(|a, b| same((b.0, &b.1), (a.0, &a.1)));
+        }
+    }
+
+    impl <'id, N, E, S : BuildHasher> NamedNode<'id, N, E, S> {
+        /// Returns an entry of the edge pointing to `dst` for in-place manipulation.
+        pub fn edge_entry(&mut self, dst : GraphPtr<'id, super::NamedNode<N, E, S>>)
+            -> NodeNamedEntry<'_, 'id, super::NamedNode<N, E, S>, E>
+        {
+            self.refs.entry(dst)
+        }
+    }
 
     pub struct TreeNode<'id, K, N, E> {
         pub refs : NodeTreeMap<'id, K, super::TreeNode<K, N, E>, E>,
@@ -69,32 +162,163 @@ pub mod node_views {
         pub(crate) fn new(data : N) -> Self {
             TreeNode { data, refs: BTreeMap::default() }
         }
+
+        /// Returns an iterator over edges with keys within `range` in key order.
+        #[allow(clippy::type_complexity)]
+        pub fn range<R>(&self, range : R) -> impl DoubleEndedIterator<Item = (&'_ K, &'_ (GraphPtr<'id, super::TreeNode<K, N, E>>, E))>
+        where R : RangeBounds<K>
+        {
+            self.refs.range(range)
+        }
+
+        /// Returns the edge with the smallest key or None if the node has no edges.
+        #[allow(clippy::type_complexity)]
+        pub fn first_edge(&self) -> Option<(&'_ K, &'_ (GraphPtr<'id, super::TreeNode<K, N, E>>, E))>
+        {
+            self.refs.iter().next()
+        }
+
+        /// Returns the edge with the largest key or None if the node has no edges.
+        #[allow(clippy::type_complexity)]
+        pub fn last_edge(&self) -> Option<(&'_ K, &'_ (GraphPtr<'id, super::TreeNode<K, N, E>>, E))>
+        {
+            self.refs.iter().next_back()
+        }
+
+        /// Returns the edge with the largest key not exceeding `key` or None if there is no such edge.
+        #[allow(clippy::type_complexity)]
+        pub fn nearest(&self, key : &K) -> Option<(&'_ K, &'_ (GraphPtr<'id, super::TreeNode<K, N, E>>, E))>
+        {
+            self.refs.range((Bound::Unbounded, Bound::Included(key))).next_back()
+        }
+    }
+
+    /// A node which stores up to `INLINE` edges without a heap allocation.
+    #[cfg(feature = "smallvec")]
+    pub struct SmallVecNode<'id, N, E, const INLINE : usize> {
+        pub refs : NodeSmallVec<'id, super::SmallVecNode<N, E, INLINE>, E, INLINE>,
+        pub data : N,
+    }
+
+    #[cfg(feature = "smallvec")]
+    impl <'id, N, E, const INLINE : usize> SmallVecNode<'id, N, E, INLINE> {
+        pub(crate) fn new(data : N) -> Self {
+            SmallVecNode { data, refs : SmallVec::new() }
+        }
+    }
+
+    /// A node which may have several parallel edges to the same node.
+    pub struct MultiNamedNode<'id, N, E> {
+        pub refs : NodeMultiMap<'id, super::MultiNamedNode<N, E>, E>,
+        pub data : N,
+    }
+
+    impl <'id, N, E> MultiNamedNode<'id, N, E> {
+        pub(crate) fn new(data : N) -> Self {
+            MultiNamedNode { data, refs : HashMap::default() }
+        }
+    }
+
+    /// A node with `D` edge slots stored inline.
+    pub struct ArrayNode<'id, N, E, const D : usize> {
+        pub refs : NodeArray<'id, super::ArrayNode<N, E, D>, E, D>,
+        pub data : N,
+    }
+
+    impl <'id, N, E, const D : usize> ArrayNode<'id, N, E, D> {
+        pub(crate) fn new(data : N) -> Self {
+            ArrayNode { data, refs : [(); D].map(|_| None) }
+        }
+    }
+
+    /// A node whose edges are stored in a user defined collection, see `EdgeFamily`.
+    pub struct CustomNode<'id, N, F : EdgeFamily> {
+        pub refs : F::Collection<'id, super::CustomNode<N, F>>,
+        pub data : N,
+    }
+
+    impl <'id, N, F : EdgeFamily> CustomNode<'id, N, F> {
+        pub(crate) fn new(data : N) -> Self {
+            CustomNode { data, refs : Default::default() }
+        }
+    }
+
+    /// A node with unweighted edges stored in a hash set.
+    pub struct SetNode<'id, N> {
+        pub refs : NodeNamedSet<'id, super::SetNode<N>>,
+        pub data : N,
+    }
+
+    impl <'id, N> SetNode<'id, N> {
+        pub(crate) fn new(data : N) -> Self {
+            SetNode { data, refs : HashSet::default() }
+        }
+    }
+
+    /// A node of a directed graph. Prefer `connect` and `disconnect` to modify `out_refs`, sources of edges are
+    /// only updated by them.
+    pub struct DiNode<'id, N, E> {
+        pub out_refs : NodeNamedMap<'id, super::DiNode<N, E>, E>,
+        pub(crate) in_refs : NodeNamedSet<'id, super::DiNode<N, E>>,
+        pub data : N,
+    }
+
+    impl <'id, N, E> DiNode<'id, N, E> {
+        pub(crate) fn new(data : N) -> Self {
+            DiNode { data, out_refs : HashMap::default(), in_refs : HashSet::default() }
+        }
+
+        /// Returns sources of edges pointing to this node.
+        pub fn in_refs(&self) -> &NodeNamedSet<'id, super::DiNode<N, E>>
+        {
+            &self.in_refs
+        }
+    }
+
+    /// A node of an undirected graph. Data of every edge is stored once by one of its endpoints, use `connect` and
+    /// `disconnect` to modify edges.
+    pub struct UndirectedNode<'id, N, E> {
+        pub(crate) refs : NodeNamedSet<'id, super::UndirectedNode<N, E>>,
+        pub data : N,
+        pub(crate) edges : NodeNamedMap<'id, super::UndirectedNode<N, E>, E>,
+    }
+
+    impl <'id, N, E> UndirectedNode<'id, N, E> {
+        pub(crate) fn new(data : N) -> Self {
+            UndirectedNode { data, refs : HashSet::default(), edges : HashMap::default() }
+        }
+
+        /// Returns all neighbours of this node.
+        pub fn refs(&self) -> &NodeNamedSet<'id, super::UndirectedNode<N, E>>
+        {
+            &self.refs
+        }
     }
 }
 
 macro_rules! impl_node_type {
-    ($NodeType:ident) => {
+    ($NodeType:ident $(, $S:ident)?) => {
 
-        pub struct $NodeType<N, E> {
-            pub(crate) internal: node_views::$NodeType<'static, N, E>,
+        pub struct $NodeType<N, E $(, $S = DefaultPtrState)?> {
+            pub(crate) internal: node_views::$NodeType<'static, N, E $(, $S)?>,
             pub(crate) meta : MetaData,
         }
 
-        impl <N, E> $NodeType<N, E> {
-            pub (crate) fn get_view<'id>(&self) -> &node_views::$NodeType<'id, N, E> {
+        impl <N, E $(, $S)?> $NodeType<N, E $(, $S)?> {
+            pub (crate) fn get_view<'id>(&self) -> &node_views::$NodeType<'id, N, E $(, $S)?> {
                 unsafe {
                     transmute(&self.internal)
                 }
             }
 
-            pub (crate) fn get_view_mut<'id>(&mut self) -> &mut node_views::$NodeType<'id, N, E> {
+            pub (crate) fn get_view_mut<'id>(&mut self) -> &mut node_views::$NodeType<'id, N, E $(, $S)?> {
                 unsafe {
                     transmute(&mut self.internal)
                 }
             }
         }
 
-        impl <N, E> GraphNode for $NodeType<N, E> {
+        impl <N, E $(, $S : BuildHasher + Default)?> GraphNode for $NodeType<N, E $(, $S)?> {
             type Node = N;
 
             fn get(&self) -> &Self::Node
@@ -119,18 +343,41 @@ macro_rules! impl_node_type {
                 NodeCollection::traverse(&self.internal.refs, cleanup);
             }
 
+            fn degree(&self) -> usize {
+                NodeCollection::len(&self.internal.refs)
+            }
+
+            fn edge_bytes(&self) -> usize {
+                NodeCollection::heap_bytes(&self.internal.refs)
+            }
+
+            fn unlink(&mut self, dst : *const Self) {
+                NodeCollection::unlink(&mut self.internal.refs, dst);
+            }
+
+            fn clear_edges(&mut self) {
+                self.internal.refs = Default::default();
+            }
+
             fn from_data(data : Self::Node) -> Self
             {
-                let meta = MetaData { cleanup_gen : CleanupGen::Even, store_index : 0 };
+                let meta = MetaData::new();
                 Self { internal : node_views::$NodeType::new(data), meta }
             }
+
+            fn into_data(self) -> Self::Node
+            {
+                self.internal.data
+            }
         }
     }
 }
 
 impl_node_type!{VecNode}
-impl_node_type!{NamedNode}
+impl_node_type!{NamedNode, S}
 impl_node_type!{OptionNode}
+#[cfg(feature = "indexmap")]
+impl_node_type!{OrderedNamedNode}
 
 pub struct TreeNode<K, N, E> {
     pub(crate) internal: node_views::TreeNode<'static, K, N, E>,
@@ -176,84 +423,1035 @@ impl <K : Ord, N, E> GraphNode for TreeNode<K, N, E> {
         NodeCollection::traverse(&self.internal.refs, cleanup);
     }
 
+    fn degree(&self) -> usize {
+        NodeCollection::len(&self.internal.refs)
+    }
+
+    fn edge_bytes(&self) -> usize {
+        NodeCollection::heap_bytes(&self.internal.refs)
+    }
+
+    fn unlink(&mut self, dst : *const Self) {
+        NodeCollection::unlink(&mut self.internal.refs, dst);
+    }
+
+    fn clear_edges(&mut self) {
+        self.internal.refs.clear();
+    }
+
     fn from_data(data : Self::Node) -> Self
     {
-        let meta = MetaData { cleanup_gen : CleanupGen::Even, store_index : 0 };
+        let meta = MetaData::new();
         Self { internal : node_views::TreeNode::new(data), meta }
     }
+
+    fn into_data(self) -> Self::Node
+    {
+        self.internal.data
+    }
 }
 
-pub unsafe trait NodeCollection<'id, NodeType : GraphNode> : Default {
-    fn traverse(this : &Self, cleanup : &mut CleanupState<NodeType>);
+#[cfg(feature = "smallvec")]
+pub struct SmallVecNode<N, E, const INLINE : usize> {
+    pub(crate) internal: node_views::SmallVecNode<'static, N, E, INLINE>,
+    pub(crate) meta : MetaData,
 }
 
-pub unsafe trait RootCollection<'id, NodeType : GraphNode> : Default {
-    fn traverse(this : &Self, cleanup : &mut CleanupState<NodeType>);
+#[cfg(feature = "smallvec")]
+impl <N, E, const INLINE : usize> SmallVecNode<N, E, INLINE> {
+    pub (crate) fn get_view<'id>(&self) -> &node_views::SmallVecNode<'id, N, E, INLINE> {
+        unsafe {
+            transmute(&self.internal)
+        }
+    }
+
+    pub (crate) fn get_view_mut<'id>(&mut self) -> &mut node_views::SmallVecNode<'id, N, E, INLINE> {
+        unsafe {
+            transmute(&mut self.internal)
+        }
+    }
 }
 
-fn traverse_touch<NodeType : GraphNode>(iter : impl Iterator<Item = *mut NodeType>, cleanup : &mut CleanupState<NodeType>) {
-    for i in iter {
-        cleanup.touch(i);
+#[cfg(feature = "smallvec")]
+impl <N, E, const INLINE : usize> GraphNode for SmallVecNode<N, E, INLINE> {
+    type Node = N;
+
+    fn get(&self) -> &Self::Node
+    {
+        &self.internal.data
+    }
+
+    fn get_mut(&mut self) -> &mut Self::Node
+    {
+        &mut self.internal.data
+    }
+
+    fn meta(&self) -> &MetaData {
+        &self.meta
+    }
+    
+    fn meta_mut(&mut self) -> &mut MetaData {
+        &mut self.meta
+    }
+
+    fn traverse(&self, cleanup : &mut CleanupState<Self>) {
+        NodeCollection::traverse(&self.internal.refs, cleanup);
+    }
+
+    fn degree(&self) -> usize {
+        NodeCollection::len(&self.internal.refs)
+    }
+
+    fn edge_bytes(&self) -> usize {
+        NodeCollection::heap_bytes(&self.internal.refs)
+    }
+
+    fn unlink(&mut self, dst : *const Self) {
+        NodeCollection::unlink(&mut self.internal.refs, dst);
+    }
+
+    fn clear_edges(&mut self) {
+        self.internal.refs = SmallVec::new();
+    }
+
+    fn from_data(data : Self::Node) -> Self
+    {
+        let meta = MetaData::new();
+        Self { internal : node_views::SmallVecNode::new(data), meta }
+    }
+
+    fn into_data(self) -> Self::Node
+    {
+        self.internal.data
     }
 }
 
-pub type RootVec<'id, T> = Vec<GraphPtr<'id, T>>;
-pub type RootNamedSet<'id, T> = HashSet<GraphPtr<'id, T>>;
-pub type RootOption<'id, T> = Option<GraphPtr<'id, T>>;
-pub type RootHashMap<'id, K, T> = HashMap<K, GraphPtr<'id, T>>;
+pub struct SetNode<N> {
+    pub(crate) internal: node_views::SetNode<'static, N>,
+    pub(crate) meta : MetaData,
+}
 
-pub type NodeVec<'id, NodeType, E> = Vec<(GraphPtr<'id, NodeType>, E)>;
-pub type NodeNamedMap<'id, NodeType, E> = HashMap<GraphPtr<'id, NodeType>, E>;
-pub type NodeOption<'id, NodeType, E> = Option<(GraphPtr<'id, NodeType>, E)>;
-pub type NodeTreeMap<'id, K, NodeType, E> = BTreeMap<K, (GraphPtr<'id, NodeType>, E)>;
+impl <N> SetNode<N> {
+    pub (crate) fn get_view<'id>(&self) -> &node_views::SetNode<'id, N> {
+        unsafe {
+            transmute(&self.internal)
+        }
+    }
 
-macro_rules! impl_root_collection {
-    ($collection:ident) => {
-        unsafe impl <'id, NodeType> RootCollection<'id, NodeType> for $collection<'id, NodeType>
-        where NodeType : GraphNode
-        {
-            fn traverse(this : &Self, cleanup : &mut CleanupState<NodeType>) {
-                traverse_touch(this.iter().map(|x| x.as_mut()), cleanup);
-            }
+    pub (crate) fn get_view_mut<'id>(&mut self) -> &mut node_views::SetNode<'id, N> {
+        unsafe {
+            transmute(&mut self.internal)
         }
     }
 }
 
+impl <N> GraphNode for SetNode<N> {
+    type Node = N;
 
-unsafe impl <'id, K, NodeType> RootCollection<'id, NodeType> for RootHashMap<'id, K, NodeType>
-where NodeType : GraphNode,
-      K : Hash + Eq
-{
-    fn traverse(this : &Self, cleanup : &mut CleanupState<NodeType>) {
-        traverse_touch(this.values().map(|x| x.as_mut()), cleanup);
+    fn get(&self) -> &Self::Node
+    {
+        &self.internal.data
+    }
+
+    fn get_mut(&mut self) -> &mut Self::Node
+    {
+        &mut self.internal.data
+    }
+
+    fn meta(&self) -> &MetaData {
+        &self.meta
+    }
+    
+    fn meta_mut(&mut self) -> &mut MetaData {
+        &mut self.meta
+    }
+
+    fn traverse(&self, cleanup : &mut CleanupState<Self>) {
+        NodeCollection::traverse(&self.internal.refs, cleanup);
+    }
+
+    fn degree(&self) -> usize {
+        NodeCollection::len(&self.internal.refs)
+    }
+
+    fn edge_bytes(&self) -> usize {
+        NodeCollection::heap_bytes(&self.internal.refs)
+    }
+
+    fn unlink(&mut self, dst : *const Self) {
+        NodeCollection::unlink(&mut self.internal.refs, dst);
+    }
+
+    fn clear_edges(&mut self) {
+        self.internal.refs.clear();
+    }
+
+    fn from_data(data : Self::Node) -> Self
+    {
+        let meta = MetaData::new();
+        Self { internal : node_views::SetNode::new(data), meta }
+    }
+
+    fn into_data(self) -> Self::Node
+    {
+        self.internal.data
     }
 }
 
-impl_root_collection!{RootVec}
-impl_root_collection!{RootNamedSet}
-impl_root_collection!{RootOption}
+pub struct CustomNode<N, F : EdgeFamily> {
+    pub(crate) internal: node_views::CustomNode<'static, N, F>,
+    pub(crate) meta : MetaData,
+}
 
-macro_rules! impl_node_collection {
-    ($collection:ident) => {
-        unsafe impl <'id, NodeType, E> NodeCollection<'id, NodeType> for $collection<'id, NodeType, E>
-        where NodeType : GraphNode
-        {
-            fn traverse(this : &Self, cleanup : &mut CleanupState<NodeType>) {
-                traverse_touch(this.iter().map(|x| x.0.as_mut()), cleanup);
-            }
+impl <N, F : EdgeFamily> CustomNode<N, F> {
+    pub (crate) fn get_view<'id>(&self) -> &node_views::CustomNode<'id, N, F> {
+        unsafe {
+            transmute(&self.internal)
+        }
+    }
+
+    pub (crate) fn get_view_mut<'id>(&mut self) -> &mut node_views::CustomNode<'id, N, F> {
+        unsafe {
+            transmute(&mut self.internal)
         }
     }
 }
 
-impl_node_collection!{NodeVec}
-impl_node_collection!{NodeNamedMap}
-impl_node_collection!{NodeOption}
+impl <N, F : EdgeFamily> GraphNode for CustomNode<N, F> {
+    type Node = N;
 
-unsafe impl <'id, K, NodeType, E> NodeCollection<'id, NodeType> for NodeTreeMap<'id, K, NodeType, E>
-where NodeType : GraphNode,
-      K : Ord
-{
+    fn get(&self) -> &Self::Node
+    {
+        &self.internal.data
+    }
+
+    fn get_mut(&mut self) -> &mut Self::Node
+    {
+        &mut self.internal.data
+    }
+
+    fn meta(&self) -> &MetaData {
+        &self.meta
+    }
+    
+    fn meta_mut(&mut self) -> &mut MetaData {
+        &mut self.meta
+    }
+
+    fn traverse(&self, cleanup : &mut CleanupState<Self>) {
+        self.internal.refs.visit_ptrs(|x| cleanup.touch(x.as_mut()));
+    }
+
+    fn degree(&self) -> usize {
+        self.internal.refs.len()
+    }
+
+    fn edge_bytes(&self) -> usize {
+        self.internal.refs.heap_bytes()
+    }
+
+    fn unlink(&mut self, dst : *const Self) {
+        self.internal.refs.retain_ptrs(|x| x.as_ptr() != dst);
+    }
+
+    fn clear_edges(&mut self) {
+        self.internal.refs = Default::default();
+    }
+
+    fn from_data(data : Self::Node) -> Self
+    {
+        let meta = MetaData::new();
+        Self { internal : node_views::CustomNode::new(data), meta }
+    }
+
+    fn into_data(self) -> Self::Node
+    {
+        self.internal.data
+    }
+}
+
+pub struct ArrayNode<N, E, const D : usize> {
+    pub(crate) internal: node_views::ArrayNode<'static, N, E, D>,
+    pub(crate) meta : MetaData,
+}
+
+impl <N, E, const D : usize> ArrayNode<N, E, D> {
+    pub (crate) fn get_view<'id>(&self) -> &node_views::ArrayNode<'id, N, E, D> {
+        unsafe {
+            transmute(&self.internal)
+        }
+    }
+
+    pub (crate) fn get_view_mut<'id>(&mut self) -> &mut node_views::ArrayNode<'id, N, E, D> {
+        unsafe {
+            transmute(&mut self.internal)
+        }
+    }
+}
+
+impl <N, E, const D : usize> GraphNode for ArrayNode<N, E, D> {
+    type Node = N;
+
+    fn get(&self) -> &Self::Node
+    {
+        &self.internal.data
+    }
+
+    fn get_mut(&mut self) -> &mut Self::Node
+    {
+        &mut self.internal.data
+    }
+
+    fn meta(&self) -> &MetaData {
+        &self.meta
+    }
+    
+    fn meta_mut(&mut self) -> &mut MetaData {
+        &mut self.meta
+    }
+
+    fn traverse(&self, cleanup : &mut CleanupState<Self>) {
+        traverse_touch(self.internal.refs.iter().flatten().map(|x| x.0.as_mut()), cleanup);
+    }
+
+    fn degree(&self) -> usize {
+        self.internal.refs.iter().flatten().count()
+    }
+
+    fn edge_bytes(&self) -> usize {
+        0
+    }
+
+    fn unlink(&mut self, dst : *const Self) {
+        for i in self.internal.refs.iter_mut() {
+            if matches!(i, Some(x) if x.0.as_ptr() == dst) {
+                *i = None;
+            }
+        }
+    }
+
+    fn clear_edges(&mut self) {
+        self.internal.refs = [(); D].map(|_| None);
+    }
+
+    fn from_data(data : Self::Node) -> Self
+    {
+        let meta = MetaData::new();
+        Self { internal : node_views::ArrayNode::new(data), meta }
+    }
+
+    fn into_data(self) -> Self::Node
+    {
+        self.internal.data
+    }
+}
+
+pub struct MultiNamedNode<N, E> {
+    pub(crate) internal: node_views::MultiNamedNode<'static, N, E>,
+    pub(crate) meta : MetaData,
+}
+
+impl <N, E> MultiNamedNode<N, E> {
+    pub (crate) fn get_view<'id>(&self) -> &node_views::MultiNamedNode<'id, N, E> {
+        unsafe {
+            transmute(&self.internal)
+        }
+    }
+
+    pub (crate) fn get_view_mut<'id>(&mut self) -> &mut node_views::MultiNamedNode<'id, N, E> {
+        unsafe {
+            transmute(&mut self.internal)
+        }
+    }
+}
+
+impl <N, E> GraphNode for MultiNamedNode<N, E> {
+    type Node = N;
+
+    fn get(&self) -> &Self::Node
+    {
+        &self.internal.data
+    }
+
+    fn get_mut(&mut self) -> &mut Self::Node
+    {
+        &mut self.internal.data
+    }
+
+    fn meta(&self) -> &MetaData {
+        &self.meta
+    }
+    
+    fn meta_mut(&mut self) -> &mut MetaData {
+        &mut self.meta
+    }
+
+    fn traverse(&self, cleanup : &mut CleanupState<Self>) {
+        NodeCollection::traverse(&self.internal.refs, cleanup);
+    }
+
+    fn degree(&self) -> usize {
+        self.internal.refs.values().map(|x| x.len()).sum()
+    }
+
+    fn edge_bytes(&self) -> usize {
+        NodeCollection::heap_bytes(&self.internal.refs)
+        + self.internal.refs.values().map(|x| x.capacity() * size_of::<E>()).sum::<usize>()
+    }
+
+    fn unlink(&mut self, dst : *const Self) {
+        NodeCollection::unlink(&mut self.internal.refs, dst);
+    }
+
+    fn clear_edges(&mut self) {
+        self.internal.refs.clear();
+    }
+
+    fn from_data(data : Self::Node) -> Self
+    {
+        let meta = MetaData::new();
+        Self { internal : node_views::MultiNamedNode::new(data), meta }
+    }
+
+    fn into_data(self) -> Self::Node
+    {
+        self.internal.data
+    }
+}
+
+pub struct DiNode<N, E> {
+    pub(crate) internal: node_views::DiNode<'static, N, E>,
+    pub(crate) meta : MetaData,
+}
+
+impl <N, E> DiNode<N, E> {
+    pub (crate) fn get_view<'id>(&self) -> &node_views::DiNode<'id, N, E> {
+        unsafe {
+            transmute(&self.internal)
+        }
+    }
+
+    pub (crate) fn get_view_mut<'id>(&mut self) -> &mut node_views::DiNode<'id, N, E> {
+        unsafe {
+            transmute(&mut self.internal)
+        }
+    }
+}
+
+impl <N, E> GraphNode for DiNode<N, E> {
+    type Node = N;
+
+    fn get(&self) -> &Self::Node
+    {
+        &self.internal.data
+    }
+
+    fn get_mut(&mut self) -> &mut Self::Node
+    {
+        &mut self.internal.data
+    }
+
+    fn meta(&self) -> &MetaData {
+        &self.meta
+    }
+    
+    fn meta_mut(&mut self) -> &mut MetaData {
+        &mut self.meta
+    }
+
+    //Sources do not keep a node alive, forget_targets keeps in_refs from dangling instead.
+    fn traverse(&self, cleanup : &mut CleanupState<Self>) {
+        NodeCollection::traverse(&self.internal.out_refs, cleanup);
+    }
+
+    fn degree(&self) -> usize {
+        NodeCollection::len(&self.internal.out_refs)
+    }
+
+    fn edge_bytes(&self) -> usize {
+        NodeCollection::heap_bytes(&self.internal.out_refs) + NodeCollection::heap_bytes(&self.internal.in_refs)
+    }
+
+    fn unlink(&mut self, dst : *const Self) {
+        NodeCollection::unlink(&mut self.internal.out_refs, dst);
+        NodeCollection::unlink(&mut self.internal.in_refs, dst);
+    }
+
+    //Targets forget about this node as well.
+    fn clear_edges(&mut self) {
+        let this = self as *const Self;
+        for (dst, _) in core::mem::take(&mut self.internal.out_refs) {
+            if dst.as_ptr() == this {
+                self.internal.in_refs.retain(|x| x.as_ptr() != this);
+            } else {
+                //(E)
+                unsafe {
+                    (*dst.as_mut()).internal.in_refs.retain(|x| x.as_ptr() != this);
+                }
+            }
+        }
+    }
+
+    fn forget_targets(&mut self) {
+        let this = self as *const Self;
+        for dst in self.internal.out_refs.keys() {
+            if dst.as_ptr() != this {
+                //(E)
+                unsafe {
+                    let key = GraphPtr::from_ptr(this, dst._guard);
+                    (*dst.as_mut()).internal.in_refs.remove(&key);
+                }
+            }
+        }
+    }
+
+    fn from_data(data : Self::Node) -> Self
+    {
+        let meta = MetaData::new();
+        Self { internal : node_views::DiNode::new(data), meta }
+    }
+
+    fn into_data(self) -> Self::Node
+    {
+        self.internal.data
+    }
+}
+
+pub struct UndirectedNode<N, E> {
+    pub(crate) internal: node_views::UndirectedNode<'static, N, E>,
+    pub(crate) meta : MetaData,
+}
+
+impl <N, E> UndirectedNode<N, E> {
+    pub (crate) fn get_view<'id>(&self) -> &node_views::UndirectedNode<'id, N, E> {
+        unsafe {
+            transmute(&self.internal)
+        }
+    }
+
+    pub (crate) fn get_view_mut<'id>(&mut self) -> &mut node_views::UndirectedNode<'id, N, E> {
+        unsafe {
+            transmute(&mut self.internal)
+        }
+    }
+}
+
+impl <N, E> GraphNode for UndirectedNode<N, E> {
+    type Node = N;
+
+    fn get(&self) -> &Self::Node
+    {
+        &self.internal.data
+    }
+
+    fn get_mut(&mut self) -> &mut Self::Node
+    {
+        &mut self.internal.data
+    }
+
+    fn meta(&self) -> &MetaData {
+        &self.meta
+    }
+    
+    fn meta_mut(&mut self) -> &mut MetaData {
+        &mut self.meta
+    }
+
+    //Keys of edges are always a subset of refs.
+    fn traverse(&self, cleanup : &mut CleanupState<Self>) {
+        NodeCollection::traverse(&self.internal.refs, cleanup);
+    }
+
+    fn degree(&self) -> usize {
+        NodeCollection::len(&self.internal.refs)
+    }
+
+    fn edge_bytes(&self) -> usize {
+        NodeCollection::heap_bytes(&self.internal.refs) + NodeCollection::heap_bytes(&self.internal.edges)
+    }
+
+    fn unlink(&mut self, dst : *const Self) {
+        NodeCollection::unlink(&mut self.internal.refs, dst);
+        NodeCollection::unlink(&mut self.internal.edges, dst);
+    }
+
+    //Neighbours forget about this node as well.
+    fn clear_edges(&mut self) {
+        let this = self as *const Self;
+        self.internal.edges.clear();
+        for dst in core::mem::take(&mut self.internal.refs) {
+            if dst.as_ptr() != this {
+                //(E)
+                unsafe {
+                    let other = &mut (*dst.as_mut()).internal;
+                    other.refs.retain(|x| x.as_ptr() != this);
+                    other.edges.retain(|x, _| x.as_ptr() != this);
+                }
+            }
+        }
+    }
+
+    fn from_data(data : Self::Node) -> Self
+    {
+        let meta = MetaData::new();
+        Self { internal : node_views::UndirectedNode::new(data), meta }
+    }
+
+    fn into_data(self) -> Self::Node
+    {
+        self.internal.data
+    }
+}
+
+/// A collection of edges stored inside a graph node.
+/// # Safety
+/// `traverse` must touch every node pointer stored in the collection, otherwise cleanup will drop nodes
+/// which are still referenced.
+pub unsafe trait NodeCollection<'id, NodeType : GraphNode> : Default {
+    fn traverse(this : &Self, cleanup : &mut CleanupState<NodeType>);
+    /// Returns the number of edges stored in the collection. Counts the pointers touched by `traverse` by default.
+    fn len(this : &Self) -> usize {
+        let mut len = 0;
+        let mut count = |_| len += 1;
+        Self::traverse(this, &mut CleanupState::from_callback(&mut count));
+        len
+    }
+    /// Removes every edge pointing to `dst`. Does nothing by default, removing a node which is still referenced by
+    /// such a collection panics.
+    fn unlink(_this : &mut Self, _dst : *const NodeType) {}
+    /// Returns an estimate of heap memory owned by the collection. Allocations owned by edges are not accounted for.
+    fn heap_bytes(_this : &Self) -> usize {
+        0
+    }
+}
+
+/// A safe counterpart of `NodeCollection`. Wrap an implementor into `SafeCollection` to use it as a `NodeCollection`.
+pub trait EdgeCollection<'id, NodeType : GraphNode> : Default {
+    /// Calls `f` on every node pointer stored in the collection.
+    fn visit_ptrs(&self, f : impl FnMut(GraphPtr<'id, NodeType>));
+    /// Keeps only the edges whose target satisfies `f`.
+    fn retain_ptrs(&mut self, f : impl FnMut(GraphPtr<'id, NodeType>) -> bool);
+
+    /// Returns the number of edges stored in the collection.
+    fn len(&self) -> usize {
+        let mut len = 0;
+        self.visit_ptrs(|_| len += 1);
+        len
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an estimate of heap memory owned by the collection. Allocations owned by edges are not accounted for.
+    fn heap_bytes(&self) -> usize {
+        0
+    }
+}
+
+/// Names an `EdgeCollection` for every brand and node type, so that `CustomNode` can store it.
+pub trait EdgeFamily {
+    type Collection<'id, NodeType : GraphNode> : EdgeCollection<'id, NodeType>;
+}
+
+/// Implements `NodeCollection` for any `EdgeCollection`.
+#[derive(Default)]
+pub struct SafeCollection<C>(pub C);
+
+unsafe impl <'id, NodeType, C> NodeCollection<'id, NodeType> for SafeCollection<C>
+where NodeType : GraphNode,
+      C : EdgeCollection<'id, NodeType>
+{
+    fn traverse(this : &Self, cleanup : &mut CleanupState<NodeType>) {
+        this.0.visit_ptrs(|x| cleanup.touch(x.as_mut()));
+    }
+
+    fn len(this : &Self) -> usize {
+        this.0.len()
+    }
+
+    fn unlink(this : &mut Self, dst : *const NodeType) {
+        this.0.retain_ptrs(|x| x.as_ptr() != dst);
+    }
+
+    fn heap_bytes(this : &Self) -> usize {
+        this.0.heap_bytes()
+    }
+}
+
+impl <C> Deref for SafeCollection<C> {
+    type Target = C;
+    fn deref(&self) -> &C {
+        &self.0
+    }
+}
+
+impl <C> DerefMut for SafeCollection<C> {
+    fn deref_mut(&mut self) -> &mut C {
+        &mut self.0
+    }
+}
+
+/// A collection of pointers which serves as the root of a graph.
+/// # Safety
+/// `traverse` must touch every node pointer stored in the collection, otherwise cleanup will drop nodes
+/// which are still referenced.
+pub unsafe trait RootCollection<'id, NodeType : GraphNode> : Default {
+    fn traverse(this : &Self, cleanup : &mut CleanupState<NodeType>);
+    /// Removes every pointer to `dst`. Does nothing by default, removing a node which is still stored in such a root
+    /// panics.
+    fn unlink(_this : &mut Self, _dst : *const NodeType) {}
+}
+
+fn traverse_touch<NodeType : GraphNode>(iter : impl Iterator<Item = *mut NodeType>, cleanup : &mut CleanupState<NodeType>) {
+    for i in iter {
+        cleanup.touch(i);
+    }
+}
+
+pub type RootVec<'id, T> = Vec<GraphPtr<'id, T>>;
+pub type RootNamedSet<'id, T, S = DefaultPtrState> = HashSet<GraphPtr<'id, T>, S>;
+pub type RootOption<'id, T> = Option<GraphPtr<'id, T>>;
+pub type RootHashMap<'id, K, T> = HashMap<K, GraphPtr<'id, T>>;
+pub type RootVecDeque<'id, T> = VecDeque<GraphPtr<'id, T>>;
+pub type RootSlab<'id, T> = Slab<GraphPtr<'id, T>>;
+
+pub type NodeVec<'id, NodeType, E> = Vec<(GraphPtr<'id, NodeType>, E)>;
+pub type NodeNamedMap<'id, NodeType, E, S = DefaultPtrState> = HashMap<GraphPtr<'id, NodeType>, E, S>;
+pub type NodeOption<'id, NodeType, E> = Option<(GraphPtr<'id, NodeType>, E)>;
+pub type NodeTreeMap<'id, K, NodeType, E> = BTreeMap<K, (GraphPtr<'id, NodeType>, E)>;
+pub type NodeNamedSet<'id, NodeType> = HashSet<GraphPtr<'id, NodeType>, DefaultPtrState>;
+#[cfg(feature = "indexmap")]
+pub type NodeOrderedMap<'id, NodeType, E> = IndexMap<GraphPtr<'id, NodeType>, E, DefaultPtrState>;
+#[cfg(feature = "smallvec")]
+pub type NodeSmallVec<'id, NodeType, E, const INLINE : usize> = SmallVec<[(GraphPtr<'id, NodeType>, E); INLINE]>;
+pub type NodeMultiMap<'id, NodeType, E> = NodeNamedMap<'id, NodeType, Vec<E>>;
+pub type NodeArray<'id, NodeType, E, const D : usize> = [Option<(GraphPtr<'id, NodeType>, E)>; D];
+pub type NodeNamedEntry<'a, 'id, NodeType, E> = Entry<'a, GraphPtr<'id, NodeType>, E>;
+
+macro_rules! impl_root_collection {
+    ($collection:ident, $Unlink:tt) => {
+        unsafe impl <'id, NodeType> RootCollection<'id, NodeType> for $collection<'id, NodeType>
+        where NodeType : GraphNode
+        {
+            fn traverse(this : &Self, cleanup : &mut CleanupState<NodeType>) {
+                traverse_touch(this.iter().map(|x| x.as_mut()), cleanup);
+            }
+
+            fn unlink(this : &mut Self, dst : *const NodeType) {
+                ($Unlink)(this, dst)
+            }
+        }
+    }
+}
+
+
+unsafe impl <'id, K, NodeType> RootCollection<'id, NodeType> for RootHashMap<'id, K, NodeType>
+where NodeType : GraphNode,
+      K : Hash + Eq
+{
+    fn traverse(this : &Self, cleanup : &mut CleanupState<NodeType>) {
+        traverse_touch(this.values().map(|x| x.as_mut()), cleanup);
+    }
+
+    fn unlink(this : &mut Self, dst : *const NodeType) {
+        this.retain(|_, x| x.as_ptr() != dst);
+    }
+}
+
+impl_root_collection!{RootVec,      {|this : &mut Self, dst| this.retain(|x| x.as_ptr() != dst)}}
+impl_root_collection!{RootOption,   {|this : &mut Self, dst| if this.is_some_and(|x| x.as_ptr() == dst) {
+    *this = None;
+}}}
+impl_root_collection!{RootVecDeque, {|this : &mut Self, dst| this.retain(|x| x.as_ptr() != dst)}}
+
+unsafe impl <'id, NodeType> RootCollection<'id, NodeType> for RootSlab<'id, NodeType>
+where NodeType : GraphNode
+{
+    fn traverse(this : &Self, cleanup : &mut CleanupState<NodeType>) {
+        traverse_touch(this.values().map(|x| x.as_mut()), cleanup);
+    }
+
+    fn unlink(this : &mut Self, dst : *const NodeType) {
+        this.retain(|_, x| x.as_ptr() != dst);
+    }
+}
+
+macro_rules! impl_root_collection_tuple {
+    ($($member:ident : $index:tt),*) => {
+        unsafe impl <'id, NodeType, $($member),*> RootCollection<'id, NodeType> for ($($member,)*)
+        where NodeType : GraphNode,
+              $($member : RootCollection<'id, NodeType>),*
+        {
+            fn traverse(this : &Self, cleanup : &mut CleanupState<NodeType>) {
+                $(RootCollection::traverse(&this.$index, cleanup);)*
+            }
+
+            fn unlink(this : &mut Self, dst : *const NodeType) {
+                $(RootCollection::unlink(&mut this.$index, dst);)*
+            }
+        }
+    }
+}
+
+impl_root_collection_tuple!{A : 0, B : 1}
+impl_root_collection_tuple!{A : 0, B : 1, C : 2}
+impl_root_collection_tuple!{A : 0, B : 1, C : 2, D : 3}
+
+/// A root collection which anchors expose with the graph brand through `root` and `root_mut`. Implemented for every
+/// root collection of the crate and tuples of them, implement it for custom roots to get the same access.
+/// # Safety
+/// `View<'id>` must be `Self` with every `'static` brand replaced by `'id`.
+pub unsafe trait RootCollectionView {
+    type View<'id>;
+}
+
+macro_rules! impl_root_collection_view {
+    ($collection:ident $(, $P:ident)*) => {
+        unsafe impl <NodeType $(, $P)*> RootCollectionView for $collection<'static, $($P, )* NodeType> {
+            type View<'id> = $collection<'id, $($P, )* NodeType>;
+        }
+    }
+}
+
+impl_root_collection_view!{RootVec}
+impl_root_collection_view!{RootOption}
+impl_root_collection_view!{RootVecDeque}
+impl_root_collection_view!{RootSlab}
+impl_root_collection_view!{RootHashMap, K}
+
+unsafe impl <NodeType, S> RootCollectionView for RootNamedSet<'static, NodeType, S> {
+    type View<'id> = RootNamedSet<'id, NodeType, S>;
+}
+
+macro_rules! impl_root_collection_view_tuple {
+    ($($member:ident),*) => {
+        unsafe impl <$($member),*> RootCollectionView for ($($member,)*)
+        where $($member : RootCollectionView),*
+        {
+            type View<'id> = ($($member::View<'id>,)*);
+        }
+    }
+}
+
+impl_root_collection_view_tuple!{A, B}
+impl_root_collection_view_tuple!{A, B, C}
+impl_root_collection_view_tuple!{A, B, C, D}
+
+/// A root collection whose nodes anchors can iterate over with `iter` and `iter_mut`. Plain collections yield
+/// an item per node, keyed collections pair every item with its key.
+/// # Safety
+/// `iter_with` must only pass pointers stored in the collection to `f`.
+pub unsafe trait RootIterable<NodeType> {
+    /// An item of root iteration built from `V`, the data and the pointer of a node.
+    type Item<'a, V> where Self : 'a;
+    /// Calls `f` on every node in the collection and yields the results along with keys, if any.
+    fn iter_with<'a, V>(this : &'a Self, f : impl FnMut(*mut NodeType) -> V + 'a) -> impl Iterator<Item = Self::Item<'a, V>> + 'a;
+}
+
+macro_rules! impl_root_iterable {
+    ($collection:ident $(, $S:ident)?) => {
+        unsafe impl <NodeType $(, $S)?> RootIterable<NodeType> for $collection<'static, NodeType $(, $S)?> {
+            type Item<'a, V> = V where Self : 'a;
+            fn iter_with<'a, V>(this : &'a Self, mut f : impl FnMut(*mut NodeType) -> V + 'a)
+                -> impl Iterator<Item = V> + 'a
+            {
+                this.iter().map(move |x| f(x.as_mut()))
+            }
+        }
+    }
+}
+
+impl_root_iterable!{RootVec}
+impl_root_iterable!{RootNamedSet, S}
+impl_root_iterable!{RootOption}
+impl_root_iterable!{RootVecDeque}
+
+unsafe impl <NodeType> RootIterable<NodeType> for RootSlab<'static, NodeType> {
+    type Item<'a, V> = (usize, V) where Self : 'a;
+    fn iter_with<'a, V>(this : &'a Self, mut f : impl FnMut(*mut NodeType) -> V + 'a)
+        -> impl Iterator<Item = (usize, V)> + 'a
+    {
+        this.iter().map(move |(k, x)| (k, f(x.as_mut())))
+    }
+}
+
+unsafe impl <K, NodeType> RootIterable<NodeType> for RootHashMap<'static, K, NodeType> {
+    type Item<'a, V> = (&'a K, V) where Self : 'a;
+    fn iter_with<'a, V>(this : &'a Self, mut f : impl FnMut(*mut NodeType) -> V + 'a)
+        -> impl Iterator<Item = (&'a K, V)> + 'a
+    {
+        this.iter().map(move |(k, x)| (k, f(x.as_mut())))
+    }
+}
+
+#[macro_export]
+/// Declares a struct whose fields are root collections and which serves as the root of a graph as a whole.
+/// The struct must be generic over the brand lifetime and the node type.
+macro_rules! composite_root
+{
+    ($(#[$attr:meta])* $vis:vis struct $name:ident<$id:lifetime, $T:ident> {
+        $($field_vis:vis $field:ident : $field_type:ty),* $(,)?
+    }) => {
+        $(#[$attr])*
+        $vis struct $name<$id, $T : $crate::GraphNode> {
+            $($field_vis $field : $field_type),*
+        }
+
+        impl <$id, $T : $crate::GraphNode> Default for $name<$id, $T> {
+            fn default() -> Self {
+                $name { $($field : Default::default()),* }
+            }
+        }
+
+        unsafe impl <$id, $T : $crate::GraphNode> $crate::RootCollection<$id, $T> for $name<$id, $T> {
+            fn traverse(this : &Self, cleanup : &mut $crate::CleanupState<$T>) {
+                $($crate::RootCollection::traverse(&this.$field, cleanup);)*
+            }
+
+            fn unlink(this : &mut Self, dst : *const $T) {
+                $($crate::RootCollection::unlink(&mut this.$field, dst);)*
+            }
+        }
+
+        unsafe impl <$T : $crate::GraphNode> $crate::RootCollectionView for $name<'static, $T> {
+            type View<$id> = $name<$id, $T>;
+        }
+    }
+}
+
+// Estimates memory of a hash table: a slot and a control byte per item.
+pub(crate) fn hash_table_bytes<T>(capacity : usize) -> usize {
+    capacity * (size_of::<T>() + 1)
+}
+
+macro_rules! impl_node_collection {
+    ($collection:ident, $Unlink:tt, $HeapBytes:tt) => {
+        unsafe impl <'id, NodeType, E> NodeCollection<'id, NodeType> for $collection<'id, NodeType, E>
+        where NodeType : GraphNode
+        {
+            fn traverse(this : &Self, cleanup : &mut CleanupState<NodeType>) {
+                traverse_touch(this.iter().map(|x| x.0.as_mut()), cleanup);
+            }
+
+            fn len(this : &Self) -> usize {
+                this.iter().len()
+            }
+
+            fn unlink(this : &mut Self, dst : *const NodeType) {
+                ($Unlink)(this, dst)
+            }
+
+            fn heap_bytes(this : &Self) -> usize {
+                ($HeapBytes)(this)
+            }
+        }
+    }
+}
+
+impl_node_collection!{NodeVec,      {|this : &mut Self, dst| this.retain(|x| x.0.as_ptr() != dst)},
+                                    {|this : &Self| this.capacity() * size_of::<(GraphPtr<'id, NodeType>, E)>()}}
+#[cfg(feature = "indexmap")]
+impl_node_collection!{NodeOrderedMap, {|this : &mut Self, dst| this.retain(|x, _| x.as_ptr() != dst)},
+                                      {|this : &Self| hash_table_bytes::<usize>(this.capacity())
+                                          + this.capacity() * size_of::<(u64, GraphPtr<'id, NodeType>, E)>()}}
+impl_node_collection!{NodeOption,   {|this : &mut Self, dst| if this.as_ref().is_some_and(|x| x.0.as_ptr() == dst) {
+    *this = None;
+}}, {|_ : &Self| 0}}
+
+unsafe impl <'id, NodeType, S> RootCollection<'id, NodeType> for RootNamedSet<'id, NodeType, S>
+where NodeType : GraphNode,
+      S : BuildHasher + Default
+{
+    fn traverse(this : &Self, cleanup : &mut CleanupState<NodeType>) {
+        traverse_touch(this.iter().map(|x| x.as_mut()), cleanup);
+    }
+
+    fn unlink(this : &mut Self, dst : *const NodeType) {
+        this.retain(|x| x.as_ptr() != dst);
+    }
+}
+
+unsafe impl <'id, NodeType, E, S> NodeCollection<'id, NodeType> for NodeNamedMap<'id, NodeType, E, S>
+where NodeType : GraphNode,
+      S : BuildHasher + Default
+{
+    fn traverse(this : &Self, cleanup : &mut CleanupState<NodeType>) {
+        traverse_touch(this.keys().map(|x| x.as_mut()), cleanup);
+    }
+
+    fn len(this : &Self) -> usize {
+        this.len()
+    }
+
+    fn unlink(this : &mut Self, dst : *const NodeType) {
+        this.retain(|x, _| x.as_ptr() != dst);
+    }
+
+    fn heap_bytes(this : &Self) -> usize {
+        hash_table_bytes::<(GraphPtr<'id, NodeType>, E)>(this.capacity())
+    }
+}
+
+unsafe impl <'id, K, NodeType, E> NodeCollection<'id, NodeType> for NodeTreeMap<'id, K, NodeType, E>
+where NodeType : GraphNode,
+      K : Ord
+{
     fn traverse(this : &Self, cleanup : &mut CleanupState<NodeType>) {
         traverse_touch(this.values().map(|x| x.0.as_mut()), cleanup);
     }
+
+    fn len(this : &Self) -> usize {
+        this.len()
+    }
+
+    fn unlink(this : &mut Self, dst : *const NodeType) {
+        this.retain(|_, x| x.0.as_ptr() != dst);
+    }
+
+    fn heap_bytes(this : &Self) -> usize {
+        // B-tree nodes hold up to 11 entries and are half full at worst.
+        this.len() * 2 * size_of::<(K, (GraphPtr<'id, NodeType>, E))>()
+    }
+}
+
+unsafe impl <'id, NodeType> NodeCollection<'id, NodeType> for NodeNamedSet<'id, NodeType>
+where NodeType : GraphNode
+{
+    fn traverse(this : &Self, cleanup : &mut CleanupState<NodeType>) {
+        traverse_touch(this.iter().map(|x| x.as_mut()), cleanup);
+    }
+
+    fn len(this : &Self) -> usize {
+        this.len()
+    }
+
+    fn unlink(this : &mut Self, dst : *const NodeType) {
+        this.retain(|x| x.as_ptr() != dst);
+    }
+
+    fn heap_bytes(this : &Self) -> usize {
+        hash_table_bytes::<GraphPtr<'id, NodeType>>(this.capacity())
+    }
+}
+
+#[cfg(feature = "smallvec")]
+unsafe impl <'id, NodeType, E, const INLINE : usize> NodeCollection<'id, NodeType> for NodeSmallVec<'id, NodeType, E, INLINE>
+where NodeType : GraphNode
+{
+    fn traverse(this : &Self, cleanup : &mut CleanupState<NodeType>) {
+        traverse_touch(this.iter().map(|x| x.0.as_mut()), cleanup);
+    }
+
+    fn len(this : &Self) -> usize {
+        this.len()
+    }
+
+    fn unlink(this : &mut Self, dst : *const NodeType) {
+        this.retain(|x| x.0.as_ptr() != dst);
+    }
+
+    fn heap_bytes(this : &Self) -> usize {
+        if this.spilled() { this.capacity() * size_of::<(GraphPtr<'id, NodeType>, E)>() } else { 0 }
+    }
 }
@@ -0,0 +1,132 @@
+use super::*;
+
+/// A sequence of nodes where every node is connected to the next one. Edges are identified by their endpoints, the
+/// first edge is used if a node type allows parallel ones. A path always has at least one node.
+pub struct Path<'id, NodeType> {
+    nodes : Vec<GraphPtr<'id, NodeType>>,
+}
+
+impl <'id, NodeType> Clone for Path<'id, NodeType> {
+    fn clone(&self) -> Self
+    {
+        Path { nodes : self.nodes.clone() }
+    }
+}
+
+impl <'id, NodeType> PartialEq for Path<'id, NodeType> {
+    fn eq(&self, other : &Self) -> bool
+    {
+        self.nodes == other.nodes
+    }
+}
+
+impl <'id, NodeType> Eq for Path<'id, NodeType> {}
+
+impl <'id, NodeType> Path<'id, NodeType> {
+    /// Creates a path which starts and ends at `src` and has no edges.
+    pub fn new(src : GraphPtr<'id, NodeType>) -> Self
+    {
+        Path { nodes : vec![src] }
+    }
+
+    /// Creates a path going through `nodes`. Returns None if `nodes` is empty.
+    pub fn from_nodes(nodes : Vec<GraphPtr<'id, NodeType>>) -> Option<Self>
+    {
+        if nodes.is_empty() { None } else { Some(Path { nodes }) }
+    }
+
+    /// Extends the path with an edge from its last node to `dst`.
+    pub fn push(&mut self, dst : GraphPtr<'id, NodeType>)
+    {
+        self.nodes.push(dst);
+    }
+
+    /// Returns the first node of the path.
+    pub fn source(&self) -> GraphPtr<'id, NodeType>
+    {
+        self.nodes[0]
+    }
+
+    /// Returns the last node of the path.
+    pub fn target(&self) -> GraphPtr<'id, NodeType>
+    {
+        self.nodes[self.nodes.len() - 1]
+    }
+
+    /// Returns the nodes of the path in order.
+    pub fn nodes(&self) -> &[GraphPtr<'id, NodeType>]
+    {
+        &self.nodes
+    }
+
+    /// Returns the number of edges in the path.
+    pub fn len(&self) -> usize
+    {
+        self.nodes.len() - 1
+    }
+
+    /// Returns true if the path has no edges.
+    pub fn is_empty(&self) -> bool
+    {
+        self.nodes.len() == 1
+    }
+
+    /// Returns pairs of endpoints of every edge in the path.
+    pub fn steps(&self) -> impl Iterator<Item = (GraphPtr<'id, NodeType>, GraphPtr<'id, NodeType>)> + '_
+    {
+        self.nodes.windows(2).map(|x| (x[0], x[1]))
+    }
+
+    /// Returns the sum of weights of edges in the path, an edge from `a` to `b` weighs `weight(a, b)`.
+    pub fn weight(&self, mut weight : impl FnMut(GraphPtr<'id, NodeType>, GraphPtr<'id, NodeType>) -> f64) -> f64
+    {
+        self.steps().map(|(a, b)| weight(a, b)).sum()
+    }
+
+    /// Returns this path followed by `other`. Returns None if `other` does not start where this path ends.
+    pub fn concat(&self, other : &Path<'id, NodeType>) -> Option<Self>
+    {
+        if self.target() != other.source() {
+            return None;
+        }
+        let mut nodes = self.nodes.clone();
+        nodes.extend_from_slice(&other.nodes[1..]);
+        Some(Path { nodes })
+    }
+
+    /// Checks that every node of the path has an edge to the next one.
+    pub fn validate<Root, A : Allocator>(&self, anchor : &Anchor<'_, 'id, GenericGraph<Root, NodeType, A>>) -> Result<(), BrokenPath>
+    where Root : RootCollection<'static, NodeType>,
+          NodeType : GraphNode
+    {
+        let mut targets = Vec::new();
+        for (i, (a, b)) in self.steps().enumerate() {
+            anchor.internal().targets_of(a.as_ptr(), &mut targets);
+            if !targets.contains(&b.as_mut()) {
+                return Err(BrokenPath(i));
+            }
+        }
+        Ok(())
+    }
+}
+
+macro_rules! impl_path_edges {
+    ($NodeType:ident $(, $S:ident)?) => {
+        impl <'id, N, E $(, $S : BuildHasher + Default)?> Path<'id, $NodeType<N, E $(, $S)?>> {
+            /// Returns views of every edge in the path, None for the edges missing from the graph.
+            pub fn edges<'a, Root, A : Allocator>(&'a self, anchor : &'a Anchor<'_, 'id, GenericGraph<Root, $NodeType<N, E $(, $S)?>, A>>)
+                -> Vec<Option<Edge<&'a N, &'a E>>>
+            where Root : RootCollection<'static, $NodeType<N, E $(, $S)?>>
+            {
+                let internal = anchor.internal();
+                self.steps().map(|(a, b)| internal.iter(a).find(|x| x.ptr == b).map(|x| x.values)).collect()
+            }
+        }
+    }
+}
+
+impl_path_edges!{NamedNode, S}
+impl_path_edges!{VecNode}
+impl_path_edges!{OptionNode}
+#[cfg(feature = "indexmap")]
+impl_path_edges!{OrderedNamedNode}
@@ -0,0 +1,198 @@
+use super::*;
+use std::collections::HashSet;
+
+/// A small graph looked for in other graphs by `match_pattern`. Every pattern node either matches any node or only
+/// the nodes whose data satisfies a predicate.
+#[allow(clippy::type_complexity)]
+pub struct Pattern<'p, N> {
+    nodes : Vec<Option<Box<dyn Fn(&N) -> bool + 'p>>>,
+    edges : Vec<(usize, usize)>,
+}
+
+impl <'p, N> Default for Pattern<'p, N> {
+    fn default() -> Self
+    {
+        Pattern::new()
+    }
+}
+
+impl <'p, N> Pattern<'p, N> {
+    /// Creates an empty pattern.
+    pub fn new() -> Self
+    {
+        Pattern { nodes : Vec::new(), edges : Vec::new() }
+    }
+
+    /// Adds a node matching any node and returns its position in assignments.
+    pub fn any(&mut self) -> usize
+    {
+        self.nodes.push(None);
+        self.nodes.len() - 1
+    }
+
+    /// Adds a node matching the nodes whose data satisfies `pred` and returns its position in assignments.
+    pub fn node(&mut self, pred : impl Fn(&N) -> bool + 'p) -> usize
+    {
+        self.nodes.push(Some(Box::new(pred)));
+        self.nodes.len() - 1
+    }
+
+    /// Requires an edge from the node assigned to `src` to the node assigned to `dst`. Panics if either position is
+    /// out of bounds.
+    pub fn edge(&mut self, src : usize, dst : usize)
+    {
+        assert!(src < self.nodes.len() && dst < self.nodes.len(), "pattern node out of bounds");
+        if !self.edges.contains(&(src, dst)) {
+            self.edges.push((src, dst));
+        }
+    }
+
+    /// Returns the number of nodes in the pattern.
+    pub fn len(&self) -> usize
+    {
+        self.nodes.len()
+    }
+
+    /// Returns true if the pattern has no nodes.
+    pub fn is_empty(&self) -> bool
+    {
+        self.nodes.is_empty()
+    }
+
+    // Orders nodes so that every node shares an edge with an earlier one whenever possible. Pairs every node with
+    // an earlier neighbor, the flag is set if the edge goes from the neighbor to the node.
+    fn order(&self) -> Vec<(usize, Option<(usize, bool)>)>
+    {
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut placed = vec![false; self.nodes.len()];
+        for start in 0..self.nodes.len() {
+            if placed[start] {
+                continue;
+            }
+            placed[start] = true;
+            order.push((start, None));
+            let mut i = order.len() - 1;
+            while i < order.len() {
+                let current = order[i].0;
+                for &(a, b) in &self.edges {
+                    let (next, forward) = if a == current { (b, true) } else if b == current { (a, false) } else { continue };
+                    if !placed[next] {
+                        placed[next] = true;
+                        order.push((next, Some((current, forward))));
+                    }
+                }
+                i += 1;
+            }
+        }
+        order
+    }
+}
+
+struct Matcher<'a, 'p, N> {
+    pattern : &'a Pattern<'p, N>,
+    data : Vec<&'a N>,
+    targets : Vec<Vec<usize>>,
+    sources : Vec<Vec<usize>>,
+    edges : HashSet<(usize, usize)>,
+    order : Vec<(usize, Option<(usize, bool)>)>,
+    out_degree : Vec<usize>,
+    in_degree : Vec<usize>,
+    assigned : Vec<Option<usize>>,
+    used : Vec<bool>,
+    found : Vec<Vec<usize>>,
+}
+
+impl <'a, 'p, N> Matcher<'a, 'p, N> {
+    // Checks whether graph node `x` may be assigned to pattern node `node` given the assignments made so far.
+    fn fits(&self, node : usize, x : usize) -> bool
+    {
+        if self.used[x] || self.targets[x].len() < self.out_degree[node] || self.sources[x].len() < self.in_degree[node] {
+            return false;
+        }
+        if let Some(pred) = &self.pattern.nodes[node] {
+            if !pred(self.data[x]) {
+                return false;
+            }
+        }
+        self.pattern.edges.iter().all(|&(a, b)| {
+            let a = if a == node { Some(x) } else { self.assigned[a] };
+            let b = if b == node { Some(x) } else { self.assigned[b] };
+            match (a, b) {
+                (Some(a), Some(b)) => self.edges.contains(&(a, b)),
+                _ => true,
+            }
+        })
+    }
+
+    fn extend(&mut self, depth : usize)
+    {
+        if depth == self.order.len() {
+            self.found.push(self.assigned.iter().map(|x| x.unwrap()).collect());
+            return;
+        }
+        let (node, parent) = self.order[depth];
+        let candidates = match parent {
+            Some((p, true)) => self.targets[self.assigned[p].unwrap()].clone(),
+            Some((p, false)) => self.sources[self.assigned[p].unwrap()].clone(),
+            None => (0..self.data.len()).collect(),
+        };
+        let mut tried = HashSet::new();
+        for x in candidates {
+            if !tried.insert(x) || !self.fits(node, x) {
+                continue;
+            }
+            self.assigned[node] = Some(x);
+            self.used[x] = true;
+            self.extend(depth + 1);
+            self.used[x] = false;
+            self.assigned[node] = None;
+        }
+    }
+}
+
+/// Finds every way to assign distinct allocated nodes to the nodes of `pattern` so that assigned nodes satisfy
+/// the predicates of their pattern nodes and every pattern edge has a matching edge in the graph. The graph may have
+/// additional edges between assigned nodes. Uses VF2-style backtracking. Every assignment lists nodes in the order
+/// they were added to the pattern; an empty pattern has a single empty assignment.
+pub fn match_pattern<'id, Root, NodeType, A : Allocator>(anchor : &Anchor<'_, 'id, GenericGraph<Root, NodeType, A>>,
+                                                         pattern : &Pattern<'_, NodeType::Node>)
+    -> Vec<Vec<GraphPtr<'id, NodeType>>>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    let internal = anchor.internal();
+    let targets = internal.adjacency();
+    let mut sources = vec![Vec::new(); targets.len()];
+    let mut edges = HashSet::new();
+    for (i, x) in targets.iter().enumerate() {
+        for &j in x {
+            sources[j].push(i);
+            edges.insert((i, j));
+        }
+    }
+    let mut out_degree = vec![0; pattern.len()];
+    let mut in_degree = vec![0; pattern.len()];
+    for &(a, b) in &pattern.edges {
+        out_degree[a] += 1;
+        in_degree[b] += 1;
+    }
+    let mut matcher = Matcher {
+        pattern,
+        //(W)
+        data : internal.data.iter().map(|x| unsafe { (*x.as_ptr()).get() }).collect(),
+        used : vec![false; targets.len()],
+        targets,
+        sources,
+        edges,
+        order : pattern.order(),
+        out_degree,
+        in_degree,
+        assigned : vec![None; pattern.len()],
+        found : Vec::new(),
+    };
+    matcher.extend(0);
+    matcher.found.into_iter().map(|x| {
+        //(W)
+        x.into_iter().map(|i| unsafe { GraphPtr::from_mut(internal.data[i].as_ptr(), anchor._guard) }).collect()
+    }).collect()
+}
@@ -0,0 +1,271 @@
+use crate::edge::*;
+use crate::graph_raw::GraphItem;
+use std::sync::Arc;
+
+// Nodes are stored in a trie with CHUNK children per level, a modification copies one path from the root to a leaf.
+const BITS : usize = 5;
+const CHUNK : usize = 1 << BITS;
+const MASK : usize = CHUNK - 1;
+
+/// A node of a `PersistentGraph`.
+pub struct PersistentNode<N, E> {
+    pub data : N,
+    /// Keys of destination nodes and edge data.
+    pub refs : Vec<(usize, E)>,
+}
+
+enum Trie<N, E> {
+    Branch(Vec<Option<Arc<Trie<N, E>>>>),
+    Leaf(Vec<Option<Arc<PersistentNode<N, E>>>>),
+}
+
+impl <N, E> Trie<N, E> {
+    // Returns a copy of `trie` where the slot of `key` holds `node`. `shift` is the number of key bits below the level.
+    fn with(trie : Option<&Arc<Self>>, shift : usize, key : usize, node : Option<Arc<PersistentNode<N, E>>>) -> Arc<Self>
+    {
+        let i = (key >> shift) & MASK;
+        if shift == 0 {
+            let mut items = match trie.map(|x| &**x) {
+                Some(Trie::Leaf(x)) => x.clone(),
+                _ => vec![None; CHUNK],
+            };
+            items[i] = node;
+            Arc::new(Trie::Leaf(items))
+        } else {
+            let mut children = match trie.map(|x| &**x) {
+                Some(Trie::Branch(x)) => x.clone(),
+                _ => vec![None; CHUNK],
+            };
+            let child = Self::with(children[i].as_ref(), shift - BITS, key, node);
+            children[i] = Some(child);
+            Arc::new(Trie::Branch(children))
+        }
+    }
+}
+
+/// An immutable graph. Every modification returns a new graph which shares unchanged nodes with the original one,
+/// so both versions stay usable. Nodes are stored in a 32-way trie, so a modification copies O(log n) trie levels
+/// besides the changed node. Cloning the graph is cheap.
+///
+/// This is a separate model from `GenericGraph`: nodes are addressed by keys returned from `spawn` instead of
+/// `GraphPtr`, keys of removed nodes are not reused and nodes are never collected automatically. Nodes which are
+/// no longer reachable from the root stay in the graph until they are removed with `remove` or `cleanup`.
+pub struct PersistentGraph<N, E> {
+    trie : Option<Arc<Trie<N, E>>>,
+    // Number of key bits below the root level of the trie.
+    shift : usize,
+    // Number of keys ever handed out.
+    slots : usize,
+    len : usize,
+    roots : Arc<Vec<usize>>,
+}
+
+impl <N, E> Clone for PersistentGraph<N, E> {
+    fn clone(&self) -> Self
+    {
+        PersistentGraph { trie : self.trie.clone(), shift : self.shift, slots : self.slots, len : self.len,
+                          roots : self.roots.clone() }
+    }
+}
+
+impl <N, E> Default for PersistentGraph<N, E> {
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl <N, E> PersistentGraph<N, E> {
+    pub fn new() -> Self
+    {
+        PersistentGraph { trie : None, shift : 0, slots : 0, len : 0, roots : Arc::new(Vec::new()) }
+    }
+
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize
+    {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.len == 0
+    }
+
+    /// Returns the node with key `key` or None if there is none.
+    pub fn node(&self, key : usize) -> Option<&PersistentNode<N, E>>
+    {
+        if key >= self.slots {
+            return None;
+        }
+        let mut level = self.trie.as_deref()?;
+        let mut shift = self.shift;
+        loop {
+            match level {
+                Trie::Branch(x) => {
+                    level = x[(key >> shift) & MASK].as_deref()?;
+                    shift -= BITS;
+                },
+                Trie::Leaf(x) => return x[key & MASK].as_deref(),
+            }
+        }
+    }
+
+    pub fn contains(&self, key : usize) -> bool
+    {
+        self.node(key).is_some()
+    }
+
+    /// Returns the data of node `key` or None if there is none.
+    pub fn get(&self, key : usize) -> Option<&N>
+    {
+        self.node(key).map(|x| &x.data)
+    }
+
+    /// Returns keys of nodes referenced by the root.
+    pub fn roots(&self) -> &[usize]
+    {
+        &self.roots
+    }
+
+    /// Returns an iterator over keys and data of every node.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &N)>
+    {
+        (0..self.slots).filter_map(move |x| self.get(x).map(|data| (x, data)))
+    }
+
+    /// Returns an iterator over edges of `src` and keys of their destinations.
+    /// # Panics
+    /// Panics if there is no node `src`.
+    pub fn edges(&self, src : usize) -> impl Iterator<Item = GraphItem<Edge<&N, &E>, usize>>
+    {
+        let node = self.node(src).expect("invalid node key");
+        node.refs.iter().map(move |(dst, edge)| {
+            let values = if *dst == src {
+                Loop(EdgeLoop { this : &node.data, edge })
+            } else {
+                Both(EdgeBoth { this : &node.data, that : self.get(*dst).expect("invalid node key"), edge })
+            };
+            GraphItem { values, ptr : *dst }
+        })
+    }
+
+    /// Returns an iterator over keys of destinations of edges of `src`.
+    /// # Panics
+    /// Panics if there is no node `src`.
+    pub fn neighbors(&self, src : usize) -> impl Iterator<Item = usize> + '_
+    {
+        self.node(src).expect("invalid node key").refs.iter().map(|x| x.0)
+    }
+
+    /// Returns a graph with a new node and the key of this node.
+    pub fn spawn(&self, data : N) -> (Self, usize)
+    {
+        let key = self.slots;
+        let (mut trie, mut shift) = (self.trie.clone(), self.shift);
+        if key >> shift >= CHUNK {
+            // The trie is full, the old root becomes the first child of a new level.
+            let mut children = vec![None; CHUNK];
+            children[0] = trie;
+            trie = Some(Arc::new(Trie::Branch(children)));
+            shift += BITS;
+        }
+        let node = Some(Arc::new(PersistentNode { data, refs : Vec::new() }));
+        let trie = Some(Trie::with(trie.as_ref(), shift, key, node));
+        let res = PersistentGraph { trie, shift, slots : key + 1, len : self.len + 1, roots : self.roots.clone() };
+        (res, key)
+    }
+
+    /// Returns a graph with `key` added to the root.
+    /// # Panics
+    /// Panics if there is no node `key`.
+    pub fn with_root(&self, key : usize) -> Self
+    {
+        assert!(self.contains(key), "invalid node key");
+        let mut roots = (*self.roots).clone();
+        roots.push(key);
+        PersistentGraph { roots : Arc::new(roots), ..self.clone() }
+    }
+
+    /// Returns a graph with `key` removed from the root.
+    pub fn without_root(&self, key : usize) -> Self
+    {
+        let roots = self.roots.iter().copied().filter(|x| *x != key).collect();
+        PersistentGraph { roots : Arc::new(roots), ..self.clone() }
+    }
+
+    // Replaces node `key` with the result of `f`.
+    fn replace(&self, key : usize, f : impl FnOnce(&PersistentNode<N, E>) -> Option<PersistentNode<N, E>>) -> Self
+    {
+        let node = f(self.node(key).expect("invalid node key"));
+        let len = if node.is_some() { self.len } else { self.len - 1 };
+        let trie = Some(Trie::with(self.trie.as_ref(), self.shift, key, node.map(Arc::new)));
+        PersistentGraph { trie, len, ..self.clone() }
+    }
+
+    /// Returns a graph without nodes unreachable from the root. Visits every node.
+    pub fn cleanup(&self) -> Self
+    {
+        let mut reachable = vec![false; self.slots];
+        let mut stack = self.roots.to_vec();
+        while let Some(key) = stack.pop() {
+            if !reachable[key] {
+                reachable[key] = true;
+                stack.extend(self.neighbors(key));
+            }
+        }
+        let mut res = self.clone();
+        for key in (0..self.slots).filter(|x| !reachable[*x] && self.contains(*x)) {
+            res = res.replace(key, |_| None);
+        }
+        res
+    }
+}
+
+impl <N : Clone, E : Clone> PersistentGraph<N, E> {
+    /// Returns a graph where node `key` holds `data`.
+    /// # Panics
+    /// Panics if there is no node `key`.
+    pub fn with_data(&self, key : usize, data : N) -> Self
+    {
+        self.replace(key, |x| Some(PersistentNode { data, refs : x.refs.clone() }))
+    }
+
+    /// Returns a graph with an edge from `src` to `dst` added.
+    /// # Panics
+    /// Panics if there is no node `src` or `dst`.
+    pub fn connect(&self, src : usize, dst : usize, edge : E) -> Self
+    {
+        assert!(self.contains(dst), "invalid node key");
+        self.replace(src, |x| {
+            let mut refs = x.refs.clone();
+            refs.push((dst, edge));
+            Some(PersistentNode { data : x.data.clone(), refs })
+        })
+    }
+
+    /// Returns a graph with every edge from `src` to `dst` removed.
+    /// # Panics
+    /// Panics if there is no node `src`.
+    pub fn disconnect(&self, src : usize, dst : usize) -> Self
+    {
+        self.replace(src, |x| {
+            let refs = x.refs.iter().filter(|x| x.0 != dst).cloned().collect();
+            Some(PersistentNode { data : x.data.clone(), refs })
+        })
+    }
+
+    /// Returns a graph without node `key`, edges pointing to it and its root entry. Visits every node.
+    /// # Panics
+    /// Panics if there is no node `key`.
+    pub fn remove(&self, key : usize) -> Self
+    {
+        let mut res = self.replace(key, |_| None).without_root(key);
+        for src in 0..self.slots {
+            if res.node(src).is_some_and(|x| x.refs.iter().any(|x| x.0 == key)) {
+                res = res.disconnect(src, key);
+            }
+        }
+        res
+    }
+}
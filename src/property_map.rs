@@ -0,0 +1,171 @@
+use super::*;
+use std::collections::HashMap;
+
+/// Per-node values kept in a vector indexed by the positions of nodes, see `ptr_to_index`. Created by `property_map`.
+/// Values follow their nodes when a cleanup compacts the storage or a node is killed: a value is found by the
+/// position of its node first and looked up by the node otherwise. Indexing a node spawned after the map was
+/// created yields the default value, assigning to it adds the value. `remap` puts values back in the order of
+/// positions, so that lookups take the fast path again, and discards values of dropped nodes.
+pub struct PropertyMap<NodeType, T> {
+    values : Vec<T>,
+    // Node and its serial number for every value. Serial numbers tell apart nodes allocated at the same address.
+    owners : Vec<(*const NodeType, u64)>,
+    // Positions of values by their owners, used once nodes moved since the last remap.
+    index : HashMap<(*const NodeType, u64), usize>,
+    default : T,
+}
+
+// Owners are only compared, never dereferenced.
+unsafe impl <NodeType, T : Send> Send for PropertyMap<NodeType, T> {}
+unsafe impl <NodeType, T : Sync> Sync for PropertyMap<NodeType, T> {}
+
+impl <NodeType, T> PropertyMap<NodeType, T>
+where NodeType : GraphNode
+{
+    // Returns the owner of the value of `dst` and the position of the node.
+    fn owner(dst : GraphPtr<'_, NodeType>) -> ((*const NodeType, u64), usize)
+    {
+        //(W)
+        let meta = unsafe { (*dst.as_ptr()).meta() };
+        ((dst.as_ptr(), meta.serial), meta.store_index)
+    }
+
+    // Returns the position of the value of `dst`, if the map has one.
+    fn slot(&self, dst : GraphPtr<'_, NodeType>) -> Option<usize>
+    {
+        let (owner, i) = Self::owner(dst);
+        if self.owners.get(i) == Some(&owner) { Some(i) } else { self.index.get(&owner).copied() }
+    }
+
+    // Appends the value of `dst` unless the map has one already and returns its position.
+    fn slot_or_insert(&mut self, dst : GraphPtr<'_, NodeType>) -> usize
+    where T : Clone
+    {
+        if let Some(i) = self.slot(dst) {
+            return i;
+        }
+        let (owner, _) = Self::owner(dst);
+        self.index.insert(owner, self.values.len());
+        self.owners.push(owner);
+        self.values.push(self.default.clone());
+        self.values.len() - 1
+    }
+
+    /// Returns the number of stored values. Values of dropped nodes are counted until `remap`.
+    pub fn len(&self) -> usize
+    {
+        self.values.len()
+    }
+
+    /// Returns true if no values are stored.
+    pub fn is_empty(&self) -> bool
+    {
+        self.values.is_empty()
+    }
+
+    /// Returns the value of `dst` or None if the map does not cover `dst` yet.
+    pub fn get(&self, dst : GraphPtr<'_, NodeType>) -> Option<&T>
+    {
+        self.slot(dst).map(|i| &self.values[i])
+    }
+
+    /// Returns the value of `dst` or None if the map does not cover `dst` yet.
+    pub fn get_mut(&mut self, dst : GraphPtr<'_, NodeType>) -> Option<&mut T>
+    {
+        self.slot(dst).map(move |i| &mut self.values[i])
+    }
+
+    /// Sets every value to the default one.
+    pub fn reset(&mut self)
+    where T : Clone
+    {
+        for i in &mut self.values {
+            i.clone_from(&self.default);
+        }
+    }
+
+    /// Returns values in the order of positions of their nodes at the last `remap`, followed by the values added
+    /// since.
+    pub fn values(&self) -> &[T]
+    {
+        &self.values
+    }
+
+    // Follows nodes of `internal` to their current positions. Values of dropped nodes are discarded, new nodes
+    // receive the default value.
+    fn remap_raw<A : Allocator>(&mut self, internal : &GraphRaw<NodeType, A>)
+    where T : Clone
+    {
+        let current = internal.data.iter().map(|x| {
+            let ptr = x.as_ptr() as *const NodeType;
+            //(W)
+            (ptr, unsafe { (*ptr).meta().serial })
+        });
+        if self.owners.len() <= internal.data.len() && current.clone().zip(&self.owners).all(|(a, b)| a == *b) {
+            for i in current.skip(self.owners.len()) {
+                self.owners.push(i);
+                self.values.push(self.default.clone());
+            }
+        } else {
+            let mut old : HashMap<_, _> = self.owners.drain(..).zip(self.values.drain(..)).collect();
+            for i in current {
+                self.values.push(old.remove(&i).unwrap_or_else(|| self.default.clone()));
+                self.owners.push(i);
+            }
+        }
+        self.index = self.owners.iter().enumerate().map(|(i, x)| (*x, i)).collect();
+    }
+}
+
+impl <'id, NodeType, T> Index<GraphPtr<'id, NodeType>> for PropertyMap<NodeType, T>
+where NodeType : GraphNode
+{
+    type Output = T;
+    /// Returns the default value if the map does not cover `dst`.
+    fn index(&self, dst : GraphPtr<'id, NodeType>) -> &T
+    {
+        self.get(dst).unwrap_or(&self.default)
+    }
+}
+
+impl <'id, NodeType, T> IndexMut<GraphPtr<'id, NodeType>> for PropertyMap<NodeType, T>
+where NodeType : GraphNode,
+      T : Clone
+{
+    /// Adds the default value first if the map does not cover `dst`.
+    fn index_mut(&mut self, dst : GraphPtr<'id, NodeType>) -> &mut T
+    {
+        let i = self.slot_or_insert(dst);
+        &mut self.values[i]
+    }
+}
+
+macro_rules! impl_anchor_property_map {
+    ($anchor_type:ident) => {
+        impl <'this, 'id, NodeType : 'this, Root : 'this, A : Allocator>
+        $anchor_type<'this, 'id, GenericGraph<Root, NodeType, A>>
+        where NodeType : GraphNode,
+              Root : RootCollection<'static, NodeType>
+        {
+            /// Creates a property map holding `default` for every allocated node.
+            pub fn property_map<T : Clone>(&self, default : T) -> PropertyMap<NodeType, T>
+            {
+                let mut map = PropertyMap { values : Vec::new(), owners : Vec::new(), index : HashMap::new(), default };
+                map.remap_raw(self.internal());
+                map
+            }
+
+            /// Moves values of `map` to the current positions of their nodes and adds default values for the nodes
+            /// spawned since the last call. Values of dropped nodes are discarded. Maps created from another graph
+            /// end up holding default values only. Lookups are correct without a remap, it only makes them take
+            /// the fast path and frees memory of dropped nodes.
+            pub fn remap<T : Clone>(&self, map : &mut PropertyMap<NodeType, T>)
+            {
+                map.remap_raw(self.internal());
+            }
+        }
+    }
+}
+
+impl_anchor_property_map!{Anchor}
+impl_anchor_property_map!{AnchorMut}
@@ -0,0 +1,135 @@
+use core::hash::{BuildHasherDefault, Hasher};
+
+/// A hasher for node pointers. Pointers are unique by construction, so the address only has to be spread over the
+/// whole hash rather than protected from collision attacks.
+#[derive(Default, Clone, Copy)]
+pub struct PtrHasher {
+    hash : u64,
+}
+
+// 2^64 divided by the golden ratio.
+const MULTIPLIER : u64 = 0x9E37_79B9_7F4A_7C15;
+
+impl Hasher for PtrHasher {
+    fn finish(&self) -> u64
+    {
+        //multiplication only carries upwards, so aligned pointers leave the low bits zero. The high bits are mixed
+        //best, rotate them down to where hash tables pick buckets.
+        self.hash.rotate_left(26)
+    }
+
+    fn write(&mut self, bytes : &[u8])
+    {
+        for &i in bytes {
+            self.write_u64(i as u64);
+        }
+    }
+
+    fn write_u64(&mut self, i : u64)
+    {
+        self.hash = (self.hash.rotate_left(5) ^ i).wrapping_mul(MULTIPLIER);
+    }
+
+    fn write_usize(&mut self, i : usize)
+    {
+        self.write_u64(i as u64);
+    }
+}
+
+/// Builds `PtrHasher`. Pass it as the hasher type parameter of `NamedGraph`, `NamedNode` or `NodeNamedMap` to hash
+/// pointers with `PtrHasher` instead of SipHash.
+pub type BuildPtrHasher = BuildHasherDefault<PtrHasher>;
+
+/// The hasher used by pointer sets and maps unless specified otherwise.
+pub type DefaultPtrState = std::collections::hash_map::RandomState;
+
+#[cfg(feature = "hashbrown")]
+pub use self::ptr_collections::*;
+
+#[cfg(feature = "hashbrown")]
+mod ptr_collections {
+    use super::*;
+    use crate::*;
+    use core::hash::BuildHasher;
+
+    /// A root which keeps nodes in a `hashbrown` set hashed with `PtrHasher`.
+    pub type RootPtrSet<'id, T> = hashbrown::HashSet<GraphPtr<'id, T>, BuildPtrHasher>;
+    /// An edge collection which keeps edges in a `hashbrown` map hashed with `PtrHasher`.
+    pub type NodePtrMap<'id, NodeType, E> = hashbrown::HashMap<GraphPtr<'id, NodeType>, E, BuildPtrHasher>;
+    pub type PtrSetGraph<T, A = Global> = GenericGraph<RootPtrSet<'static, T>, T, A>;
+
+    unsafe impl <'id, NodeType : GraphNode> RootCollection<'id, NodeType> for RootPtrSet<'id, NodeType> {
+        fn traverse(this : &Self, cleanup : &mut CleanupState<NodeType>) {
+            for i in this {
+                cleanup.touch(i.as_mut());
+            }
+        }
+
+        fn unlink(this : &mut Self, dst : *const NodeType) {
+            this.retain(|x| x.as_ptr() != dst);
+        }
+    }
+
+    unsafe impl <NodeType> RootCollectionView for RootPtrSet<'static, NodeType> {
+        type View<'id> = RootPtrSet<'id, NodeType>;
+    }
+
+    unsafe impl <NodeType> RootIterable<NodeType> for RootPtrSet<'static, NodeType> {
+        type Item<'a, V> = V where Self : 'a;
+        fn iter_with<'a, V>(this : &'a Self, mut f : impl FnMut(*mut NodeType) -> V + 'a)
+            -> impl Iterator<Item = V> + 'a
+        {
+            this.iter().map(move |x| f(x.as_mut()))
+        }
+    }
+
+    unsafe impl <'id, NodeType : GraphNode, E> NodeCollection<'id, NodeType> for NodePtrMap<'id, NodeType, E> {
+        fn traverse(this : &Self, cleanup : &mut CleanupState<NodeType>) {
+            for i in this.keys() {
+                cleanup.touch(i.as_mut());
+            }
+        }
+
+        fn len(this : &Self) -> usize {
+            this.len()
+        }
+
+        fn unlink(this : &mut Self, dst : *const NodeType) {
+            this.retain(|x, _| x.as_ptr() != dst);
+        }
+
+        fn heap_bytes(this : &Self) -> usize {
+            crate::nodes::hash_table_bytes::<(GraphPtr<'id, NodeType>, E)>(this.capacity())
+        }
+    }
+
+    /// Lookups of pointer sets and maps by raw node pointers, such as the ones `as_ptr` returns. Pointers are
+    /// looked up with the raw entry API of `hashbrown`, so no `GraphPtr` has to be made up for the search.
+    pub trait PtrLookup<'id, T, V> {
+        /// Returns the stored pointer equal to `ptr` along with its value.
+        fn get_by_ptr(&self, ptr : *const T) -> Option<(&GraphPtr<'id, T>, &V)>;
+        /// Returns true if a pointer equal to `ptr` is stored.
+        fn contains_ptr(&self, ptr : *const T) -> bool {
+            self.get_by_ptr(ptr).is_some()
+        }
+    }
+
+    impl <'id, T, V, S : BuildHasher> PtrLookup<'id, T, V> for hashbrown::HashMap<GraphPtr<'id, T>, V, S> {
+        fn get_by_ptr(&self, ptr : *const T) -> Option<(&GraphPtr<'id, T>, &V)>
+        {
+            //GraphPtr hashes the same way as the pointer it wraps.
+            let hash = self.hasher().hash_one(ptr);
+            self.raw_entry().from_hash(hash, |x| x.as_ptr() == ptr)
+        }
+    }
+
+    impl <'id, T, S : BuildHasher> PtrLookup<'id, T, ()> for hashbrown::HashSet<GraphPtr<'id, T>, S> {
+        fn get_by_ptr(&self, ptr : *const T) -> Option<(&GraphPtr<'id, T>, &())>
+        {
+            let hash = self.hasher().hash_one(ptr);
+            let bucket = self.raw_table().find(hash, |x| x.0.as_ptr() == ptr)?;
+            //the bucket is full and the set is borrowed for the lifetime of the result
+            Some((unsafe { &bucket.as_ref().0 }, &()))
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use super::*;
+use core::fmt;
+use std::collections::HashMap;
+
+/// Displays the nodes reachable from a node of a `TreeNode` graph as an indented tree, see `display_tree`.
+pub struct TreeDisplay<'a, 'id, K, N, E, A : Allocator> {
+    internal : &'a GraphRaw<TreeNode<K, N, E>, A>,
+    root : GraphPtr<'id, TreeNode<K, N, E>>,
+}
+
+impl <'a, 'id, K, N, E, A : Allocator> TreeDisplay<'a, 'id, K, N, E, A>
+where K : Ord + fmt::Display,
+      N : fmt::Display
+{
+    // Writes a line for every child of `node` and descends into the children which are not in `ancestors`.
+    fn fmt_children(&self, f : &mut fmt::Formatter<'_>, node : GraphPtr<'id, TreeNode<K, N, E>>, prefix : &mut String,
+                    ancestors : &mut Vec<GraphPtr<'id, TreeNode<K, N, E>>>) -> fmt::Result
+    {
+        let refs = &self.internal.get_view(node).refs;
+        for (i, (key, (child, _))) in refs.iter().enumerate() {
+            let last = i + 1 == refs.len();
+            write!(f, "\n{}{}{}: {}", prefix, if last { "`-- " } else { "|-- " }, key, self.internal.get_view(*child).data)?;
+            if ancestors.contains(child) {
+                write!(f, " (cycle)")?;
+                continue;
+            }
+            let len = prefix.len();
+            prefix.push_str(if last { "    " } else { "|   " });
+            ancestors.push(*child);
+            self.fmt_children(f, *child, prefix, ancestors)?;
+            ancestors.pop();
+            prefix.truncate(len);
+        }
+        Ok(())
+    }
+}
+
+impl <'a, 'id, K, N, E, A : Allocator> fmt::Display for TreeDisplay<'a, 'id, K, N, E, A>
+where K : Ord + fmt::Display,
+      N : fmt::Display
+{
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.internal.get_view(self.root).data)?;
+        self.fmt_children(f, self.root, &mut String::new(), &mut vec![self.root])
+    }
+}
+
+macro_rules! impl_anchor_render_tree {
+    ($anchor_type:ident) => {
+        impl <'this, 'id, K : 'this, N : 'this, E : 'this, Root : 'this, A : Allocator>
+        $anchor_type<'this, 'id, GenericGraph<Root, TreeNode<K, N, E>, A>>
+        where Root : RootCollection<'static, TreeNode<K, N, E>>, K : Ord
+        {
+            /// Returns an adapter which displays `root` and the nodes reachable from it as an ASCII tree, one node
+            /// per line preceded by the key of its edge. A node which is its own ancestor is marked as a cycle and
+            /// not expanded again.
+            pub fn display_tree(&self, root : GraphPtr<'id, TreeNode<K, N, E>>) -> TreeDisplay<'_, 'id, K, N, E, A>
+            {
+                TreeDisplay { internal : self.internal(), root }
+            }
+
+            /// Renders the tree growing from `root` into a string, see `display_tree`.
+            pub fn render_tree(&self, root : GraphPtr<'id, TreeNode<K, N, E>>) -> String
+            where K : fmt::Display,
+                  N : fmt::Display
+            {
+                self.display_tree(root).to_string()
+            }
+        }
+    }
+}
+
+impl_anchor_render_tree!{Anchor}
+impl_anchor_render_tree!{AnchorMut}
+
+/// Displays a node as its position among allocated nodes, e.g. `#3`, see `display`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct PtrDisplay {
+    index : usize,
+}
+
+impl PtrDisplay {
+    /// Returns the position of the node, see `ptr_to_index`.
+    pub fn index(self) -> usize
+    {
+        self.index
+    }
+}
+
+impl fmt::Display for PtrDisplay {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "#{}", self.index)
+    }
+}
+
+impl fmt::Debug for PtrDisplay {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "#{}", self.index)
+    }
+}
+
+macro_rules! impl_anchor_display {
+    ($anchor_type:ident) => {
+        impl <'this, 'id, NodeType : 'this, Root : 'this, A : Allocator>
+        $anchor_type<'this, 'id, GenericGraph<Root, NodeType, A>>
+        where NodeType : GraphNode,
+              Root : RootCollection<'static, NodeType>
+        {
+            /// Returns a printable id of `dst`: its position among allocated nodes, see `ptr_to_index`. Unlike
+            /// addresses printed by `Debug` of `GraphPtr`, positions are small numbers which are the same between
+            /// runs as long as the graph is built the same way.
+            pub fn display(&self, dst : GraphPtr<'id, NodeType>) -> PtrDisplay
+            {
+                PtrDisplay { index : self.ptr_to_index(dst) }
+            }
+        }
+    }
+}
+
+impl_anchor_display!{Anchor}
+impl_anchor_display!{AnchorMut}
+
+// Writes a line for every node reachable from `root`: its position in breadth-first order, its data and the edges
+// written by `write_edges`, which receives positions of all nodes.
+fn dump<NodeType, W, A : Allocator>(internal : &GraphRaw<NodeType, A>, root : &impl RootCollection<'static, NodeType>, out : &mut W,
+                                    mut write_edges : impl FnMut(&mut W, *mut NodeType, &HashMap<*const NodeType, usize>) -> fmt::Result)
+    -> fmt::Result
+where NodeType : GraphNode,
+      NodeType::Node : fmt::Debug,
+      W : fmt::Write
+{
+    let nodes = internal.reachable(root);
+    let index : HashMap<_, _> = nodes.iter().enumerate().map(|(i, &x)| (x as *const NodeType, i)).collect();
+    for (i, &x) in nodes.iter().enumerate() {
+        //(W)
+        write!(out, "{}: {:?} -> ", i, unsafe { (*x).get() })?;
+        write_edges(out, x, &index)?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+macro_rules! impl_anchor_debug_dump {
+    ($anchor_type:ident) => {
+        impl <'this, 'id, N : 'this, NodeType : 'this, Root : 'this, A : Allocator>
+        $anchor_type<'this, 'id, GenericGraph<Root, NodeType, A>>
+        where NodeType : GraphNode<Node = N>,
+              Root : RootCollection<'static, NodeType>,
+              N : fmt::Debug
+        {
+            /// Writes a line for every node accessible from the root: its position in breadth-first order, its data
+            /// and positions of the targets of its edges, e.g. `0: "a" -> [1, 2]`. Positions don't change unless
+            /// the graph is modified.
+            pub fn debug_dump(&self, out : &mut impl fmt::Write) -> fmt::Result
+            {
+                let mut targets = Vec::new();
+                dump(self.internal(), &self.parent.root, out, |out, x, index| {
+                    targets.clear();
+                    GraphRaw::<NodeType>::for_each_target(x, |y| targets.push(index[&(y as *const NodeType)]));
+                    write!(out, "{:?}", targets)
+                })
+            }
+        }
+    }
+}
+
+impl_anchor_debug_dump!{Anchor}
+impl_anchor_debug_dump!{AnchorMut}
+
+macro_rules! impl_anchor_debug {
+    ($anchor_type:ident, $NodeType:ident $(, $S:ident)?) => {
+        /// Lists every node accessible from the root like `debug_dump`, along with the data of its edges, e.g.
+        /// `0: "a" -> {1: 5, 2: 3}`.
+        impl <'this, 'id, N : 'this, E : 'this $(, $S : BuildHasher + Default + 'this)?, Root : 'this, A : Allocator> fmt::Debug
+        for $anchor_type<'this, 'id, GenericGraph<Root, $NodeType<N, E $(, $S)?>, A>>
+        where Root : RootCollection<'static, $NodeType<N, E $(, $S)?>>,
+              N : fmt::Debug,
+              E : fmt::Debug
+        {
+            fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+                let internal = self.internal();
+                dump(internal, &self.parent.root, f, |f, x, index| {
+                    //(W)
+                    let src = unsafe { GraphPtr::from_mut(x, self._guard) };
+                    let mut map = f.debug_map();
+                    for i in internal.iter(src) {
+                        map.entry(&index[&i.ptr.as_ptr()], i.values.edge());
+                    }
+                    map.finish()
+                })
+            }
+        }
+    }
+}
+
+impl_anchor_debug!{Anchor, NamedNode, S}
+impl_anchor_debug!{Anchor, VecNode}
+impl_anchor_debug!{Anchor, OptionNode}
+#[cfg(feature = "indexmap")]
+impl_anchor_debug!{Anchor, OrderedNamedNode}
+impl_anchor_debug!{AnchorMut, NamedNode, S}
+impl_anchor_debug!{AnchorMut, VecNode}
+impl_anchor_debug!{AnchorMut, OptionNode}
+#[cfg(feature = "indexmap")]
+impl_anchor_debug!{AnchorMut, OrderedNamedNode}
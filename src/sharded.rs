@@ -0,0 +1,148 @@
+use super::*;
+use std::collections::hash_map::RandomState;
+use std::sync::RwLock;
+
+/// A set of independent graphs shared between threads, every shard is guarded by its own lock. This is not a single
+/// graph: nodes are assigned to shards by a user-provided key and edges can only connect nodes of the same shard,
+/// as GraphPtrs of different graphs are incompatible. Fits workloads which partition naturally, e.g. one graph per
+/// tenant or per document. Use `SyncGraph` to share a single graph.
+pub struct ShardedGraphs<Root, NodeType>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    shards : Vec<RwLock<GenericGraph<Root, NodeType>>>,
+    hasher : RandomState,
+}
+
+impl <Root, NodeType> ShardedGraphs<Root, NodeType>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    /// Creates a graph with `shards` empty shards.
+    /// # Panics
+    /// Panics if `shards` is zero.
+    pub fn new(shards : usize) -> Self
+    {
+        assert!(shards > 0, "ShardedGraphs requires at least one shard");
+        ShardedGraphs { shards : (0..shards).map(|_| RwLock::new(GenericGraph::new())).collect(), hasher : RandomState::new() }
+    }
+
+    /// Returns the number of shards.
+    pub fn shard_count(&self) -> usize
+    {
+        self.shards.len()
+    }
+
+    /// Returns the index of the shard responsible for `key`.
+    pub fn shard_of(&self, key : &impl Hash) -> usize
+    {
+        (self.hasher.hash_one(key) % self.shards.len() as u64) as usize
+    }
+
+    /// Passes an anchor of shard `shard` to `f`. Several threads may read the same shard at once.
+    pub fn read_shard<R>(&self, shard : usize,
+                         f : impl for<'a, 'id> FnOnce(Anchor<'a, 'id, GenericGraph<Root, NodeType>>) -> R) -> R
+    {
+        let graph = self.shards[shard].read().unwrap_or_else(|x| x.into_inner());
+        make_guard!(g);
+        //the guard is unique
+        f(unsafe { graph.anchor(Id::from(g)) })
+    }
+
+    /// Passes a mutable anchor of shard `shard` to `f`. The shard is cleaned up according to `strategy` before
+    /// the lock is released.
+    pub fn write_shard<R>(&self, shard : usize, strategy : CleanupStrategy,
+                          f : impl for<'a, 'id> FnOnce(AnchorMut<'a, 'id, GenericGraph<Root, NodeType>>) -> R) -> R
+    {
+        let mut graph = self.shards[shard].write().unwrap_or_else(|x| x.into_inner());
+        make_guard!(g);
+        //the guard is unique
+        f(unsafe { graph.anchor_mut(Id::from(g), strategy) })
+    }
+
+    /// Same as `read_shard` for the shard responsible for `key`.
+    pub fn read<R>(&self, key : &impl Hash,
+                   f : impl for<'a, 'id> FnOnce(Anchor<'a, 'id, GenericGraph<Root, NodeType>>) -> R) -> R
+    {
+        self.read_shard(self.shard_of(key), f)
+    }
+
+    /// Same as `write_shard` for the shard responsible for `key`.
+    pub fn write<R>(&self, key : &impl Hash, strategy : CleanupStrategy,
+                    f : impl for<'a, 'id> FnOnce(AnchorMut<'a, 'id, GenericGraph<Root, NodeType>>) -> R) -> R
+    {
+        self.write_shard(self.shard_of(key), strategy, f)
+    }
+
+    /// Returns the shards as separate graphs.
+    pub fn into_shards(self) -> Vec<GenericGraph<Root, NodeType>>
+    {
+        self.shards.into_iter().map(|x| x.into_inner().unwrap_or_else(|x| x.into_inner())).collect()
+    }
+}
+
+/// A graph shared between threads behind a single lock. Any number of threads may read the graph at once, writers
+/// get exclusive access. See `ShardedGraphs` for workloads which partition into independent graphs.
+pub struct SyncGraph<Root, NodeType>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    graph : RwLock<GenericGraph<Root, NodeType>>,
+}
+
+impl <Root, NodeType> SyncGraph<Root, NodeType>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    /// Creates an empty graph.
+    pub fn new() -> Self
+    {
+        SyncGraph { graph : RwLock::new(GenericGraph::new()) }
+    }
+
+    /// Passes an anchor of the graph to `f`. Several threads may read the graph at once.
+    pub fn read<R>(&self, f : impl for<'a, 'id> FnOnce(Anchor<'a, 'id, GenericGraph<Root, NodeType>>) -> R) -> R
+    {
+        let graph = self.graph.read().unwrap_or_else(|x| x.into_inner());
+        make_guard!(g);
+        //the guard is unique
+        f(unsafe { graph.anchor(Id::from(g)) })
+    }
+
+    /// Passes a mutable anchor of the graph to `f`. The graph is cleaned up according to `strategy` before the lock
+    /// is released.
+    pub fn write<R>(&self, strategy : CleanupStrategy,
+                    f : impl for<'a, 'id> FnOnce(AnchorMut<'a, 'id, GenericGraph<Root, NodeType>>) -> R) -> R
+    {
+        let mut graph = self.graph.write().unwrap_or_else(|x| x.into_inner());
+        make_guard!(g);
+        //the guard is unique
+        f(unsafe { graph.anchor_mut(Id::from(g), strategy) })
+    }
+
+    /// Returns the underlying graph.
+    pub fn into_inner(self) -> GenericGraph<Root, NodeType>
+    {
+        self.graph.into_inner().unwrap_or_else(|x| x.into_inner())
+    }
+}
+
+impl <Root, NodeType> Default for SyncGraph<Root, NodeType>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl <Root, NodeType> From<GenericGraph<Root, NodeType>> for SyncGraph<Root, NodeType>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    fn from(graph : GenericGraph<Root, NodeType>) -> Self
+    {
+        SyncGraph { graph : RwLock::new(graph) }
+    }
+}
@@ -0,0 +1,146 @@
+use core::ops::{Index, IndexMut};
+
+enum Slot<T> {
+    Occupied(T),
+    // Index of the next vacant slot.
+    Vacant(usize),
+}
+
+/// A collection which keeps the key of every item stable until it is removed. Keys of removed
+/// items are reused.
+pub struct Slab<T> {
+    slots : Vec<Slot<T>>,
+    // Head of the list of vacant slots, equals to slots.len() if there are none.
+    next : usize,
+    len : usize,
+}
+
+impl <T> Slab<T> {
+    pub fn new() -> Self
+    {
+        Slab { slots : Vec::new(), next : 0, len : 0 }
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.len == 0
+    }
+
+    /// Inserts an item and returns its key.
+    pub fn insert(&mut self, item : T) -> usize
+    {
+        let key = self.next;
+        if key == self.slots.len() {
+            self.slots.push(Slot::Occupied(item));
+            self.next = self.slots.len();
+        } else {
+            match core::mem::replace(&mut self.slots[key], Slot::Occupied(item)) {
+                Slot::Vacant(next) => self.next = next,
+                Slot::Occupied(_) => unreachable!(),
+            }
+        }
+        self.len += 1;
+        key
+    }
+
+    /// Removes an item by its key. Returns None if the key is vacant.
+    pub fn remove(&mut self, key : usize) -> Option<T>
+    {
+        if !self.contains(key) {
+            return None;
+        }
+        self.len -= 1;
+        let slot = core::mem::replace(&mut self.slots[key], Slot::Vacant(self.next));
+        self.next = key;
+        match slot {
+            Slot::Occupied(item) => Some(item),
+            Slot::Vacant(_) => unreachable!(),
+        }
+    }
+
+    pub fn contains(&self, key : usize) -> bool
+    {
+        matches!(self.slots.get(key), Some(Slot::Occupied(_)))
+    }
+
+    pub fn get(&self, key : usize) -> Option<&T>
+    {
+        match self.slots.get(key) {
+            Some(Slot::Occupied(item)) => Some(item),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, key : usize) -> Option<&mut T>
+    {
+        match self.slots.get_mut(key) {
+            Some(Slot::Occupied(item)) => Some(item),
+            _ => None,
+        }
+    }
+
+    pub fn retain(&mut self, mut f : impl FnMut(usize, &T) -> bool)
+    {
+        for key in 0..self.slots.len() {
+            if matches!(&self.slots[key], Slot::Occupied(item) if !f(key, item)) {
+                self.remove(key);
+            }
+        }
+    }
+
+    pub fn clear(&mut self)
+    {
+        *self = Self::new();
+    }
+
+    /// Returns an iterator over keys and items.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)>
+    {
+        self.slots.iter().enumerate().filter_map(|(key, x)| match x {
+            Slot::Occupied(item) => Some((key, item)),
+            Slot::Vacant(_) => None,
+        })
+    }
+
+    /// Returns a mutable iterator over keys and items.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut T)>
+    {
+        self.slots.iter_mut().enumerate().filter_map(|(key, x)| match x {
+            Slot::Occupied(item) => Some((key, item)),
+            Slot::Vacant(_) => None,
+        })
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T>
+    {
+        self.iter().map(|x| x.1)
+    }
+}
+
+impl <T> Default for Slab<T> {
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl <T> Index<usize> for Slab<T> {
+    type Output = T;
+
+    fn index(&self, key : usize) -> &T
+    {
+        self.get(key).expect("invalid slab key")
+    }
+}
+
+impl <T> IndexMut<usize> for Slab<T> {
+    fn index_mut(&mut self, key : usize) -> &mut T
+    {
+        self.get_mut(key).expect("invalid slab key")
+    }
+}
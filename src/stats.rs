@@ -0,0 +1,43 @@
+/// A summary of the graph storage. Every allocated node is accounted for, including nodes which are no longer
+/// accessible from the root but were not cleaned up yet.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GraphStats {
+    /// Number of allocated nodes.
+    pub node_count : usize,
+    /// Number of edges stored in allocated nodes.
+    pub edge_count : usize,
+    /// Ratio of edges between distinct nodes to the maximum possible number of such edges. Self-loops are not counted.
+    pub density : f64,
+    /// Largest number of edges attached to a single node.
+    pub max_degree : usize,
+    /// Smallest number of edges attached to a single node.
+    pub min_degree : usize,
+    /// Average number of edges attached to a node.
+    pub avg_degree : f64,
+}
+
+/// A summary of the work performed by a cleanup.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CleanupReport {
+    /// Number of nodes dropped by the cleanup.
+    pub nodes_freed : usize,
+    /// Number of nodes which survived the cleanup.
+    pub nodes_retained : usize,
+    /// Memory released by dropped nodes. Heap allocations owned by node data and edge collections are not
+    /// accounted for.
+    pub bytes_freed_estimate : usize,
+}
+
+/// An estimate of memory used by the graph. Heap allocations owned by node data and edge payloads are not
+/// accounted for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    /// Memory reserved by the index of allocated nodes.
+    pub index_bytes : usize,
+    /// Memory reserved for nodes, including the memory of dropped nodes kept for reuse.
+    pub node_bytes : usize,
+    /// Memory owned by edge collections of allocated nodes.
+    pub edge_bytes : usize,
+    /// Sum of all of the above.
+    pub total_bytes : usize,
+}
@@ -0,0 +1,271 @@
+use super::*;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+const PUT : u8 = 1;
+const REMOVE : u8 = 2;
+
+fn read_u32(reader : &mut impl Read) -> io::Result<u32>
+{
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(reader : &mut impl Read) -> io::Result<u64>
+{
+    let mut buf = [0; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+// Reads the body of a PUT record: the payload and edge targets.
+fn read_node(reader : &mut impl Read) -> io::Result<(Vec<u8>, Vec<u64>)>
+{
+    let mut payload = vec![0; read_u32(reader)? as usize];
+    reader.read_exact(&mut payload)?;
+    let count = read_u32(reader)? as usize;
+    let mut targets = Vec::with_capacity(count);
+    for _ in 0..count {
+        targets.push(read_u64(reader)?);
+    }
+    Ok((payload, targets))
+}
+
+// Encodes a PUT record for `key`.
+fn put_record(key : u64, payload : &[u8], targets : &[u64]) -> Vec<u8>
+{
+    let mut record = Vec::with_capacity(17 + payload.len() + 8 * targets.len());
+    record.push(PUT);
+    record.extend_from_slice(&key.to_le_bytes());
+    record.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    record.extend_from_slice(payload);
+    record.extend_from_slice(&(targets.len() as u32).to_le_bytes());
+    for x in targets {
+        record.extend_from_slice(&x.to_le_bytes());
+    }
+    record
+}
+
+/// Nodes kept in a file, each one a byte payload along with keys of the targets of its edges. The file is a log of
+/// records, an in-memory index maps keys to the offsets of their latest records, so only the index has to fit in
+/// memory. Records are read on demand. Replaced and removed records stay in the file until `compact` is called.
+pub struct FileStorage {
+    path : PathBuf,
+    file : File,
+    // Offsets of the bodies of the latest PUT records.
+    index : HashMap<u64, u64>,
+    end : u64,
+}
+
+impl FileStorage {
+    /// Opens the storage in the file at `path`, creating the file if it does not exist, and rebuilds the index.
+    /// A truncated record at the end of the file, left by an interrupted write, is discarded.
+    pub fn open(path : impl Into<PathBuf>) -> io::Result<Self>
+    {
+        let path = path.into();
+        let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&path)?;
+        let mut index = HashMap::new();
+        let mut end = 0;
+        {
+            let mut reader = BufReader::new(&mut file);
+            loop {
+                let mut tag = [0; 1];
+                if reader.read(&mut tag)? == 0 {
+                    break;
+                }
+                let record = (|| {
+                    let key = read_u64(&mut reader)?;
+                    match tag[0] {
+                        PUT => {
+                            let (payload, targets) = read_node(&mut reader)?;
+                            index.insert(key, end + 9);
+                            Ok(17 + payload.len() as u64 + 8 * targets.len() as u64)
+                        },
+                        REMOVE => {
+                            index.remove(&key);
+                            Ok(9)
+                        },
+                        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "unknown record")),
+                    }
+                })();
+                match record {
+                    Ok(len) => end += len,
+                    Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        file.set_len(end)?;
+        Ok(FileStorage { path, file, index, end })
+    }
+
+    /// Returns the number of stored nodes.
+    pub fn len(&self) -> usize
+    {
+        self.index.len()
+    }
+
+    /// Returns true if no nodes are stored.
+    pub fn is_empty(&self) -> bool
+    {
+        self.index.is_empty()
+    }
+
+    /// Returns true if a node is stored under `key`.
+    pub fn contains(&self, key : u64) -> bool
+    {
+        self.index.contains_key(&key)
+    }
+
+    /// Returns keys of stored nodes in arbitrary order.
+    pub fn keys(&self) -> impl Iterator<Item = u64> + '_
+    {
+        self.index.keys().copied()
+    }
+
+    fn append(&mut self, record : &[u8]) -> io::Result<()>
+    {
+        self.file.seek(SeekFrom::Start(self.end))?;
+        self.file.write_all(record)?;
+        self.end += record.len() as u64;
+        Ok(())
+    }
+
+    /// Stores a node holding `payload` with edges to `targets` under `key`, replacing the previous one.
+    pub fn put(&mut self, key : u64, payload : &[u8], targets : &[u64]) -> io::Result<()>
+    {
+        let offset = self.end + 9;
+        self.append(&put_record(key, payload, targets))?;
+        self.index.insert(key, offset);
+        Ok(())
+    }
+
+    /// Reads the payload and edge targets of the node stored under `key`.
+    pub fn get(&mut self, key : u64) -> io::Result<Option<(Vec<u8>, Vec<u64>)>>
+    {
+        match self.index.get(&key) {
+            Some(&offset) => {
+                self.file.seek(SeekFrom::Start(offset))?;
+                read_node(&mut BufReader::new(&mut self.file)).map(Some)
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Removes the node stored under `key`. Returns false if there was no such node.
+    pub fn remove(&mut self, key : u64) -> io::Result<bool>
+    {
+        if !self.index.contains_key(&key) {
+            return Ok(false);
+        }
+        let mut record = vec![REMOVE];
+        record.extend_from_slice(&key.to_le_bytes());
+        self.append(&record)?;
+        self.index.remove(&key);
+        Ok(true)
+    }
+
+    /// Removes every node.
+    pub fn clear(&mut self) -> io::Result<()>
+    {
+        self.file.set_len(0)?;
+        self.index.clear();
+        self.end = 0;
+        Ok(())
+    }
+
+    /// Rewrites the file keeping only the latest records of stored nodes. The new file replaces the old one once
+    /// it is complete, so an interrupted compaction leaves the storage intact.
+    pub fn compact(&mut self) -> io::Result<()>
+    {
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".compact");
+        let tmp = PathBuf::from(tmp);
+        let mut index = HashMap::with_capacity(self.index.len());
+        let mut end = 0;
+        {
+            let mut writer = BufWriter::new(File::create(&tmp)?);
+            let keys : Vec<_> = self.keys().collect();
+            for key in keys {
+                let (payload, targets) = self.get(key)?.unwrap();
+                let record = put_record(key, &payload, &targets);
+                writer.write_all(&record)?;
+                index.insert(key, end + 9);
+                end += record.len() as u64;
+            }
+            writer.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        }
+        fs::rename(&tmp, &self.path)?;
+        self.file = OpenOptions::new().read(true).write(true).open(&self.path)?;
+        self.index = index;
+        self.end = end;
+        Ok(())
+    }
+
+    /// Waits until every record reaches the disk.
+    pub fn flush(&mut self) -> io::Result<()>
+    {
+        self.file.sync_data()
+    }
+
+    /// Flushes the storage and closes the file.
+    pub fn close(mut self) -> io::Result<()>
+    {
+        self.flush()
+    }
+
+    /// Replaces the contents of the storage with the nodes accessible from the root of the graph. Nodes are keyed
+    /// by their position in breadth-first order, payloads are produced by `encode`. Edge data is not stored.
+    pub fn save<'id, Root, NodeType, A : Allocator>(&mut self, anchor : &Anchor<'_, 'id, GenericGraph<Root, NodeType, A>>,
+                                                    mut encode : impl FnMut(&NodeType::Node) -> Vec<u8>) -> io::Result<()>
+    where Root : RootCollection<'static, NodeType>,
+          NodeType : GraphNode
+    {
+        let nodes = anchor.internal().reachable(&anchor.parent.root);
+        let keys : HashMap<_, _> = nodes.iter().enumerate().map(|(i, &x)| (x as *const NodeType, i as u64)).collect();
+        self.clear()?;
+        let mut targets = Vec::new();
+        for (i, &x) in nodes.iter().enumerate() {
+            targets.clear();
+            GraphRaw::<NodeType>::for_each_target(x, |y| targets.push(keys[&(y as *const NodeType)]));
+            //(W)
+            let payload = encode(unsafe { (*x).get() });
+            self.put(i as u64, &payload, &targets)?;
+        }
+        self.flush()
+    }
+
+    /// Spawns a node for every stored one with data produced by `decode` and calls `connect` for every stored edge.
+    /// Returns the spawned nodes by key. Nodes are not attached to the root. Fails if a payload is rejected by
+    /// `decode` or an edge points to a missing node.
+    pub fn load<'id, Root, NodeType, A : Allocator>(&mut self, anchor : &mut AnchorMut<'_, 'id, GenericGraph<Root, NodeType, A>>,
+                                                    mut decode : impl FnMut(&[u8]) -> Option<NodeType::Node>,
+                                                    mut connect : impl FnMut(&mut AnchorMut<'_, 'id, GenericGraph<Root, NodeType, A>>,
+                                                              GraphPtr<'id, NodeType>, GraphPtr<'id, NodeType>))
+        -> io::Result<HashMap<u64, GraphPtr<'id, NodeType>>>
+    where Root : RootCollection<'static, NodeType>,
+          NodeType : GraphNode
+    {
+        let invalid = |message| io::Error::new(io::ErrorKind::InvalidData, message);
+        let mut keys : Vec<_> = self.keys().collect();
+        keys.sort_unstable();
+        let mut nodes = HashMap::with_capacity(keys.len());
+        let mut edges = Vec::with_capacity(keys.len());
+        for key in keys {
+            let (payload, targets) = self.get(key)?.unwrap();
+            let data = decode(&payload).ok_or_else(|| invalid("payload rejected by decoder"))?;
+            nodes.insert(key, anchor.spawn(data));
+            edges.push((key, targets));
+        }
+        for (key, targets) in edges {
+            for x in targets {
+                let dst = *nodes.get(&x).ok_or_else(|| invalid("edge to a missing node"))?;
+                connect(anchor, nodes[&key], dst);
+            }
+        }
+        Ok(nodes)
+    }
+}
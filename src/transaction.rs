@@ -0,0 +1,348 @@
+use super::*;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+
+type Undo<'t, 'this, 'id, Root, NodeType, A> = Box<dyn FnOnce(&mut AnchorMut<'this, 'id, GenericGraph<Root, NodeType, A>>) + 't>;
+
+/// A group of modifications which is rolled back unless it succeeds, see `AnchorMut::transaction`. Nodes spawned
+/// through the transaction, edges modified by its `connect` and `disconnect` and the root are restored on
+/// rollback. The anchor can only be read through the transaction, other modifications have to be made in steps
+/// registered with `on_rollback`.
+pub struct Transaction<'t, 'this, 'id, Root, NodeType, A>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode,
+      A : Allocator
+{
+    anchor : &'t mut AnchorMut<'this, 'id, GenericGraph<Root, NodeType, A>>,
+    undo : Vec<Undo<'t, 'this, 'id, Root, NodeType, A>>,
+    spawned : Vec<GraphPtr<'id, NodeType>>,
+    root : Root,
+}
+
+impl <'t, 'this, 'id, N : 'this, NodeType : 'this, Root : 'this, A : Allocator> Transaction<'t, 'this, 'id, Root, NodeType, A>
+where NodeType : GraphNode<Node = N>,
+      Root : RootCollection<'static, NodeType>
+{
+    /// Allocates a new node, the node is detached from the graph on rollback.
+    pub fn spawn(&mut self, data : N) -> GraphPtr<'id, NodeType>
+    {
+        let ptr = self.anchor.spawn(data);
+        self.spawned.push(ptr);
+        ptr
+    }
+
+    /// Provides direct mutable access to the collection of the root. The root is restored on rollback, so nodes
+    /// attached and detached through it are detached and attached back.
+    pub fn root_mut(&mut self) -> &mut Root::View<'id>
+    where Root : RootCollectionView
+    {
+        self.anchor.root_mut()
+    }
+
+    /// Registers a step performed on rollback. Steps are performed in reverse order of registration.
+    pub fn on_rollback(&mut self, f : impl FnOnce(&mut AnchorMut<'this, 'id, GenericGraph<Root, NodeType, A>>) + 't)
+    {
+        self.undo.push(Box::new(f));
+    }
+
+    // Spawned nodes stay allocated until the next cleanup, as pointers to them may have escaped the transaction.
+    fn rollback(self)
+    {
+        let Transaction { anchor, undo, spawned, root } = self;
+        for f in undo.into_iter().rev() {
+            f(anchor);
+        }
+        for i in spawned {
+            anchor.internal_mut().unlink(i.as_ptr());
+        }
+        anchor.parent.root = root;
+    }
+}
+
+macro_rules! impl_transaction_named {
+    ($NodeType:ident $(, $S:ident)?) => {
+        impl <'t, 'this, 'id, N : 'this, E : 'this $(, $S : BuildHasher + Default + 'this)?, Root : 'this, A : Allocator>
+        Transaction<'t, 'this, 'id, Root, $NodeType<N, E $(, $S)?>, A>
+        where Root : RootCollection<'static, $NodeType<N, E $(, $S)?>>,
+              E : Clone
+        {
+            /// Same as `AnchorMut::connect`, the previous state of the edge is restored on rollback.
+            pub fn connect(&mut self, src : GraphPtr<'id, $NodeType<N, E $(, $S)?>>, dst : GraphPtr<'id, $NodeType<N, E $(, $S)?>>, edge : E)
+                -> Option<E>
+            {
+                let old = self.anchor.connect(src, dst, edge);
+                let restore = old.clone();
+                self.on_rollback(move |x| match restore {
+                    Some(edge) => { x.connect(src, dst, edge); }
+                    None => { x.disconnect(src, dst); }
+                });
+                old
+            }
+
+            /// Same as `AnchorMut::disconnect`, the edge is restored on rollback.
+            pub fn disconnect(&mut self, src : GraphPtr<'id, $NodeType<N, E $(, $S)?>>, dst : GraphPtr<'id, $NodeType<N, E $(, $S)?>>)
+                -> Option<E>
+            {
+                let old = self.anchor.disconnect(src, dst);
+                if let Some(edge) = old.clone() {
+                    self.on_rollback(move |x| { x.connect(src, dst, edge); });
+                }
+                old
+            }
+        }
+    }
+}
+
+impl_transaction_named!{NamedNode, S}
+impl_transaction_named!{DiNode}
+impl_transaction_named!{UndirectedNode}
+
+impl <'t, 'this, 'id, N : 'this, E : 'this, Root : 'this, A : Allocator> Transaction<'t, 'this, 'id, Root, VecNode<N, E>, A>
+where Root : RootCollection<'static, VecNode<N, E>>,
+      E : Clone
+{
+    /// Same as `AnchorMut::connect`, the previous state of the edge is restored on rollback.
+    pub fn connect(&mut self, src : GraphPtr<'id, VecNode<N, E>>, dst : GraphPtr<'id, VecNode<N, E>>, edge : E)
+        -> Option<E>
+    {
+        let old = self.anchor.connect(src, dst, edge);
+        let restore = old.clone();
+        self.on_rollback(move |x| match restore {
+            Some(edge) => { x.connect(src, dst, edge); }
+            None => { x.disconnect(src, dst); }
+        });
+        old
+    }
+
+    /// Same as `AnchorMut::disconnect`, the edge is restored at its position on rollback.
+    pub fn disconnect(&mut self, src : GraphPtr<'id, VecNode<N, E>>, dst : GraphPtr<'id, VecNode<N, E>>) -> Option<E>
+    {
+        let position = self.anchor[src].refs.iter().position(|x| x.0 == dst);
+        let old = self.anchor.disconnect(src, dst);
+        if let (Some(i), Some(edge)) = (position, old.clone()) {
+            self.on_rollback(move |x| {
+                x.connect(src, dst, edge);
+                let refs = &mut x[src].refs;
+                let last = refs.pop().unwrap();
+                refs.insert(i, last);
+            });
+        }
+        old
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl <'t, 'this, 'id, N : 'this, E : 'this, Root : 'this, A : Allocator> Transaction<'t, 'this, 'id, Root, OrderedNamedNode<N, E>, A>
+where Root : RootCollection<'static, OrderedNamedNode<N, E>>,
+      E : Clone
+{
+    /// Same as `AnchorMut::connect`, the previous state of the edge is restored on rollback.
+    pub fn connect(&mut self, src : GraphPtr<'id, OrderedNamedNode<N, E>>, dst : GraphPtr<'id, OrderedNamedNode<N, E>>,
+                   edge : E) -> Option<E>
+    {
+        let old = self.anchor.connect(src, dst, edge);
+        let restore = old.clone();
+        self.on_rollback(move |x| match restore {
+            Some(edge) => { x.connect(src, dst, edge); }
+            None => { x.disconnect(src, dst); }
+        });
+        old
+    }
+
+    /// Same as `AnchorMut::disconnect`, the edge is restored at its position on rollback.
+    pub fn disconnect(&mut self, src : GraphPtr<'id, OrderedNamedNode<N, E>>, dst : GraphPtr<'id, OrderedNamedNode<N, E>>)
+        -> Option<E>
+    {
+        let position = self.anchor[src].refs.get_index_of(&dst);
+        let old = self.anchor.disconnect(src, dst);
+        if let (Some(i), Some(edge)) = (position, old.clone()) {
+            self.on_rollback(move |x| {
+                x.connect(src, dst, edge);
+                let refs = &mut x[src].refs;
+                refs.move_index(refs.len() - 1, i);
+            });
+        }
+        old
+    }
+}
+
+impl <'t, 'this, 'id, N : 'this, E : 'this, Root : 'this, A : Allocator> Transaction<'t, 'this, 'id, Root, OptionNode<N, E>, A>
+where Root : RootCollection<'static, OptionNode<N, E>>,
+      E : Clone
+{
+    /// Same as `AnchorMut::connect`, the previous edge is restored on rollback.
+    pub fn connect(&mut self, src : GraphPtr<'id, OptionNode<N, E>>, dst : GraphPtr<'id, OptionNode<N, E>>, edge : E)
+        -> Option<E>
+    {
+        let restore = self.anchor[src].refs.clone();
+        self.on_rollback(move |x| match restore {
+            Some((dst, edge)) => { x.connect(src, dst, edge); }
+            None => { x.disconnect(src, dst); }
+        });
+        self.anchor.connect(src, dst, edge)
+    }
+
+    /// Same as `AnchorMut::disconnect`, the edge is restored on rollback.
+    pub fn disconnect(&mut self, src : GraphPtr<'id, OptionNode<N, E>>, dst : GraphPtr<'id, OptionNode<N, E>>) -> Option<E>
+    {
+        let old = self.anchor.disconnect(src, dst);
+        if let Some(edge) = old.clone() {
+            self.on_rollback(move |x| { x.connect(src, dst, edge); });
+        }
+        old
+    }
+}
+
+impl <'t, 'this, 'id, K : 'this, N : 'this, E : 'this, Root : 'this, A : Allocator> Transaction<'t, 'this, 'id, Root, TreeNode<K, N, E>, A>
+where Root : RootCollection<'static, TreeNode<K, N, E>>,
+      K : Ord + Clone,
+      E : Clone
+{
+    /// Same as `AnchorMut::connect`, the previous edge stored under `key` is restored on rollback.
+    #[allow(clippy::type_complexity)]
+    pub fn connect(&mut self, src : GraphPtr<'id, TreeNode<K, N, E>>, key : K, dst : GraphPtr<'id, TreeNode<K, N, E>>,
+                   edge : E) -> Option<(GraphPtr<'id, TreeNode<K, N, E>>, E)>
+    {
+        let old = self.anchor.connect(src, key.clone(), dst, edge);
+        let restore = old.clone();
+        self.on_rollback(move |x| match restore {
+            Some((dst, edge)) => { x.connect(src, key, dst, edge); }
+            None => { x.disconnect(src, &key); }
+        });
+        old
+    }
+
+    /// Same as `AnchorMut::disconnect`, the edge is restored on rollback.
+    #[allow(clippy::type_complexity)]
+    pub fn disconnect(&mut self, src : GraphPtr<'id, TreeNode<K, N, E>>, key : &K)
+        -> Option<(GraphPtr<'id, TreeNode<K, N, E>>, E)>
+    {
+        let old = self.anchor.disconnect(src, key);
+        if let Some((dst, edge)) = old.clone() {
+            let key = key.clone();
+            self.on_rollback(move |x| { x.connect(src, key, dst, edge); });
+        }
+        old
+    }
+}
+
+impl <'t, 'this, 'id, N : 'this, E : 'this, Root : 'this, const D : usize, A : Allocator> Transaction<'t, 'this, 'id, Root, ArrayNode<N, E, D>, A>
+where Root : RootCollection<'static, ArrayNode<N, E, D>>,
+      E : Clone
+{
+    /// Same as `AnchorMut::connect`, the previous edge stored in `slot` is restored on rollback.
+    pub fn connect(&mut self, src : GraphPtr<'id, ArrayNode<N, E, D>>, slot : usize, dst : GraphPtr<'id, ArrayNode<N, E, D>>,
+                   edge : E) -> Option<(GraphPtr<'id, ArrayNode<N, E, D>>, E)>
+    {
+        let old = self.anchor.connect(src, slot, dst, edge);
+        let restore = old.clone();
+        self.on_rollback(move |x| match restore {
+            Some((dst, edge)) => { x.connect(src, slot, dst, edge); }
+            None => { x.disconnect(src, slot); }
+        });
+        old
+    }
+
+    /// Same as `AnchorMut::disconnect`, the edge is restored on rollback.
+    pub fn disconnect(&mut self, src : GraphPtr<'id, ArrayNode<N, E, D>>, slot : usize)
+        -> Option<(GraphPtr<'id, ArrayNode<N, E, D>>, E)>
+    {
+        let old = self.anchor.disconnect(src, slot);
+        if let Some((dst, edge)) = old.clone() {
+            self.on_rollback(move |x| { x.connect(src, slot, dst, edge); });
+        }
+        old
+    }
+}
+
+impl <'t, 'this, 'id, N : 'this, E : 'this, Root : 'this, A : Allocator> Transaction<'t, 'this, 'id, Root, MultiNamedNode<N, E>, A>
+where Root : RootCollection<'static, MultiNamedNode<N, E>>,
+      E : Clone
+{
+    /// Same as `AnchorMut::connect`, the edge is removed on rollback.
+    pub fn connect(&mut self, src : GraphPtr<'id, MultiNamedNode<N, E>>, dst : GraphPtr<'id, MultiNamedNode<N, E>>, edge : E)
+    {
+        self.anchor.connect(src, dst, edge);
+        self.on_rollback(move |x| {
+            let mut edges = x.disconnect(src, dst);
+            edges.pop();
+            for i in edges {
+                x.connect(src, dst, i);
+            }
+        });
+    }
+
+    /// Same as `AnchorMut::disconnect`, the edges are restored in their order on rollback.
+    pub fn disconnect(&mut self, src : GraphPtr<'id, MultiNamedNode<N, E>>, dst : GraphPtr<'id, MultiNamedNode<N, E>>) -> Vec<E>
+    {
+        let old = self.anchor.disconnect(src, dst);
+        let restore = old.clone();
+        self.on_rollback(move |x| {
+            for i in restore {
+                x.connect(src, dst, i);
+            }
+        });
+        old
+    }
+}
+
+impl <'t, 'this, 'id, N : 'this, Root : 'this, A : Allocator> Transaction<'t, 'this, 'id, Root, SetNode<N>, A>
+where Root : RootCollection<'static, SetNode<N>>
+{
+    /// Same as `AnchorMut::connect`, the edge is removed on rollback if it was added.
+    pub fn connect(&mut self, src : GraphPtr<'id, SetNode<N>>, dst : GraphPtr<'id, SetNode<N>>) -> bool
+    {
+        let added = self.anchor.connect(src, dst);
+        if added {
+            self.on_rollback(move |x| { x.disconnect(src, dst); });
+        }
+        added
+    }
+
+    /// Same as `AnchorMut::disconnect`, the edge is restored on rollback if it was removed.
+    pub fn disconnect(&mut self, src : GraphPtr<'id, SetNode<N>>, dst : GraphPtr<'id, SetNode<N>>) -> bool
+    {
+        let removed = self.anchor.disconnect(src, dst);
+        if removed {
+            self.on_rollback(move |x| { x.connect(src, dst); });
+        }
+        removed
+    }
+}
+
+impl <'t, 'this, 'id, Root, NodeType, A : Allocator> Deref for Transaction<'t, 'this, 'id, Root, NodeType, A>
+where Root : RootCollection<'static, NodeType>,
+      NodeType : GraphNode
+{
+    type Target = AnchorMut<'this, 'id, GenericGraph<Root, NodeType, A>>;
+
+    fn deref(&self) -> &Self::Target
+    {
+        self.anchor
+    }
+}
+
+impl <'this, 'id, N : 'this, NodeType : 'this, Root : 'this, A : Allocator>
+AnchorMut<'this, 'id, GenericGraph<Root, NodeType, A>>
+where NodeType : GraphNode<Node = N>,
+      Root : RootCollection<'static, NodeType> + Clone
+{
+    /// Runs `f` and rolls back the modifications it made through the transaction if it returns Err or panics.
+    /// Panics are propagated after the rollback.
+    pub fn transaction<R, Err>(&mut self, f : impl FnOnce(&mut Transaction<'_, 'this, 'id, Root, NodeType, A>) -> Result<R, Err>)
+        -> Result<R, Err>
+    {
+        let root = self.parent.root.clone();
+        let mut txn = Transaction { anchor : self, undo : Vec::new(), spawned : Vec::new(), root };
+        match catch_unwind(AssertUnwindSafe(|| f(&mut txn))) {
+            Ok(Ok(x)) => Ok(x),
+            Ok(Err(x)) => {
+                txn.rollback();
+                Err(x)
+            }
+            Err(x) => {
+                txn.rollback();
+                resume_unwind(x)
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use crate::edge::*;
+use crate::graph_raw::GraphItem;
+
+/// A variant of an enum used as node data. Implemented by `payload_enum!`.
+pub trait Variant<Payload> : Sized {
+    /// Wraps the value into the enum.
+    fn wrap(self) -> Payload;
+    /// Returns the value if `payload` holds this variant.
+    fn peek(payload : &Payload) -> Option<&Self>;
+    /// Returns the value if `payload` holds this variant.
+    fn peek_mut(payload : &mut Payload) -> Option<&mut Self>;
+}
+
+#[macro_export]
+/// Declares an enum whose every variant wraps a distinct type and implements `Variant` for these types.
+macro_rules! payload_enum
+{
+    ($(#[$attr:meta])* $vis:vis enum $name:ident {
+        $($variant:ident($variant_type:ty)),* $(,)?
+    }) => {
+        $(#[$attr])*
+        $vis enum $name {
+            $($variant($variant_type)),*
+        }
+
+        $(
+            impl $crate::Variant<$name> for $variant_type {
+                fn wrap(self) -> $name {
+                    $name::$variant(self)
+                }
+
+                #[allow(unreachable_patterns)]
+                fn peek(payload : &$name) -> Option<&Self> {
+                    match payload {
+                        $name::$variant(x) => Some(x),
+                        _ => None,
+                    }
+                }
+
+                #[allow(unreachable_patterns)]
+                fn peek_mut(payload : &mut $name) -> Option<&mut Self> {
+                    match payload {
+                        $name::$variant(x) => Some(x),
+                        _ => None,
+                    }
+                }
+            }
+
+            impl From<$variant_type> for $name {
+                fn from(value : $variant_type) -> Self {
+                    $name::$variant(value)
+                }
+            }
+        )*
+    }
+}
+
+/// An add-on to edge iterators of graphs with enum node data.
+pub trait VariantEdges<'a, N : 'a, E : 'a, P> : Iterator<Item = GraphItem<Edge<&'a N, &'a E>, P>> + Sized {
+    /// Keeps edges pointing to nodes which hold variant `V`. Returns the destination data and the edge, same as `Edge::that`.
+    fn edges_filtered<V : Variant<N> + 'a>(self) -> impl Iterator<Item = GraphItem<EdgeLoop<&'a V, &'a E>, P>> {
+        self.filter_map(|x| {
+            let ptr = x.ptr;
+            let that = x.values.that();
+            V::peek(that.this).map(|this| GraphItem { values : EdgeLoop { this, edge : that.edge }, ptr })
+        })
+    }
+}
+
+impl <'a, N : 'a, E : 'a, P, I> VariantEdges<'a, N, E, P> for I
+where I : Iterator<Item = GraphItem<Edge<&'a N, &'a E>, P>> {}
@@ -0,0 +1,99 @@
+use super::*;
+use core::marker::PhantomData;
+
+/// A set of nodes stored as a bit per position of a node, see `ptr_to_index`. Created by `visit_set`. Meant to
+/// track visited nodes during traversals, it is much faster than hashing pointers. Grows to fit nodes spawned after
+/// it was created.
+pub struct VisitSet<'id, NodeType> {
+    bits : Vec<u64>,
+    len : usize,
+    _guard : Id<'id>,
+    _node : PhantomData<fn() -> NodeType>,
+}
+
+impl <'id, NodeType> VisitSet<'id, NodeType>
+where NodeType : GraphNode
+{
+    fn position(dst : GraphPtr<'id, NodeType>) -> (usize, u64)
+    {
+        //(W)
+        let i = unsafe { (*dst.as_ptr()).meta().store_index };
+        (i / 64, 1 << (i % 64))
+    }
+
+    /// Adds `dst` to the set. Returns false if it was already there.
+    pub fn insert(&mut self, dst : GraphPtr<'id, NodeType>) -> bool
+    {
+        let (word, bit) = Self::position(dst);
+        if word >= self.bits.len() {
+            self.bits.resize(word + 1, 0);
+        }
+        let fresh = self.bits[word] & bit == 0;
+        self.bits[word] |= bit;
+        self.len += fresh as usize;
+        fresh
+    }
+
+    /// Returns true if `dst` is in the set.
+    pub fn contains(&self, dst : GraphPtr<'id, NodeType>) -> bool
+    {
+        let (word, bit) = Self::position(dst);
+        self.bits.get(word).is_some_and(|x| x & bit != 0)
+    }
+
+    /// Removes `dst` from the set. Returns false if it was not there.
+    pub fn remove(&mut self, dst : GraphPtr<'id, NodeType>) -> bool
+    {
+        let (word, bit) = Self::position(dst);
+        match self.bits.get_mut(word) {
+            Some(x) if *x & bit != 0 => {
+                *x &= !bit;
+                self.len -= 1;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Returns the number of nodes in the set.
+    pub fn len(&self) -> usize
+    {
+        self.len
+    }
+
+    /// Returns true if the set is empty.
+    pub fn is_empty(&self) -> bool
+    {
+        self.len == 0
+    }
+
+    /// Removes every node keeping the allocated memory.
+    pub fn clear(&mut self)
+    {
+        for x in &mut self.bits {
+            *x = 0;
+        }
+        self.len = 0;
+    }
+}
+
+macro_rules! impl_anchor_visit_set {
+    ($anchor_type:ident) => {
+        impl <'this, 'id, NodeType : 'this, Root : 'this, A : Allocator>
+        $anchor_type<'this, 'id, GenericGraph<Root, NodeType, A>>
+        where NodeType : GraphNode,
+              Root : RootCollection<'static, NodeType>
+        {
+            /// Creates an empty visit set with room for every allocated node. Killing or taking a node moves another
+            /// one to its position, so the set should be cleared afterwards.
+            pub fn visit_set(&self) -> VisitSet<'id, NodeType>
+            {
+                let len = self.internal().data.len();
+                VisitSet { bits : vec![0; len.div_ceil(64)], len : 0, _guard : self._guard, _node : PhantomData }
+            }
+        }
+    }
+}
+
+impl_anchor_visit_set!{Anchor}
+impl_anchor_visit_set!{AnchorMut}
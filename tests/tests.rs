@@ -51,8 +51,8 @@ fn test_bfs() {
         }
         graph.root_mut().push(vec[0]);
 
-        fn bfs_edges<'id, T>(nodes : &[GraphPtr<'id, T>]) -> HashMap<GraphPtr<'id, T>, ()> {
-            let mut res = HashMap::new();
+        fn bfs_edges<'id, T : GraphNode>(nodes : &[GraphPtr<'id, T>]) -> NodeNamedMap<'id, T, ()> {
+            let mut res = NodeNamedMap::default();
             for i in nodes {
                 res.insert(*i, ());
             }
@@ -74,6 +74,7 @@ fn test_bfs() {
 type BFNode = NamedNode<usize, usize>;
 type BFRef<'id> = GraphPtr<'id, BFNode>;
 
+#[allow(clippy::map_clone, clippy::needless_borrow)]
 fn bellman_ford<'a>(graph : &AnchorMut<'a, 'a, VecGraph<BFNode>>,
                     count : usize, source : BFRef<'a>) -> HashMap::<BFRef<'a>, BFRef<'a>>
 {
@@ -320,4 +321,3412 @@ fn test_max_flow() {
         graph[sink].refs.insert(v4,   f(0));
     }
     assert_eq!(edmonds_karp(&mut graph), 23);
-}
\ No newline at end of file
+}
+#[test]
+fn test_stats() {
+    let mut graph = VecGraph::<NamedNode<i32, ()>>::new();
+    anchor_mut!(graph, Never);
+
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    let v3 = graph.spawn(3);
+    graph.spawn(4);
+    graph.root_mut().push(v1);
+
+    graph[v1].refs.insert(v2, ());
+    graph[v1].refs.insert(v3, ());
+    graph[v2].refs.insert(v1, ());
+
+    assert_eq!(graph.node_count(), 4);
+    assert_eq!(graph.reachable_count(), 3);
+    assert_eq!(graph.edge_count(), 3);
+    assert_eq!(graph.degree(v1), 2);
+    assert_eq!(graph.cursor(v2).degree(), 1);
+
+    let stats = graph.stats();
+    assert_eq!(stats.max_degree, 2);
+    assert_eq!(stats.min_degree, 0);
+    assert_eq!(stats.avg_degree, 0.75);
+    assert_eq!(stats.density, 0.25);
+}
+
+#[test]
+fn test_stats_self_loops() {
+    let mut graph = VecGraph::<NamedNode<i32, ()>>::new();
+    anchor_mut!(graph, Never);
+
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    graph.connect(v1, v1, ());
+    graph.connect(v2, v2, ());
+    graph.connect(v1, v2, ());
+
+    let stats = graph.stats();
+    assert_eq!(stats.edge_count, 3);
+    assert_eq!(stats.density, 0.5);
+}
+
+#[test]
+fn test_neighbors() {
+    let mut graph = VecGraph::<VecNode<i32, ()>>::new();
+    anchor_mut!(graph, Never);
+
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    let v3 = graph.spawn(3);
+
+    graph[v1].refs.push((v2, ()));
+    graph[v1].refs.push((v3, ()));
+    graph[v1].refs.push((v1, ()));
+
+    let neighbors : Vec<_> = graph.neighbors(v1).collect();
+    assert!(neighbors == vec![v2, v3, v1]);
+    assert_eq!(graph.cursor(v2).neighbors().count(), 0);
+}
+
+#[test]
+fn test_all_nodes() {
+    let mut graph = VecGraph::<OptionNode<i32, ()>>::new();
+    anchor_mut!(graph, Never);
+
+    let v1 = graph.spawn(1);
+    graph.spawn(2);
+    graph.spawn(3);
+    graph.root_mut().push(v1);
+
+    let mut data : Vec<_> = graph.all_nodes().map(|x| *x.values).collect();
+    data.sort();
+    assert_eq!(data, vec![1, 2, 3]);
+    assert!(graph.all_nodes().any(|x| x.ptr == v1));
+}
+
+#[test]
+fn test_find() {
+    let mut graph = VecGraph::<NamedNode<i32, ()>>::new();
+    anchor_mut!(graph, Never);
+
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    let v3 = graph.spawn(3);
+    graph.spawn(4);
+    graph.root_mut().push(v1);
+    graph[v1].refs.insert(v2, ());
+    graph[v2].refs.insert(v3, ());
+
+    assert!(graph.find(|x| *x == 3) == Some(v3));
+    assert!(graph.find(|x| *x == 4).is_none());
+    assert!(graph.find_all(|x| *x % 2 == 1) == vec![v1, v3]);
+}
+
+#[test]
+fn test_find_unreachable() {
+    let mut graph = VecGraph::<NamedNode<i32, ()>>::new();
+    anchor_mut!(graph, Never);
+
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    let v3 = graph.spawn(3);
+    graph.root_mut().push(v1);
+    graph[v1].refs.insert(v2, ());
+    graph[v3].refs.insert(v1, ());
+
+    assert!(graph.find_unreachable() == vec![v3]);
+    assert_eq!(graph.node_count(), 3);
+}
+
+#[test]
+fn test_cleanup_report() {
+    let mut graph = VecGraph::<NamedNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let v1 = graph.spawn(1);
+        graph.spawn(2);
+        graph.spawn(3);
+        graph.root_mut().push(v1);
+    }
+    let report = graph.cleanup_precise();
+    assert_eq!(report.nodes_freed, 2);
+    assert_eq!(report.nodes_retained, 1);
+    assert!(report.bytes_freed_estimate > 0);
+
+    assert_eq!(graph.cleanup().nodes_freed, 0);
+}
+
+#[test]
+fn test_cleanup_heuristic() {
+    let mut graph = VecGraph::<NamedNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        for i in 0..8 {
+            let v = graph.spawn(i);
+            graph.root_mut().push(v);
+        }
+    }
+    assert_eq!(graph.cleanup().nodes_retained, 8);
+    {
+        anchor_mut!(graph, Never);
+        graph.spawn(8);
+    }
+    //A single spawn is not worth a traversal.
+    assert_eq!(graph.cleanup().nodes_freed, 0);
+    assert_eq!(graph.cleanup_precise().nodes_freed, 1);
+}
+
+#[test]
+fn test_garbage_estimate() {
+    let mut graph = VecGraph::<NamedNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        for i in 0..4 {
+            let v = graph.spawn(i);
+            graph.root_mut().push(v);
+        }
+    }
+    assert_eq!(graph.garbage_estimate(), 4);
+    graph.cleanup_precise();
+    {
+        anchor_mut!(graph, Never);
+        let a = graph.root()[0];
+        let b = graph.root()[1];
+        //Neither changing data nor adding edges can detach a node.
+        for x in graph.iter_mut() {
+            *x.values += 1;
+        }
+        graph.connect(a, b, ());
+        for x in graph.edges_mut(a) {
+            *x.values.that().this += 1;
+        }
+    }
+    assert_eq!(graph.garbage_estimate(), 0);
+    {
+        anchor_mut!(graph, Never);
+        let a = graph.root()[0];
+        let b = graph.root()[1];
+        graph.disconnect(a, b);
+    }
+    assert_eq!(graph.garbage_estimate(), 1);
+    {
+        anchor_mut!(graph, Never);
+        let a = graph.root()[0];
+        graph[a].refs.clear();
+    }
+    assert_eq!(graph.garbage_estimate(), 2);
+    {
+        anchor_mut!(graph, Always);
+        graph.spawn(4);
+    }
+    //Always collects regardless of the estimate.
+    assert_eq!(graph.node_count(), 4);
+    assert_eq!(graph.garbage_estimate(), 0);
+}
+
+#[test]
+fn test_cleanup_generations() {
+    let mut graph = VecGraph::<NamedNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let a = graph.spawn(0);
+        let b = graph.spawn(1);
+        graph.root_mut().push(a);
+        graph[a].refs.insert(b, ());
+    }
+    //Survivors of several cleanups are moved to the old generation.
+    for _ in 0..3 {
+        {
+            anchor_mut!(graph, Never);
+            graph.root_mut();
+        }
+        assert_eq!(graph.cleanup().nodes_retained, 2);
+    }
+    {
+        anchor_mut!(graph, Never);
+        let a = graph.root()[0];
+        let b = graph.neighbors(a).next().unwrap();
+        let c = graph.spawn(2);
+        graph[b].refs.insert(c, ());
+    }
+    //A young node attached to an old one survives the minor cleanup.
+    assert_eq!(graph.cleanup().nodes_freed, 0);
+    {
+        anchor_mut!(graph, Never);
+        assert_eq!(graph.reachable_count(), 3);
+        let a = graph.root()[0];
+        graph[a].refs.clear();
+    }
+    //Old garbage is only collected by the full cleanup.
+    assert_eq!(graph.cleanup().nodes_freed, 0);
+    assert_eq!(graph.cleanup_precise().nodes_freed, 2);
+}
+
+#[test]
+fn test_cleanup_generations_periodic() {
+    let mut graph = VecGraph::<NamedNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let a = graph.spawn(0);
+        let b = graph.spawn(1);
+        graph.root_mut().push(a);
+        graph.connect(a, b, ());
+    }
+    for _ in 0..3 {
+        {
+            anchor_mut!(graph, Never);
+            graph.root_mut();
+        }
+        graph.cleanup();
+    }
+    {
+        anchor_mut!(graph, Never);
+        graph.root_mut().clear();
+    }
+    //Heuristic cleanup turns into a full one every once in a while.
+    let mut freed = 0;
+    for _ in 0..8 {
+        {
+            anchor_mut!(graph, Never);
+            graph.root_mut();
+        }
+        freed += graph.cleanup().nodes_freed;
+    }
+    assert_eq!(freed, 2);
+}
+
+#[test]
+fn test_cleanup_generations_remembered() {
+    let mut graph = VecGraph::<NamedNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let a = graph.spawn(0);
+        let b = graph.spawn(1);
+        graph.root_mut().push(a);
+        graph.root_mut().push(b);
+    }
+    for _ in 0..3 {
+        {
+            anchor_mut!(graph, Never);
+            graph.root_mut();
+        }
+        graph.cleanup();
+    }
+    //Every way of adding an edge to an old node keeps its young target alive through minor cleanups.
+    type Node = NamedNode<i32, ()>;
+    type Attach = for<'a, 'id> fn(&mut AnchorMut<'a, 'id, VecGraph<Node>>, GraphPtr<'id, Node>, GraphPtr<'id, Node>);
+    let attach : [Attach; 5] = [
+        |g, a, c| { g.connect(a, c, ()); },
+        |g, a, c| { g.connect_or_update(a, c, (), |_| ()); },
+        |g, a, c| { g[a].refs.insert(c, ()); },
+        |g, a, c| { g.bridge(a, c).unwrap().0.refs.insert(c, ()); },
+        |g, a, c| { g.cursor_mut(a).refs.insert(c, ()); },
+    ];
+    for f in attach {
+        {
+            anchor_mut!(graph, Never);
+            let a = graph.root()[0];
+            let c = graph.spawn(2);
+            f(&mut graph, a, c);
+        }
+        assert_eq!(graph.cleanup().nodes_freed, 0);
+        {
+            anchor_mut!(graph, Never);
+            let a = graph.root()[0];
+            graph[a].refs.clear();
+        }
+        graph.cleanup_precise();
+        for _ in 0..3 {
+            {
+                anchor_mut!(graph, Never);
+                graph.root_mut();
+            }
+            graph.cleanup();
+        }
+    }
+}
+
+#[test]
+fn test_cleanup_thresholds() {
+    let mut graph = VecGraph::<NamedNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, WhenCapacityExceeds(2));
+        graph.spawn(1);
+        graph.spawn(2);
+    }
+    assert_eq!(graph.node_count(), 2);
+    {
+        anchor_mut!(graph, WhenCapacityExceeds(2));
+        graph.spawn(3);
+    }
+    assert_eq!(graph.node_count(), 0);
+    {
+        anchor_mut!(graph, WhenGarbageExceeds(2));
+        graph.spawn(1);
+        graph.spawn(2);
+    }
+    assert_eq!(graph.node_count(), 2);
+    {
+        anchor_mut!(graph, WhenGarbageExceeds(2));
+        graph.spawn(3);
+    }
+    assert_eq!(graph.node_count(), 0);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_cleanup_parallel() {
+    let mut graph = VecGraph::<VecNode<usize, ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let root = graph.spawn(0);
+        graph.root_mut().push(root);
+        let mut level = vec![root];
+        for _ in 0..3 {
+            let mut next = Vec::new();
+            for i in &level {
+                for _ in 0..16 {
+                    let child = graph.spawn(0);
+                    graph[*i].refs.push((child, ()));
+                    graph[child].refs.push((root, ()));
+                    graph.spawn(1);
+                    next.push(child);
+                }
+            }
+            level = next;
+        }
+    }
+    let report = graph.cleanup_parallel();
+    assert_eq!(report.nodes_retained, 1 + 16 + 256 + 4096);
+    assert_eq!(report.nodes_freed, 16 + 256 + 4096);
+
+    anchor_mut!(graph, Never);
+    assert!(graph.all_nodes().all(|x| *x.values == 0));
+    assert_eq!(graph.reachable_count(), graph.node_count());
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_par_for_each_mut() {
+    let mut graph = VecGraph::<VecNode<usize, ()>>::new();
+    anchor_mut!(graph, Never);
+    let root = graph.spawn(0);
+    graph.root_mut().push(root);
+    for i in 1..5000 {
+        let node = graph.spawn(i);
+        graph[root].refs.push((node, ()));
+    }
+    graph.par_for_each_mut(|x| *x *= 2);
+    let sum : usize = graph.all_nodes().map(|x| *x.values).sum();
+    assert_eq!(sum, 4999 * 5000);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_par_bfs() {
+    use std::sync::Mutex;
+
+    // A binary tree with an extra edge back to the root from every leaf.
+    let mut graph = VecGraph::<VecNode<usize, ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let nodes : Vec<_> = (0..4095).map(|i| graph.spawn(i)).collect();
+        graph.root_mut().push(nodes[0]);
+        for i in 0..2047 {
+            graph[nodes[i]].refs.push((nodes[2 * i + 1], ()));
+            graph[nodes[i]].refs.push((nodes[2 * i + 2], ()));
+        }
+        for i in 2047..4095 {
+            graph[nodes[i]].refs.push((nodes[0], ()));
+        }
+        graph.spawn(4095);
+    }
+    anchor!(graph);
+    let depths = Mutex::new(vec![None; 4096]);
+    algo::par_bfs(&graph, graph.root()[0], |x, depth| {
+        let mut depths = depths.lock().unwrap();
+        assert!(depths[graph[x].data].is_none());
+        depths[graph[x].data] = Some(depth);
+    });
+    let depths = depths.into_inner().unwrap();
+    for (i, x) in depths.iter().enumerate().take(4095) {
+        assert_eq!(*x, Some((usize::BITS - (i + 1).leading_zeros() - 1) as usize));
+    }
+    assert_eq!(depths[4095], None);
+}
+
+#[test]
+fn test_two_color() {
+    let mut graph = VecGraph::<VecNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let nodes : Vec<_> = (0..4).map(|i| graph.spawn(i)).collect();
+        graph.root_mut().push(nodes[0]);
+        for i in 0..4 {
+            graph.connect(nodes[i], nodes[(i + 1) % 4], ());
+        }
+    }
+    {
+        anchor!(graph);
+        let colors = algo::two_color(&graph).unwrap();
+        let root = graph.root()[0];
+        assert_eq!(colors.len(), 4);
+        assert_eq!(colors[&root], algo::Color::Left);
+        for (&x, &c) in colors.iter() {
+            assert_eq!(c == algo::Color::Left, graph[x].data % 2 == 0);
+        }
+    }
+    {
+        anchor_mut!(graph, Never);
+        let root = graph.root()[0];
+        let x = graph.spawn(4);
+        graph.connect(root, x, ());
+        let last = graph.find(|x| *x == 3).unwrap();
+        graph.connect(x, last, ());
+    }
+    anchor!(graph);
+    assert!(algo::two_color(&graph).err() == Some(OddCycle));
+}
+
+#[test]
+fn test_bipartite_matching() {
+    let mut graph = VecGraph::<VecNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let workers : Vec<_> = (0..4).map(|i| graph.spawn(i)).collect();
+        let jobs : Vec<_> = (10..14).map(|i| graph.spawn(i)).collect();
+        // Worker 3 competes with worker 0 for the only job it can take, so a greedy matching is not maximal.
+        for (w, j) in [(0, 0), (0, 1), (1, 1), (1, 2), (2, 2), (3, 0)] {
+            graph.connect(workers[w], jobs[j], ());
+        }
+        graph.connect(workers[0], workers[1], ());
+        graph.root_mut().extend(workers);
+    }
+    anchor!(graph);
+    let matching = algo::max_bipartite_matching(&graph, graph.root().iter().copied());
+    assert_eq!(matching.len(), 3);
+    let mut matched_jobs : Vec<_> = matching.iter().map(|x| graph[x.1].data).collect();
+    matched_jobs.sort();
+    assert_eq!(matched_jobs, vec![10, 11, 12]);
+    for (w, j) in matching {
+        assert!(graph[w].data < 10 && graph.edges(w).any(|x| x.ptr == j));
+    }
+}
+
+#[test]
+fn test_centrality() {
+    let mut graph = VecGraph::<VecNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let nodes : Vec<_> = (0..4).map(|i| graph.spawn(i)).collect();
+        for i in 1..4 {
+            graph.connect(nodes[i], nodes[0], ());
+        }
+        graph.connect(nodes[0], nodes[1], ());
+        graph.root_mut().extend(nodes);
+    }
+    anchor!(graph);
+    let hub = graph.root()[0];
+    let rank = algo::pagerank(&graph, 0.85, 50);
+    assert_eq!(rank.len(), 4);
+    assert!((rank.values().sum::<f64>() - 1.0).abs() < 1e-9);
+    let leaf = graph.find(|x| *x == 2).unwrap();
+    let spoke = graph.find(|x| *x == 1).unwrap();
+    assert!(rank[&hub] > rank[&spoke] && rank[&spoke] > rank[&leaf]);
+
+    let degree = algo::degree_centrality(&graph);
+    assert_eq!(degree[&hub], 4.0 / 3.0);
+    assert_eq!(degree[&leaf], 1.0 / 3.0);
+}
+
+#[test]
+fn test_betweenness() {
+    // A diamond with a heavy upper half and a tail.
+    let mut graph = VecGraph::<VecNode<i32, f64>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let nodes : Vec<_> = (0..5).map(|i| graph.spawn(i)).collect();
+        for (a, b, w) in [(0, 1, 1.0), (0, 2, 5.0), (1, 3, 1.0), (2, 3, 1.0), (3, 4, 1.0)] {
+            graph.connect(nodes[a], nodes[b], w);
+        }
+        graph.root_mut().extend(nodes);
+    }
+    anchor!(graph);
+    let nodes : Vec<_> = graph.root().to_vec();
+    let scores = algo::betweenness(&graph);
+    let expected = [0.0, 1.0, 1.0, 3.0, 0.0];
+    for i in 0..5 {
+        assert_eq!(scores[&nodes[i]], expected[i]);
+    }
+    let weighted = algo::weighted_betweenness(&graph, |a, b| graph[a].refs.iter().find(|x| x.0 == b).unwrap().1);
+    let expected = [0.0, 2.0, 0.0, 3.0, 0.0];
+    for i in 0..5 {
+        assert_eq!(weighted[&nodes[i]], expected[i]);
+    }
+    #[cfg(feature = "parallel")]
+    {
+        let parallel = algo::par_betweenness(&graph);
+        assert!(nodes.iter().all(|x| parallel[x] == scores[x]));
+    }
+}
+
+#[test]
+fn test_label_propagation() {
+    // Two cliques joined by a single edge.
+    let mut graph = VecGraph::<VecNode<usize, ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let nodes : Vec<_> = (0..8).map(|i| graph.spawn(i)).collect();
+        for a in 0..8 {
+            for b in a + 1..8 {
+                if a / 4 == b / 4 {
+                    graph.connect(nodes[a], nodes[b], ());
+                }
+            }
+        }
+        graph.connect(nodes[3], nodes[4], ());
+        graph.root_mut().extend(nodes);
+    }
+    anchor!(graph);
+    let mut seed = 0x2545f4914f6cdd1du64;
+    let labels = algo::label_propagation(&graph, 100, || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    });
+    let nodes = graph.root();
+    for a in 0..8 {
+        for b in 0..8 {
+            if a / 4 == b / 4 {
+                assert_eq!(labels[&nodes[a]], labels[&nodes[b]]);
+            }
+        }
+    }
+    assert_ne!(labels[&nodes[0]], labels[&nodes[7]]);
+}
+
+#[test]
+fn test_eulerian_path() {
+    let mut graph = VecGraph::<VecNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let nodes : Vec<_> = (0..4).map(|i| graph.spawn(i)).collect();
+        for (a, b) in [(0, 1), (1, 2), (2, 0), (0, 3), (3, 0), (1, 3)] {
+            graph.connect(nodes[a], nodes[b], ());
+        }
+        graph.root_mut().extend(nodes);
+    }
+    {
+        anchor!(graph);
+        let path = algo::eulerian_path(&graph).unwrap();
+        assert_eq!(path.len(), 6);
+        assert_eq!(graph[path[0].0].data, 1);
+        assert_eq!(graph[path[5].1].data, 3);
+        for i in 1..path.len() {
+            assert!(path[i - 1].1 == path[i].0);
+        }
+        for &(a, b) in &path {
+            assert!(graph.edges(a).any(|x| x.ptr == b));
+        }
+    }
+    {
+        anchor_mut!(graph, Never);
+        let (a, b) = (graph.root()[2], graph.root()[3]);
+        graph.connect(a, b, ());
+    }
+    anchor!(graph);
+    assert!(algo::eulerian_path(&graph).is_none());
+}
+
+#[test]
+fn test_k_shortest_paths() {
+    let mut graph = VecGraph::<VecNode<i32, f64>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let nodes : Vec<_> = (0..5).map(|i| graph.spawn(i)).collect();
+        for (a, b, w) in [(0, 1, 1.0), (1, 4, 1.0), (0, 2, 1.0), (2, 4, 2.0), (1, 2, 0.5), (0, 3, 4.0), (3, 4, 0.5)] {
+            graph.connect(nodes[a], nodes[b], w);
+        }
+        graph.root_mut().extend(nodes);
+    }
+    anchor!(graph);
+    let nodes = graph.root();
+    let weight = |a, b| graph[a].refs.iter().find(|x| x.0 == b).unwrap().1;
+    let paths = algo::k_shortest_paths(&graph, nodes[0], nodes[4], 10, weight);
+    let found : Vec<_> = paths.iter().map(|(length, path)| {
+        (*length, path.iter().map(|x| graph[*x].data).collect::<Vec<_>>())
+    }).collect();
+    assert_eq!(found, vec![(2.0, vec![0, 1, 4]), (3.0, vec![0, 2, 4]), (3.5, vec![0, 1, 2, 4]), (4.5, vec![0, 3, 4])]);
+    assert_eq!(algo::k_shortest_paths(&graph, nodes[0], nodes[4], 2, weight).len(), 2);
+    assert!(algo::k_shortest_paths(&graph, nodes[4], nodes[0], 2, weight).is_empty());
+}
+
+#[test]
+fn test_johnson() {
+    let mut graph = VecGraph::<VecNode<i32, f64>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let nodes : Vec<_> = (0..4).map(|i| graph.spawn(i)).collect();
+        for (a, b, w) in [(0, 1, 4.0), (0, 2, 1.0), (2, 1, -2.0), (1, 3, 1.0), (3, 0, 2.0)] {
+            graph.connect(nodes[a], nodes[b], w);
+        }
+        graph.root_mut().extend(nodes);
+    }
+    {
+        anchor!(graph);
+        let nodes = graph.root();
+        let dist = algo::johnson(&graph, |a, b| graph[a].refs.iter().find(|x| x.0 == b).unwrap().1).unwrap();
+        assert_eq!(dist.len(), 16);
+        assert_eq!(dist[&(nodes[0], nodes[1])], -1.0);
+        assert_eq!(dist[&(nodes[0], nodes[3])], 0.0);
+        assert_eq!(dist[&(nodes[3], nodes[1])], 1.0);
+        assert_eq!(dist[&(nodes[2], nodes[2])], 0.0);
+    }
+    {
+        anchor_mut!(graph, Never);
+        let (a, b) = (graph.root()[3], graph.root()[0]);
+        graph.connect(a, b, -1.0);
+    }
+    anchor!(graph);
+    assert!(algo::johnson(&graph, |a, b| graph[a].refs.iter().find(|x| x.0 == b).unwrap().1).err() == Some(NegativeCycle));
+}
+
+#[test]
+fn test_random_walk() {
+    let mut graph = VecGraph::<VecNode<i32, f64>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let nodes : Vec<_> = (0..4).map(|i| graph.spawn(i)).collect();
+        for (a, b, w) in [(0, 1, 1.0), (0, 2, 3.0), (1, 0, 1.0), (2, 0, 1.0), (2, 3, 0.0)] {
+            graph.connect(nodes[a], nodes[b], w);
+        }
+        graph.connect(nodes[3], nodes[3], 0.0);
+        graph.root_mut().extend(nodes);
+    }
+    anchor!(graph);
+    let src = graph.root()[0];
+    let mut seed = 0x9e3779b97f4a7c15u64;
+    let mut rng = || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+    let walk : Vec<_> = graph.weighted_random_walk(src, &mut rng, |a, b| graph[a].refs.iter().find(|x| x.0 == b).unwrap().1)
+        .take(4001).map(|x| graph[x].data).collect();
+    assert_eq!(walk.len(), 4001);
+    assert!(walk.iter().step_by(2).all(|&x| x == 0));
+    let twos = walk.iter().filter(|&&x| x == 2).count();
+    assert!(twos > 1300 && twos < 1700);
+
+    let dead_end = graph.find(|x| *x == 3).unwrap();
+    assert_eq!(graph.random_walk(dead_end, &mut rng).take(10).count(), 10);
+    assert_eq!(graph.weighted_random_walk(dead_end, &mut rng, |_, _| 0.0).count(), 1);
+}
+
+#[test]
+fn test_on_collect() {
+    use std::sync::{Arc, Mutex};
+
+    let collected = Arc::new(Mutex::new(Vec::new()));
+    let mut graph = VecGraph::<NamedNode<String, ()>>::new();
+    let sink = collected.clone();
+    graph.on_collect(move |x| sink.lock().unwrap().push(x));
+    {
+        anchor_mut!(graph, Always);
+        let v1 = graph.spawn("root".to_string());
+        graph.spawn("garbage".to_string());
+        graph.root_mut().push(v1);
+    }
+    assert_eq!(*collected.lock().unwrap(), vec!["garbage".to_string()]);
+}
+
+#[test]
+fn test_observers() {
+    use std::sync::{Arc, Mutex};
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut graph = VecGraph::<NamedNode<i32, ()>>::new();
+    let sink = events.clone();
+    graph.add_observer(move |x| sink.lock().unwrap().push(x));
+    let (p1, p2);
+    {
+        anchor_mut!(graph, Always);
+        let v1 = graph.spawn(1);
+        let v2 = graph.spawn(2);
+        graph.root_mut().push(v1);
+        assert!(graph.connect(v1, v2, ()).is_none());
+        assert!(graph.connect(v1, v2, ()).is_some());
+        let mut cursor = graph.cursor_mut(v1);
+        cursor.detach(v2);
+        p1 = v1.as_ptr();
+        p2 = v2.as_ptr();
+    }
+    assert_eq!(*events.lock().unwrap(), vec![GraphEvent::NodeSpawned(p1), GraphEvent::NodeSpawned(p2),
+                                             GraphEvent::EdgeAdded { src : p1, dst : p2 },
+                                             GraphEvent::EdgeRemoved { src : p1, dst : p2 },
+                                             GraphEvent::NodeCollected(p2)]);
+}
+
+#[test]
+fn test_drain_dirty() {
+    let mut graph = VecGraph::<NamedNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        graph.spawn(0);
+        assert_eq!(graph.drain_dirty().count(), 0);
+    }
+    graph.cleanup_precise();
+    graph.set_dirty_tracking(true);
+    {
+        anchor_mut!(graph, Never);
+        let v1 = graph.spawn(1);
+        let v2 = graph.spawn(2);
+        let v3 = graph.spawn(3);
+        graph.root_mut().push(v1);
+        assert_eq!(graph.drain_dirty().count(), 3);
+        assert_eq!(graph.drain_dirty().count(), 0);
+
+        graph.connect(v2, v3, ());
+        graph[v2].data = 4;
+        let dirty : Vec<_> = graph.drain_dirty().collect();
+        assert_eq!(dirty.len(), 1);
+        assert!(dirty[0] == v2);
+
+        graph.connect(v1, v2, ());
+        graph[v3].data = 5;
+        graph.remove(v3);
+    }
+    anchor_mut!(graph, Never);
+    let mut dirty : Vec<_> = graph.drain_dirty().collect();
+    dirty.sort_by_key(|x| graph[*x].data);
+    let data : Vec<_> = dirty.iter().map(|x| graph[*x].data).collect();
+    assert_eq!(data, vec![1, 4]);
+}
+
+#[test]
+fn test_acyclic() {
+    let mut graph = VecGraph::<NamedNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let v1 = graph.spawn(1);
+        let v2 = graph.spawn(2);
+        graph.root_mut().push(v1);
+        graph.root_mut().push(v2);
+        assert_eq!(graph.try_connect(v2, v1, ()), Ok(None));
+        assert_eq!(graph.try_connect(v1, v2, ()), Err(WouldCycle));
+        graph.connect(v1, v2, ());
+    }
+    assert_eq!(graph.set_acyclic(true), Err(WouldCycle));
+    {
+        anchor_mut!(graph, Never);
+        let v1 = graph.root()[0];
+        let v2 = graph.root()[1];
+        graph.disconnect(v1, v2);
+    }
+    assert_eq!(graph.set_acyclic(true), Ok(()));
+    {
+        anchor_mut!(graph, Never);
+        let v1 = graph.root()[0];
+        let v2 = graph.root()[1];
+        let v3 = graph.spawn(3);
+        let v4 = graph.spawn(4);
+        assert_eq!(graph.try_connect(v3, v2, ()), Ok(None));
+        assert_eq!(graph.try_connect(v4, v3, ()), Ok(None));
+        assert_eq!(graph.try_connect(v1, v4, ()), Err(WouldCycle));
+        assert_eq!(graph.try_connect(v4, v4, ()), Err(WouldCycle));
+        assert_eq!(graph.try_connect(v1, v3, ()), Err(WouldCycle));
+        assert_eq!(graph.try_connect(v4, v2, ()), Ok(None));
+    }
+    assert!(graph.is_acyclic());
+}
+
+#[test]
+fn test_acyclic_panic() {
+    let mut graph = VecGraph::<DiNode<i32, ()>>::new();
+    assert_eq!(graph.set_acyclic(true), Ok(()));
+    anchor_mut!(graph, Never);
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    graph.connect(v1, v2, ());
+    let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        graph.connect(v2, v1, ());
+    }));
+    //The edge is rejected before either node is modified.
+    assert!(panicked.is_err());
+    assert!(graph[v2].out_refs.is_empty());
+    assert!(graph[v1].in_refs().is_empty());
+    assert_eq!(graph.try_connect(v2, v1, ()), Err(WouldCycle));
+}
+
+#[test]
+fn test_acyclic_views() {
+    let mut graph = VecGraph::<NamedNode<i32, ()>>::new();
+    assert_eq!(graph.set_acyclic(true), Ok(()));
+    anchor_mut!(graph, Never);
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    let v3 = graph.spawn(3);
+    graph.connect(v1, v2, ());
+    //Edges added through views are taken into account by the next connection.
+    graph[v3].refs.insert(v1, ());
+    assert_eq!(graph.try_connect(v2, v3, ()), Err(WouldCycle));
+    graph[v2].refs.insert(v3, ());
+    let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        graph.try_connect(v1, v3, ())
+    }));
+    assert!(panicked.is_err());
+}
+
+#[test]
+fn test_remove() {
+    let mut graph = VecGraph::<VecNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let v1 = graph.spawn(1);
+        let v2 = graph.spawn(2);
+        let v3 = graph.spawn(3);
+        graph.root_mut().push(v1);
+        graph.root_mut().push(v2);
+        graph[v1].refs.push((v2, ()));
+        graph[v1].refs.push((v3, ()));
+        graph[v3].refs.push((v2, ()));
+
+        assert_eq!(graph.remove(v2), 2);
+    }
+    anchor!(graph);
+    let v1 = graph.root()[0];
+    assert_eq!(graph.node_count(), 2);
+    assert_eq!(graph.degree(v1), 1);
+    let v3 = graph.neighbors(v1).next().unwrap();
+    assert_eq!(graph.degree(v3), 0);
+    assert_eq!(graph.root().len(), 1);
+}
+
+#[test]
+fn test_take() {
+    let mut graph = VecGraph::<NamedNode<String, ()>>::new();
+    {
+        anchor_mut!(graph, Always);
+        let v1 = graph.spawn("first".to_string());
+        let v2 = graph.spawn("second".to_string());
+        let v3 = graph.spawn("third".to_string());
+        graph.root_mut().push(v1);
+        graph.root_mut().push(v2);
+        graph[v1].refs.insert(v2, ());
+        graph[v1].refs.insert(v3, ());
+
+        assert_eq!(unsafe { graph.take(v2) }, "second");
+        assert_eq!(graph.node_count(), 2);
+        assert_eq!(graph.degree(v1), 1);
+        assert_eq!(unsafe { graph.take(v3) }, "third");
+        assert_eq!(graph.degree(v1), 0);
+    }
+    anchor!(graph);
+    assert_eq!(graph.node_count(), 1);
+    let v1 = graph.root()[0];
+    assert_eq!(graph.degree(v1), 0);
+    assert_eq!(graph[v1].data, "first");
+}
+
+#[test]
+fn test_old_nodes_survive_full_cleanup() {
+    let mut graph = VecGraph::<VecNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let x = graph.spawn(1);
+        let y = graph.spawn(2);
+        graph.connect(x, y, ());
+        graph.root_mut().push(x);
+    }
+    // Minor cleanups only touch the old nodes held by the root, Y is skipped by every one of them.
+    for _ in 0..6 {
+        {
+            anchor_mut!(graph, Never);
+            graph.root_mut();
+        }
+        graph.cleanup();
+    }
+    assert_eq!(graph.cleanup_precise().nodes_freed, 0);
+    anchor!(graph);
+    let x = graph.root()[0];
+    let y = graph.edges(x).next().unwrap().ptr;
+    assert_eq!(graph[y].data, 2);
+}
+
+#[test]
+fn test_pin() {
+    let mut graph = VecGraph::<NamedNode<i32, ()>>::new();
+    anchor_mut!(first, graph, Always);
+    let v1 = first.spawn(1);
+    let v2 = first.spawn(2);
+    first[v1].refs.insert(v2, ());
+    let token = first.pin(v1);
+    first.spawn(3);
+    drop(first);
+    assert_eq!(graph.cleanup_precise().nodes_retained, 2);
+
+    anchor_mut!(second, graph, Never);
+    let v1 = second.pinned(&token).unwrap();
+    assert_eq!(second[v1].data, 1);
+    assert!(second.find_unreachable().is_empty());
+    second.unpin(token);
+    drop(second);
+    assert_eq!(graph.cleanup_precise().nodes_freed, 2);
+}
+
+#[test]
+fn test_pin_stale() {
+    let mut graph = VecGraph::<NamedNode<i32, ()>>::new();
+    anchor_mut!(first, graph, Never);
+    let v1 = first.spawn(1);
+    let stale = first.pin(v1);
+    first.remove(v1);
+
+    anchor_mut!(second, graph, Never);
+    assert!(second.pinned(&stale).is_none());
+    let v2 = second.spawn(2);
+    let token = second.pin(v2);
+    second.unpin(stale);
+    assert!(second.pinned(&token) == Some(v2));
+    drop(second);
+    assert_eq!(graph.cleanup_precise().nodes_retained, 1);
+}
+
+#[test]
+fn test_in_neighbors() {
+    let mut graph = VecGraph::<VecNode<i32, ()>>::new();
+    for indexed in [false, true] {
+        graph.set_reverse_index(indexed);
+        anchor_mut!(graph, Always);
+        let v1 = graph.spawn(1);
+        let v2 = graph.spawn(2);
+        let v3 = graph.spawn(3);
+        graph[v1].refs.push((v3, ()));
+        graph[v2].refs.push((v3, ()));
+        graph[v2].refs.push((v3, ()));
+
+        assert_eq!(graph.in_degree(v3), 3);
+        assert_eq!(graph.in_degree(v1), 0);
+
+        graph[v2].refs.clear();
+        assert!(graph.in_neighbors(v3).collect::<Vec<_>>() == vec![v1]);
+    }
+}
+
+#[test]
+fn test_reverse_index_incremental() {
+    let mut graph = VecGraph::<VecNode<i32, ()>>::new();
+    graph.set_reverse_index(true);
+    {
+        anchor_mut!(graph, Never);
+        let v1 = graph.spawn(1);
+        let v2 = graph.spawn(2);
+        let v3 = graph.spawn(3);
+        graph.root_mut().push(v1);
+        graph.root_mut().push(v3);
+        graph.connect(v1, v3, ());
+        assert_eq!(graph.in_degree(v3), 1);
+        graph.connect(v2, v3, ());
+        graph.connect(v2, v1, ());
+        assert_eq!(graph.in_degree(v3), 2);
+        assert!(graph.in_neighbors(v1).collect::<Vec<_>>() == vec![v2]);
+        graph.disconnect(v1, v3);
+        assert!(graph.in_neighbors(v3).collect::<Vec<_>>() == vec![v2]);
+    }
+    //Collected nodes are no longer sources.
+    graph.cleanup_precise();
+    anchor_mut!(graph, Never);
+    let v1 = graph.root()[0];
+    let v3 = graph.root()[1];
+    assert_eq!(graph.in_degree(v3), 0);
+    assert_eq!(graph.in_degree(v1), 0);
+    graph.connect(v3, v1, ());
+    assert!(graph.in_neighbors(v1).collect::<Vec<_>>() == vec![v3]);
+}
+
+#[test]
+fn test_di_node() {
+    let mut graph = VecGraph::<DiNode<i32, i32>>::new();
+    anchor_mut!(graph, Always);
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    let v3 = graph.spawn(3);
+    graph.root_mut().push(v1);
+
+    graph.connect(v1, v2, 12);
+    graph.connect(v3, v2, 32);
+    assert_eq!(graph.connect(v1, v2, 21), Some(12));
+
+    let mut incoming : Vec<_> = graph.edges_in(v2).map(|x| *x.values.edge()).collect();
+    incoming.sort();
+    assert_eq!(incoming, vec![21, 32]);
+    assert!(graph[v2].in_refs().contains(&v3));
+
+    assert_eq!(graph.disconnect(v3, v2), Some(32));
+    assert_eq!(graph.disconnect(v3, v2), None);
+    let cursor = graph.cursor(v2);
+    assert!(cursor.edges_in().map(|x| x.ptr).collect::<Vec<_>>() == vec![v1]);
+    assert_eq!(cursor.edges_out().count(), 0);
+    assert_eq!(graph.edges_out(v1).map(|x| *x.values.that().this).sum::<i32>(), 2);
+}
+
+#[test]
+fn test_di_node_garbage() {
+    let mut graph = VecGraph::<DiNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let v1 = graph.spawn(1);
+        let v2 = graph.spawn(2);
+        let v3 = graph.spawn(3);
+        let v4 = graph.spawn(4);
+        graph.root_mut().push(v1);
+        graph.connect(v1, v2, ());
+        graph.connect(v3, v2, ());
+        graph.connect(v4, v1, ());
+    }
+    assert_eq!(graph.cleanup_precise().nodes_freed, 2);
+    {
+        anchor_mut!(graph, Never);
+        let v1 = graph.root()[0];
+        let v2 = graph.edges_out(v1).next().unwrap().ptr;
+        assert!(graph[v1].in_refs().is_empty());
+        assert!(graph[v2].in_refs().len() == 1);
+        graph.root_mut().push(v2);
+        graph.remove(v1);
+    }
+    anchor!(graph);
+    assert!(graph[graph.root()[0]].in_refs().is_empty());
+}
+
+#[test]
+fn test_undirected_node() {
+    let mut graph = VecGraph::<UndirectedNode<i32, i32>>::new();
+    anchor_mut!(graph, Always);
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    let v3 = graph.spawn(3);
+    graph.root_mut().push(v1);
+
+    graph.connect(v1, v2, 12);
+    graph.connect(v3, v2, 23);
+    assert_eq!(graph.connect(v2, v1, 21), Some(12));
+    assert_eq!(graph.edge(v1, v2), Some(&21));
+    assert_eq!(graph.edge(v2, v1), Some(&21));
+    assert!(graph[v3].refs().contains(&v2));
+
+    let mut edges : Vec<_> = graph.edges(v2).map(|x| *x.values.edge()).collect();
+    edges.sort();
+    assert_eq!(edges, vec![21, 23]);
+    for item in graph.edges_mut(v2) {
+        *item.values.edge() += 100;
+    }
+    assert_eq!(graph.edge(v3, v2), Some(&123));
+
+    assert_eq!(graph.disconnect(v2, v3), Some(123));
+    assert_eq!(graph.disconnect(v3, v2), None);
+    assert!(graph[v3].refs().is_empty());
+    assert!(graph.cursor(v2).edges().map(|x| x.ptr).collect::<Vec<_>>() == vec![v1]);
+}
+
+#[test]
+fn test_connect() {
+    let mut graph = NamedGraph::<NamedNode<i32, i32>>::new();
+    anchor_mut!(graph, Never);
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    assert_eq!(graph.connect(v1, v2, 12), None);
+    assert_eq!(graph.connect(v1, v2, 13), Some(12));
+    assert_eq!(*graph.connect_or_update(v1, v2, 0, |e| *e += 1), 14);
+    assert_eq!(*graph.connect_or_update(v2, v1, 21, |e| *e += 1), 21);
+    assert_eq!(graph.disconnect(v1, v2), Some(14));
+    assert_eq!(graph.disconnect(v1, v2), None);
+
+    let mut graph = VecGraph::<VecNode<i32, i32>>::new();
+    anchor_mut!(graph, Never);
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    assert_eq!(graph.connect(v1, v2, 12), None);
+    assert_eq!(graph.connect(v1, v2, 13), Some(12));
+    assert_eq!(graph[v1].refs.len(), 1);
+    assert_eq!(*graph.connect_or_update(v1, v1, 11, |e| *e += 1), 11);
+    assert_eq!(*graph.connect_or_update(v1, v1, 0, |e| *e += 1), 12);
+    assert_eq!(graph.disconnect(v1, v2), Some(13));
+    assert_eq!(graph[v1].refs.len(), 1);
+
+    let mut graph = OptionGraph::<OptionNode<i32, i32>>::new();
+    anchor_mut!(graph, Never);
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    assert_eq!(graph.connect(v1, v2, 12), None);
+    assert_eq!(graph.disconnect(v1, v1), None);
+    assert_eq!(*graph.connect_or_update(v1, v1, 11, |e| *e += 1), 11);
+    assert_eq!(*graph.connect_or_update(v1, v1, 0, |e| *e += 1), 12);
+    assert_eq!(graph.disconnect(v1, v1), Some(12));
+    assert!(graph[v1].refs.is_none());
+
+    let mut graph = VecGraph::<TreeNode<char, i32, i32>>::new();
+    anchor_mut!(graph, Never);
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    assert!(graph.connect(v1, 'a', v2, 12).is_none());
+    assert!(graph.connect(v1, 'a', v1, 11).map(|x| x.1) == Some(12));
+    assert_eq!(*graph.connect_or_update(v1, 'a', v2, 0, |e| *e += 1), 12);
+    assert!(graph[v1].refs[&'a'].0 == v2);
+    let removed = graph.disconnect(v1, &'a');
+    assert!(removed.map(|x| x.1) == Some(12));
+    assert!(graph.disconnect(v1, &'a').is_none());
+}
+
+#[test]
+fn test_connect_sym() {
+    let mut graph = NamedGraph::<NamedNode<i32, i32>>::new();
+    anchor_mut!(graph, Never);
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    let v3 = graph.spawn(3);
+    assert_eq!(graph.connect_sym(v1, v2, 12), (None, None));
+    assert_eq!(graph.connect_sym_pair(v1, v2, 12, 21), (Some(12), Some(12)));
+    assert_eq!(graph[v2].refs[&v1], 21);
+
+    let mut cursor = graph.cursor_mut(v3);
+    cursor.connect_sym(v1, 31);
+    assert_eq!(cursor.disconnect_sym(v2), (None, None));
+    assert_eq!(graph[v1].refs[&v3], 31);
+    assert_eq!(graph.disconnect_sym(v2, v1), (Some(21), Some(12)));
+    assert_eq!(graph[v1].refs.len(), 1);
+    let mut cursor = graph.cursor_mut(v3);
+    assert_eq!(cursor.disconnect_sym(v1), (Some(31), Some(31)));
+}
+
+#[test]
+fn test_spawn_attached() {
+    let mut graph = VecGraph::<VecNode<i32, i32>>::new();
+    anchor_mut!(graph, Always);
+    let root = graph.spawn(0);
+    graph.root_mut().push(root);
+
+    let mut cursor = graph.cursor_mut(root);
+    let mut last = root;
+    for i in 1..4 {
+        last = cursor.spawn_attached(i, i * 10);
+        cursor.jump(last);
+    }
+    assert_eq!(cursor.data, 3);
+    assert_eq!(graph.reachable_count(), 4);
+    assert!(graph[root].refs.len() == 1);
+    assert_eq!(graph[last].data, 3);
+
+    let mut graph = VecGraph::<TreeNode<u8, i32, ()>>::new();
+    anchor_mut!(graph, Always);
+    let root = graph.spawn(0);
+    graph.root_mut().push(root);
+    let mut cursor = graph.cursor_mut(root);
+    let left = cursor.spawn_attached(0, 1, ());
+    cursor.spawn_attached(1, 2, ());
+    assert!(graph[root].refs[&0].0 == left);
+    assert_eq!(graph.reachable_count(), 3);
+}
+
+#[test]
+fn test_cursor_attach() {
+    let mut graph = NamedGraph::<NamedNode<i32, i32>>::new();
+    anchor_mut!(graph, Always);
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    graph.root_mut().insert(v1);
+    let mut cursor = graph.cursor_mut(v1);
+    assert_eq!(cursor.attach(v2, 12), None);
+    assert_eq!(cursor.attach(v2, 13), Some(12));
+    assert_eq!(cursor.detach(v2), Some(13));
+    assert_eq!(cursor.detach(v2), None);
+
+    let mut graph = OptionGraph::<OptionNode<i32, ()>>::new();
+    anchor_mut!(graph, Always);
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    let mut cursor = graph.cursor_mut(v1);
+    cursor.attach(v2, ());
+    assert!(cursor.refs.is_some());
+    assert_eq!(cursor.detach(v1), None);
+    assert_eq!(cursor.detach(v2), Some(()));
+
+    let mut graph = VecGraph::<TreeNode<u8, i32, ()>>::new();
+    anchor_mut!(graph, Always);
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    let mut cursor = graph.cursor_mut(v1);
+    assert!(cursor.attach(0, v2, ()).is_none());
+    assert!(cursor.attach(1, v1, ()).is_none());
+    assert!(cursor.detach(&0).map(|x| x.0) == Some(v2));
+    assert_eq!(cursor.refs.len(), 1);
+}
+
+#[test]
+fn test_cursor_history() {
+    let mut graph = VecGraph::<VecNode<i32, ()>>::new();
+    anchor_mut!(graph, Never);
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    let v3 = graph.spawn(3);
+    let mut cursor = graph.cursor(v1);
+    assert!(!cursor.back());
+    cursor.push_jump(v2);
+    cursor.push_jump(v3);
+    assert!(cursor.trail() == [v1, v2]);
+    assert!(cursor.back());
+    assert!(cursor.is_at(v2));
+    assert!(cursor.back());
+    assert!(cursor.is_at(v1));
+    assert!(cursor.forward());
+    assert!(cursor.is_at(v2));
+    cursor.push_jump(v1);
+    assert!(!cursor.forward());
+    assert!(cursor.trail() == [v1, v2]);
+    assert_eq!(cursor.data, 1);
+}
+
+#[test]
+fn test_cursor_step() {
+    let mut graph = VecGraph::<OptionNode<i32, ()>>::new();
+    anchor_mut!(graph, Never);
+    let head = graph.spawn(0);
+    let mut cursor = graph.cursor_mut(head);
+    for i in 1..4 {
+        let next = cursor.spawn_attached(i, ());
+        cursor.jump(next);
+    }
+    let mut cursor = graph.cursor(head);
+    let mut sum = 0;
+    while cursor.step(()).is_ok() {
+        sum += cursor.data;
+    }
+    assert_eq!(sum, 6);
+    assert_eq!(cursor.step(()), Err(StepError::NoEdge));
+
+    let mut graph = VecGraph::<VecNode<i32, ()>>::new();
+    anchor_mut!(graph, Never);
+    let v1 = graph.spawn(1);
+    let v2 = graph.cursor_mut(v1).spawn_attached(2, ());
+    let mut cursor = graph.cursor(v1);
+    assert_eq!(cursor.step(1), Err(StepError::OutOfBounds));
+    assert_eq!(cursor.step(0), Ok(()));
+    assert!(cursor.is_at(v2));
+
+    let mut graph = VecGraph::<TreeNode<char, i32, ()>>::new();
+    anchor_mut!(graph, Never);
+    let v1 = graph.spawn(1);
+    graph.cursor_mut(v1).spawn_attached('l', 2, ());
+    let mut cursor = graph.cursor(v1);
+    assert_eq!(cursor.step(&'r'), Err(StepError::NoEdge));
+    assert_eq!(cursor.step(&'l'), Ok(()));
+    assert_eq!(cursor.data, 2);
+
+    let mut graph = NamedGraph::<NamedNode<i32, ()>>::new();
+    anchor_mut!(graph, Never);
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    let mut cursor = graph.cursor_mut(v1);
+    assert_eq!(cursor.step(v2), Err(StepError::NoEdge));
+    cursor.attach(v2, ());
+    assert_eq!(cursor.step(v2), Ok(()));
+}
+
+#[test]
+fn test_remove_current() {
+    let mut graph = VecGraph::<OptionNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, Always);
+        let v1 = graph.spawn(1);
+        graph.root_mut().push(v1);
+        let mut cursor = graph.cursor_mut(v1);
+        let v2 = cursor.spawn_attached(2, ());
+        cursor.jump(v2);
+        let v3 = cursor.spawn_attached(3, ());
+        graph.root_mut().push(v2);
+
+        let mut cursor = graph.cursor_mut(v1);
+        fn is_send<T : Send>(_ : &T) {}
+        is_send(&cursor);
+        cursor.push_jump(v2);
+        assert_eq!(unsafe { cursor.remove_current(v3) }, 2);
+        assert!(cursor.is_at(v3));
+        assert!(cursor.trail() == [v1]);
+        assert_eq!(graph.node_count(), 2);
+        assert!(graph[v1].refs.is_none());
+        assert!(graph.root().len() == 1);
+        graph.cursor_mut(v1).attach(v3, ());
+    }
+    anchor!(graph);
+    assert_eq!(graph.node_count(), 2);
+}
+
+#[test]
+fn test_clear_edges() {
+    let mut graph = VecGraph::<NamedNode<i32, ()>>::new();
+    anchor_mut!(graph, Never);
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    graph.connect_sym(v1, v2, ());
+    graph.clear_edges(v1);
+    assert!(graph[v1].refs.is_empty());
+    assert_eq!(graph[v2].refs.len(), 1);
+    graph.cursor_mut(v2).clear_edges();
+    assert!(graph[v2].refs.is_empty());
+
+    let mut graph = VecGraph::<DiNode<i32, ()>>::new();
+    anchor_mut!(graph, Never);
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    graph.connect(v1, v2, ());
+    graph.connect(v1, v1, ());
+    graph.connect(v2, v1, ());
+    graph.clear_edges(v1);
+    assert!(graph[v1].out_refs.is_empty());
+    assert!(graph[v2].in_refs().is_empty());
+    assert!(graph[v1].in_refs().contains(&v2));
+    assert_eq!(graph[v1].in_refs().len(), 1);
+
+    let mut graph = VecGraph::<UndirectedNode<i32, ()>>::new();
+    anchor_mut!(graph, Never);
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    let v3 = graph.spawn(3);
+    graph.connect(v1, v2, ());
+    graph.connect(v3, v2, ());
+    graph.cursor_mut(v2).clear_edges();
+    assert!(graph[v1].refs().is_empty());
+    assert!(graph[v3].refs().is_empty());
+    assert!(graph.edge(v1, v2).is_none());
+}
+
+#[test]
+fn test_get_disjoint_mut() {
+    let mut graph = VecGraph::<TreeNode<bool, i32, ()>>::new();
+    anchor_mut!(graph, Never);
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    let v3 = graph.spawn(3);
+    assert!(graph.get_disjoint_mut([v1, v2, v1]).is_none());
+
+    // rotate v1 -> v2 -> v3 into v2 -> {v1, v3}
+    graph.connect(v1, true, v2, ());
+    graph.connect(v2, true, v3, ());
+    let [a, b, c] = graph.get_disjoint_mut([v1, v2, v3]).unwrap();
+    b.refs.clear();
+    a.refs.clear();
+    b.refs.insert(false, (v1, ()));
+    b.refs.insert(true, (v3, ()));
+    core::mem::swap(&mut a.data, &mut c.data);
+    assert_eq!(graph[v1].data, 3);
+    assert_eq!(graph.neighbors(v2).count(), 2);
+
+    let mut graph = VecGraph::<VecNode<i32, ()>>::new();
+    anchor_mut!(graph, Never);
+    let v1 = graph.spawn(1);
+    let [a] = graph.get_disjoint_mut([v1]).unwrap();
+    a.data = 10;
+    assert_eq!(graph[v1].data, 10);
+}
+
+#[test]
+fn test_swap_data() {
+    let mut graph = VecGraph::<VecNode<String, ()>>::new();
+    anchor_mut!(graph, Never);
+    let v1 = graph.spawn("a".to_string());
+    let v2 = graph.spawn("b".to_string());
+    graph.connect(v1, v2, ());
+    graph.swap_data(v1, v2);
+    graph.swap_data(v1, v1);
+    assert_eq!(graph[v1].data, "b");
+    assert_eq!(graph[v2].data, "a");
+    assert!(graph.neighbors(v1).next() == Some(v2));
+}
+
+#[test]
+fn test_edge_entry() {
+    let mut graph = NamedGraph::<NamedNode<i32, i32>>::new();
+    anchor_mut!(graph, Never);
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    *graph.edge_entry(v1, v2).or_insert(0) += 5;
+    graph.edge_entry(v1, v2).and_modify(|e| *e *= 2).or_insert(0);
+    graph.edge_entry(v2, v1).and_modify(|e| *e *= 2).or_insert(7);
+    assert!(*graph.edge_entry(v1, v2).key() == v2);
+    assert_eq!(graph[v1].refs[&v2], 10);
+    assert_eq!(graph[v2].refs[&v1], 7);
+}
+
+#[test]
+fn test_cursor_edge_entry() {
+    let mut graph = NamedGraph::<NamedNode<i32, i32>>::new();
+    anchor_mut!(graph, Never);
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    let mut cursor = graph.cursor_mut(v1);
+    cursor.edge_entry(v2).or_insert(3);
+    cursor.edge_entry(v2).and_modify(|e| *e += 1).or_insert(0);
+    cursor.jump(v2);
+    cursor.edge_entry(v1).and_modify(|e| *e += 1).or_insert(9);
+    assert_eq!(graph[v1].refs[&v2], 4);
+    assert_eq!(graph[v2].refs[&v1], 9);
+}
+
+#[test]
+fn test_tree_ordered_edges() {
+    let mut graph = VecGraph::<TreeNode<u32, char, ()>>::new();
+    anchor_mut!(graph, Never);
+    let root = graph.spawn('r');
+    let mut cursor = graph.cursor_mut(root);
+    assert!(cursor.first_edge().is_none());
+    for (key, data) in [(10, 'a'), (20, 'b'), (30, 'c'), (40, 'd')] {
+        cursor.spawn_attached(key, data, ());
+    }
+    cursor.attach(0, root, ());
+
+    let cursor = graph.cursor(root);
+    let keys : Vec<_> = cursor.range(15..=30).map(|x| *x.0).collect();
+    assert_eq!(keys, vec![20, 30]);
+    let data : String = cursor.range(20..).rev().map(|x| *x.1.values.that().this).collect();
+    assert_eq!(data, "dcb");
+    let (key, item) = cursor.first_edge().unwrap();
+    assert_eq!(*key, 0);
+    assert!(item.ptr == root);
+    assert_eq!(*cursor.last_edge().unwrap().1.values.that().this, 'd');
+    assert_eq!(*cursor.nearest(&25).unwrap().0, 20);
+    assert_eq!(*cursor.nearest(&30).unwrap().0, 30);
+    assert!(graph.cursor(item.ptr).nearest(&0).is_some());
+
+    let view = &graph[root];
+    let keys : Vec<_> = view.range(..20).map(|x| *x.0).collect();
+    assert_eq!(keys, vec![0, 10]);
+    assert!(view.first_edge().unwrap().1.0 == root);
+    assert_eq!(*view.last_edge().unwrap().0, 40);
+    assert_eq!(*view.nearest(&39).unwrap().0, 30);
+}
+
+#[test]
+fn test_vec_edge_helpers() {
+    let mut graph = VecGraph::<VecNode<i32, i32>>::new();
+    anchor_mut!(graph, Never);
+    let root = graph.spawn(0);
+    let v1 = graph.spawn(1);
+    let v2 = graph.spawn(2);
+    let mut cursor = graph.cursor_mut(root);
+    cursor.attach(v1, 3);
+    cursor.insert_edge_at(0, v2, 1);
+    cursor.insert_edge_at(1, v1, 2);
+    cursor.insert_edge_at(3, v2, 1);
+    cursor.sort_edges_by(|a, b| a.1.cmp(b.1));
+    let order : Vec<_> = cursor.refs.iter().map(|x| x.1).collect();
+    assert_eq!(order, vec![1, 1, 2, 3]);
+    cursor.dedup_edges_by(|a, b| a.0 == b.0);
+    assert_eq!(cursor.refs.len(), 2);
+    cursor.swap_edges(0, 1);
+    assert!(cursor.refs[0].0 == v1 && cursor.refs[1].0 == v2);
+    assert_eq!(cursor.remove_edge_at(0).map(|x| x.1), Some(2));
+    assert!(cursor.remove_edge_at(5).is_none());
+    assert_eq!(graph[root].refs.len(), 1);
+}
+
+#[cfg(feature = "smallvec")]
+#[test]
+fn test_small_vec_node() {
+    let mut graph = VecGraph::<SmallVecNode<i32, i32, 2>>::new();
+    {
+        anchor_mut!(graph, Always);
+        let root = graph.spawn(0);
+        graph.root_mut().push(root);
+        let children : Vec<_> = (1..5).map(|i| graph.spawn(i)).collect();
+        for &child in &children {
+            let weight = graph[child].data * 10;
+            graph[root].refs.push((child, weight));
+        }
+        assert!(graph[root].refs.spilled());
+        assert_eq!(graph.edges(root).map(|x| *x.values.edge()).sum::<i32>(), 100);
+        for item in graph.edges_mut(root) {
+            *item.values.that().this += 10;
+        }
+        let removed = graph[root].refs.remove(0);
+        assert!(removed.0 == children[0]);
+        assert!(graph.cursor(root).neighbors().collect::<Vec<_>>() == children[1..]);
+        graph.cursor_mut(root).refs.pop();
+        graph[root].refs.shrink_to_fit();
+        assert!(!graph[root].refs.spilled());
+        assert_eq!(graph.degree(root), 2);
+        assert_eq!(graph[children[1]].data, 12);
+    }
+    anchor!(graph);
+    assert_eq!(graph.node_count(), 3);
+}
+
+#[cfg(feature = "indexmap")]
+#[test]
+fn test_ordered_named_node() {
+    let mut graph = VecGraph::<OrderedNamedNode<i32, i32>>::new();
+    {
+        anchor_mut!(graph, Always);
+        let root = graph.spawn(0);
+        graph.root_mut().push(root);
+        let nodes : Vec<_> = (1..6).map(|i| graph.spawn(i)).collect();
+        for &node in nodes.iter().rev() {
+            graph.connect(root, node, 0);
+        }
+        assert_eq!(graph.connect(root, nodes[2], 7), Some(0));
+        assert_eq!(graph.disconnect(root, nodes[3]), Some(0));
+        *graph.connect_or_update(root, nodes[3], 9, |_| ()) += 1;
+
+        let order : Vec<_> = graph.edges(root).map(|x| *x.values.that().this).collect();
+        assert_eq!(order, vec![5, 3, 2, 1, 4]);
+        assert_eq!(graph[root].refs[&nodes[2]], 7);
+        assert_eq!(graph[root].refs.get_index(4).map(|x| *x.1), Some(10));
+
+        let res : Result<(), ()> = graph.transaction(|txn| {
+            txn.disconnect(root, nodes[2]);
+            txn.connect(root, nodes[0], 1);
+            Err(())
+        });
+        assert!(res.is_err());
+        let restored : Vec<_> = graph.edges(root).map(|x| (graph[x.ptr].data, *x.values.edge())).collect();
+        assert_eq!(restored, vec![(5, 0), (3, 7), (2, 0), (1, 0), (4, 10)]);
+    }
+    anchor!(graph);
+    assert_eq!(graph.node_count(), 6);
+}
+
+#[test]
+fn test_custom_hasher() {
+    use std::hash::BuildHasherDefault;
+    use std::collections::hash_map::DefaultHasher;
+    type Fixed = BuildHasherDefault<DefaultHasher>;
+
+    let mut graph = NamedGraph::<NamedNode<i32, i32>, Fixed>::new();
+    {
+        anchor_mut!(graph, Always);
+        let a = graph.spawn(1);
+        let b = graph.spawn(2);
+        graph.root_mut().insert(a);
+        graph.root_mut().insert(b);
+        graph.connect(a, b, 3);
+        let sum : i32 = graph.iter().map(|x| *x.values).sum();
+        assert_eq!(sum, 3);
+        graph.root_mut().remove(&b);
+    }
+    anchor!(graph);
+    assert_eq!(graph.node_count(), 2);
+
+    let mut graph = NamedGraph::<NamedNode<i32, i32, Fixed>, Fixed>::new();
+    {
+        anchor_mut!(graph, Always);
+        let a = graph.spawn(1);
+        let b = graph.spawn(2);
+        graph.root_mut().insert(a);
+        graph.connect(a, b, 3);
+        graph.cursor_mut(a).attach(a, 4);
+        assert_eq!(graph.edges(a).map(|x| *x.values.edge()).sum::<i32>(), 7);
+        assert_eq!(graph[a].refs.get(&b), Some(&3));
+        assert_eq!(graph.disconnect(a, b), Some(3));
+    }
+    anchor!(graph);
+    assert_eq!(graph.node_count(), 1);
+}
+
+#[test]
+fn test_set_node() {
+    let mut graph = VecGraph::<SetNode<i32>>::new();
+    {
+        anchor_mut!(graph, Always);
+        let root = graph.spawn(0);
+        graph.root_mut().push(root);
+        let a = graph.spawn(1);
+        let b = graph.spawn(2);
+        assert!(graph.connect(root, a));
+        assert!(!graph.connect(root, a));
+        assert!(graph.connect(root, b));
+        assert!(graph.connect(a, b));
+        for i in graph.edges_mut(root) {
+            *i.values.that().this *= 10;
+        }
+        let mut sum : i32 = graph.edges(root).map(|x| *x.values.that().this).sum();
+        assert_eq!(sum, 30);
+
+        let mut cursor = graph.cursor_mut(a);
+        assert!(cursor.step(root).is_err());
+        let c = cursor.spawn_attached(3);
+        assert!(cursor.detach(b));
+        sum = cursor.neighbors().count() as i32;
+        assert_eq!(sum, 1);
+        assert!(cursor.refs.contains(&c));
+    }
+    anchor!(graph);
+    assert_eq!(graph.node_count(), 4);
+}
+
+#[test]
+fn test_array_node() {
+    //a binary tree
+    let mut graph = OptionGraph::<ArrayNode<i32, (), 2>>::new();
+    {
+        anchor_mut!(graph, Always);
+        let root = graph.spawn(1);
+        *graph.root_mut() = Some(root);
+        let mut cursor = graph.cursor_mut(root);
+        let left = cursor.spawn_attached(0, 2, ());
+        cursor.spawn_attached(1, 3, ());
+        cursor.step(0).unwrap();
+        cursor.spawn_attached(1, 4, ());
+        assert_eq!(cursor.step(0), Err(StepError::NoEdge));
+        assert_eq!(cursor.step(2), Err(StepError::OutOfBounds));
+        assert_eq!(*cursor.get_edge(1).unwrap().that().this, 4);
+        cursor.detach(1);
+        assert!(cursor.get_edge(1).is_none());
+        cursor.jump(root);
+        *cursor.get_edge_mut(0).unwrap().that().this += 10;
+        assert!(cursor.refs[0].as_ref().unwrap().0 == left);
+    }
+    anchor!(graph);
+    assert_eq!(graph.node_count(), 3);
+    let sum : i32 = graph.edges(graph.root().unwrap()).map(|x| *x.values.that().this).sum();
+    assert_eq!(sum, 15);
+}
+
+#[test]
+fn test_multi_named_node() {
+    let mut graph = VecGraph::<MultiNamedNode<&str, i32>>::new();
+    {
+        anchor_mut!(graph, Always);
+        let a = graph.spawn("a");
+        let b = graph.spawn("b");
+        graph.root_mut().push(a);
+        graph.connect(a, b, 5);
+        graph.connect(a, b, 7);
+        graph.connect(a, a, 1);
+
+        let mut parallel : Vec<_> = graph.edges_between(a, b).map(|x| *x.edge()).collect();
+        parallel.sort();
+        assert_eq!(parallel, vec![5, 7]);
+        assert_eq!(graph.edges(a).count(), 3);
+        assert_eq!(graph.neighbors(a).count(), 2);
+
+        let mut cursor = graph.cursor_mut(a);
+        cursor.attach(b, 9);
+        assert_eq!(cursor.edges_between(b).count(), 3);
+        let mut removed = cursor.detach(b);
+        removed.sort();
+        assert_eq!(removed, vec![5, 7, 9]);
+        assert!(cursor.step(b).is_err());
+    }
+    anchor!(graph);
+    assert_eq!(graph.node_count(), 1);
+}
+
+struct Prioritized<'id, T> {
+    items : Vec<(u32, GraphPtr<'id, T>)>,
+}
+
+impl <'id, T> Default for Prioritized<'id, T> {
+    fn default() -> Self {
+        Prioritized { items : Vec::new() }
+    }
+}
+
+impl <'id, T : GraphNode> EdgeCollection<'id, T> for Prioritized<'id, T> {
+    fn visit_ptrs(&self, mut f : impl FnMut(GraphPtr<'id, T>)) {
+        self.items.iter().for_each(|x| f(x.1));
+    }
+
+    fn retain_ptrs(&mut self, mut f : impl FnMut(GraphPtr<'id, T>) -> bool) {
+        self.items.retain(|x| f(x.1));
+    }
+}
+
+struct PrioritizedFamily;
+
+impl EdgeFamily for PrioritizedFamily {
+    type Collection<'id, T : GraphNode> = Prioritized<'id, T>;
+}
+
+#[test]
+fn test_safe_collection() {
+    let mut graph = VecGraph::<NamedNode<i32, ()>>::new();
+    anchor_mut!(graph, Always);
+    let a = graph.spawn(1);
+    let b = graph.spawn(2);
+    let mut edges = SafeCollection(Prioritized::default());
+    edges.items.push((1, a));
+    edges.items.push((3, b));
+    edges.items.push((2, a));
+    assert_eq!(NodeCollection::len(&edges), 3);
+    NodeCollection::unlink(&mut edges, a.as_ptr());
+    assert_eq!(NodeCollection::len(&edges), 1);
+    assert!(edges.items[0].1 == b);
+}
+
+#[test]
+fn test_custom_node() {
+    let mut graph = VecGraph::<CustomNode<i32, PrioritizedFamily>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let a = graph.spawn(1);
+        let b = graph.spawn(2);
+        let c = graph.spawn(3);
+        graph.spawn(4);
+        graph[a].refs.items.push((1, b));
+        graph[b].refs.items.push((2, c));
+        graph[c].refs.items.push((3, a));
+        graph.root_mut().push(a);
+    }
+    assert_eq!(graph.cleanup_precise().nodes_freed, 1);
+    anchor_mut!(graph, Always);
+    let a = graph.root()[0];
+    let b = graph[a].refs.items[0].1;
+    let c = graph[b].refs.items[0].1;
+    assert_eq!(graph[b].data, 2);
+    assert_eq!(graph[c].data, 3);
+    assert!(graph[c].refs.items[0].1 == a);
+}
+
+#[test]
+fn test_map_graph() {
+    let mut graph = MapGraph::<&str, NamedNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, Always);
+        let a = graph.spawn(1);
+        let b = graph.spawn(2);
+        graph.spawn(3);
+        graph.root_mut().insert("a", a);
+        graph.root_mut().insert("b", b);
+        for (_, i) in graph.iter_mut() {
+            *i.values *= 10;
+        }
+        let mut keys : Vec<_> = graph.iter().map(|(k, x)| (*k, *x.values)).collect();
+        keys.sort();
+        assert_eq!(keys, vec![("a", 10), ("b", 20)]);
+        graph.root_mut().remove("b");
+    }
+    anchor!(graph);
+    assert_eq!(graph.node_count(), 1);
+    assert_eq!(graph[graph.root()["a"]].data, 10);
+}
+
+#[test]
+fn test_deque_and_slab_roots() {
+    let mut graph = DequeGraph::<NamedNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, Always);
+        for i in 0..3 {
+            let node = graph.spawn(i);
+            graph.root_mut().push_front(node);
+        }
+        graph.root_mut().pop_back();
+        let data : Vec<_> = graph.iter().map(|x| *x.values).collect();
+        assert_eq!(data, vec![2, 1]);
+    }
+    anchor!(graph);
+    assert_eq!(graph.node_count(), 2);
+
+    let mut graph = SlabGraph::<NamedNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, Always);
+        let keys : Vec<_> = (0..3).map(|i| {
+            let node = graph.spawn(i);
+            graph.root_mut().insert(node)
+        }).collect();
+        graph.root_mut().remove(keys[0]);
+        //the remaining keys stay valid
+        assert_eq!(graph[graph.root()[keys[2]]].data, 2);
+        let reused = {
+            let node = graph.spawn(3);
+            graph.root_mut().insert(node)
+        };
+        assert_eq!(reused, keys[0]);
+        for (_, x) in graph.iter_mut() {
+            *x.values += 1;
+        }
+        let mut data : Vec<_> = graph.iter().map(|(k, x)| (k, *x.values)).collect();
+        data.sort();
+        assert_eq!(data, vec![(0, 4), (1, 2), (2, 3)]);
+        graph.root_mut().remove(keys[1]);
+    }
+    anchor!(graph);
+    assert_eq!(graph.node_count(), 2);
+}
+
+composite_root!{
+    struct CfgRoots<'id, T> {
+        entry : RootOption<'id, T>,
+        exits : RootVec<'id, T>,
+        named : RootHashMap<'id, &'static str, T>,
+    }
+}
+
+#[test]
+fn test_composite_root() {
+    let mut graph = GenericGraph::<CfgRoots<'static, VecNode<i32, ()>>, _>::new();
+    {
+        anchor_mut!(graph, Always);
+        let entry = graph.spawn(0);
+        let exit = graph.spawn(1);
+        let named = graph.spawn(2);
+        graph.spawn(3);
+        graph.root_mut().entry = Some(entry);
+        graph.root_mut().exits.push(exit);
+        graph.root_mut().named.insert("main", named);
+        assert!(graph.root().exits[0] == exit);
+    }
+    {
+        anchor!(graph);
+        assert_eq!(graph.node_count(), 3);
+    }
+
+    let mut graph = GenericGraph::<(RootVec<'static, _>, RootOption<'static, _>), VecNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, Always);
+        graph.spawn(0);
+    }
+    anchor!(graph);
+    assert_eq!(graph.node_count(), 0);
+}
+
+struct Block(u32);
+struct Branch;
+
+payload_enum!{
+    enum CfgNode {
+        Block(Block),
+        Branch(Branch),
+    }
+}
+
+#[test]
+fn test_enum_payload() {
+    let mut graph = VecGraph::<NamedNode<CfgNode, i32>>::new();
+    anchor_mut!(graph, Never);
+    let branch = graph.spawn_variant(Branch);
+    graph.root_mut().push(branch);
+    let mut cursor = graph.cursor_mut(branch);
+    cursor.spawn_attached(Block(1).into(), 10);
+    let b = cursor.spawn_attached(Block(2).into(), 20);
+    cursor.attach(branch, 30);
+
+    let mut blocks : Vec<_> = graph.edges(branch).edges_filtered::<Block>().map(|x| (x.values.this.0, *x.values.edge)).collect();
+    blocks.sort();
+    assert_eq!(blocks, vec![(1, 10), (2, 20)]);
+    assert_eq!(graph.edges(branch).edges_filtered::<Branch>().count(), 1);
+
+    graph.get_variant_mut::<Block>(b).unwrap().0 = 5;
+    assert_eq!(graph.get_variant::<Block>(b).map(|x| x.0), Some(5));
+    assert!(graph.get_variant::<Branch>(b).is_none());
+}
+
+struct Function {
+    body : VecGraph<VecNode<i32, ()>>,
+}
+
+impl NestedGraphs for Function {
+    fn cleanup_nested(&mut self) {
+        self.body.cleanup_precise();
+    }
+}
+
+#[test]
+fn test_nested_graphs() {
+    let mut module = VecGraph::<VecNode<Function, ()>>::new();
+    {
+        anchor_mut!(module, Never);
+        let f = module.spawn(Function { body : VecGraph::new() });
+        module.root_mut().push(f);
+        let sum = module.descend(f, |x| &mut x.body, Never, |mut body| {
+            let entry = body.spawn(1);
+            body.root_mut().push(entry);
+            let exit = body.spawn(2);
+            body.connect(entry, exit, ());
+            body.spawn(3);
+            body.iter().map(|x| *x.values).sum::<i32>()
+        });
+        assert_eq!(sum, 1);
+    }
+    module.cleanup_recursive();
+    anchor!(module);
+    let f = module.root()[0];
+    let count = module.descend(f, |x| &x.body, |body| body.node_count());
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn test_ptr_hasher() {
+    let mut graph = NamedGraph::<NamedNode<i32, ()>, BuildPtrHasher>::new();
+    {
+        anchor_mut!(graph, Always);
+        let nodes : Vec<_> = (0..100).map(|i| graph.spawn(i)).collect();
+        for &i in &nodes {
+            graph.root_mut().insert(i);
+        }
+        for pair in nodes.windows(2) {
+            graph.connect(pair[0], pair[1], ());
+        }
+        assert!(graph.root().contains(&nodes[50]));
+        assert_eq!(graph.iter().count(), 100);
+    }
+    anchor!(graph);
+    assert_eq!(graph.node_count(), 100);
+}
+
+#[test]
+fn test_ptr_hasher_low_bits() {
+    use std::hash::Hasher;
+    //hash tables pick buckets by the low bits, aligned pointers have to spread over them
+    let mut buckets = std::collections::HashSet::new();
+    for i in 0..64usize {
+        let mut hasher = PtrHasher::default();
+        hasher.write_usize(0x7f00_0000_1000 + i * 64);
+        buckets.insert(hasher.finish() & 63);
+    }
+    assert!(buckets.len() > 32);
+}
+
+#[cfg(feature = "hashbrown")]
+#[test]
+fn test_ptr_lookup() {
+    let mut graph = PtrSetGraph::<NamedNode<i32, ()>>::new();
+    {
+        anchor_mut!(graph, Always);
+        let a = graph.spawn(0);
+        let b = graph.spawn(1);
+        graph.root_mut().insert(a);
+        assert!(graph.root().contains_ptr(a.as_ptr()));
+        assert!(!graph.root().contains_ptr(b.as_ptr()));
+        assert!(graph.root().get_by_ptr(a.as_ptr()).unwrap().0 == &a);
+
+        let mut map = NodePtrMap::default();
+        map.insert(b, 5);
+        assert_eq!(map.get_by_ptr(b.as_ptr()).map(|x| *x.1), Some(5));
+        assert!(map.get_by_ptr(a.as_ptr()).is_none());
+    }
+    assert_eq!(graph.node_count(), 1);
+}
+
+struct Counting(std::sync::Arc<std::sync::atomic::AtomicIsize>);
+
+impl Counting {
+    fn live(&self) -> isize {
+        self.0.load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
+unsafe impl Allocator for Counting {
+    fn allocate(&self, layout : std::alloc::Layout) -> Result<core::ptr::NonNull<[u8]>, AllocError> {
+        self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr : core::ptr::NonNull<u8>, layout : std::alloc::Layout) {
+        self.0.fetch_sub(1, std::sync::atomic::Ordering::Relaxed);
+        Global.deallocate(ptr, layout)
+    }
+}
+
+#[test]
+fn test_custom_allocator() {
+    let live = Counting(Default::default());
+    let mut graph = VecGraph::<NamedNode<String, ()>, Counting>::with_allocator(Counting(live.0.clone()));
+    {
+        anchor_mut!(graph, Always);
+        let a = graph.spawn("a".to_string());
+        graph.root_mut().push(a);
+        for i in 0..10 {
+            graph.spawn(i.to_string());
+        }
+        // nodes share chunks
+        assert!(live.live() > 0 && live.live() < 11);
+        assert_eq!(graph.cursor(a).data, "a");
+    }
+    // memory of collected nodes is kept for reuse
+    let chunks = live.live();
+    {
+        anchor_mut!(graph, Always);
+        for i in 0..10 {
+            graph.spawn(i.to_string());
+        }
+    }
+    assert_eq!(live.live(), chunks);
+    drop(graph);
+    assert_eq!(live.live(), 0);
+}
+
+#[test]
+fn test_reserve() {
+    let mut graph = VecGraph::<NamedNode<usize, ()>>::with_capacity(100);
+    {
+        anchor_mut!(graph, Never);
+        for i in 0..100 {
+            let node = graph.spawn(i);
+            graph.root_mut().push(node);
+        }
+        assert_eq!(graph.node_count(), 100);
+    }
+
+    let live = Counting(Default::default());
+    let mut graph = VecGraph::<NamedNode<usize, ()>, Counting>::with_allocator(Counting(live.0.clone()));
+    anchor_mut!(graph, Never);
+    graph.spawn(0);
+    graph.reserve(1000);
+    let chunks = live.live();
+    for i in 0..1000 {
+        graph.spawn(i);
+    }
+    assert_eq!(live.live(), chunks);
+    graph.spawn(0);
+    assert_eq!(live.live(), chunks + 1);
+}
+
+#[test]
+fn test_shrink_policy() {
+    let mut graph = VecGraph::<NamedNode<usize, ()>>::new();
+    graph.set_shrink_policy(ShrinkPolicy::Never);
+    {
+        anchor_mut!(graph, Always);
+        for i in 0..100 {
+            graph.spawn(i);
+        }
+    }
+    {
+        anchor_mut!(graph, Never);
+        assert_eq!(graph.node_count(), 0);
+        assert!(graph.capacity() >= 100);
+        assert!(graph.memory_usage().node_bytes > 0);
+        graph.shrink_to_fit();
+        assert_eq!(graph.capacity(), 0);
+        assert_eq!(graph.memory_usage().node_bytes, 0);
+    }
+    {
+        // A surviving node keeps its chunk, empty chunks are released.
+        anchor_mut!(graph, Always);
+        for i in 0..100 {
+            let node = graph.spawn(i);
+            if i == 0 {
+                graph.root_mut().push(node);
+            }
+        }
+    }
+    let before = {
+        anchor!(graph);
+        graph.memory_usage().node_bytes
+    };
+    {
+        anchor_mut!(graph, Never);
+        graph.shrink_to_fit();
+        let after = graph.memory_usage().node_bytes;
+        assert!(after > 0 && after < before);
+        graph.root_mut().clear();
+    }
+    {
+        anchor_mut!(graph, Always);
+    }
+    {
+        anchor_mut!(graph, Never);
+        graph.shrink_to_fit();
+        assert_eq!(graph.memory_usage().node_bytes, 0);
+    }
+
+    graph.set_shrink_policy(ShrinkPolicy::WhenUnusedExceeds(50));
+    let capacity = {
+        anchor_mut!(graph, Always);
+        graph.reserve(40);
+        for i in 0..10 {
+            graph.spawn(i);
+        }
+        graph.capacity()
+    };
+    {
+        anchor!(graph);
+        assert_eq!(graph.capacity(), capacity);
+    }
+    {
+        anchor_mut!(graph, Always);
+        graph.reserve(100);
+    }
+    anchor!(graph);
+    assert_eq!(graph.capacity(), 0);
+}
+
+#[test]
+fn test_memory_usage() {
+    let mut graph = VecGraph::<VecNode<usize, usize>>::new();
+    anchor_mut!(graph, Never);
+    let empty = graph.memory_usage();
+    assert_eq!(empty, MemoryReport::default());
+
+    let root = graph.spawn(0);
+    graph.root_mut().push(root);
+    for i in 0..10 {
+        let node = graph.spawn(i);
+        graph[root].refs.push((node, i));
+    }
+    let report = graph.memory_usage();
+    assert!(report.index_bytes >= 11 * std::mem::size_of::<usize>());
+    assert!(report.node_bytes >= 11 * std::mem::size_of::<usize>());
+    assert!(report.edge_bytes >= 10 * 2 * std::mem::size_of::<usize>());
+    assert_eq!(report.total_bytes, report.index_bytes + report.node_bytes + report.edge_bytes);
+}
+
+#[test]
+fn test_stable_id() {
+    let mut graph = VecGraph::<VecNode<usize, ()>>::new();
+    let (kept, dropped, killed) = {
+        anchor_mut!(graph, Always);
+        let root = graph.spawn(0);
+        graph.root_mut().push(root);
+        let garbage = graph.spawn(1);
+        let victim = graph.spawn(2);
+        let kept = graph.stable_id(root);
+        assert_eq!(graph.stable_id(root), kept);
+        let dropped = graph.stable_id(garbage);
+        let killed = graph.stable_id(victim);
+        assert!(graph.resolve(killed) == Some(victim));
+        unsafe {
+            graph.kill(victim);
+        }
+        assert!(graph.resolve(killed).is_none());
+        (kept, dropped, killed)
+    };
+    anchor!(graph);
+    assert_eq!(graph[graph.resolve(kept).unwrap()].data, 0);
+    assert!(graph.resolve(dropped).is_none());
+    assert!(graph.resolve(killed).is_none());
+    assert!(graph.resolve(NodeId::from_u64(kept.as_u64())) == graph.resolve(kept));
+}
+
+#[test]
+fn test_ptr_index() {
+    let mut graph = VecGraph::<VecNode<&str, ()>>::new();
+    let selected = {
+        anchor_mut!(graph, Always);
+        let root = graph.spawn("root");
+        graph.root_mut().push(root);
+        graph.spawn("garbage");
+        let selected = graph.spawn("selected");
+        graph[root].refs.push((selected, ()));
+        let index = graph.ptr_to_index(selected);
+        assert!(graph.index_to_ptr(index) == Some(selected));
+        graph.spawn("new");
+        assert_eq!(graph.ptr_to_index(selected), index);
+        assert!(graph.index_to_ptr(graph.node_count()).is_none());
+        for (i, x) in graph.all_nodes().enumerate() {
+            assert_eq!(graph.ptr_to_index(x.ptr), i);
+        }
+        index
+    };
+    anchor!(graph);
+    assert_eq!(graph.node_count(), 2);
+    assert!(graph.index_to_ptr(selected).is_none());
+    for i in 0..graph.node_count() {
+        assert_eq!(graph.ptr_to_index(graph.index_to_ptr(i).unwrap()), i);
+    }
+}
+
+#[test]
+fn test_weak_ptr() {
+    let mut graph = VecGraph::<VecNode<usize, ()>>::new();
+    let (kept, dropped) = {
+        anchor_mut!(graph, Always);
+        let root = graph.spawn(0);
+        graph.root_mut().push(root);
+        let garbage = graph.spawn(1);
+        let kept = graph.downgrade(root);
+        let dropped = graph.downgrade(garbage);
+        assert!(kept == graph.downgrade(root));
+        assert!(graph.upgrade(dropped) == Some(garbage));
+        (kept, dropped)
+    };
+    {
+        anchor_mut!(graph, Always);
+        assert!(graph.upgrade(dropped).is_none());
+        // The memory of the dropped node is reused, but the weak pointer does not match the new node.
+        let reused = graph.spawn(2);
+        assert_eq!(reused.as_ptr(), dropped.as_ptr());
+        assert!(graph.upgrade(dropped).is_none());
+        let kept = graph.upgrade(kept).unwrap();
+        assert_eq!(graph[kept].data, 0);
+    }
+}
+
+#[test]
+fn test_shared_anchors() {
+    let mut graph = VecGraph::<VecNode<usize, usize>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let root = graph.spawn(0);
+        graph.root_mut().push(root);
+        for i in 1..=100 {
+            let node = graph.spawn(i);
+            graph[root].refs.push((node, i));
+        }
+    }
+    graph.set_reverse_index(true);
+
+    anchor!(first, graph);
+    anchor!(second, graph);
+    assert_eq!(first.node_count(), second.node_count());
+
+    let graph = first;
+    let root = graph.root()[0];
+    let (sum, sources) = std::thread::scope(|s| {
+        let sum = s.spawn(move || graph.edges(root).map(|x| *x.values.that().this).sum::<usize>());
+        let sources = s.spawn(move || graph.edges(root).map(|x| graph.in_neighbors(x.ptr).count()).sum::<usize>());
+        (sum.join().unwrap(), sources.join().unwrap())
+    });
+    assert_eq!(sum, 5050);
+    assert_eq!(sources, 100);
+}
+
+#[test]
+fn test_send_sync() {
+    fn assert_send<T : Send>() {}
+    fn assert_sync<T : Sync>() {}
+    assert_send::<VecGraph<VecNode<String, usize>>>();
+    assert_sync::<VecGraph<VecNode<String, usize>>>();
+    assert_send::<NamedGraph<DiNode<String, usize>>>();
+    assert_sync::<NamedGraph<UndirectedNode<String, usize>>>();
+    assert_send::<GraphPtr<'static, VecNode<std::rc::Rc<()>, ()>>>();
+
+    let mut graph = VecGraph::<VecNode<String, usize>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let root = graph.spawn("root".to_string());
+        graph.root_mut().push(root);
+    }
+    let graph = std::thread::spawn(move || {
+        {
+            anchor_mut!(graph, Never);
+            let root = graph.root()[0];
+            graph[root].data.push('!');
+        }
+        graph
+    }).join().unwrap();
+    anchor!(graph);
+    assert_eq!(graph[graph.root()[0]].data, "root!");
+}
+
+#[test]
+fn test_sharded_graphs() {
+    let graph = ShardedGraphs::<RootNamedSet<'static, NamedNode<String, ()>>, NamedNode<String, ()>>::new(4);
+    std::thread::scope(|s| {
+        for t in 0..4 {
+            let graph = &graph;
+            s.spawn(move || {
+                for i in 0..50 {
+                    let key = format!("{}-{}", t, i);
+                    graph.write(&key, CleanupStrategy::Never, |mut anchor| {
+                        let node = anchor.spawn(key.clone());
+                        anchor.root_mut().insert(node);
+                    });
+                    assert!(graph.read(&key, |anchor| anchor.root().iter().any(|x| anchor[*x].data == key)));
+                }
+            });
+        }
+    });
+    let total : usize = (0..graph.shard_count()).map(|i| graph.read_shard(i, |x| x.node_count())).sum();
+    assert_eq!(total, 200);
+    assert_eq!(graph.into_shards().len(), 4);
+}
+
+#[test]
+fn test_sync_graph() {
+    let graph = SyncGraph::<RootVec<'static, VecNode<usize, ()>>, VecNode<usize, ()>>::new();
+    graph.write(CleanupStrategy::Never, |mut anchor| {
+        let root = anchor.spawn(0);
+        anchor.root_mut().push(root);
+    });
+    std::thread::scope(|s| {
+        for t in 0..4 {
+            let graph = &graph;
+            s.spawn(move || {
+                for i in 0..50 {
+                    graph.write(CleanupStrategy::Never, |mut anchor| {
+                        let root = anchor.root()[0];
+                        let node = anchor.spawn(t * 50 + i);
+                        anchor[root].refs.push((node, ()));
+                    });
+                    assert!(graph.read(|anchor| anchor.node_count() > i));
+                }
+            });
+        }
+    });
+    graph.write(CleanupStrategy::Always, |mut anchor| {
+        let root = anchor.root()[0];
+        anchor[root].refs.truncate(100);
+    });
+    let graph = graph.into_inner();
+    anchor!(graph);
+    assert_eq!(graph.node_count(), 101);
+    let mut data : Vec<_> = graph[graph.root()[0]].refs.iter().map(|x| graph[x.0].data).collect();
+    data.sort();
+    data.dedup();
+    assert_eq!(data.len(), 100);
+}
+
+#[test]
+fn test_export() {
+    use std::sync::Arc;
+
+    let mut graph = VecGraph::<NamedNode<Arc<String>, u32>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let a = graph.spawn(Arc::new("a".to_string()));
+        let b = graph.spawn(Arc::new("b".to_string()));
+        graph.root_mut().push(a);
+        graph.connect(a, b, 1);
+        graph.connect(b, a, 2);
+    }
+    let export = graph.export();
+    {
+        anchor_mut!(graph, Always);
+        let a = graph.root()[0];
+        graph.root_mut().clear();
+        graph[a].data = Arc::new("changed".to_string());
+    }
+    anchor!(graph);
+    assert_eq!(graph.node_count(), 0);
+
+    assert_eq!(export.len(), 2);
+    assert_eq!(export.roots(), &[0]);
+    assert_eq!(*export[0], "a");
+    assert_eq!(export.neighbors(0), &[1]);
+    assert_eq!(export.neighbors(1), &[0]);
+    assert_eq!(export.edges(0), &[1]);
+    assert_eq!(export.edges(1), &[2]);
+    let names : Vec<_> = export.iter().map(|(_, x)| x.as_str()).collect();
+    assert_eq!(names, vec!["a", "b"]);
+}
+
+#[test]
+fn test_diff() {
+    fn build(nodes : &[(u32, &'static str)], edges : &[(usize, usize)]) -> VecGraph<NamedNode<(u32, &'static str), ()>> {
+        let mut graph : VecGraph<NamedNode<_, ()>> = VecGraph::new();
+        {
+            anchor_mut!(graph, Never);
+            let ptrs : Vec<_> = nodes.iter().map(|x| graph.spawn(*x)).collect();
+            for &(a, b) in edges {
+                graph.connect(ptrs[a], ptrs[b], ());
+            }
+            graph.root_mut().push(ptrs[0]);
+        }
+        graph
+    }
+
+    let old = build(&[(1, "a"), (2, "b"), (3, "c"), (4, "d")], &[(0, 1), (1, 2), (0, 3)]);
+    let new = build(&[(1, "a"), (2, "B"), (3, "c"), (5, "e")], &[(0, 1), (0, 2), (2, 3)]);
+    anchor!(old);
+    anchor!(new);
+    let changes = diff(&old, &new, |x| x.0);
+    assert_eq!(changes.added_nodes, vec![5]);
+    assert_eq!(changes.removed_nodes, vec![4]);
+    assert_eq!(changes.changed_nodes, vec![2]);
+    let mut added = changes.added_edges.clone();
+    added.sort();
+    assert_eq!(added, vec![(1, 3), (3, 5)]);
+    let mut removed = changes.removed_edges.clone();
+    removed.sort();
+    assert_eq!(removed, vec![(1, 4), (2, 3)]);
+    assert!(diff(&old, &old, |x| x.0).is_empty());
+}
+
+#[test]
+fn test_match_pattern() {
+    let mut graph = VecGraph::<NamedNode<&'static str, ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let a = graph.spawn("if");
+        let b = graph.spawn("then");
+        let c = graph.spawn("else");
+        let d = graph.spawn("join");
+        let e = graph.spawn("if");
+        graph.connect(a, b, ());
+        graph.connect(a, c, ());
+        graph.connect(b, d, ());
+        graph.connect(c, d, ());
+        graph.connect(d, e, ());
+        graph.root_mut().push(a);
+    }
+    anchor!(graph);
+
+    let mut diamond = Pattern::new();
+    let top = diamond.node(|x : &&str| *x == "if");
+    let left = diamond.any();
+    let right = diamond.any();
+    let bottom = diamond.any();
+    diamond.edge(top, left);
+    diamond.edge(top, right);
+    diamond.edge(left, bottom);
+    diamond.edge(right, bottom);
+    let found = match_pattern(&graph, &diamond);
+    assert_eq!(found.len(), 2);
+    for x in &found {
+        assert_eq!(graph[x[top]].data, "if");
+        assert_eq!(graph[x[bottom]].data, "join");
+    }
+    assert!(found[0][left] != found[1][left]);
+
+    let mut chain = Pattern::new();
+    let a = chain.any();
+    let b = chain.node(|x : &&str| *x == "if");
+    chain.edge(a, b);
+    let found = match_pattern(&graph, &chain);
+    assert_eq!(found.len(), 1);
+    assert_eq!(graph[found[0][a]].data, "join");
+
+    let mut cycle = Pattern::new();
+    let a = cycle.any();
+    let b = cycle.any();
+    cycle.edge(a, b);
+    cycle.edge(b, a);
+    assert!(match_pattern(&graph, &cycle).is_empty());
+    assert_eq!(match_pattern(&graph, &Pattern::new()).len(), 1);
+}
+
+#[test]
+fn test_split_node() {
+    let mut graph = VecGraph::<NamedNode<&'static str, u32>>::new();
+    anchor_mut!(graph, Never);
+    let a = graph.spawn("a");
+    let b = graph.spawn("b");
+    let tail = graph.spawn("tail");
+    let x = graph.spawn("x");
+    let y = graph.spawn("y");
+    graph.root_mut().push(a);
+    graph.connect(a, tail, 1);
+    graph.connect(b, tail, 2);
+    graph.connect(tail, x, 3);
+    graph.connect(tail, y, 4);
+    graph.connect(tail, tail, 5);
+
+    let copy = graph.split_node(tail, |x| *x, |dst, _| dst != y, |src| src == b);
+    assert_eq!(graph[copy].data, "tail");
+    let mut targets : Vec<_> = graph.edges(copy).map(|x| (graph[x.ptr].data, *x.values.edge())).collect();
+    targets.sort();
+    assert_eq!(targets, vec![("tail", 5), ("x", 3)]);
+    assert!(graph[copy].refs.contains_key(&copy));
+    assert_eq!(graph.degree(tail), 3);
+    assert!(graph[a].refs.contains_key(&tail));
+    assert!(!graph[b].refs.contains_key(&tail));
+    assert_eq!(graph[b].refs.get(&copy), Some(&2));
+    assert_eq!(graph.in_degree(tail), 2);
+}
+
+#[test]
+fn test_reroot() {
+    let mut graph = VecGraph::<NamedNode<u32, ()>>::new();
+    let report = {
+        anchor_mut!(graph, Never);
+        let a = graph.spawn(1);
+        let b = graph.spawn(2);
+        let c = graph.spawn(3);
+        graph.connect(a, b, ());
+        graph.connect(c, b, ());
+        graph.root_mut().push(a);
+        graph.reroot(vec![c, c]);
+        assert_eq!(graph.root().len(), 2);
+        assert_eq!(graph.find_unreachable().len(), 1);
+        graph.reroot_and_cleanup(Some(c))
+    };
+    assert_eq!(report.nodes_freed, 1);
+    anchor!(graph);
+    assert_eq!(graph.node_count(), 2);
+    assert_eq!(graph.root().len(), 1);
+    assert_eq!(graph[graph.root()[0]].data, 3);
+}
+
+#[test]
+fn test_path() {
+    let mut graph = VecGraph::<NamedNode<u32, f64>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let a = graph.spawn(1);
+        let b = graph.spawn(2);
+        let c = graph.spawn(3);
+        graph.connect(a, b, 1.5);
+        graph.connect(b, c, 2.0);
+        graph.root_mut().push(a);
+    }
+    anchor!(graph);
+    let a = graph.root()[0];
+    let b = graph.neighbors(a).next().unwrap();
+    let c = graph.neighbors(b).next().unwrap();
+
+    let mut first = Path::new(a);
+    assert!(first.is_empty());
+    first.push(b);
+    let second = Path::from_nodes(vec![b, c]).unwrap();
+    assert!(Path::<NamedNode<u32, f64>>::from_nodes(Vec::new()).is_none());
+    assert!(second.concat(&first).is_none());
+    let path = first.concat(&second).unwrap();
+    assert_eq!(path.len(), 2);
+    assert!(path.source() == a && path.target() == c);
+    assert_eq!(path.validate(&graph), Ok(()));
+    let weights : Vec<_> = path.edges(&graph).into_iter().map(|x| *x.unwrap().edge()).collect();
+    assert_eq!(weights, vec![1.5, 2.0]);
+    assert_eq!(path.weight(|x, y| *graph[x].refs.get(&y).unwrap()), 3.5);
+
+    let broken = Path::from_nodes(vec![a, b, a]).unwrap();
+    assert_eq!(broken.validate(&graph), Err(BrokenPath(1)));
+    assert!(broken.edges(&graph)[1].is_none());
+}
+
+#[test]
+fn test_shortest_path() {
+    let mut graph = VecGraph::<NamedNode<u32, f64>>::new();
+    anchor_mut!(graph, Never);
+    let a = graph.spawn(0);
+    let b = graph.spawn(1);
+    let c = graph.spawn(2);
+    let d = graph.spawn(3);
+    graph.root_mut().push(a);
+    graph.connect(a, b, 1.0);
+    graph.connect(b, c, 1.0);
+    graph.connect(a, c, 3.0);
+
+    let path = graph.shortest_path(a, c, |x, y| graph[x].refs[&y]).unwrap();
+    assert!(path.nodes() == [a, b, c]);
+    assert!(graph.shortest_path(a, d, |_, _| 1.0).is_none());
+    assert!(graph.shortest_path(a, a, |_, _| 1.0).unwrap().is_empty());
+
+    graph.connect(a, d, 4.0);
+    graph.connect(d, c, -3.0);
+    let path = graph.shortest_path(a, c, |x, y| graph[x].refs[&y]).unwrap();
+    assert!(path.nodes() == [a, d, c]);
+    assert_eq!(path.weight(|x, y| graph[x].refs[&y]), 1.0);
+
+    graph.connect(c, d, 1.0);
+    assert!(graph.shortest_path(a, c, |x, y| graph[x].refs[&y]).is_none());
+    assert!(graph.shortest_path(a, b, |x, y| graph[x].refs[&y]).is_some());
+}
+
+#[test]
+fn test_render_tree() {
+    let mut graph = VecGraph::<TreeNode<char, &'static str, ()>>::new();
+    anchor_mut!(graph, Never);
+    let root = graph.spawn("root");
+    let a = graph.spawn("a");
+    let b = graph.spawn("b");
+    let c = graph.spawn("c");
+    graph.root_mut().push(root);
+    graph.connect(root, 'x', a, ());
+    graph.connect(root, 'y', b, ());
+    graph.connect(a, 'z', c, ());
+    graph.connect(c, 'w', root, ());
+    graph.connect(b, 'v', c, ());
+
+    let expected = [
+        "root",
+        "|-- x: a",
+        "|   `-- z: c",
+        "|       `-- w: root (cycle)",
+        "`-- y: b",
+        "    `-- v: c",
+        "        `-- w: root (cycle)",
+    ].join("\n");
+    assert_eq!(graph.render_tree(root), expected);
+    assert_eq!(format!("{}", graph.display_tree(b)), "b\n`-- v: c\n    `-- w: root\n        |-- x: a\n        |   `-- z: c (cycle)\n        `-- y: b (cycle)");
+}
+
+#[test]
+fn test_debug_dump() {
+    let mut graph = VecGraph::<VecNode<&'static str, u32>>::new();
+    anchor_mut!(graph, Never);
+    let a = graph.spawn("a");
+    let b = graph.spawn("b");
+    let c = graph.spawn("c");
+    graph.spawn("unreachable");
+    graph.root_mut().push(a);
+    graph.connect(a, b, 5);
+    graph.connect(a, c, 3);
+    graph.connect(c, a, 1);
+
+    let mut dump = String::new();
+    graph.debug_dump(&mut dump).unwrap();
+    assert_eq!(dump, "0: \"a\" -> [1, 2]\n1: \"b\" -> []\n2: \"c\" -> [0]\n");
+    assert_eq!(format!("{:?}", graph), "0: \"a\" -> {1: 5, 2: 3}\n1: \"b\" -> {}\n2: \"c\" -> {0: 1}\n");
+}
+
+#[test]
+fn test_edge_list() {
+    let input = "# comment\n1\t2\t0.5\n2\t3\t1.5\n\n3\t1\t2\n1\t2\t0.25\n";
+    let mut graph = NamedGraph::<NamedNode<u32, f64>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let nodes = dynamic_graph::import::edge_list(&mut graph, input.as_bytes(), '\t', |x| x.parse().ok(),
+                                                     |x| x.parse().ok()).unwrap();
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(graph.root().len(), 3);
+        assert_eq!(graph[nodes[&1]].refs[&nodes[&2]], 0.25);
+        assert_eq!(graph[nodes[&3]].refs[&nodes[&1]], 2.0);
+    }
+    {
+        anchor!(graph);
+        let mut out = Vec::new();
+        dynamic_graph::export::edge_list(&graph, &mut out, ',', |x| x.to_string(), |x| x.to_string()).unwrap();
+        let mut rows : Vec<_> = String::from_utf8(out).unwrap().lines().map(str::to_string).collect();
+        rows.sort();
+        assert_eq!(rows, vec!["1,2,0.25", "2,3,1.5", "3,1,2"]);
+    }
+
+    let mut graph = NamedGraph::<NamedNode<String, ()>>::new();
+    anchor_mut!(graph, Never);
+    let nodes = dynamic_graph::import::edge_list(&mut graph, "a, b\nb, a\n".as_bytes(), ',', |x| Some(x.to_string()),
+                                                 |_| Some(())).unwrap();
+    assert_eq!(graph[nodes["a"]].refs.len(), 1);
+    let err = dynamic_graph::import::edge_list(&mut graph, "a,b\nc\n".as_bytes(), ',', |x| Some(x.to_string()),
+                                               |_| Some(()));
+    assert!(matches!(err, Err(ImportError::Malformed(2))));
+}
+
+#[cfg(feature = "storage")]
+#[test]
+fn test_file_storage() {
+    let path = std::env::temp_dir().join(format!("dynamic_graph_storage_{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut storage = FileStorage::open(&path).unwrap();
+    storage.put(1, b"one", &[2]).unwrap();
+    storage.put(2, b"two", &[]).unwrap();
+    storage.put(2, b"TWO", &[1, 2]).unwrap();
+    storage.put(3, b"three", &[]).unwrap();
+    assert!(storage.remove(3).unwrap());
+    assert!(!storage.remove(3).unwrap());
+    assert_eq!(storage.get(2).unwrap(), Some((b"TWO".to_vec(), vec![1, 2])));
+    storage.close().unwrap();
+
+    //A record cut short by a crash is dropped.
+    let len = std::fs::metadata(&path).unwrap().len();
+    std::io::Write::write_all(&mut std::fs::OpenOptions::new().append(true).open(&path).unwrap(), &[1, 4, 0, 0]).unwrap();
+    let mut storage = FileStorage::open(&path).unwrap();
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), len);
+    assert_eq!(storage.len(), 2);
+    assert_eq!(storage.get(1).unwrap(), Some((b"one".to_vec(), vec![2])));
+    assert_eq!(storage.get(3).unwrap(), None);
+    storage.compact().unwrap();
+    assert!(std::fs::metadata(&path).unwrap().len() < len);
+    assert_eq!(storage.get(2).unwrap(), Some((b"TWO".to_vec(), vec![1, 2])));
+
+    let mut graph = VecGraph::<NamedNode<String, ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let nodes = storage.load(&mut graph, |x| String::from_utf8(x.to_vec()).ok(), |g, a, b| { g.connect(a, b, ()); })
+                           .unwrap();
+        graph.root_mut().push(nodes[&1]);
+        assert_eq!(graph[nodes[&2]].refs.len(), 2);
+    }
+    {
+        anchor!(graph);
+        storage.save(&graph, |x| x.to_uppercase().into_bytes()).unwrap();
+    }
+    drop(storage);
+    let mut storage = FileStorage::open(&path).unwrap();
+    assert_eq!(storage.get(0).unwrap(), Some((b"ONE".to_vec(), vec![1])));
+    assert_eq!(storage.get(1).unwrap().unwrap().1.len(), 2);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_lazy_nodes() {
+    let mut source : HashMap<u32, String> = (0..3).map(|x| (x, format!("node {}", x))).collect();
+    let mut graph = VecGraph::<NamedNode<Lazy<u32, String>, ()>>::new();
+    anchor_mut!(graph, Never);
+    let a = graph.spawn_stub(0);
+    let b = graph.spawn_stub(1);
+    let c = graph.spawn(Lazy::loaded(2, "fresh".to_string()));
+    graph.connect(a, b, ());
+    graph.root_mut().push(a);
+    assert!(!graph[a].data.is_loaded());
+    assert_eq!(graph[a].data.key(), &0);
+
+    graph.hydrate(a, &mut source).push('!');
+    assert_eq!(graph[a].data.get().unwrap(), "node 0!");
+    assert_eq!(graph.hydrate(a, &mut source), "node 0!");
+    assert_eq!(source[&0], "node 0");
+    assert!(graph[b].data.get().is_none());
+
+    graph.store_loaded(&mut source);
+    assert_eq!(source[&0], "node 0!");
+    assert_eq!(source[&2], "fresh");
+    graph[c].data.get_mut().unwrap().push('?');
+    assert!(graph.evict(c, &mut source));
+    assert!(!graph.evict(c, &mut source));
+    assert!(!graph[c].data.is_loaded());
+    assert_eq!(source[&2], "fresh?");
+}
+
+#[test]
+fn test_property_map() {
+    let mut graph = VecGraph::<NamedNode<&str, ()>>::new();
+    let mut map = {
+        anchor_mut!(graph, Always);
+        let a = graph.spawn("a");
+        let b = graph.spawn("b");
+        let mut map = graph.property_map(0);
+        let c = graph.spawn("c");
+        assert!(map.get(c).is_none());
+        graph.remap(&mut map);
+        map[a] = 1;
+        map[b] = 2;
+        map[c] = 3;
+        assert_eq!(map.values(), &[1, 2, 3]);
+        graph.root_mut().push(c);
+        graph.root_mut().push(b);
+        map
+    };
+    {
+        anchor_mut!(graph, Never);
+        let (c, b) = (graph.root()[0], graph.root()[1]);
+        graph.remap(&mut map);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map[b], 2);
+        assert_eq!(map[c], 3);
+        let d = graph.spawn("d");
+        graph.remap(&mut map);
+        assert_eq!(map[d], 0);
+        map.reset();
+        assert_eq!(map[c], 0);
+    }
+}
+
+#[test]
+fn test_property_map_follows_cleanup() {
+    let mut graph = VecGraph::<VecNode<u32, ()>>::new();
+    let mut map = {
+        anchor_mut!(graph, Always);
+        let nodes : Vec<_> = (0..8).map(|x| graph.spawn(x)).collect();
+        let mut map = graph.property_map(0);
+        for &i in &nodes {
+            map[i] = graph[i].data * 10;
+            if graph[i].data % 2 == 1 {
+                graph.root_mut().push(i);
+            }
+        }
+        map
+    };
+    anchor_mut!(graph, Never);
+    // the cleanup dropped even nodes and moved odd ones
+    assert_eq!(graph.node_count(), 4);
+    let root = graph.root().clone();
+    for &i in &root {
+        assert_eq!(map[i], graph[i].data * 10);
+    }
+    let new = graph.spawn(8);
+    assert!(map.get(new).is_none());
+    assert_eq!(map[new], 0);
+    map[new] = 80;
+    assert_eq!(map[new], 80);
+    assert_eq!(map.len(), 9);
+    graph.remap(&mut map);
+    assert_eq!(map.len(), 5);
+    assert_eq!(map[new], 80);
+    for &i in &root {
+        assert_eq!(map[i], graph[i].data * 10);
+    }
+}
+
+#[test]
+fn test_marks() {
+    let mut graph = VecGraph::<VecNode<u32, ()>>::new();
+    anchor_mut!(graph, Never);
+    let nodes : Vec<_> = (0..4).map(|x| graph.spawn(x)).collect();
+    for w in nodes.windows(2) {
+        graph.connect(w[0], w[1], ());
+    }
+    graph.connect(nodes[3], nodes[0], ());
+    assert_eq!(graph.get_mark(nodes[0]), 0);
+
+    let mut visited = 0;
+    let mut current = nodes[0];
+    while graph.get_mark(current) == 0 {
+        graph.set_mark(current, 1);
+        visited += 1;
+        current = graph[current].refs[0].0;
+    }
+    assert_eq!(visited, 4);
+    graph.set_mark(nodes[2], 0b101);
+    assert_eq!(graph.get_mark(nodes[2]), 0b101);
+
+    graph.clear_marks();
+    assert!(nodes.iter().all(|&x| graph.get_mark(x) == 0));
+    graph.set_mark(nodes[1], 2);
+    assert_eq!(graph.get_mark(nodes[1]), 2);
+    assert_eq!(graph.get_mark(nodes[2]), 0);
+}
+
+#[test]
+fn test_visit_set() {
+    let mut graph = VecGraph::<VecNode<u32, ()>>::new();
+    anchor_mut!(graph, Never);
+    let nodes : Vec<_> = (0..100).map(|x| graph.spawn(x)).collect();
+    for w in nodes.windows(2) {
+        graph.connect(w[0], w[1], ());
+    }
+    graph.connect(nodes[99], nodes[50], ());
+
+    let mut visited = graph.visit_set();
+    let mut stack = vec![nodes[0]];
+    while let Some(x) = stack.pop() {
+        if visited.insert(x) {
+            stack.extend(graph[x].refs.iter().map(|y| y.0));
+        }
+    }
+    assert_eq!(visited.len(), 100);
+    assert!(!visited.insert(nodes[70]));
+
+    let late = graph.spawn(100);
+    assert!(!visited.contains(late));
+    assert!(visited.insert(late));
+    assert!(visited.remove(nodes[3]));
+    assert!(!visited.remove(nodes[3]));
+    assert!(!visited.contains(nodes[3]));
+    assert_eq!(visited.len(), 100);
+    visited.clear();
+    assert!(visited.is_empty());
+    assert!(!visited.contains(nodes[0]));
+}
+
+#[test]
+fn test_last_modified() {
+    let mut graph = VecGraph::<NamedNode<u32, ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let a = graph.spawn(0);
+        assert_eq!(graph.last_modified(a), 0);
+        assert_eq!(graph.modification_epoch(), 0);
+    }
+    graph.set_modification_tracking(true);
+    anchor_mut!(graph, Never);
+    let a = graph.spawn(1);
+    let b = graph.spawn(2);
+    assert!(graph.last_modified(a) < graph.last_modified(b));
+    let seen = graph.modification_epoch();
+    assert_eq!(seen, graph.last_modified(b));
+
+    let _ = graph[b].data;
+    assert_eq!(graph.last_modified(b), seen);
+    graph[a].data += 1;
+    assert!(graph.last_modified(a) > seen);
+    graph.connect(b, a, ());
+    assert!(graph.last_modified(b) > graph.last_modified(a));
+    assert_eq!(graph.modification_epoch(), graph.last_modified(b));
+    graph.cursor_mut(a).data = 5;
+    assert_eq!(graph.modification_epoch(), graph.last_modified(a));
+    graph.root_mut().push(b);
+    let seen = graph.modification_epoch();
+    for x in graph.iter_mut() {
+        *x.values += 1;
+    }
+    assert!(graph.last_modified(b) > seen);
+}
+
+#[test]
+fn test_ptr_order() {
+    use std::collections::BTreeSet;
+
+    let mut graph = VecGraph::<VecNode<u32, ()>>::new();
+    anchor_mut!(graph, Never);
+    let nodes : Vec<_> = (0..10).map(|x| graph.spawn(x)).collect();
+    let mut queue : BTreeSet<_> = nodes.iter().copied().collect();
+    assert_eq!(queue.len(), 10);
+    let mut sorted = nodes.clone();
+    sorted.sort();
+    assert!(queue.iter().copied().eq(sorted.iter().copied()));
+    assert!(sorted.windows(2).all(|w| w[0] < w[1] && w[0].as_ptr() < w[1].as_ptr()));
+
+    for i in 10..20 {
+        graph.spawn(i);
+    }
+    assert!(queue.iter().copied().eq(sorted.iter().copied()));
+    let first = queue.pop_first().unwrap();
+    assert!(first == sorted[0]);
+    assert!(!queue.contains(&first));
+}
+
+#[test]
+fn test_display_ptr() {
+    let mut graph = VecGraph::<VecNode<u32, ()>>::new();
+    anchor_mut!(graph, Never);
+    let a = graph.spawn(0);
+    let b = graph.spawn(1);
+    assert_eq!(graph.display(a).to_string(), "#0");
+    assert_eq!(format!("{:?}", [graph.display(a), graph.display(b)]), "[#0, #1]");
+    assert_eq!(graph.display(b).index(), 1);
+    assert_eq!(format!("{:?}", b), format!("GraphPtr({:p})", b.as_ptr()));
+    assert_eq!(a, a);
+}
+
+#[test]
+fn test_edge_combinators() {
+    let mut graph = VecGraph::<VecNode<u32, u32>>::new();
+    anchor_mut!(graph, Never);
+    let a = graph.spawn(1);
+    let b = graph.spawn(2);
+    graph.connect(a, b, 10);
+    graph.connect(b, b, 20);
+
+    for e in graph.edges_mut(a) {
+        let (this, that, edge) = e.values.split();
+        *this += *edge;
+        *that.unwrap() += 1;
+        assert!(e.ptr == b);
+    }
+    assert_eq!(graph[a].data, 11);
+    assert_eq!(graph[b].data, 3);
+
+    let edge = graph.edges(b).next().unwrap().values;
+    let (this, that, sum) = edge.map_nodes(|x| *x * 2).map_edge(|e| *e + 1).split();
+    assert_eq!((this, that, sum), (6, None, 21));
+
+    let mut both = graph.edges(a).next().unwrap().values.map_nodes(|x| *x);
+    *both.as_mut().split().0 += 1;
+    assert_eq!(both.as_ref().split(), (&12, Some(&3), &&10));
+}
+
+#[test]
+fn test_option_edge_adapters() {
+    let mut graph = VecGraph::<NamedNode<u32, u32>>::new();
+    anchor_mut!(graph, Never);
+    let a = graph.spawn(1);
+    let b = graph.spawn(2);
+    graph.connect(a, b, 10);
+    graph.connect(a, a, 20);
+
+    let mut cursor = graph.cursor_mut(a);
+    let mut edge = cursor.get_edge_mut(b);
+    **edge.this_mut().unwrap() += 1;
+    **edge.that_mut().unwrap() += 2;
+    **edge.edge_mut().unwrap() += 3;
+    let both = edge.expect_both("a and b are connected");
+    assert_eq!((*both.this, *both.that, *both.edge), (2, 4, 13));
+
+    let mut lp = cursor.get_edge_mut(a);
+    **lp.that_mut().unwrap() += 1;
+    assert!(lp.unzip_both().is_none());
+    assert_eq!(cursor.get_edge(a).split(), Some((&3, None, &20)));
+    assert_eq!(cursor.get_edge(b).unzip_both(), Some((&3, &4, &13)));
+    assert!(cursor.get_edge_mut(b).split().is_some());
+}
+
+#[test]
+#[should_panic(expected = "not connected")]
+fn test_option_edge_expect_both() {
+    let mut graph = VecGraph::<NamedNode<u32, u32>>::new();
+    anchor_mut!(graph, Never);
+    let a = graph.spawn(1);
+    graph.connect(a, a, 20);
+    graph.cursor(a).get_edge(a).expect_both("not connected");
+}
+
+#[test]
+fn test_graph_item_adapters() {
+    let mut graph = VecGraph::<VecNode<u32, u32>>::new();
+    anchor_mut!(graph, Never);
+    let a = graph.spawn(0);
+    let b = graph.spawn(1);
+    let c = graph.spawn(2);
+    graph.connect(a, b, 10);
+    graph.connect(a, c, 20);
+    graph.connect(a, a, 30);
+
+    let ptrs : Vec<_> = graph.edges(a).ptrs().collect();
+    assert_eq!(ptrs, vec![b, c, a]);
+    let weights : Vec<_> = graph.edges(a).values().map(|x| *x.edge()).collect();
+    assert_eq!(weights, vec![10, 20, 30]);
+    let heavy : Vec<_> = graph.edges(a).filter_edges(|x| **x.as_ref().edge() >= 20).ptrs().collect();
+    assert_eq!(heavy, vec![c, a]);
+
+    let (data, ptr) = graph.edges(a).next().unwrap().map_values(|x| *x.that().this).into_parts();
+    assert_eq!((data, ptr), (1, b));
+    for e in graph.edges_mut(a).filter_edges(|x| x.as_ref().both().is_some()) {
+        *e.values.that().this += 100;
+    }
+    assert_eq!((graph[a].data, graph[b].data, graph[c].data), (0, 101, 102));
+}
+
+// Fails every allocation once the given number of chunks is allocated.
+struct Limited(std::sync::atomic::AtomicUsize);
+
+unsafe impl Allocator for Limited {
+    fn allocate(&self, layout : std::alloc::Layout) -> Result<core::ptr::NonNull<[u8]>, AllocError> {
+        let left = self.0.load(std::sync::atomic::Ordering::Relaxed);
+        if left == 0 {
+            return Err(AllocError);
+        }
+        self.0.store(left - 1, std::sync::atomic::Ordering::Relaxed);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr : core::ptr::NonNull<u8>, layout : std::alloc::Layout) {
+        Global.deallocate(ptr, layout)
+    }
+}
+
+#[test]
+fn test_try_spawn() {
+    let mut graph = VecGraph::<NamedNode<usize, ()>, Limited>::with_allocator(Limited(1.into()));
+    anchor_mut!(graph, Never);
+    let mut spawned = Vec::new();
+    let error = loop {
+        match graph.try_spawn(spawned.len()) {
+            Ok(x) => spawned.push(x),
+            Err(e) => break e,
+        }
+    };
+    assert_eq!(error, GraphError::AllocationFailed);
+    assert!(!spawned.is_empty());
+    assert_eq!(graph.node_count(), spawned.len());
+    for (i, &x) in spawned.iter().enumerate() {
+        assert_eq!(graph[x].data, i);
+    }
+    assert!(graph.try_spawn(0).is_err());
+
+    let error : GraphError = StepError::NoEdge.into();
+    assert_eq!(error, GraphError::Step(StepError::NoEdge));
+    assert_eq!(GraphError::from(WouldCycle).to_string(), WouldCycle.to_string());
+}
+
+#[test]
+fn test_spawn_iter() {
+    let mut graph = VecGraph::<VecNode<usize, ()>>::new();
+    {
+        anchor_mut!(graph, Always);
+        let nodes = graph.spawn_iter(0..1000);
+        assert_eq!(nodes.len(), 1000);
+        assert!(nodes.iter().enumerate().all(|(i, &x)| graph[x].data == i));
+        let roots = graph.extend_root((0..10).map(|x| x * 2));
+        assert_eq!(graph.root().len(), 10);
+        assert!(graph.root().iter().zip(&roots).all(|(a, b)| a == b));
+        assert_eq!(graph[roots[3]].data, 6);
+        graph.connect(roots[0], nodes[5], ());
+    }
+    assert_eq!(graph.node_count(), 11);
+}
+
+#[test]
+fn test_connect_all() {
+    let mut graph = VecGraph::<NamedNode<u32, u32>>::new();
+    anchor_mut!(graph, Never);
+    let nodes = graph.spawn_iter(0..4);
+    let added = graph.connect_all(nodes.windows(2).map(|w| (w[0], w[1], 1)));
+    assert_eq!(added, 3);
+    assert_eq!(graph.connect_all(vec![(nodes[0], nodes[1], 5), (nodes[3], nodes[0], 7)]), 1);
+    assert_eq!(graph[nodes[0]].refs[&nodes[1]], 5);
+    assert_eq!(graph[nodes[3]].refs[&nodes[0]], 7);
+    assert_eq!(graph.edge_count(), 4);
+
+    let mut tree = VecGraph::<TreeNode<&str, u32, ()>>::new();
+    anchor_mut!(tree, Never);
+    let root = tree.spawn(0);
+    let children = tree.spawn_iter(1..3);
+    assert_eq!(tree.connect_all(vec![(root, "left", children[0], ()), (root, "right", children[1], ())]), 2);
+    assert_eq!(tree.connect_all(vec![(root, "left", children[1], ())]), 0);
+    assert!(tree[root].refs[&"left"].0 == children[1]);
+}
+
+#[test]
+fn test_get_or_spawn() {
+    let mut graph = MapGraph::<String, NamedNode<u32, ()>>::new();
+    anchor_mut!(graph, Always);
+    let records = [("a", "b"), ("b", "c"), ("a", "c"), ("c", "a")];
+    let mut spawned = 0;
+    for (src, dst) in records.iter() {
+        let src = graph.get_or_spawn(src.to_string(), || { spawned += 1; spawned });
+        let dst = graph.get_or_spawn(dst.to_string(), || { spawned += 1; spawned });
+        graph.connect(src, dst, ());
+    }
+    assert_eq!(spawned, 3);
+    assert_eq!(graph.node_count(), 3);
+    assert_eq!(graph.root().len(), 3);
+    let a = graph.root()["a"];
+    assert_eq!(graph[a].data, 1);
+    assert_eq!(graph[a].refs.len(), 2);
+    assert!(graph.get_or_spawn("c".to_string(), || unreachable!()) == graph.root()["c"]);
+}
+
+#[test]
+fn test_key_index() {
+    let mut graph = VecGraph::<VecNode<(u32, &str), ()>>::new();
+    {
+        anchor_mut!(graph, Never);
+        let a = graph.spawn((1, "a"));
+        graph.root_mut().push(a);
+    }
+    graph.set_key_index(|x : &(u32, &str)| x.0);
+    {
+        anchor_mut!(graph, Always);
+        let a = graph.lookup(&1u32).unwrap();
+        assert_eq!(graph[a].data.1, "a");
+        let b = graph.spawn((2, "b"));
+        let c = graph.spawn((3, "c"));
+        graph.root_mut().push(b);
+        graph.connect(b, c, ());
+        assert!(graph.lookup(&2u32) == Some(b));
+        assert!(graph.lookup(&3u32) == Some(c));
+
+        graph[b].data.0 = 20;
+        assert!(graph.lookup(&2u32).is_none());
+        assert!(graph.lookup(&20u32) == Some(b));
+        for e in graph.edges_mut(b) {
+            e.values.that().this.0 = 30;
+        }
+        assert!(graph.lookup(&30u32) == Some(c));
+        graph.disconnect(b, c);
+        graph.spawn((4, "d"));
+    }
+    {
+        anchor_mut!(graph, Never);
+        assert!(graph.lookup(&30u32).is_none());
+        assert!(graph.lookup(&4u32).is_none());
+        assert_eq!(graph[graph.lookup(&20u32).unwrap()].data.1, "b");
+    }
+    graph.remove_key_index();
+    anchor!(graph);
+    assert!(graph.lookup(&1u32).is_none());
+}
+
+#[test]
+fn test_generic_root() {
+    type Node = VecNode<u32, ()>;
+    let mut graph = GenericGraph::<(RootVec<'static, Node>, RootOption<'static, Node>), Node>::new();
+    {
+        anchor_mut!(graph, Always);
+        let a = graph.spawn(1);
+        let b = graph.spawn(2);
+        graph.spawn(3);
+        graph.root_mut().0.push(a);
+        graph.root_mut().1 = Some(b);
+    }
+    {
+        anchor!(graph);
+        assert_eq!(graph.node_count(), 2);
+        let (list, single) = graph.root();
+        assert_eq!(graph[list[0]].data, 1);
+        assert_eq!(graph[single.unwrap()].data, 2);
+    }
+
+    let mut graph = SlabGraph::<Node>::new();
+    anchor_mut!(graph, Never);
+    let a = graph.spawn(1);
+    let key = graph.root_mut().insert(a);
+    assert!(graph.root()[key] == a);
+}
+
+#[test]
+fn test_root_iter() {
+    let mut graph = MapGraph::<&str, VecNode<u32, ()>>::new();
+    {
+        anchor_mut!(graph, Always);
+        let a = graph.spawn(1);
+        graph.root_mut().insert("a", a);
+        graph.get_or_spawn("b", || 2);
+        for (_, x) in graph.iter_mut() {
+            *x.values *= 10;
+        }
+    }
+    {
+        anchor!(graph);
+        let mut items : Vec<_> = graph.iter().map(|(k, x)| (*k, *x.values)).collect();
+        items.sort();
+        assert_eq!(items, vec![("a", 10), ("b", 20)]);
+    }
+
+    let mut graph = DequeGraph::<VecNode<u32, ()>>::new();
+    {
+        anchor_mut!(graph, Always);
+        let nodes = graph.spawn_iter(0..3);
+        graph.root_mut().extend(nodes);
+        graph.iter_mut().for_each(|x| *x.values += 1);
+    }
+    anchor!(graph);
+    assert_eq!(graph.iter().map(|x| *x.values).collect::<Vec<_>>(), vec![1, 2, 3]);
+    assert!(graph.iter().map(|x| x.ptr).eq(graph.root().iter().copied()));
+}
+
+#[test]
+fn test_persistent_graph() {
+    use std::sync::Arc;
+
+    let empty = PersistentGraph::<Arc<String>, u32>::new();
+    let (g1, a) = empty.spawn(Arc::new("a".to_string()));
+    let (g1, b) = g1.spawn(Arc::new("b".to_string()));
+    let g1 = g1.with_root(a).connect(a, b, 1).connect(b, b, 2);
+    let g2 = g1.with_data(a, Arc::new("a2".to_string())).disconnect(a, b);
+    let g3 = g2.remove(b);
+
+    assert!(empty.is_empty());
+    assert_eq!(g1.len(), 2);
+    assert_eq!(g1.get(a).unwrap().as_str(), "a");
+    assert_eq!(g1.neighbors(a).collect::<Vec<_>>(), vec![b]);
+    let edge = g1.edges(a).next().unwrap();
+    assert_eq!(edge.ptr, b);
+    assert_eq!(edge.values.that().this.as_str(), "b");
+    assert!(matches!(g1.edges(b).next().unwrap().values, Loop(_)));
+
+    assert_eq!(g2.get(a).unwrap().as_str(), "a2");
+    assert_eq!(g2.neighbors(a).count(), 0);
+    // b is untouched by g2 and shared with g1.
+    assert!(std::ptr::eq(g1.node(b).unwrap(), g2.node(b).unwrap()));
+
+    assert_eq!(g3.len(), 1);
+    assert!(!g3.contains(b));
+    assert_eq!(g3.roots(), &[a]);
+    assert_eq!(g3.iter().map(|x| x.0).collect::<Vec<_>>(), vec![a]);
+    assert!(g1.contains(b));
+
+    // Enough nodes for a trie of three levels.
+    let mut g4 = PersistentGraph::<usize, ()>::new();
+    for i in 0..1100 {
+        let (g, key) = g4.spawn(i);
+        assert_eq!(key, i);
+        g4 = if i > 0 { g.connect(i - 1, i, ()) } else { g.with_root(i) };
+    }
+    let g5 = g4.disconnect(500, 501).cleanup();
+    assert_eq!(g4.len(), 1100);
+    assert_eq!(g5.len(), 501);
+    assert_eq!(g5.get(500), Some(&500));
+    assert!(!g5.contains(501) && g4.contains(1099));
+    assert!(std::ptr::eq(g4.node(10).unwrap(), g5.node(10).unwrap()));
+}
+
+#[test]
+fn test_transaction() {
+    let mut graph = VecGraph::<NamedNode<&str, u32>>::new();
+    anchor_mut!(graph, Never);
+    let a = graph.spawn("a");
+    let b = graph.spawn("b");
+    graph.root_mut().push(a);
+    graph.connect(a, b, 1);
+
+    let res : Result<(), &str> = graph.transaction(|txn| {
+        let c = txn.spawn("c");
+        txn.root_mut().push(c);
+        txn.connect(a, c, 2);
+        assert_eq!(txn.connect(a, b, 3), Some(1));
+        txn.disconnect(a, b);
+        txn.connect(b, a, 4);
+        Err("failed")
+    });
+    assert_eq!(res, Err("failed"));
+    assert_eq!(graph.root().len(), 1);
+    assert_eq!(graph.edges(a).count(), 1);
+    assert_eq!(graph[a].refs.get(&b), Some(&1));
+    assert_eq!(graph.edges(b).count(), 0);
+
+    let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let _ : Result<(), ()> = graph.transaction(|txn| {
+            txn.disconnect(a, b);
+            panic!("rewrite failed");
+        });
+    }));
+    assert!(panicked.is_err());
+    assert_eq!(graph[a].refs.get(&b), Some(&1));
+
+    let c = graph.transaction(|txn| -> Result<_, ()> {
+        let c = txn.spawn("c");
+        txn.connect(a, c, 5);
+        Ok(c)
+    }).unwrap();
+    assert_eq!(graph[a].refs.get(&c), Some(&5));
+}
+
+#[test]
+fn test_transaction_node_kinds() {
+    let mut graph = VecGraph::<VecNode<i32, i32>>::new();
+    anchor_mut!(graph, Never);
+    let a = graph.spawn(0);
+    let b = graph.spawn(1);
+    let c = graph.spawn(2);
+    graph.connect(a, b, 1);
+    graph.connect(a, c, 2);
+    let _ : Result<(), ()> = graph.transaction(|txn| {
+        txn.disconnect(a, b);
+        txn.connect(a, c, 3);
+        txn.connect(a, a, 4);
+        Err(())
+    });
+    //Edges are restored at their positions.
+    assert_eq!(graph[a].refs, vec![(b, 1), (c, 2)]);
+
+    let mut graph = VecGraph::<OptionNode<i32, i32>>::new();
+    anchor_mut!(graph, Never);
+    let a = graph.spawn(0);
+    let b = graph.spawn(1);
+    graph.connect(a, b, 1);
+    let _ : Result<(), ()> = graph.transaction(|txn| {
+        txn.connect(a, a, 2);
+        Err(())
+    });
+    assert_eq!(graph[a].refs, Some((b, 1)));
+
+    let mut graph = VecGraph::<TreeNode<&str, i32, i32>>::new();
+    anchor_mut!(graph, Never);
+    let a = graph.spawn(0);
+    let b = graph.spawn(1);
+    graph.connect(a, "x", b, 1);
+    let _ : Result<(), ()> = graph.transaction(|txn| {
+        txn.connect(a, "x", a, 2);
+        txn.connect(a, "y", b, 3);
+        Err(())
+    });
+    assert_eq!(graph[a].refs.len(), 1);
+    assert!(graph[a].refs.get("x") == Some(&(b, 1)));
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_tracing() {
+    use std::fmt::Write;
+    use std::sync::{Arc, Mutex};
+    use tracing::{Event, Metadata, Subscriber};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{self, Attributes, Record};
+
+    // Records spans and events as lines of their fields.
+    #[derive(Clone, Default)]
+    struct Log(Arc<Mutex<Vec<String>>>);
+
+    struct Line(String);
+
+    impl Visit for Line {
+        fn record_debug(&mut self, field : &Field, value : &dyn std::fmt::Debug) {
+            write!(self.0, " {}={:?}", field.name(), value).unwrap();
+        }
+    }
+
+    impl Subscriber for Log {
+        fn enabled(&self, _ : &Metadata<'_>) -> bool { true }
+        fn new_span(&self, span : &Attributes<'_>) -> span::Id {
+            let mut line = Line(format!("span {}", span.metadata().name()));
+            span.record(&mut line);
+            self.0.lock().unwrap().push(line.0);
+            span::Id::from_u64(1)
+        }
+        fn record(&self, _ : &span::Id, _ : &Record<'_>) {}
+        fn record_follows_from(&self, _ : &span::Id, _ : &span::Id) {}
+        fn event(&self, event : &Event<'_>) {
+            let mut line = Line("event".to_string());
+            event.record(&mut line);
+            self.0.lock().unwrap().push(line.0);
+        }
+        fn enter(&self, _ : &span::Id) {}
+        fn exit(&self, _ : &span::Id) {}
+    }
+
+    let log = Log::default();
+    tracing::subscriber::with_default(log.clone(), || {
+        let mut graph = VecGraph::<VecNode<usize, ()>>::new();
+        anchor_mut!(graph, Always);
+        let root = graph.spawn(0);
+        graph.root_mut().push(root);
+        graph.spawn(1);
+    });
+    let size = std::mem::size_of::<VecNode<usize, ()>>();
+    assert_eq!(*log.0.lock().unwrap(), vec![
+        "event message=anchor created mutable=true".to_string(),
+        "event message=spawn nodes=1".to_string(),
+        "event message=spawn nodes=2".to_string(),
+        "event message=anchor dropped".to_string(),
+        "span cleanup kind=\"precise\" nodes=2".to_string(),
+        format!("event message=cleanup finished nodes_freed=1 nodes_retained=1 bytes_freed_estimate={}", size),
+    ]);
+}